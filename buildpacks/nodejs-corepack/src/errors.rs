@@ -38,6 +38,16 @@ fn on_buildpack_error(bp_err: CorepackBuildpackError) {
         ),
         CorepackBuildpackError::ShimLayer(err) => on_layer_error("shim", &err),
         CorepackBuildpackError::ManagerLayer(err) => on_layer_error("manager", &err),
+        CorepackBuildpackError::Config(err) => log_error(
+            "heroku/nodejs-corepack configuration error",
+            formatdoc! {"
+                There was an error while attempting to read the Node.js
+                buildpack configuration from this project's project.toml
+                or environment variables.
+
+                Details: {err}
+            "},
+        ),
         CorepackBuildpackError::PackageJson(err) => log_error(
             "heroku/nodejs-corepack package.json error",
             formatdoc! {"
@@ -1,13 +1,15 @@
+use heroku_nodejs_utils::config::{ConfigError, NodejsConfig};
 use heroku_nodejs_utils::package_json::{PackageJson, PackageJsonError};
 use libcnb::build::{BuildContext, BuildResult, BuildResultBuilder};
 use libcnb::data::build_plan::BuildPlanBuilder;
 use libcnb::detect::{DetectContext, DetectResult, DetectResultBuilder};
 use libcnb::generic::GenericMetadata;
 use libcnb::generic::GenericPlatform;
-use libcnb::{buildpack_main, Buildpack, Env};
+use libcnb::{buildpack_main, Buildpack, Env, Platform};
 use libherokubuildpack::log::log_header;
 use opentelemetry::trace::{TraceContextExt, Tracer};
 use opentelemetry::KeyValue;
+use std::path::{Path, PathBuf};
 
 use crate::enable_corepack::enable_corepack;
 use crate::install_integrity_keys::install_integrity_keys;
@@ -40,7 +42,8 @@ impl Buildpack for CorepackBuildpack {
             .in_span("detect", |_cx| {
                 // Corepack requires the `packageManager` key from `package.json`.
                 // This buildpack won't be detected without it.
-                let pkg_json_path = context.app_dir.join("package.json");
+                let pkg_json_path =
+                    app_root_dir(&context.app_dir, context.platform.env())?.join("package.json");
                 if pkg_json_path.exists() {
                     let pkg_json = PackageJson::read(pkg_json_path)
                         .map_err(CorepackBuildpackError::PackageJson)?;
@@ -67,7 +70,8 @@ impl Buildpack for CorepackBuildpack {
     fn build(&self, context: BuildContext<Self>) -> libcnb::Result<BuildResult, Self::Error> {
         opentelemetry::global::tracer(context.buildpack_descriptor.buildpack.id.to_string())
             .in_span("build", |cx| {
-                let pkg_mgr = PackageJson::read(context.app_dir.join("package.json"))
+                let app_dir = app_root_dir(&context.app_dir, context.platform.env())?;
+                let pkg_mgr = PackageJson::read(app_dir.join("package.json"))
                     .map_err(CorepackBuildpackError::PackageJson)?
                     .package_manager
                     .ok_or(CorepackBuildpackError::PackageManagerMissing)?;
@@ -105,8 +109,18 @@ impl Buildpack for CorepackBuildpack {
     }
 }
 
+/// The directory of the app root from the Node.js buildpack configuration,
+/// which holds the `package.json` with the `packageManager` key.
+fn app_root_dir(app_dir: &Path, env: &Env) -> Result<PathBuf, CorepackBuildpackError> {
+    NodejsConfig::read(app_dir)
+        .and_then(|config| config.app_root(env))
+        .map(|app_root| app_root.dir(app_dir))
+        .map_err(CorepackBuildpackError::Config)
+}
+
 #[derive(Debug)]
 enum CorepackBuildpackError {
+    Config(ConfigError),
     PackageManagerMissing,
     PackageJson(PackageJsonError),
     ShimLayer(std::io::Error),
@@ -20,6 +20,13 @@
  *     counters: MemoryCounters;
  *     gauges: EventLoopGauges;
  * }} MetricsPayload
+ *
+ * @typedef {{
+ *     url: URL;
+ *     interval: number;
+ *     headers: Record<string, string>;
+ *     format: (payload: MetricsPayload, startTime: number, endTime: number) => object;
+ * }} MetricsExporter
  */
 const { setInterval } = require('timers')
 const { URL } = require('url');
@@ -43,14 +50,14 @@ try {
 function registerInstrumentation() {
     log('Registering metrics instrumentation')
 
-    const herokuMetricsUrl = parseHerokuMetricsUrl()
-    if (herokuMetricsUrl === undefined) {
+    const exporter = process.env.NODEJS_RUNTIME_METRICS_EXPORTER === 'otlp'
+        ? createOtlpExporter()
+        : createHerokuExporter()
+    if (exporter === undefined) {
         log('Metrics will not be collected for this application')
         return
     }
 
-    const herokuMetricsInterval = parseHerokuMetricsInterval()
-
     let memoryCounters = initializeMemoryCounters()
     const gcObserver = new PerformanceObserver((value) => {
         value.getEntries().forEach(entry => updateMemoryCounters(memoryCounters, entry))
@@ -61,6 +68,7 @@ function registerInstrumentation() {
     eventLoopHistogram.enable()
 
     let previousEventLoopUtilization = performance.eventLoopUtilization()
+    let intervalStartTime = Date.now()
 
     const timeout  = setInterval(() => {
         try {
@@ -68,12 +76,14 @@ function registerInstrumentation() {
             eventLoopHistogram.disable()
             gcObserver.disconnect()
 
-            sendMetrics(herokuMetricsUrl, {
+            const intervalEndTime = Date.now()
+            sendMetrics(exporter, exporter.format({
                 counters: {...memoryCounters},
                 gauges: captureEventLoopGauges(eventLoopUtilization, eventLoopHistogram)
-            })
+            }, intervalStartTime, intervalEndTime))
 
             // reset memory and event loop measures
+            intervalStartTime = intervalEndTime
             previousEventLoopUtilization = eventLoopUtilization
             memoryCounters = initializeMemoryCounters()
             gcObserver.observe({ entryTypes: ['gc'] })
@@ -82,7 +92,7 @@ function registerInstrumentation() {
         } catch (e) {
             log(`An unexpected error occurred: ${e.stack}`)
         }
-    }, herokuMetricsInterval)
+    }, exporter.interval)
 
     // `setInterval` actually returns a Timeout object but this isn't recognized by the type-checker which
     // thinks it's a number so adding this little guard to silence the type warnings
@@ -99,6 +109,50 @@ function log(msg) {
     debuglog('heroku')(`[heroku-metrics] ${msg}`)
 }
 
+/**
+ * Creates the exporter that posts metrics to Heroku Language Metrics in the format it expects.
+ * @returns {MetricsExporter | undefined}
+ */
+function createHerokuExporter() {
+    const url = parseHerokuMetricsUrl()
+    if (url === undefined) {
+        return
+    }
+    return {
+        url,
+        interval: parseMetricsInterval('METRICS_INTERVAL_OVERRIDE', 20 * 1000),
+        headers: {},
+        format: (payload) => payload
+    }
+}
+
+/**
+ * Creates the exporter that posts metrics to an OpenTelemetry collector using OTLP/HTTP with JSON encoding. It's
+ * configured with the standard `OTEL_*` environment variables.
+ * @see https://opentelemetry.io/docs/specs/otel/protocol/exporter/
+ * @returns {MetricsExporter | undefined}
+ */
+function createOtlpExporter() {
+    const url = parseOtlpMetricsUrl()
+    if (url === undefined) {
+        return
+    }
+    const configuredAttributes = parseKeyValueList(process.env.OTEL_RESOURCE_ATTRIBUTES)
+    const resourceAttributes = {
+        ...configuredAttributes,
+        'service.name': process.env.OTEL_SERVICE_NAME || configuredAttributes['service.name'] || 'unknown_service:node',
+        'process.pid': String(process.pid),
+        'process.runtime.name': 'nodejs',
+        'process.runtime.version': process.versions.node,
+    }
+    return {
+        url,
+        interval: parseMetricsInterval('OTEL_METRIC_EXPORT_INTERVAL', 60 * 1000),
+        headers: parseKeyValueList(process.env.OTEL_EXPORTER_OTLP_METRICS_HEADERS || process.env.OTEL_EXPORTER_OTLP_HEADERS),
+        format: (payload, startTime, endTime) => toOtlpPayload(payload, resourceAttributes, startTime, endTime)
+    }
+}
+
 /**
  * The url is where the runtime metrics will be posted to. This is parsed from the environment variable `HEROKU_METRICS_URL`
  * which is added to dynos by runtime only if the app has opted into the heroku runtime metrics beta. If this value is not
@@ -121,17 +175,18 @@ function parseHerokuMetricsUrl() {
 
 /**
  * Returns the time in milliseconds to wait between requests to send metrics to the collecting service. This value is
- * either parsed from the environment variable `METRICS_INTERVAL_OVERRIDE` or defaults to 20s. The parsed value also
- * can be no less than 10s.
+ * either parsed from the given environment variable (`METRICS_INTERVAL_OVERRIDE` for Heroku, `OTEL_METRIC_EXPORT_INTERVAL`
+ * for OTLP) or defaults to the given interval. The parsed value also can be no less than 10s.
+ * @param {string} name
+ * @param {number} defaultInterval
  * @returns {number}
  */
-function parseHerokuMetricsInterval() {
+function parseMetricsInterval(name, defaultInterval) {
     const minimumInterval = 10 * 1000 // 10 seconds
-    const defaultInterval = 20 * 1000 // 20 seconds
 
-    const value = process.env.METRICS_INTERVAL_OVERRIDE
+    const value = process.env[name]
     if (value) {
-        log(`METRICS_INTERVAL_OVERRIDE set to "${value}"`)
+        log(`${name} set to "${value}"`)
         const parsedValue = parseInt(value, 10)
 
         if (isNaN(parsedValue)) {
@@ -152,6 +207,113 @@ function parseHerokuMetricsInterval() {
     return defaultInterval
 }
 
+/**
+ * The OTLP metrics url is parsed from `OTEL_EXPORTER_OTLP_METRICS_ENDPOINT`, which is used as-is, or from
+ * `OTEL_EXPORTER_OTLP_ENDPOINT`, which gets `v1/metrics` appended. Defaults to a collector on localhost.
+ * @returns {URL | undefined}
+ */
+function parseOtlpMetricsUrl() {
+    const metricsEndpoint = process.env.OTEL_EXPORTER_OTLP_METRICS_ENDPOINT
+    const endpoint = process.env.OTEL_EXPORTER_OTLP_ENDPOINT
+    let value = 'http://localhost:4318/v1/metrics'
+    if (metricsEndpoint) {
+        log(`OTEL_EXPORTER_OTLP_METRICS_ENDPOINT set to "${metricsEndpoint}"`)
+        value = metricsEndpoint
+    } else if (endpoint) {
+        log(`OTEL_EXPORTER_OTLP_ENDPOINT set to "${endpoint}"`)
+        value = `${endpoint.replace(/\/+$/, '')}/v1/metrics`
+    } else {
+        log(`OTLP endpoint was not set in the environment, using ${value}`)
+    }
+    try {
+        return new URL(value)
+    } catch (e) {
+        log(`Invalid URL: ${e}`)
+    }
+}
+
+/**
+ * Parses a list of `key=value` pairs separated by commas, as used by `OTEL_EXPORTER_OTLP_HEADERS` and
+ * `OTEL_RESOURCE_ATTRIBUTES`. Keys and values are percent-decoded and invalid pairs are ignored.
+ * @param {string | undefined} value
+ * @returns {Record<string, string>}
+ */
+function parseKeyValueList(value) {
+    /** @type {Record<string, string>} */
+    const pairs = {}
+    for (const pair of (value || '').split(',')) {
+        const separator = pair.indexOf('=')
+        if (separator <= 0) {
+            continue
+        }
+        try {
+            const key = decodeURIComponent(pair.slice(0, separator).trim())
+            pairs[key] = decodeURIComponent(pair.slice(separator + 1).trim())
+        } catch (e) {
+            log(`Ignoring invalid key-value pair: ${e}`)
+        }
+    }
+    return pairs
+}
+
+/**
+ * Converts the collected metrics into an OTLP `ExportMetricsServiceRequest` using the JSON encoding. Counters are reset
+ * after every export, so they're reported as monotonic sums with delta temporality.
+ * @see https://github.com/open-telemetry/opentelemetry-proto/blob/main/opentelemetry/proto/metrics/v1/metrics.proto
+ * @param {MetricsPayload} payload
+ * @param {Record<string, string>} resourceAttributes
+ * @param {number} startTime the start of the collection interval in milliseconds since the epoch
+ * @param {number} endTime the end of the collection interval in milliseconds since the epoch
+ * @returns {object}
+ */
+function toOtlpPayload(payload, resourceAttributes, startTime, endTime) {
+    const AGGREGATION_TEMPORALITY_DELTA = 1
+    const startTimeUnixNano = millisecondsToUnixNano(startTime)
+    const timeUnixNano = millisecondsToUnixNano(endTime)
+
+    const sums = Object.entries(payload.counters).map(([name, value]) => ({
+        name,
+        unit: name.endsWith('.ns') ? 'ns' : '1',
+        sum: {
+            aggregationTemporality: AGGREGATION_TEMPORALITY_DELTA,
+            isMonotonic: true,
+            dataPoints: [{ startTimeUnixNano, timeUnixNano, asInt: String(Math.round(value)) }]
+        }
+    }))
+
+    const gauges = Object.entries(payload.gauges).map(([name, value]) => ({
+        name,
+        unit: name.includes('.ms.') ? 'ms' : '1',
+        gauge: {
+            dataPoints: [{ timeUnixNano, asDouble: value }]
+        }
+    }))
+
+    return {
+        resourceMetrics: [{
+            resource: {
+                attributes: Object.entries(resourceAttributes).map(([key, value]) => ({
+                    key,
+                    value: { stringValue: value }
+                }))
+            },
+            scopeMetrics: [{
+                scope: { name: 'heroku-nodejs-runtime-metrics' },
+                metrics: [...sums, ...gauges]
+            }]
+        }]
+    }
+}
+
+/**
+ * Converts milliseconds since the epoch into the nanosecond string OTLP/JSON uses for 64-bit timestamps.
+ * @param {number} ms
+ * @returns {string}
+ */
+function millisecondsToUnixNano(ms) {
+    return (BigInt(ms) * BigInt(1e6)).toString()
+}
+
 /**
  * Initializes all the memory counters with their starting values
  * @returns {MemoryCounters}
@@ -236,12 +398,13 @@ function nanosecondsToMilliseconds(ns) {
 }
 
 /**
- * Sends the collected metrics to the given endpoint using a POST request.
- * @param {URL} url
- * @param {MetricsPayload} payload
+ * Sends the collected metrics to the exporter's endpoint using a POST request.
+ * @param {MetricsExporter} exporter
+ * @param {object} payload
  * @returns void
  */
-function sendMetrics(url, payload) {
+function sendMetrics(exporter, payload) {
+    const url = exporter.url
     const request = url.protocol === 'https:' ? secureRequest : insecureRequest
     const payloadAsJson = JSON.stringify(payload)
 
@@ -251,8 +414,9 @@ function sendMetrics(url, payload) {
         protocol: url.protocol,
         hostname: url.hostname,
         port: url.port,
-        path: url.pathname,
+        path: url.pathname + url.search,
         headers: {
+            ...exporter.headers,
             'Content-Type': 'application/json',
             'Content-Length': Buffer.byteLength(payloadAsJson)
         }
@@ -155,6 +155,74 @@ describe('Metrics plugin', function () {
         })
     })
 
+    describe('otlp exporter', () => {
+        it('should send metrics to the otlp endpoint', async () => {
+            metricsReceiver = await startMetricsReceiver()
+            application = await spawnApplication('single_process_app.cjs', {
+                env: {
+                    NODEJS_RUNTIME_METRICS_EXPORTER: 'otlp',
+                    OTEL_EXPORTER_OTLP_ENDPOINT: `${metricsReceiver.url}/`,
+                    OTEL_EXPORTER_OTLP_HEADERS: 'x-api-key=secret%20value,invalid',
+                    OTEL_METRIC_EXPORT_INTERVAL: 10000,
+                    OTEL_RESOURCE_ATTRIBUTES: 'deployment.environment=test,service.name=ignored',
+                    OTEL_SERVICE_NAME: 'metrics-test',
+                }
+            })
+            assert.match(application.pluginOutput, new RegExp(`\\[heroku-metrics] OTEL_EXPORTER_OTLP_ENDPOINT set to "${metricsReceiver.url}/"`))
+            assert.match(application.pluginOutput, /\[heroku-metrics] OTEL_METRIC_EXPORT_INTERVAL set to "10000"/)
+            assert.match(application.pluginOutput, new RegExp(`\\[heroku-metrics] Sending metrics to ${metricsReceiver.url}/v1/metrics`))
+            assert.match(application.pluginOutput, /\[heroku-metrics] Metrics sent successfully/)
+            assert.doesNotMatch(application.pluginOutput, /HEROKU_METRICS_URL/)
+
+            assert.equal(metricsReceiver.requests.length, 2)
+            for (const request of metricsReceiver.requests) {
+                assert.equal(request.path, '/v1/metrics')
+                assert.equal(request.headers['x-api-key'], 'secret value')
+            }
+
+            for (const metric of metricsReceiver.metricsReceived) {
+                const [resourceMetrics] = metric.resourceMetrics
+                const attributes = Object.fromEntries(resourceMetrics.resource.attributes.map(({ key, value }) => [key, value.stringValue]))
+                assert.equal(attributes['service.name'], 'metrics-test')
+                assert.equal(attributes['deployment.environment'], 'test')
+
+                const metrics = Object.fromEntries(resourceMetrics.scopeMetrics[0].metrics.map(m => [m.name, m]))
+                const [collections] = metrics['node.gc.collections'].sum.dataPoints
+                assert.equal(metrics['node.gc.collections'].sum.isMonotonic, true)
+                assert.equal(metrics['node.gc.collections'].sum.aggregationTemporality, 1)
+                assert.match(collections.asInt, /^\d+$/)
+                assert.ok(BigInt(collections.startTimeUnixNano) < BigInt(collections.timeUnixNano))
+
+                const [delay] = metrics['node.eventloop.delay.ms.p95'].gauge.dataPoints
+                assert.equal(metrics['node.eventloop.delay.ms.p95'].unit, 'ms')
+                assert.equal(typeof delay.asDouble, 'number')
+            }
+        })
+
+        it('should use the signal-specific endpoint as-is', async () => {
+            metricsReceiver = await startMetricsReceiver()
+            application = await spawnApplication('single_process_app.cjs', {
+                env: {
+                    NODEJS_RUNTIME_METRICS_EXPORTER: 'otlp',
+                    OTEL_EXPORTER_OTLP_ENDPOINT: 'http://localhost:1',
+                    OTEL_EXPORTER_OTLP_METRICS_ENDPOINT: `${metricsReceiver.url}/custom/metrics`,
+                    OTEL_METRIC_EXPORT_INTERVAL: 10000,
+                }
+            })
+            assert.match(application.pluginOutput, new RegExp(`\\[heroku-metrics] Sending metrics to ${metricsReceiver.url}/custom/metrics`))
+            assert.equal(metricsReceiver.requests[0].path, '/custom/metrics')
+        })
+
+        it('should use a default interval of 60 seconds', async () => {
+            application = await spawnApplication('single_process_app.cjs', {
+                env: { NODEJS_RUNTIME_METRICS_EXPORTER: 'otlp' },
+                msToExecute: 1000
+            })
+            assert.match(application.pluginOutput, /\[heroku-metrics] OTLP endpoint was not set in the environment, using http:\/\/localhost:4318\/v1\/metrics/)
+            assert.match(application.pluginOutput, /\[heroku-metrics] Using default interval of 60000ms/)
+        })
+    })
+
     describe('http error', () => {
         it('should report when requests fail', async () => {
             metricsReceiver = await startMetricsReceiver( {
@@ -170,6 +238,7 @@ describe('Metrics plugin', function () {
 
 function startMetricsReceiver(options = {}) {
     const metricsReceived = []
+    const requests = []
     options = {
         responseStatusCode: 200,
         ...options
@@ -180,6 +249,7 @@ function startMetricsReceiver(options = {}) {
             let data = ''
             req.on('data', d => data += d)
             req.on('end', () => {
+                requests.push({ path: req.url, headers: req.headers })
                 metricsReceived.push(JSON.parse(data))
                 res.statusCode = options.responseStatusCode
                 res.end()
@@ -194,6 +264,7 @@ function startMetricsReceiver(options = {}) {
             resolve({
                 url: `http://localhost:${port}`,
                 metricsReceived,
+                requests,
                 disconnect: () => metricsReceiver.close()
             })
         })
@@ -217,6 +288,7 @@ function spawnApplication(fixture, options = {}) {
         metricsUrl: undefined,
         metricsIntervalOverride: undefined,
         msToExecute: TEST_TIMEOUT - 200, // kill the service just before the timeout is reached (so tests don't hang)
+        env: {},
         ...options
     }
 
@@ -226,6 +298,7 @@ function spawnApplication(fixture, options = {}) {
         FORCE_COLOR: 0,
         NODE_DEBUG: 'heroku',
         NODE_OPTIONS: `--require ${metricsScript}`,
+        ...options.env,
     }
 
     if (options.metricsUrl) {
@@ -3,10 +3,64 @@ use libcnb::build::BuildContext;
 use libcnb::data::layer_name;
 use libcnb::layer::UncachedLayerDefinition;
 use libcnb::layer_env::{LayerEnv, ModificationBehavior, Scope};
+use libcnb::{Env, Platform};
+use std::fmt::{Display, Formatter};
 use thiserror::Error;
 
+/// Environment variable read by the metrics script to pick an exporter.
+const EXPORTER_ENV_VAR: &str = "NODEJS_RUNTIME_METRICS_EXPORTER";
+
+/// Standard OpenTelemetry variables for the OTLP endpoint. The signal-specific
+/// one takes precedence in the metrics script.
+const OTLP_ENDPOINT_ENV_VARS: [&str; 2] = [
+    "OTEL_EXPORTER_OTLP_METRICS_ENDPOINT",
+    "OTEL_EXPORTER_OTLP_ENDPOINT",
+];
+
+const OTEL_METRICS_EXPORTER_ENV_VAR: &str = "OTEL_METRICS_EXPORTER";
+
+/// Where the metrics script sends the collected runtime metrics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RuntimeMetricsExporter {
+    /// Posts to `HEROKU_METRICS_URL`, which is set on dynos that opted into
+    /// Heroku Language Metrics.
+    Heroku,
+    /// Posts OTLP/HTTP JSON to the endpoint configured with the standard
+    /// `OTEL_EXPORTER_OTLP_*` variables.
+    Otlp,
+}
+
+impl Display for RuntimeMetricsExporter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RuntimeMetricsExporter::Heroku => write!(f, "heroku"),
+            RuntimeMetricsExporter::Otlp => write!(f, "otlp"),
+        }
+    }
+}
+
+/// Picks the OTLP exporter if an OTLP endpoint is configured or
+/// `OTEL_METRICS_EXPORTER=otlp`. Returns `None` for
+/// `OTEL_METRICS_EXPORTER=none`, which disables metrics export entirely.
+pub(crate) fn runtime_metrics_exporter(env: &Env) -> Option<RuntimeMetricsExporter> {
+    let metrics_exporter = env
+        .get_string_lossy(OTEL_METRICS_EXPORTER_ENV_VAR)
+        .map(|value| value.trim().to_lowercase());
+    let has_otlp_endpoint = OTLP_ENDPOINT_ENV_VARS
+        .iter()
+        .any(|name| env.get_string_lossy(name).is_some_and(|v| !v.is_empty()));
+
+    match metrics_exporter.as_deref() {
+        Some("none") => None,
+        Some("otlp") => Some(RuntimeMetricsExporter::Otlp),
+        _ if has_otlp_endpoint => Some(RuntimeMetricsExporter::Otlp),
+        _ => Some(RuntimeMetricsExporter::Heroku),
+    }
+}
+
 pub(crate) fn attach_runtime_metrics(
     context: &BuildContext<NodeJsEngineBuildpack>,
+    exporter: RuntimeMetricsExporter,
 ) -> Result<(), libcnb::Error<NodeJsEngineBuildpackError>> {
     let web_env_layer = context.uncached_layer(
         layer_name!("node_runtime_metrics"),
@@ -24,21 +78,38 @@ pub(crate) fn attach_runtime_metrics(
     )
     .map_err(NodeRuntimeMetricsError::WriteMetricsScript)?;
 
-    web_env_layer.write_env(
-        LayerEnv::new()
-            .chainable_insert(
-                Scope::Launch,
-                ModificationBehavior::Delimiter,
-                "NODE_OPTIONS",
-                " ",
-            )
-            .chainable_insert(
-                Scope::Launch,
-                ModificationBehavior::Append,
-                "NODE_OPTIONS",
-                format!("--require {}", metrics_script.display()),
-            ),
-    )?;
+    let mut layer_env = LayerEnv::new()
+        .chainable_insert(
+            Scope::Launch,
+            ModificationBehavior::Delimiter,
+            "NODE_OPTIONS",
+            " ",
+        )
+        .chainable_insert(
+            Scope::Launch,
+            ModificationBehavior::Append,
+            "NODE_OPTIONS",
+            format!("--require {}", metrics_script.display()),
+        )
+        .chainable_insert(
+            Scope::Launch,
+            ModificationBehavior::Override,
+            EXPORTER_ENV_VAR,
+            exporter.to_string(),
+        );
+
+    // Endpoints configured at build time become launch defaults so the image
+    // works outside the build environment, while runtime values still win.
+    // Headers are deliberately not persisted since they usually hold secrets.
+    if exporter == RuntimeMetricsExporter::Otlp {
+        for name in OTLP_ENDPOINT_ENV_VARS {
+            if let Some(value) = context.platform.env().get(name) {
+                layer_env.insert(Scope::Launch, ModificationBehavior::Default, name, value);
+            }
+        }
+    }
+
+    web_env_layer.write_env(layer_env)?;
 
     Ok(())
 }
@@ -54,3 +125,57 @@ impl From<NodeRuntimeMetricsError> for libcnb::Error<NodeJsEngineBuildpackError>
         libcnb::Error::BuildpackError(NodeJsEngineBuildpackError::NodeRuntimeMetricsError(value))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(vars: &[(&str, &str)]) -> Env {
+        let mut env = Env::new();
+        for (name, value) in vars {
+            env.insert(name, value);
+        }
+        env
+    }
+
+    #[test]
+    fn exporter_selection() {
+        assert_eq!(
+            runtime_metrics_exporter(&env(&[])),
+            Some(RuntimeMetricsExporter::Heroku)
+        );
+        assert_eq!(
+            runtime_metrics_exporter(&env(&[(
+                "OTEL_EXPORTER_OTLP_ENDPOINT",
+                "http://collector:4318"
+            )])),
+            Some(RuntimeMetricsExporter::Otlp)
+        );
+        assert_eq!(
+            runtime_metrics_exporter(&env(&[(
+                "OTEL_EXPORTER_OTLP_METRICS_ENDPOINT",
+                "http://collector:4318/v1/metrics"
+            )])),
+            Some(RuntimeMetricsExporter::Otlp)
+        );
+        assert_eq!(
+            runtime_metrics_exporter(&env(&[("OTEL_METRICS_EXPORTER", "otlp")])),
+            Some(RuntimeMetricsExporter::Otlp)
+        );
+        assert_eq!(
+            runtime_metrics_exporter(&env(&[
+                ("OTEL_METRICS_EXPORTER", "none"),
+                ("OTEL_EXPORTER_OTLP_ENDPOINT", "http://collector:4318")
+            ])),
+            None
+        );
+        assert_eq!(
+            runtime_metrics_exporter(&env(&[("OTEL_METRICS_EXPORTER", " None ")])),
+            None
+        );
+        assert_eq!(
+            runtime_metrics_exporter(&env(&[("OTEL_EXPORTER_OTLP_ENDPOINT", "")])),
+            Some(RuntimeMetricsExporter::Heroku)
+        );
+    }
+}
@@ -8,21 +8,53 @@ use std::cmp;
 use std::collections::HashMap;
 use std::env;
 use std::fs;
+use std::num::NonZeroUsize;
+use std::path::Path;
+use std::thread;
 
 fn main() {
-    write_exec_d_program_output(web_env(read_env("WEB_CONCURRENCY"), read_env("WEB_MEMORY")));
+    write_exec_d_program_output(web_env(
+        Path::new(CGROUP_ROOT),
+        &RuntimeConfig {
+            web_concurrency: read_env("WEB_CONCURRENCY"),
+            web_memory: read_env("WEB_MEMORY"),
+            node_options: env::var("NODE_OPTIONS").ok(),
+            uv_threadpool_size: read_env("UV_THREADPOOL_SIZE"),
+            auto_max_old_space_size: read_flag(AUTO_MAX_OLD_SPACE_SIZE_ENV_VAR),
+            auto_uv_threadpool_size: read_flag(AUTO_UV_THREADPOOL_SIZE_ENV_VAR),
+        },
+    ));
 }
 
-fn web_env(
-    concurrency: Option<usize>,
-    memory: Option<usize>,
-) -> HashMap<ExecDProgramOutputKey, String> {
-    let available_memory = detect_available_memory();
-    let web_memory = memory.unwrap_or_else(|| default_web_memory(available_memory));
-    let web_concurrency =
-        concurrency.unwrap_or_else(|| calculate_web_concurrency(available_memory, web_memory));
+/// Opts into setting `--max-old-space-size` in `NODE_OPTIONS` from `WEB_MEMORY`.
+const AUTO_MAX_OLD_SPACE_SIZE_ENV_VAR: &str = "NODEJS_AUTO_MAX_OLD_SPACE_SIZE";
+/// Opts into setting `UV_THREADPOOL_SIZE` from the available CPUs.
+const AUTO_UV_THREADPOOL_SIZE_ENV_VAR: &str = "NODEJS_AUTO_UV_THREADPOOL_SIZE";
 
-    HashMap::from([
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+/// Runtime configuration read from the environment of the launched process.
+#[derive(Debug, Default)]
+struct RuntimeConfig {
+    web_concurrency: Option<usize>,
+    web_memory: Option<usize>,
+    node_options: Option<String>,
+    uv_threadpool_size: Option<usize>,
+    auto_max_old_space_size: bool,
+    auto_uv_threadpool_size: bool,
+}
+
+fn web_env(cgroup_root: &Path, config: &RuntimeConfig) -> HashMap<ExecDProgramOutputKey, String> {
+    let available_memory = detect_available_memory(cgroup_root);
+    let cpu_quota = detect_cpu_quota(cgroup_root);
+    let web_memory = config
+        .web_memory
+        .unwrap_or_else(|| default_web_memory(available_memory));
+    let web_concurrency = config
+        .web_concurrency
+        .unwrap_or_else(|| calculate_web_concurrency(available_memory, web_memory, cpu_quota));
+
+    let mut output = HashMap::from([
         (
             exec_d_program_output_key!("WEB_CONCURRENCY"),
             web_concurrency.to_string(),
@@ -31,28 +63,91 @@ fn web_env(
             exec_d_program_output_key!("WEB_MEMORY"),
             web_memory.to_string(),
         ),
-    ])
+    ]);
+
+    let node_options = config.node_options.clone().unwrap_or_default();
+    if config.auto_max_old_space_size && !node_options.contains("--max-old-space-size") {
+        output.insert(
+            exec_d_program_output_key!("NODE_OPTIONS"),
+            format!(
+                "{node_options} --max-old-space-size={}",
+                max_old_space_size(web_memory)
+            )
+            .trim_start()
+            .to_string(),
+        );
+    }
+
+    if config.auto_uv_threadpool_size && config.uv_threadpool_size.is_none() {
+        let available_cpus =
+            cpu_quota.or_else(|| thread::available_parallelism().ok().map(NonZeroUsize::get));
+        output.insert(
+            exec_d_program_output_key!("UV_THREADPOOL_SIZE"),
+            uv_threadpool_size(available_cpus).to_string(),
+        );
+    }
+
+    output
 }
 
 fn read_env(key: &str) -> Option<usize> {
     env::var(key).ok().and_then(|var| var.parse().ok())
 }
 
+fn read_flag(key: &str) -> bool {
+    env::var(key).is_ok_and(|var| var.trim() == "true")
+}
+
 const MAX_AVAILABLE_MEMORY_MB: usize = 129_024;
 const DEFAULT_AVAILABLE_MEMORY_MB: usize = 512;
 const BYTES_PER_MB: usize = 1_048_576;
 
-fn detect_available_memory() -> usize {
-    [
-        "/sys/fs/cgroup/memory.max",
-        "/sys/fs/cgroup/memory/memory.limit_in_bytes",
-    ]
-    .iter()
-    .find_map(|path| fs::read_to_string(path).ok())
-    .and_then(|contents| contents.trim().parse().ok())
-    .map_or(DEFAULT_AVAILABLE_MEMORY_MB, |max_bytes: usize| {
-        cmp::min(MAX_AVAILABLE_MEMORY_MB, max_bytes / BYTES_PER_MB)
-    })
+/// Reads the memory limit in MB from cgroup v2 `memory.max` or cgroup v1
+/// `memory.limit_in_bytes`.
+fn detect_available_memory(cgroup_root: &Path) -> usize {
+    ["memory.max", "memory/memory.limit_in_bytes"]
+        .iter()
+        .find_map(|path| fs::read_to_string(cgroup_root.join(path)).ok())
+        .and_then(|contents| contents.trim().parse().ok())
+        .map_or(DEFAULT_AVAILABLE_MEMORY_MB, |max_bytes: usize| {
+            cmp::min(MAX_AVAILABLE_MEMORY_MB, max_bytes / BYTES_PER_MB)
+        })
+}
+
+/// Reads the CPU quota from cgroup v2 `cpu.max` (`<quota> <period>`) or cgroup
+/// v1 `cpu.cfs_quota_us` and `cpu.cfs_period_us`, rounded up to whole CPUs.
+/// Returns `None` when there's no quota.
+fn detect_cpu_quota(cgroup_root: &Path) -> Option<usize> {
+    let read = |path: &str| fs::read_to_string(cgroup_root.join(path)).ok();
+
+    let (quota, period) = if let Some(cpu_max) = read("cpu.max") {
+        let mut parts = cpu_max.split_whitespace();
+        (
+            parts.next()?.parse::<i64>().ok()?,
+            parts.next()?.parse().ok()?,
+        )
+    } else {
+        ["cpu", "cpu,cpuacct"].iter().find_map(|controller| {
+            Some((
+                read(&format!("{controller}/cpu.cfs_quota_us"))?
+                    .trim()
+                    .parse::<i64>()
+                    .ok()?,
+                read(&format!("{controller}/cpu.cfs_period_us"))?
+                    .trim()
+                    .parse::<i64>()
+                    .ok()?,
+            ))
+        })?
+    };
+
+    // A quota of `-1` (cgroup v1) means there's no limit.
+    if quota <= 0 || period <= 0 {
+        return None;
+    }
+    usize::try_from((quota + period - 1) / period)
+        .ok()
+        .map(|cpus| cmp::max(1, cpus))
 }
 
 const DEFAULT_WEB_MEMORY_BREAKPOINT_MB: usize = 16384;
@@ -66,17 +161,51 @@ fn default_web_memory(available_memory: usize) -> usize {
     DEFAULT_WEB_MEMORY_MB
 }
 
-fn calculate_web_concurrency(available_memory: usize, web_memory: usize) -> usize {
-    cmp::max(1, available_memory / web_memory)
+/// Runs as many processes as fit into the available memory, but no more than
+/// the CPU quota allows, as each Node.js process mostly uses a single CPU.
+fn calculate_web_concurrency(
+    available_memory: usize,
+    web_memory: usize,
+    cpu_quota: Option<usize>,
+) -> usize {
+    let concurrency = available_memory / web_memory;
+    cmp::max(
+        1,
+        cpu_quota.map_or(concurrency, |cpus| cmp::min(concurrency, cpus)),
+    )
+}
+
+/// The V8 old space size for a process with `web_memory` MB, leaving a quarter
+/// of the memory for buffers, code, and other memory outside the heap.
+fn max_old_space_size(web_memory: usize) -> usize {
+    web_memory * 3 / 4
+}
+
+const DEFAULT_UV_THREADPOOL_SIZE: usize = 4;
+const MAX_UV_THREADPOOL_SIZE: usize = 1024;
+
+/// Sizes the libuv threadpool to the available CPUs, but never below the libuv
+/// default of 4 threads.
+fn uv_threadpool_size(available_cpus: Option<usize>) -> usize {
+    available_cpus.map_or(DEFAULT_UV_THREADPOOL_SIZE, |cpus| {
+        cpus.clamp(DEFAULT_UV_THREADPOOL_SIZE, MAX_UV_THREADPOOL_SIZE)
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::path::PathBuf;
+
+    fn fixture(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/cgroups")
+            .join(name)
+    }
 
     #[test]
     fn test_web_env_default() {
-        let web_env = web_env(None, None);
+        let web_env = web_env(Path::new(CGROUP_ROOT), &RuntimeConfig::default());
         let web_concurrency: usize = web_env
             .get("WEB_CONCURRENCY")
             .expect("WEB_CONCURRENCY should exist")
@@ -95,7 +224,14 @@ mod tests {
 
     #[test]
     fn test_web_env_does_not_rewrite() {
-        let web_env = web_env(Some(42), Some(4242));
+        let web_env = web_env(
+            Path::new(CGROUP_ROOT),
+            &RuntimeConfig {
+                web_concurrency: Some(42),
+                web_memory: Some(4242),
+                ..RuntimeConfig::default()
+            },
+        );
         let web_concurrency: usize = web_env
             .get("WEB_CONCURRENCY")
             .expect("WEB_CONCURRENCY should exist")
@@ -128,14 +264,89 @@ mod tests {
     #[test]
     fn test_calculate_web_concurrency() {
         // heroku standard-1x
-        assert_eq!(calculate_web_concurrency(512, 512), 1);
+        assert_eq!(calculate_web_concurrency(512, 512, None), 1);
         // heroku performance-m
-        assert_eq!(calculate_web_concurrency(2560, 512), 5);
+        assert_eq!(calculate_web_concurrency(2560, 512, None), 5);
         // heroku performance-l
-        assert_eq!(calculate_web_concurrency(14336, 512), 28);
+        assert_eq!(calculate_web_concurrency(14336, 512, None), 28);
         // large memory heavy instance
-        assert_eq!(calculate_web_concurrency(63488, 2048), 31);
+        assert_eq!(calculate_web_concurrency(63488, 2048, None), 31);
         // assert that the calculation won't select a value < 1
-        assert_eq!(calculate_web_concurrency(512, 2048), 1);
+        assert_eq!(calculate_web_concurrency(512, 2048, None), 1);
+        // CPU quotas cap the concurrency
+        assert_eq!(calculate_web_concurrency(14336, 512, Some(8)), 8);
+        assert_eq!(calculate_web_concurrency(2560, 512, Some(8)), 5);
+        assert_eq!(calculate_web_concurrency(512, 2048, Some(2)), 1);
+    }
+
+    #[test]
+    fn test_detect_limits_cgroup_v2() {
+        let root = fixture("v2");
+        assert_eq!(detect_available_memory(&root), 2048);
+        assert_eq!(detect_cpu_quota(&root), Some(2));
+
+        let root = fixture("v2-unlimited");
+        assert_eq!(detect_available_memory(&root), DEFAULT_AVAILABLE_MEMORY_MB);
+        assert_eq!(detect_cpu_quota(&root), None);
+    }
+
+    #[test]
+    fn test_detect_limits_cgroup_v1() {
+        let root = fixture("v1");
+        assert_eq!(detect_available_memory(&root), 14336);
+        assert_eq!(detect_cpu_quota(&root), Some(4));
+
+        let root = fixture("v1-unlimited");
+        assert_eq!(detect_cpu_quota(&root), None);
+
+        assert_eq!(detect_cpu_quota(&fixture("missing")), None);
+    }
+
+    #[test]
+    fn test_web_env_from_cgroup_limits() {
+        let web_env = web_env(&fixture("v1"), &RuntimeConfig::default());
+        assert_eq!(web_env["WEB_CONCURRENCY"], "4");
+        assert_eq!(web_env["WEB_MEMORY"], "512");
+        assert!(!web_env.contains_key("NODE_OPTIONS"));
+        assert!(!web_env.contains_key("UV_THREADPOOL_SIZE"));
+    }
+
+    #[test]
+    fn test_web_env_runtime_tuning() {
+        let env = web_env(
+            &fixture("v2"),
+            &RuntimeConfig {
+                node_options: Some("--enable-source-maps".to_string()),
+                auto_max_old_space_size: true,
+                auto_uv_threadpool_size: true,
+                ..RuntimeConfig::default()
+            },
+        );
+        assert_eq!(
+            env["NODE_OPTIONS"],
+            "--enable-source-maps --max-old-space-size=384"
+        );
+        assert_eq!(env["UV_THREADPOOL_SIZE"], "4");
+
+        let env = web_env(
+            &fixture("v2"),
+            &RuntimeConfig {
+                node_options: Some("--max-old-space-size=1024".to_string()),
+                uv_threadpool_size: Some(16),
+                auto_max_old_space_size: true,
+                auto_uv_threadpool_size: true,
+                ..RuntimeConfig::default()
+            },
+        );
+        assert!(!env.contains_key("NODE_OPTIONS"));
+        assert!(!env.contains_key("UV_THREADPOOL_SIZE"));
+    }
+
+    #[test]
+    fn test_uv_threadpool_size() {
+        assert_eq!(uv_threadpool_size(None), 4);
+        assert_eq!(uv_threadpool_size(Some(2)), 4);
+        assert_eq!(uv_threadpool_size(Some(16)), 16);
+        assert_eq!(uv_threadpool_size(Some(4096)), 1024);
     }
 }
@@ -7,7 +7,8 @@ use libcnb::data::layer_name;
 use libcnb::layer::{
     CachedLayerDefinition, InvalidMetadataAction, LayerState, RestoredLayerAction,
 };
-use libherokubuildpack::download::download_file;
+use libcnb::layer_env::{LayerEnv, ModificationBehavior, Scope};
+use libcnb::sbom::Sbom;
 use libherokubuildpack::fs::move_directory_contents;
 use libherokubuildpack::inventory::artifact::Artifact;
 use libherokubuildpack::log::log_info;
@@ -17,19 +18,57 @@ use sha2::{Digest, Sha256};
 use tempfile::NamedTempFile;
 use thiserror::Error;
 
+use heroku_nodejs_utils::mirror::{download_artifact, ArtifactDownloadError, ArtifactMirror};
+use heroku_nodejs_utils::node_artifact::{Libc, NodeArtifactMetadata};
+use heroku_nodejs_utils::resolved_node::{NODE_HOME_ENV_VAR, NODE_VERSION_ENV_VAR};
+use heroku_nodejs_utils::sbom::{SbomComponent, SbomDocument};
 use heroku_nodejs_utils::vrs::Version;
 
 use crate::{NodeJsEngineBuildpack, NodeJsEngineBuildpackError};
 
+/// Files in the Node.js distribution that are only needed during the build:
+/// headers for compiling native addons, docs, and man pages.
+const BUILD_ONLY_PATHS: [&str; 4] = ["include", "share", "CHANGELOG.md", "README.md"];
+
+/// The package managers bundled with the Node.js distribution, which are only
+/// installed for launch when `launch_tools` is set.
+const TOOLING_PATHS: [&str; 4] = ["lib", "bin/npm", "bin/npx", "bin/corepack"];
+
+/// Installs the Node.js runtime into the `dist` layer, which is available at
+/// build and launch, and the files only needed to build the app into the
+/// build-only `dist_build` layer.
 pub(crate) fn install_node(
     context: &BuildContext<NodeJsEngineBuildpack>,
-    distribution_artifact: &Artifact<Version, Sha256, Option<()>>,
+    distribution_artifact: &Artifact<Version, Sha256, Option<NodeArtifactMetadata>>,
+    artifact_mirror: Option<&ArtifactMirror>,
+    launch_tools: bool,
+    sbom_spdx: bool,
 ) -> Result<(), libcnb::Error<NodeJsEngineBuildpackError>> {
     let new_metadata = DistLayerMetadata {
         artifact: distribution_artifact.clone(),
         layer_version: LAYER_VERSION.to_string(),
+        launch_tools,
     };
 
+    let build_layer = context.cached_layer(
+        layer_name!("dist_build"),
+        CachedLayerDefinition {
+            build: true,
+            launch: false,
+            invalid_metadata_action: &|_| InvalidMetadataAction::DeleteLayer,
+            restored_layer_action: &|old_metadata: &DistLayerMetadata, _| {
+                if old_metadata == &new_metadata {
+                    RestoredLayerAction::KeepLayer
+                } else {
+                    RestoredLayerAction::DeleteLayer
+                }
+            },
+        },
+    )?;
+    let build_layer_restored = matches!(build_layer.state, LayerState::Restored { .. });
+
+    // Both layers are installed from the same download, so the distribution
+    // layer is only kept when the build layer could be kept as well.
     let distribution_layer = context.cached_layer(
         layer_name!("dist"),
         CachedLayerDefinition {
@@ -37,7 +76,7 @@ pub(crate) fn install_node(
             launch: true,
             invalid_metadata_action: &|_| InvalidMetadataAction::DeleteLayer,
             restored_layer_action: &|old_metadata: &DistLayerMetadata, _| {
-                if old_metadata == &new_metadata {
+                if build_layer_restored && old_metadata == &new_metadata {
                     RestoredLayerAction::KeepLayer
                 } else {
                     RestoredLayerAction::DeleteLayer
@@ -46,49 +85,214 @@ pub(crate) fn install_node(
         },
     )?;
 
-    let version_tag = format!(
-        "{} ({}-{})",
-        distribution_artifact.version, distribution_artifact.os, distribution_artifact.arch
-    );
+    let version_tag = match Libc::for_artifact(distribution_artifact.metadata.as_ref()) {
+        Libc::Glibc => format!(
+            "{} ({}-{})",
+            distribution_artifact.version, distribution_artifact.os, distribution_artifact.arch
+        ),
+        Libc::Musl => format!(
+            "{} ({}-{}-musl)",
+            distribution_artifact.version, distribution_artifact.os, distribution_artifact.arch
+        ),
+    };
     match distribution_layer.state {
         LayerState::Restored { .. } => {
             log_info(format!("Reusing Node.js {version_tag}"));
         }
         LayerState::Empty { .. } => {
-            distribution_layer.write_metadata(new_metadata)?;
+            distribution_layer.write_metadata(new_metadata.clone())?;
+            build_layer.write_metadata(new_metadata)?;
 
-            let node_tgz = NamedTempFile::new().map_err(DistLayerError::TempFile)?;
+            download_distribution(
+                distribution_artifact,
+                artifact_mirror,
+                &version_tag,
+                &distribution_layer.path(),
+            )?;
+            split_build_only_paths(
+                &distribution_layer.path(),
+                &build_layer.path(),
+                launch_tools,
+            )
+            .map_err(DistLayerError::Installation)?;
+        }
+    };
 
-            log_info(format!(
-                "Downloading Node.js {} from {}",
-                version_tag, distribution_artifact.url
-            ));
-            download_file(&distribution_artifact.url, node_tgz.path())
-                .map_err(DistLayerError::Download)?;
+    let excluded_bytes = disk_usage(&build_layer.path()).map_err(DistLayerError::DiskUsage)?;
+    log_info(format!(
+        "Excluded {} of build-only files from the launch image{}",
+        format_size(excluded_bytes),
+        if launch_tools {
+            " (keeping npm, npx, and corepack)"
+        } else {
+            ""
+        }
+    ));
 
-            log_info("Verifying checksum");
-            let digest = sha256(node_tgz.path()).map_err(DistLayerError::ReadTempFile)?;
-            if distribution_artifact.checksum.value != digest {
-                Err(DistLayerError::ChecksumVerification)?;
-            }
+    if let Some(node_gyp_env) = node_gyp_env(&build_layer.path()) {
+        log_info("Configuring node-gyp to build native addons with the installed Node.js headers");
+        build_layer.write_env(node_gyp_env)?;
+    }
 
-            log_info(format!("Extracting Node.js {version_tag}"));
-            decompress_tarball(&mut node_tgz.into_file(), distribution_layer.path())
-                .map_err(DistLayerError::Untar)?;
+    // These are only published to later buildpacks. `NODE_HOME` is the
+    // runtime layer, so it doesn't contain the build-only files (headers,
+    // bundled package managers) that were moved into the build layer.
+    distribution_layer.write_env(
+        LayerEnv::new()
+            .chainable_insert(
+                Scope::Build,
+                ModificationBehavior::Override,
+                NODE_VERSION_ENV_VAR,
+                distribution_artifact.version.to_string(),
+            )
+            .chainable_insert(
+                Scope::Build,
+                ModificationBehavior::Override,
+                NODE_HOME_ENV_VAR,
+                distribution_layer.path(),
+            ),
+    )?;
 
-            log_info(format!("Installing Node.js {version_tag}"));
+    // Layer SBOMs aren't cached, so they're written on every build.
+    distribution_layer.write_sboms(&node_sboms(distribution_artifact, sbom_spdx))?;
 
-            let dist_name = extract_tarball_prefix(&distribution_artifact.url)
-                .ok_or_else(|| DistLayerError::TarballPrefix(distribution_artifact.url.clone()))?;
-            let dist_path = distribution_layer.path().join(dist_name);
-            move_directory_contents(dist_path, distribution_layer.path())
-                .map_err(DistLayerError::Installation)?;
-        }
+    Ok(())
+}
+
+/// Lists the Node.js runtime itself, as downloaded from its upstream URL.
+fn node_sboms(
+    distribution_artifact: &Artifact<Version, Sha256, Option<NodeArtifactMetadata>>,
+    spdx: bool,
+) -> Vec<Sbom> {
+    SbomDocument {
+        name: "node".to_string(),
+        components: vec![SbomComponent::node_runtime(
+            &distribution_artifact.version.to_string(),
+            &distribution_artifact.url,
+            &distribution_artifact.checksum.value,
+        )],
+        direct_dependencies: vec![],
+    }
+    .sboms(spdx)
+}
+
+/// node-gyp reads its configuration from `npm_config_*` environment variables
+/// no matter which package manager runs it, so this covers npm, Yarn, and pnpm.
+const NODE_GYP_NODEDIR_ENV_VAR: &str = "npm_config_nodedir";
+
+/// Points node-gyp at the headers in the build-only layer, so native addons are
+/// built without downloading headers from nodejs.org. Returns `None` when the
+/// distribution doesn't include headers.
+fn node_gyp_env(build_path: &Path) -> Option<LayerEnv> {
+    build_path.join("include/node").is_dir().then(|| {
+        LayerEnv::new().chainable_insert(
+            Scope::Build,
+            ModificationBehavior::Override,
+            NODE_GYP_NODEDIR_ENV_VAR,
+            build_path,
+        )
+    })
+}
+
+/// Downloads the Node.js distribution, verifies its checksum, and extracts it
+/// into `destination`.
+fn download_distribution(
+    distribution_artifact: &Artifact<Version, Sha256, Option<NodeArtifactMetadata>>,
+    artifact_mirror: Option<&ArtifactMirror>,
+    version_tag: &str,
+    destination: &Path,
+) -> Result<(), libcnb::Error<NodeJsEngineBuildpackError>> {
+    let node_tgz = NamedTempFile::new().map_err(DistLayerError::TempFile)?;
+
+    let download_url = if let Some(mirror) = artifact_mirror {
+        let url = mirror
+            .rewrite(&distribution_artifact.url)
+            .map_err(NodeJsEngineBuildpackError::ArtifactMirrorError)?;
+        log_info(format!(
+            "Downloading Node.js {version_tag} from {url} (mirror {mirror})"
+        ));
+        url
+    } else {
+        log_info(format!(
+            "Downloading Node.js {version_tag} from {}",
+            distribution_artifact.url
+        ));
+        distribution_artifact.url.clone()
     };
+    download_artifact(&download_url, node_tgz.path()).map_err(DistLayerError::Download)?;
+
+    log_info("Verifying checksum");
+    let digest = sha256(node_tgz.path()).map_err(DistLayerError::ReadTempFile)?;
+    if distribution_artifact.checksum.value != digest {
+        Err(DistLayerError::ChecksumVerification)?;
+    }
+
+    log_info(format!("Extracting Node.js {version_tag}"));
+    decompress_tarball(&mut node_tgz.into_file(), destination).map_err(DistLayerError::Untar)?;
 
+    log_info(format!("Installing Node.js {version_tag}"));
+
+    let dist_name = extract_tarball_prefix(&distribution_artifact.url)
+        .ok_or_else(|| DistLayerError::TarballPrefix(distribution_artifact.url.clone()))?;
+    let dist_path = destination.join(dist_name);
+    move_directory_contents(dist_path, destination).map_err(DistLayerError::Installation)?;
+    Ok(())
+}
+
+/// Moves the files that are only needed during the build from the extracted
+/// distribution to the build-only layer.
+fn split_build_only_paths(
+    distribution_path: &Path,
+    build_path: &Path,
+    launch_tools: bool,
+) -> Result<(), std::io::Error> {
+    let build_only_paths = if launch_tools {
+        BUILD_ONLY_PATHS.to_vec()
+    } else {
+        [BUILD_ONLY_PATHS, TOOLING_PATHS].concat()
+    };
+    for path in build_only_paths {
+        move_path(&distribution_path.join(path), &build_path.join(path))?;
+    }
     Ok(())
 }
 
+/// Moves a file or directory, replacing anything already at `destination`.
+/// Missing sources are skipped, as not every distribution contains every file.
+fn move_path(source: &Path, destination: &Path) -> Result<(), std::io::Error> {
+    if fs::symlink_metadata(source).is_err() {
+        return Ok(());
+    }
+    match fs::symlink_metadata(destination) {
+        Ok(metadata) if metadata.is_dir() => fs::remove_dir_all(destination)?,
+        Ok(_) => fs::remove_file(destination)?,
+        Err(_) => {}
+    }
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::rename(source, destination)
+}
+
+/// Sums the size of the files in a directory, without following symlinks.
+fn disk_usage(path: &Path) -> Result<u64, std::io::Error> {
+    let metadata = fs::symlink_metadata(path)?;
+    if metadata.is_dir() {
+        fs::read_dir(path)?.try_fold(0, |total, entry| Ok(total + disk_usage(&entry?.path())?))
+    } else {
+        Ok(metadata.len())
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    let tenths_of_mib = bytes * 10 / (1024 * 1024);
+    format!(
+        "{}.{} MiB ({bytes} bytes)",
+        tenths_of_mib / 10,
+        tenths_of_mib % 10
+    )
+}
+
 fn sha256(path: impl AsRef<Path>) -> Result<Vec<u8>, std::io::Error> {
     let mut file = fs::File::open(path.as_ref())?;
     let mut buffer = [0x00; 10 * 1024];
@@ -110,13 +314,14 @@ fn extract_tarball_prefix(url: &str) -> Option<&str> {
     })
 }
 
-const LAYER_VERSION: &str = "1";
+const LAYER_VERSION: &str = "2";
 
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct DistLayerMetadata {
-    artifact: Artifact<Version, Sha256, Option<()>>,
+    artifact: Artifact<Version, Sha256, Option<NodeArtifactMetadata>>,
     layer_version: String,
+    launch_tools: bool,
 }
 
 #[derive(Error, Debug)]
@@ -124,7 +329,7 @@ pub(crate) enum DistLayerError {
     #[error("Couldn't create tempfile for Node.js distribution: {0}")]
     TempFile(std::io::Error),
     #[error("Couldn't download Node.js distribution: {0}")]
-    Download(libherokubuildpack::download::DownloadError),
+    Download(ArtifactDownloadError),
     #[error("Couldn't decompress Node.js distribution: {0}")]
     Untar(std::io::Error),
     #[error("Couldn't extract tarball prefix from artifact URL: {0}")]
@@ -135,6 +340,8 @@ pub(crate) enum DistLayerError {
     ChecksumVerification,
     #[error("Couldn't read tempfile for Node.js distribution: {0}")]
     ReadTempFile(std::io::Error),
+    #[error("Couldn't measure the size of build-only Node.js files: {0}")]
+    DiskUsage(std::io::Error),
 }
 
 impl From<DistLayerError> for libcnb::Error<NodeJsEngineBuildpackError> {
@@ -183,11 +390,27 @@ mod tests {
         assert_eq!(
             DistLayerMetadata {
                 artifact: node_version_22_1_0_linux_arm.clone(),
-                layer_version: LAYER_VERSION.to_string()
+                layer_version: LAYER_VERSION.to_string(),
+                launch_tools: false,
             },
             DistLayerMetadata {
                 artifact: node_version_22_1_0_linux_arm.clone(),
-                layer_version: LAYER_VERSION.to_string()
+                layer_version: LAYER_VERSION.to_string(),
+                launch_tools: false,
+            }
+        );
+
+        // this is a check to ensure that keeping npm at launch does invalidate the cache
+        assert_ne!(
+            DistLayerMetadata {
+                artifact: node_version_22_1_0_linux_arm.clone(),
+                layer_version: LAYER_VERSION.to_string(),
+                launch_tools: false,
+            },
+            DistLayerMetadata {
+                artifact: node_version_22_1_0_linux_arm.clone(),
+                layer_version: LAYER_VERSION.to_string(),
+                launch_tools: true,
             }
         );
 
@@ -195,11 +418,13 @@ mod tests {
         assert_ne!(
             DistLayerMetadata {
                 artifact: node_version_22_1_0_linux_arm,
-                layer_version: LAYER_VERSION.to_string()
+                layer_version: LAYER_VERSION.to_string(),
+                launch_tools: false,
             },
             DistLayerMetadata {
                 artifact: node_version_22_1_0_linux_amd,
-                layer_version: LAYER_VERSION.to_string()
+                layer_version: LAYER_VERSION.to_string(),
+                launch_tools: false,
             }
         );
     }
@@ -219,10 +444,12 @@ mod tests {
                 metadata: None,
             },
             layer_version: LAYER_VERSION.to_string(),
+            launch_tools: false,
         };
         let actual = toml::to_string(&metadata).unwrap();
         let expected = r#"
-layer_version = "1"
+layer_version = "2"
+launch_tools = false
 
 [artifact]
 version = "22.1.0"
@@ -236,4 +463,50 @@ checksum = "sha256:d8ae35a9e2bb0c0c0611ee9bacf564ea51cc8291ace1447f95ee6aeaf4f1d
         let from_toml: DistLayerMetadata = toml::from_str(&actual).unwrap();
         assert_eq!(metadata, from_toml);
     }
+
+    #[test]
+    fn move_build_only_paths() {
+        let layers = tempfile::tempdir().unwrap();
+        let dist = layers.path().join("dist");
+        let build = layers.path().join("dist_build");
+        fs::create_dir_all(dist.join("include/node")).unwrap();
+        fs::write(dist.join("include/node/node.h"), "#define NODE").unwrap();
+        fs::create_dir_all(build.join("include")).unwrap();
+        fs::write(build.join("include/stale.h"), "stale").unwrap();
+
+        move_path(&dist.join("include"), &build.join("include")).unwrap();
+        move_path(&dist.join("share"), &build.join("share")).unwrap();
+
+        assert!(!dist.join("include").exists());
+        assert!(!build.join("include/stale.h").exists());
+        assert!(!build.join("share").exists());
+        assert_eq!(disk_usage(&build).unwrap(), 12);
+    }
+
+    #[test]
+    fn format_sizes() {
+        assert_eq!(format_size(12), "0.0 MiB (12 bytes)");
+        assert_eq!(format_size(52_428_800), "50.0 MiB (52428800 bytes)");
+        assert_eq!(format_size(1_572_864), "1.5 MiB (1572864 bytes)");
+    }
+
+    #[test]
+    fn node_gyp_env_points_at_headers() {
+        let build = tempfile::tempdir().unwrap();
+        assert!(node_gyp_env(build.path()).is_none());
+
+        fs::create_dir_all(build.path().join("include/node")).unwrap();
+        let env = node_gyp_env(build.path())
+            .unwrap()
+            .apply(Scope::Build, &libcnb::Env::new());
+        assert_eq!(
+            env.get(NODE_GYP_NODEDIR_ENV_VAR),
+            Some(&build.path().as_os_str().to_os_string())
+        );
+        assert!(node_gyp_env(build.path())
+            .unwrap()
+            .apply(Scope::Launch, &libcnb::Env::new())
+            .get(NODE_GYP_NODEDIR_ENV_VAR)
+            .is_none());
+    }
 }
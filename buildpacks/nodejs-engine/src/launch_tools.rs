@@ -0,0 +1,254 @@
+use heroku_nodejs_utils::package_json::PackageJson;
+use heroku_nodejs_utils::procfile::Procfile;
+use heroku_nodejs_utils::workspaces::{find_workspaces, select_workspace};
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+/// Environment variable used to keep (`true`) or exclude (`false`) npm, npx,
+/// and corepack from the launch image regardless of what the app's processes
+/// use.
+pub(crate) const LAUNCH_TOOLS_ENV_VAR: &str = "NODEJS_LAUNCH_TOOLS";
+
+const PACKAGE_MANAGER_COMMANDS: [&str; 5] = ["npm", "npx", "yarn", "pnpm", "corepack"];
+
+/// Why the package managers bundled with Node.js are kept in the launch image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum LaunchToolsReason {
+    Configured,
+    Process {
+        process_type: String,
+        source: &'static str,
+    },
+    DefaultWebProcess {
+        workspace: Option<String>,
+    },
+}
+
+impl Display for LaunchToolsReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LaunchToolsReason::Configured => write!(f, "{LAUNCH_TOOLS_ENV_VAR}=true"),
+            LaunchToolsReason::Process {
+                process_type,
+                source,
+            } => {
+                write!(f, "the `{process_type}` process in {source} uses them")
+            }
+            LaunchToolsReason::DefaultWebProcess { workspace: None } => {
+                write!(f, "the default web process runs the `start` script")
+            }
+            LaunchToolsReason::DefaultWebProcess {
+                workspace: Some(workspace),
+            } => {
+                write!(
+                    f,
+                    "the default web process runs the `start` script of the `{workspace}` workspace"
+                )
+            }
+        }
+    }
+}
+
+/// Determines whether npm, npx, and corepack need to be available at launch.
+/// They're kept when configured with `NODEJS_LAUNCH_TOOLS=true`, when a
+/// process declared in the Procfile or project.toml runs a package manager,
+/// or when there are none and the package manager buildpacks will add a web
+/// process for the `start` script. When an npm workspace is selected, that's
+/// the `start` script of the workspace instead of the root package.
+///
+/// # Errors
+///
+/// Returns the configured value if it isn't `true` or `false`.
+pub(crate) fn launch_tools_reason(
+    app_dir: &Path,
+    procfile: Option<&(Procfile, &'static str)>,
+    npm_workspace: Option<&str>,
+    configured: Option<&str>,
+) -> Result<Option<LaunchToolsReason>, String> {
+    match configured.map(str::trim) {
+        Some("true") => return Ok(Some(LaunchToolsReason::Configured)),
+        Some("false") => return Ok(None),
+        Some(value) => return Err(value.to_string()),
+        None => {}
+    }
+
+    if let Some((procfile, source)) = procfile {
+        return Ok(procfile.processes.iter().find_map(|process| {
+            process
+                .command
+                .split_whitespace()
+                .any(|word| PACKAGE_MANAGER_COMMANDS.contains(&word))
+                .then(|| LaunchToolsReason::Process {
+                    process_type: process.process_type.to_string(),
+                    source,
+                })
+        }));
+    }
+
+    Ok(start_package_json(app_dir, npm_workspace)
+        .is_some_and(|package_json| package_json.has_start_script())
+        .then(|| LaunchToolsReason::DefaultWebProcess {
+            workspace: npm_workspace.map(ToString::to_string),
+        }))
+}
+
+/// Reads the `package.json` whose `start` script the default web process runs.
+/// Returns `None` if it can't be read, including when the selected workspace
+/// doesn't exist, which the npm install buildpack reports.
+fn start_package_json(app_dir: &Path, npm_workspace: Option<&str>) -> Option<PackageJson> {
+    let package_json = PackageJson::read(app_dir.join("package.json")).ok()?;
+    let Some(selector) = npm_workspace else {
+        return Some(package_json);
+    };
+    let workspaces = find_workspaces(app_dir, package_json.workspaces.as_ref()?).ok()?;
+    select_workspace(&workspaces, selector)
+        .ok()
+        .map(|workspace| workspace.package_json.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn app(files: &[(&str, &str)]) -> tempfile::TempDir {
+        let app_dir = tempfile::tempdir().unwrap();
+        for (name, contents) in files {
+            fs::write(app_dir.path().join(name), contents).unwrap();
+        }
+        app_dir
+    }
+
+    fn procfile(app_dir: &tempfile::TempDir) -> Option<(Procfile, &'static str)> {
+        Procfile::read(app_dir.path())
+            .unwrap()
+            .map(|procfile| (procfile, "Procfile"))
+    }
+
+    #[test]
+    fn launch_tools_configured() {
+        let app_dir = app(&[("Procfile", "web: npm start")]);
+        assert_eq!(
+            launch_tools_reason(
+                app_dir.path(),
+                procfile(&app_dir).as_ref(),
+                None,
+                Some("true")
+            )
+            .unwrap(),
+            Some(LaunchToolsReason::Configured)
+        );
+        assert_eq!(
+            launch_tools_reason(
+                app_dir.path(),
+                procfile(&app_dir).as_ref(),
+                None,
+                Some("false")
+            )
+            .unwrap(),
+            None
+        );
+        assert_eq!(
+            launch_tools_reason(
+                app_dir.path(),
+                procfile(&app_dir).as_ref(),
+                None,
+                Some("yes")
+            )
+            .unwrap_err(),
+            "yes"
+        );
+    }
+
+    #[test]
+    fn launch_tools_for_procfile_processes() {
+        let app_dir = app(&[
+            (
+                "Procfile",
+                "web: node server.js\nworker: cd jobs && yarn run work\n",
+            ),
+            (
+                "package.json",
+                r#"{ "scripts": { "start": "node server.js" } }"#,
+            ),
+        ]);
+        assert_eq!(
+            launch_tools_reason(app_dir.path(), procfile(&app_dir).as_ref(), None, None).unwrap(),
+            Some(LaunchToolsReason::Process {
+                process_type: "worker".to_string(),
+                source: "Procfile"
+            })
+        );
+
+        let app_dir = app(&[
+            ("Procfile", "web: node server.js"),
+            (
+                "package.json",
+                r#"{ "scripts": { "start": "node server.js" } }"#,
+            ),
+        ]);
+        assert_eq!(
+            launch_tools_reason(app_dir.path(), procfile(&app_dir).as_ref(), None, None).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn launch_tools_for_default_web_process() {
+        let app_dir = app(&[(
+            "package.json",
+            r#"{ "scripts": { "start": "node server.js" } }"#,
+        )]);
+        assert_eq!(
+            launch_tools_reason(app_dir.path(), procfile(&app_dir).as_ref(), None, None).unwrap(),
+            Some(LaunchToolsReason::DefaultWebProcess { workspace: None })
+        );
+
+        let app_dir = app(&[("package.json", "{}"), ("index.js", "")]);
+        assert_eq!(
+            launch_tools_reason(app_dir.path(), procfile(&app_dir).as_ref(), None, None).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn launch_tools_for_npm_workspace_web_process() {
+        let app_dir = app(&[(
+            "package.json",
+            r#"{ "workspaces": ["packages/*"], "scripts": { "start": "node server.js" } }"#,
+        )]);
+        for (name, package_json) in [
+            (
+                "api",
+                r#"{ "name": "api", "scripts": { "start": "npm run serve" } }"#,
+            ),
+            ("web", r#"{ "name": "web" }"#),
+        ] {
+            let workspace_dir = app_dir.path().join("packages").join(name);
+            fs::create_dir_all(&workspace_dir).unwrap();
+            fs::write(workspace_dir.join("package.json"), package_json).unwrap();
+        }
+
+        assert_eq!(
+            launch_tools_reason(app_dir.path(), None, Some("api"), None).unwrap(),
+            Some(LaunchToolsReason::DefaultWebProcess {
+                workspace: Some("api".to_string())
+            })
+        );
+        assert_eq!(
+            launch_tools_reason(app_dir.path(), None, Some("packages/api"), None).unwrap(),
+            Some(LaunchToolsReason::DefaultWebProcess {
+                workspace: Some("packages/api".to_string())
+            })
+        );
+        // The root `start` script isn't run when a workspace is selected.
+        assert_eq!(
+            launch_tools_reason(app_dir.path(), None, Some("web"), None).unwrap(),
+            None
+        );
+        assert_eq!(
+            launch_tools_reason(app_dir.path(), None, Some("missing"), None).unwrap(),
+            None
+        );
+    }
+}
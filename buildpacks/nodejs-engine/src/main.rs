@@ -1,26 +1,44 @@
-use std::env::consts;
-
-use crate::attach_runtime_metrics::{attach_runtime_metrics, NodeRuntimeMetricsError};
+use crate::attach_runtime_metrics::{
+    attach_runtime_metrics, runtime_metrics_exporter, NodeRuntimeMetricsError,
+};
 use crate::configure_web_env::configure_web_env;
 use crate::install_node::{install_node, DistLayerError};
+use crate::launch_tools::{launch_tools_reason, LAUNCH_TOOLS_ENV_VAR};
+use crate::web_process::{entry_points, EntryPoint, ROOT_ENTRY_POINTS};
+use chrono::{NaiveDate, Utc};
+use heroku_nodejs_utils::buildplan::{
+    read_node_version_requirements, NodeVersionMetadataError, NODE_BUILD_PLAN_NAME,
+};
+use heroku_nodejs_utils::config::{AppRoot, ConfigError, NodejsConfig};
+use heroku_nodejs_utils::mirror::{ArtifactMirror, ArtifactMirrorError, ARTIFACT_MIRROR_ENV_VAR};
+use heroku_nodejs_utils::node_artifact::{
+    Libc, NodeArtifactMetadata, NodeArtifactRequirement, PrereleaseChannel, PrereleaseChannelError,
+    PRERELEASE_CHANNEL_ENV_VAR,
+};
+use heroku_nodejs_utils::node_version_source::{
+    detect_node_versions, NodeRequirement, NodeVersionSourceError,
+};
 use heroku_nodejs_utils::package_json::{PackageJson, PackageJsonError};
+use heroku_nodejs_utils::procfile::{Procfile, ProcfileError};
+use heroku_nodejs_utils::release_schedule::{ReleaseSchedule, SupportStatus};
 use heroku_nodejs_utils::vrs::{Requirement, Version};
 use libcnb::build::{BuildContext, BuildResult, BuildResultBuilder};
 use libcnb::data::build_plan::BuildPlanBuilder;
-use libcnb::data::launch::{LaunchBuilder, ProcessBuilder};
+use libcnb::data::launch::{Launch, LaunchBuilder, ProcessBuilder};
 use libcnb::data::process_type;
 use libcnb::detect::{DetectContext, DetectResult, DetectResultBuilder};
 use libcnb::generic::GenericMetadata;
 use libcnb::generic::GenericPlatform;
-use libcnb::{buildpack_main, Buildpack};
+use libcnb::{buildpack_main, Buildpack, Platform};
 #[cfg(test)]
 use libcnb_test as _;
 use libherokubuildpack::inventory::artifact::{Arch, Os};
 use libherokubuildpack::inventory::Inventory;
-use libherokubuildpack::log::{log_error, log_header, log_info};
+use libherokubuildpack::log::{log_error, log_header, log_info, log_warning};
 #[cfg(test)]
 use serde_json as _;
 use sha2::Sha256;
+use std::path::{Path, PathBuf};
 #[cfg(test)]
 use test_support as _;
 use thiserror::Error;
@@ -30,11 +48,11 @@ use ureq as _;
 mod attach_runtime_metrics;
 mod configure_web_env;
 mod install_node;
+mod launch_tools;
+mod web_process;
 
 const INVENTORY: &str = include_str!("../inventory.toml");
 
-const LTS_VERSION: &str = "22.x";
-
 const MINIMUM_NODE_VERSION_FOR_METRICS: &str = ">=14.10";
 
 struct NodeJsEngineBuildpack;
@@ -54,10 +72,13 @@ impl Buildpack for NodeJsEngineBuildpack {
         // If there are common node artifacts, this buildpack should both
         // provide and require node so that it may be used without other
         // buildpacks.
-        if ["package.json", "index.js", "server.js"]
-            .map(|name| context.app_dir.join(name))
-            .iter()
-            .any(|path| path.exists())
+        let app_dir = NodejsConfig::read(&context.app_dir)
+            .and_then(|config| config.app_root(context.platform.env()))
+            .map_err(NodeJsEngineBuildpackError::ConfigError)?
+            .dir(&context.app_dir);
+        if std::iter::once("package.json")
+            .chain(ROOT_ENTRY_POINTS)
+            .any(|name| app_dir.join(name).exists())
         {
             plan_builder = plan_builder.requires("node");
         }
@@ -72,49 +93,106 @@ impl Buildpack for NodeJsEngineBuildpack {
 
     fn build(&self, context: BuildContext<Self>) -> libcnb::Result<BuildResult, Self::Error> {
         log_header("Heroku Node.js Engine Buildpack");
+
+        let config = NodejsConfig::read(&context.app_dir)
+            .map_err(NodeJsEngineBuildpackError::ConfigError)?;
+        for warning in config.warnings() {
+            log_warning("Unknown project.toml configuration", warning);
+        }
+        let (app_root, app_dir) = enter_app_root(&context, &config)?;
+
         log_header("Checking Node.js version");
 
-        let inv: Inventory<Version, Sha256, Option<()>> =
+        let inv: Inventory<Version, Sha256, Option<NodeArtifactMetadata>> =
             toml::from_str(INVENTORY).map_err(NodeJsEngineBuildpackError::InventoryParseError)?;
+        let release_schedule: ReleaseSchedule =
+            toml::from_str(INVENTORY).map_err(NodeJsEngineBuildpackError::InventoryParseError)?;
+        let today = Utc::now().date_naive();
 
-        let requested_version_range = PackageJson::read(context.app_dir.join("package.json"))
-            .map_err(NodeJsEngineBuildpackError::PackageJsonError)
-            .map(|package_json| package_json.engines.and_then(|e| e.node))?;
+        let version_range = resolve_version_range(&context, &app_dir, &release_schedule, today)?;
 
-        let version_range = if let Some(value) = requested_version_range {
-            log_info(format!("Detected Node.js version range: {value}"));
-            value
-        } else {
-            log_info(format!(
-                "Node.js version not specified, using {LTS_VERSION}"
-            ));
-            Requirement::parse(LTS_VERSION).expect("The default Node.js version should be valid")
+        let (Ok(os), Ok(arch)) = (
+            context.target.os.parse::<Os>(),
+            context.target.arch.parse::<Arch>(),
+        ) else {
+            Err(NodeJsEngineBuildpackError::UnsupportedTargetError(
+                context.target.os.clone(),
+                context.target.arch.clone(),
+            ))?
         };
+        let libc = Libc::for_distro(&context.target.distro_name);
+        let prerelease_channel = read_prerelease_channel(&context)?;
 
-        let target_artifact = match (consts::OS.parse::<Os>(), consts::ARCH.parse::<Arch>()) {
-            (Ok(os), Ok(arch)) => inv.resolve(os, arch, &version_range),
-            (_, _) => None,
-        }
-        .ok_or(NodeJsEngineBuildpackError::UnknownVersionError(
-            version_range.to_string(),
-        ))?;
+        let target_artifact = inv
+            .resolve(
+                os,
+                arch,
+                &NodeArtifactRequirement {
+                    version: version_range.clone(),
+                    libc,
+                    prerelease_channel,
+                },
+            )
+            .ok_or(NodeJsEngineBuildpackError::UnknownVersionError(format!(
+                "{version_range} ({os}-{arch}, {libc})"
+            )))?;
 
         log_info(format!(
             "Resolved Node.js version: {}",
             target_artifact.version
         ));
 
+        warn_on_prerelease(&target_artifact.version);
+        warn_on_end_of_life(&release_schedule, &target_artifact.version, today);
+
+        let artifact_mirror = context
+            .platform
+            .env()
+            .get_string_lossy(ARTIFACT_MIRROR_ENV_VAR)
+            .map(|mirror| ArtifactMirror::parse(&mirror))
+            .transpose()
+            .map_err(NodeJsEngineBuildpackError::ArtifactMirrorError)?;
+
+        let runtime_metrics = config
+            .runtime_metrics(context.platform.env())
+            .map_err(NodeJsEngineBuildpackError::ConfigError)?;
+
+        let procfile = config
+            .declared_processes(&context.app_dir)
+            .map_err(NodeJsEngineBuildpackError::ProcfileError)?;
+        let launch_tools = read_launch_tools(&context, &app_dir, &config, procfile.as_ref())?;
+        let sbom_spdx = config
+            .sbom_spdx(context.platform.env())
+            .map_err(NodeJsEngineBuildpackError::ConfigError)?;
+
         log_header("Installing Node.js distribution");
-        install_node(&context, target_artifact)?;
+        install_node(
+            &context,
+            target_artifact,
+            artifact_mirror.as_ref(),
+            launch_tools,
+            sbom_spdx.value,
+        )?;
 
         configure_web_env(&context)?;
 
-        if Requirement::parse(MINIMUM_NODE_VERSION_FOR_METRICS)
+        if !runtime_metrics.value {
+            log_info(format!(
+                "Not installing application metrics scripts ({})",
+                runtime_metrics.reason("runtime_metrics")
+            ));
+        } else if Requirement::parse(MINIMUM_NODE_VERSION_FOR_METRICS)
             .expect("should be a valid version range")
             .satisfies(&target_artifact.version)
         {
-            log_info("Installing application metrics scripts");
-            attach_runtime_metrics(&context)?;
+            if let Some(exporter) = runtime_metrics_exporter(context.platform.env()) {
+                log_info(format!(
+                    "Installing application metrics scripts (exporter: {exporter})"
+                ));
+                attach_runtime_metrics(&context, exporter)?;
+            } else {
+                log_info("Not installing application metrics scripts (OTEL_METRICS_EXPORTER=none)");
+            }
         } else {
             log_info(format!(
                 "Not installing application metrics scripts, it is unsupported for Node.js {}",
@@ -122,25 +200,10 @@ impl Buildpack for NodeJsEngineBuildpack {
             ));
         }
 
-        let launchjs = ["server.js", "index.js"]
-            .map(|name| context.app_dir.join(name))
-            .iter()
-            .find(|path| path.exists())
-            .map(|path| {
-                LaunchBuilder::new()
-                    .process(
-                        ProcessBuilder::new(
-                            process_type!("web"),
-                            ["node", &path.to_string_lossy()],
-                        )
-                        .default(true)
-                        .build(),
-                    )
-                    .build()
-            });
+        let launch = configure_launch(&app_dir, &app_root, procfile);
 
         let resulter = BuildResultBuilder::new();
-        match launchjs {
+        match launch {
             Some(l) => resulter.launch(l).build(),
             None => resulter.build(),
         }
@@ -151,7 +214,9 @@ impl Buildpack for NodeJsEngineBuildpack {
             libcnb::Error::BuildpackError(bp_err) => {
                 let err_string = bp_err.to_string();
                 match bp_err {
-                    NodeJsEngineBuildpackError::DistLayerError(_) => {
+                    NodeJsEngineBuildpackError::DistLayerError(_)
+                    | NodeJsEngineBuildpackError::ArtifactMirrorError(_)
+                    | NodeJsEngineBuildpackError::LaunchToolsConfigError(_) => {
                         log_error("Node.js engine distribution error", err_string);
                     }
                     NodeJsEngineBuildpackError::InventoryParseError(_) => {
@@ -160,7 +225,19 @@ impl Buildpack for NodeJsEngineBuildpack {
                     NodeJsEngineBuildpackError::PackageJsonError(_) => {
                         log_error("Node.js engine package.json error", err_string);
                     }
-                    NodeJsEngineBuildpackError::UnknownVersionError(_) => {
+                    NodeJsEngineBuildpackError::ConfigError(_) => {
+                        log_error("Node.js engine configuration error", err_string);
+                    }
+                    NodeJsEngineBuildpackError::ProcfileError(_) => {
+                        log_error("Node.js engine Procfile error", err_string);
+                    }
+                    NodeJsEngineBuildpackError::UnknownVersionError(_)
+                    | NodeJsEngineBuildpackError::UnsupportedTargetError(..)
+                    | NodeJsEngineBuildpackError::DefaultVersionError
+                    | NodeJsEngineBuildpackError::BuildPlanVersionConflict { .. }
+                    | NodeJsEngineBuildpackError::NodeVersionMetadataError(_)
+                    | NodeJsEngineBuildpackError::PrereleaseChannelError(_)
+                    | NodeJsEngineBuildpackError::NodeVersionSourceError(_) => {
                         log_error("Node.js engine version error", err_string);
                     }
                     NodeJsEngineBuildpackError::NodeRuntimeMetricsError(_) => {
@@ -175,6 +252,275 @@ impl Buildpack for NodeJsEngineBuildpack {
     }
 }
 
+/// Resolves the app root and runs the rest of the build in it.
+fn enter_app_root(
+    context: &BuildContext<NodeJsEngineBuildpack>,
+    config: &NodejsConfig,
+) -> Result<(AppRoot, PathBuf), NodeJsEngineBuildpackError> {
+    let app_root = config
+        .app_root(context.platform.env())
+        .map_err(NodeJsEngineBuildpackError::ConfigError)?;
+    if !app_root.is_app_dir() {
+        log_info(format!("Using app root `{app_root}`"));
+    }
+    let app_dir = app_root
+        .enter(&context.app_dir)
+        .map_err(NodeJsEngineBuildpackError::ConfigError)?;
+    Ok((app_root, app_dir))
+}
+
+/// Uses the processes from the Procfile or project.toml when there are any,
+/// otherwise a default web process that runs the detected entry point with `node`
+/// in the app root.
+fn configure_launch(
+    app_dir: &Path,
+    app_root: &AppRoot,
+    procfile: Option<(Procfile, &'static str)>,
+) -> Option<Launch> {
+    match procfile {
+        Some((procfile, _)) if procfile.processes.is_empty() => {
+            log_info("Skipping default web process (Procfile detected)");
+            None
+        }
+        Some((procfile, source)) => {
+            for process in &procfile.processes {
+                log_info(format!(
+                    "Adding `{}` process from {source}: `{}`",
+                    process.process_type, process.command
+                ));
+            }
+            Some(procfile.launch())
+        }
+        None => default_web_process(app_dir).map(|entry_point| {
+            LaunchBuilder::new()
+                .process(
+                    ProcessBuilder::new(
+                        process_type!("web"),
+                        ["node", &app_dir.join(&entry_point.path).to_string_lossy()],
+                    )
+                    .working_directory(app_root.working_directory())
+                    .default(true)
+                    .build(),
+                )
+                .build()
+        }),
+    }
+}
+
+/// Picks the file to run with `node` as the default web process, explaining
+/// the choice in the build log and warning when there's more than one candidate.
+fn default_web_process(app_dir: &Path) -> Option<EntryPoint> {
+    let package_json = PackageJson::read(app_dir.join("package.json")).ok();
+    let mut entry_points = entry_points(app_dir, package_json.as_ref()).into_iter();
+    let entry_point = entry_points.next()?;
+    log_info(format!("Using {entry_point} as the default web process"));
+    let others = entry_points
+        .map(|other| other.to_string())
+        .collect::<Vec<_>>();
+    if !others.is_empty() {
+        log_warning(
+            "Multiple default web process candidates",
+            format!(
+                "Found other files that could run the default web process: {}. Using `{}`. \
+                To run a different file, set `main` in package.json, add a `start` script, \
+                or declare a `web` process in a Procfile.",
+                others.join(", "),
+                entry_point.path.display()
+            ),
+        );
+    }
+    Some(entry_point)
+}
+
+/// Determines the Node.js version range to install from the versions requested
+/// by the application and the requirements of other buildpacks in the build plan.
+fn resolve_version_range(
+    context: &BuildContext<NodeJsEngineBuildpack>,
+    app_dir: &Path,
+    release_schedule: &ReleaseSchedule,
+    today: NaiveDate,
+) -> Result<Requirement, NodeJsEngineBuildpackError> {
+    let package_json = PackageJson::read(app_dir.join("package.json"))
+        .map_err(NodeJsEngineBuildpackError::PackageJsonError)?;
+
+    let detected_versions = detect_node_versions(app_dir, &package_json, release_schedule, today)
+        .map_err(NodeJsEngineBuildpackError::NodeVersionSourceError)?;
+
+    let requested_range = detected_versions.split_first().map(|(preferred, others)| {
+        log_info(format!(
+            "Detected Node.js version range: {} (from {})",
+            preferred.requested, preferred.source
+        ));
+        if let NodeRequirement::Alias(alias) = &preferred.requested {
+            log_info(format!(
+                "Resolved release alias {alias} to {}",
+                preferred.requirement
+            ));
+        }
+        for other in others {
+            log_info(format!(
+                "Also found compatible Node.js version {} in {}",
+                other.requested, other.source
+            ));
+        }
+        preferred.requirement.clone()
+    });
+
+    let buildpack_requirements = read_node_version_requirements(&context.buildpack_plan)
+        .map_err(NodeJsEngineBuildpackError::NodeVersionMetadataError)?;
+    let required_range = intersect_buildpack_requirements(&buildpack_requirements)?;
+
+    if let Some(requested) = requested_range {
+        return match required_range {
+            None => Ok(requested),
+            Some(required) => requested.intersect(&required).ok_or(
+                NodeJsEngineBuildpackError::BuildPlanVersionConflict {
+                    requested,
+                    required,
+                },
+            ),
+        };
+    }
+
+    let lts = release_schedule
+        .latest_lts(today)
+        .ok_or(NodeJsEngineBuildpackError::DefaultVersionError)?;
+    let default_range = release_schedule
+        .default_requirement(today)
+        .ok_or(NodeJsEngineBuildpackError::DefaultVersionError)?;
+    match required_range {
+        Some(required) if default_range.intersect(&required).is_none() => {
+            log_info(format!(
+                "Node.js version not specified, using {required} required by other buildpacks"
+            ));
+            Ok(required)
+        }
+        required_range => {
+            log_info(format!(
+                "Node.js version not specified, using {}.x (latest LTS)",
+                lts.major
+            ));
+            Ok(required_range
+                .and_then(|required| default_range.intersect(&required))
+                .unwrap_or(default_range))
+        }
+    }
+}
+
+/// Combines the Node.js version requirements declared by other buildpacks in
+/// the build plan, failing if any of them conflict.
+fn intersect_buildpack_requirements(
+    buildpack_requirements: &[Requirement],
+) -> Result<Option<Requirement>, NodeJsEngineBuildpackError> {
+    buildpack_requirements
+        .iter()
+        .try_fold(None, |combined: Option<Requirement>, required| {
+            log_info(format!(
+                "Found Node.js version requirement {required} from another buildpack"
+            ));
+            match combined {
+                None => Ok(Some(required.clone())),
+                Some(combined) => combined.intersect(required).map(Some).ok_or(
+                    NodeJsEngineBuildpackError::BuildPlanVersionConflict {
+                        requested: combined,
+                        required: required.clone(),
+                    },
+                ),
+            }
+        })
+}
+
+/// Determines whether npm, npx, and corepack are kept in the launch image.
+fn read_launch_tools(
+    context: &BuildContext<NodeJsEngineBuildpack>,
+    app_dir: &Path,
+    config: &NodejsConfig,
+    procfile: Option<&(Procfile, &'static str)>,
+) -> Result<bool, NodeJsEngineBuildpackError> {
+    let configured = context
+        .platform
+        .env()
+        .get_string_lossy(LAUNCH_TOOLS_ENV_VAR);
+    let npm_workspace = config.npm_workspace(context.platform.env());
+    let reason = launch_tools_reason(
+        app_dir,
+        procfile,
+        npm_workspace
+            .as_ref()
+            .map(|workspace| workspace.value.as_str()),
+        configured.as_deref(),
+    )
+    .map_err(NodeJsEngineBuildpackError::LaunchToolsConfigError)?;
+    if let Some(reason) = &reason {
+        log_info(format!(
+            "Keeping npm, npx, and corepack in the launch image, {reason}"
+        ));
+    }
+    Ok(reason.is_some())
+}
+
+/// Reads the prerelease channel the application opted into, if any.
+fn read_prerelease_channel(
+    context: &BuildContext<NodeJsEngineBuildpack>,
+) -> Result<Option<PrereleaseChannel>, NodeJsEngineBuildpackError> {
+    let prerelease_channel = context
+        .platform
+        .env()
+        .get_string_lossy(PRERELEASE_CHANNEL_ENV_VAR)
+        .map(|channel| channel.parse::<PrereleaseChannel>())
+        .transpose()
+        .map_err(NodeJsEngineBuildpackError::PrereleaseChannelError)?;
+    if let Some(channel) = prerelease_channel {
+        log_info(format!(
+            "Including Node.js {channel} prereleases ({PRERELEASE_CHANNEL_ENV_VAR}={channel})"
+        ));
+    }
+    Ok(prerelease_channel)
+}
+
+fn warn_on_prerelease(version: &Version) {
+    if let Some(channel) = PrereleaseChannel::for_version(version) {
+        log_warning(
+            "Node.js version is a prerelease",
+            format!(
+                "Node.js {version} is from the {channel} channel. Prereleases are intended \
+                for testing and shouldn't be used for production applications."
+            ),
+        );
+    }
+}
+
+fn warn_on_end_of_life(release_schedule: &ReleaseSchedule, version: &Version, today: NaiveDate) {
+    let upgrade_hint = release_schedule
+        .latest_lts(today)
+        .map(|lts| {
+            format!(
+                " Upgrade to a supported release line, such as {}.x.",
+                lts.major
+            )
+        })
+        .unwrap_or_default();
+    match release_schedule.support_status(version, today) {
+        SupportStatus::EndOfLife(end_of_life) => log_warning(
+            "Node.js version is end-of-life",
+            format!(
+                "Node.js {}.x reached end-of-life on {end_of_life} and no longer receives \
+                security updates.{upgrade_hint}",
+                version.major()
+            ),
+        ),
+        SupportStatus::NearingEndOfLife(end_of_life) => log_warning(
+            "Node.js version is nearing end-of-life",
+            format!(
+                "Node.js {}.x reaches end-of-life on {end_of_life} and will stop receiving \
+                security updates.{upgrade_hint}",
+                version.major()
+            ),
+        ),
+        SupportStatus::Supported | SupportStatus::Unknown => {}
+    }
+}
+
 #[derive(Error, Debug)]
 enum NodeJsEngineBuildpackError {
     #[error("Couldn't parse Node.js inventory: {0}")]
@@ -183,6 +529,31 @@ enum NodeJsEngineBuildpackError {
     PackageJsonError(PackageJsonError),
     #[error("Couldn't resolve Node.js version: {0}")]
     UnknownVersionError(String),
+    #[error("Unsupported build target: {0}-{1}. Node.js can only be installed for linux-amd64 and linux-arm64 targets.")]
+    UnsupportedTargetError(String, String),
+    #[error("Couldn't determine requested Node.js version: {0}")]
+    NodeVersionSourceError(NodeVersionSourceError),
+    #[error(
+        "Couldn't determine the default Node.js version, the inventory has no LTS release lines"
+    )]
+    DefaultVersionError,
+    #[error("Node.js version `{requested}` conflicts with the Node.js version `{required}` required by another buildpack. Update the requested Node.js version so it's compatible with `{required}`.")]
+    BuildPlanVersionConflict {
+        requested: Requirement,
+        required: Requirement,
+    },
+    #[error("Couldn't parse Node.js version metadata for the buildplan named {NODE_BUILD_PLAN_NAME}: {0:?}")]
+    NodeVersionMetadataError(NodeVersionMetadataError),
+    #[error("Couldn't read {PRERELEASE_CHANNEL_ENV_VAR}: {0}")]
+    PrereleaseChannelError(PrereleaseChannelError),
+    #[error("Couldn't configure the artifact mirror: {0}")]
+    ArtifactMirrorError(ArtifactMirrorError),
+    #[error("Invalid {LAUNCH_TOOLS_ENV_VAR} value `{0}`, expected `true` or `false`")]
+    LaunchToolsConfigError(String),
+    #[error(transparent)]
+    ConfigError(ConfigError),
+    #[error(transparent)]
+    ProcfileError(ProcfileError),
     #[error(transparent)]
     DistLayerError(#[from] DistLayerError),
     #[error(transparent)]
@@ -0,0 +1,290 @@
+use heroku_nodejs_utils::package_json::{Bin, PackageJson};
+use std::fmt::{Display, Formatter};
+use std::path::{Component, Path, PathBuf};
+
+/// Files in the app root that are used as the default web process. These are
+/// preferred over entry points from `package.json`, so apps that relied on
+/// them keep the same web process.
+const PREFERRED_ROOT_ENTRY_POINTS: [&str; 2] = ["server.js", "index.js"];
+
+/// Files in the app root that are used as the default web process, in order of
+/// preference. Files other than `PREFERRED_ROOT_ENTRY_POINTS` are only used
+/// when `package.json` doesn't point at an entry point.
+pub(crate) const ROOT_ENTRY_POINTS: [&str; 6] = [
+    "server.js",
+    "server.mjs",
+    "server.cjs",
+    "index.js",
+    "index.mjs",
+    "index.cjs",
+];
+
+/// Where a candidate entry point for the default web process came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum EntryPointSource {
+    Main,
+    Bin(Option<String>),
+    RootFile,
+}
+
+impl Display for EntryPointSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EntryPointSource::Main => write!(f, "`main` in package.json"),
+            EntryPointSource::Bin(None) => write!(f, "`bin` in package.json"),
+            EntryPointSource::Bin(Some(command)) => {
+                write!(f, "`bin.{command}` in package.json")
+            }
+            EntryPointSource::RootFile => write!(f, "file in the app root"),
+        }
+    }
+}
+
+/// A file that `node` can run as the default web process, relative to the app
+/// directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct EntryPoint {
+    pub(crate) path: PathBuf,
+    pub(crate) source: EntryPointSource,
+    pub(crate) module: bool,
+}
+
+impl Display for EntryPoint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "`{}` ({}, {})",
+            self.path.display(),
+            self.source,
+            if self.module { "ES module" } else { "CommonJS" }
+        )
+    }
+}
+
+/// Finds the files that could be run as the default web process. The first one
+/// is preferred: `server.js` or `index.js` in the app root, then the `main`
+/// field, then a single-entry `bin`, then the other conventional files in the
+/// app root. Each file is only listed once.
+pub(crate) fn entry_points(app_dir: &Path, package_json: Option<&PackageJson>) -> Vec<EntryPoint> {
+    let root_files = |names: &'static [&'static str]| {
+        names
+            .iter()
+            .filter(|name| app_dir.join(name).is_file())
+            .map(|name| (PathBuf::from(name), EntryPointSource::RootFile))
+    };
+
+    let mut candidates: Vec<(PathBuf, EntryPointSource)> =
+        root_files(&PREFERRED_ROOT_ENTRY_POINTS).collect();
+
+    if let Some(package_json) = package_json {
+        if let Some(main) = &package_json.main {
+            candidates
+                .extend(resolve_file(app_dir, main).map(|path| (path, EntryPointSource::Main)));
+        }
+        let bin = match &package_json.bin {
+            Some(Bin::Path(path)) => Some((path, None)),
+            Some(Bin::Commands(commands)) if commands.len() == 1 => commands
+                .iter()
+                .next()
+                .map(|(command, path)| (path, Some(command.clone()))),
+            _ => None,
+        };
+        if let Some((path, command)) = bin {
+            candidates.extend(
+                resolve_file(app_dir, path).map(|path| (path, EntryPointSource::Bin(command))),
+            );
+        }
+    }
+
+    candidates.extend(root_files(&ROOT_ENTRY_POINTS));
+
+    let is_module = package_json.is_some_and(PackageJson::is_module);
+    let mut entry_points: Vec<EntryPoint> = vec![];
+    for (path, source) in candidates {
+        if entry_points
+            .iter()
+            .all(|entry_point| entry_point.path != path)
+        {
+            let module = match path.extension().and_then(|ext| ext.to_str()) {
+                Some("mjs") => true,
+                Some("cjs") => false,
+                _ => is_module,
+            };
+            entry_points.push(EntryPoint {
+                path,
+                source,
+                module,
+            });
+        }
+    }
+    entry_points
+}
+
+/// Resolves a path from package.json to a file in the app directory like
+/// `require` would, trying a `.js` extension and an `index.js` in a directory.
+/// Paths that escape the app directory are ignored.
+fn resolve_file(app_dir: &Path, path: &str) -> Option<PathBuf> {
+    let path = normalize(Path::new(path))?;
+    [
+        path.clone(),
+        PathBuf::from(format!("{}.js", path.display())),
+        path.join("index.js"),
+    ]
+    .into_iter()
+    .find(|candidate| !candidate.as_os_str().is_empty() && app_dir.join(candidate).is_file())
+}
+
+fn normalize(path: &Path) -> Option<PathBuf> {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => normalized.push(part),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    Some(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn app(files: &[&str]) -> tempfile::TempDir {
+        let app_dir = tempfile::tempdir().unwrap();
+        for name in files {
+            let path = app_dir.path().join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+        app_dir
+    }
+
+    fn package_json(json: &str) -> PackageJson {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn paths(entry_points: &[EntryPoint]) -> Vec<String> {
+        entry_points
+            .iter()
+            .map(|entry_point| entry_point.path.display().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn root_entry_points() {
+        let app_dir = app(&["index.js", "server.mjs"]);
+        let entry_points = entry_points(app_dir.path(), None);
+        assert_eq!(paths(&entry_points), ["index.js", "server.mjs"]);
+        assert!(!entry_points[0].module);
+        assert!(entry_points[1].module);
+        assert_eq!(entry_points[0].source, EntryPointSource::RootFile);
+
+        let app_dir = app(&[]);
+        assert!(super::entry_points(app_dir.path(), None).is_empty());
+    }
+
+    #[test]
+    fn main_entry_point() {
+        let app_dir = app(&["index.mjs", "src/app.js", "lib/index.js"]);
+        assert_eq!(
+            paths(&entry_points(
+                app_dir.path(),
+                Some(&package_json(r#"{ "main": "./src/app.js" }"#))
+            )),
+            ["src/app.js", "index.mjs"]
+        );
+        assert_eq!(
+            paths(&entry_points(
+                app_dir.path(),
+                Some(&package_json(r#"{ "main": "src/app" }"#))
+            )),
+            ["src/app.js", "index.mjs"]
+        );
+        assert_eq!(
+            paths(&entry_points(
+                app_dir.path(),
+                Some(&package_json(r#"{ "main": "lib" }"#))
+            )),
+            ["lib/index.js", "index.mjs"]
+        );
+
+        let app_dir = app(&["index.js", "src/app.js"]);
+        // `server.js` and `index.js` in the app root are preferred over `main`
+        assert_eq!(
+            paths(&entry_points(
+                app_dir.path(),
+                Some(&package_json(r#"{ "main": "./src/app.js" }"#))
+            )),
+            ["index.js", "src/app.js"]
+        );
+        // a file found through `main` and in the app root is only listed once
+        assert_eq!(
+            paths(&entry_points(
+                app_dir.path(),
+                Some(&package_json(r#"{ "main": "index.js" }"#))
+            )),
+            ["index.js"]
+        );
+        assert_eq!(
+            paths(&entry_points(
+                app_dir.path(),
+                Some(&package_json(r#"{ "main": "missing.js" }"#))
+            )),
+            ["index.js"]
+        );
+        assert_eq!(
+            paths(&entry_points(
+                app_dir.path(),
+                Some(&package_json(r#"{ "main": "../index.js" }"#))
+            )),
+            ["index.js"]
+        );
+    }
+
+    #[test]
+    fn bin_entry_point() {
+        let app_dir = app(&["bin/serve.js", "bin/other.js"]);
+        let entry_points = entry_points(
+            app_dir.path(),
+            Some(&package_json(r#"{ "bin": { "serve": "bin/serve.js" } }"#)),
+        );
+        assert_eq!(paths(&entry_points), ["bin/serve.js"]);
+        assert_eq!(
+            entry_points[0].source,
+            EntryPointSource::Bin(Some("serve".to_string()))
+        );
+
+        assert_eq!(
+            paths(&super::entry_points(
+                app_dir.path(),
+                Some(&package_json(r#"{ "bin": "./bin/serve.js" }"#))
+            )),
+            ["bin/serve.js"]
+        );
+        assert!(super::entry_points(
+            app_dir.path(),
+            Some(&package_json(
+                r#"{ "bin": { "serve": "bin/serve.js", "other": "bin/other.js" } }"#
+            ))
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn module_entry_points() {
+        let app_dir = app(&["server.js", "index.cjs"]);
+        let entry_points = entry_points(
+            app_dir.path(),
+            Some(&package_json(r#"{ "type": "module" }"#)),
+        );
+        assert_eq!(paths(&entry_points), ["server.js", "index.cjs"]);
+        assert!(entry_points[0].module);
+        assert!(!entry_points[1].module);
+        assert_eq!(
+            entry_points[0].to_string(),
+            "`server.js` (file in the app root, ES module)"
+        );
+    }
+}
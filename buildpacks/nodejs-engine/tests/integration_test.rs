@@ -44,6 +44,76 @@ fn simple_serverjs() {
     });
 }
 
+#[test]
+#[ignore]
+fn node_version_from_nvmrc() {
+    nodejs_integration_test_with_config(
+        "./fixtures/node-with-indexjs",
+        |config| {
+            config.app_dir_preprocessor(|app_dir| {
+                std::fs::write(app_dir.join(".nvmrc"), "v20.11.0\n").unwrap();
+            });
+        },
+        |ctx| {
+            assert_contains!(
+                ctx.pack_stdout,
+                "Detected Node.js version range: 20.11.0 (from .nvmrc)"
+            );
+            assert_contains!(ctx.pack_stdout, "Installing Node.js 20.11.0");
+        },
+    );
+}
+
+#[test]
+#[ignore]
+fn node_from_artifact_mirror() {
+    nodejs_integration_test_with_config(
+        "./fixtures/node-with-serverjs",
+        |config| {
+            config.env("NODEJS_ARTIFACT_MIRROR_URL", "https://nodejs.org/");
+        },
+        |ctx| {
+            assert_contains!(
+                ctx.pack_stdout,
+                "from https://nodejs.org/download/release/v16.0.0/"
+            );
+            assert_contains!(ctx.pack_stdout, "(mirror https://nodejs.org/)");
+            assert_contains!(ctx.pack_stdout, "Installing Node.js 16.0.0");
+        },
+    );
+}
+
+#[test]
+#[ignore]
+fn build_only_files_are_excluded_from_launch_image() {
+    nodejs_integration_test("./fixtures/node-with-indexjs", |ctx| {
+        assert_contains!(ctx.pack_stdout, "of build-only files from the launch image");
+        let output = ctx.run_shell_command(
+            "node --version && ! command -v npm && test -z \"$NODE_HOME\" && test ! -d \"$(dirname \"$(dirname \"$(command -v node)\")\")/include\" && echo slim",
+        );
+        assert_contains!(output.stdout, "slim");
+    });
+}
+
+#[test]
+#[ignore]
+fn launch_tools_are_kept_when_configured() {
+    nodejs_integration_test_with_config(
+        "./fixtures/node-with-indexjs",
+        |config| {
+            config.env("NODEJS_LAUNCH_TOOLS", "true");
+        },
+        |ctx| {
+            assert_contains!(
+                ctx.pack_stdout,
+                "Keeping npm, npx, and corepack in the launch image, NODEJS_LAUNCH_TOOLS=true"
+            );
+            let output = ctx.run_shell_command("npm --version && corepack --version");
+            assert_not_contains!(output.stderr, "not found");
+        },
+    );
+}
+
 #[test]
 #[ignore]
 fn reinstalls_node_if_version_changes() {
@@ -212,3 +282,230 @@ fn runtime_metrics_script_is_not_activated_when_node_version_is_less_than_v14_10
         },
     );
 }
+
+#[test]
+#[ignore]
+fn runtime_metrics_script_uses_otlp_exporter_when_otlp_endpoint_is_set() {
+    nodejs_integration_test_with_config(
+        "./fixtures/node-with-indexjs",
+        |config| {
+            config.env("OTEL_EXPORTER_OTLP_ENDPOINT", "http://localhost:4318");
+        },
+        |ctx| {
+            assert_contains!(
+                ctx.pack_stdout,
+                "Installing application metrics scripts (exporter: otlp)"
+            );
+
+            let mut container_config = ContainerConfig::new();
+            container_config
+                .expose_port(PORT)
+                .env("NODE_DEBUG", "heroku")
+                .env(
+                    "OTEL_METRIC_EXPORT_INTERVAL",
+                    METRICS_SEND_INTERVAL.as_millis().to_string(),
+                );
+
+            ctx.start_container(container_config, |container| {
+                wait_for(
+                    || {
+                        assert_contains!(container.logs_now().stdout, "App started");
+                    },
+                    APPLICATION_STARTUP_TIMEOUT,
+                );
+                let stderr = container.logs_now().stderr;
+                assert_contains!(
+                    stderr,
+                    "OTEL_EXPORTER_OTLP_ENDPOINT set to \"http://localhost:4318\""
+                );
+                assert_not_contains!(stderr, "HEROKU_METRICS_URL");
+
+                wait_for(
+                    || {
+                        assert_contains!(
+                            container.logs_now().stderr,
+                            "Sending metrics to http://localhost:4318/v1/metrics"
+                        );
+                    },
+                    METRICS_SEND_TIMEOUT,
+                );
+            });
+        },
+    );
+}
+
+#[test]
+#[ignore]
+fn runtime_metrics_script_is_not_installed_when_otel_metrics_exporter_is_none() {
+    nodejs_integration_test_with_config(
+        "./fixtures/node-with-indexjs",
+        |config| {
+            config
+                .env("OTEL_METRICS_EXPORTER", "none")
+                .env("OTEL_EXPORTER_OTLP_ENDPOINT", "http://localhost:4318");
+        },
+        |ctx| {
+            assert_contains!(
+                ctx.pack_stdout,
+                "Not installing application metrics scripts (OTEL_METRICS_EXPORTER=none)"
+            );
+
+            let mut container_config = ContainerConfig::new();
+            container_config
+                .expose_port(PORT)
+                .env("NODE_DEBUG", "heroku");
+
+            ctx.start_container(container_config, |container| {
+                wait_for(
+                    || {
+                        assert_contains!(container.logs_now().stdout, "App started");
+                    },
+                    APPLICATION_STARTUP_TIMEOUT,
+                );
+                assert_not_contains!(
+                    container.logs_now().stderr,
+                    "Registering metrics instrumentation"
+                );
+            });
+        },
+    );
+}
+
+#[test]
+#[ignore]
+fn runtime_metrics_script_is_not_installed_when_disabled_in_project_toml() {
+    nodejs_integration_test_with_config(
+        "./fixtures/node-with-indexjs",
+        |config| {
+            config.app_dir_preprocessor(|app_dir| {
+                std::fs::write(
+                    app_dir.join("project.toml"),
+                    "[_]\nschema-version = \"0.2\"\n\n[com.heroku.buildpacks.nodejs]\nruntime_metrics = false\n",
+                )
+                .unwrap();
+            });
+        },
+        |ctx| {
+            assert_contains!(
+                ctx.pack_stdout,
+                "Not installing application metrics scripts (`runtime_metrics` is set to `false` in project.toml)"
+            );
+
+            let mut container_config = ContainerConfig::new();
+            container_config
+                .expose_port(PORT)
+                .env("NODE_DEBUG", "heroku")
+                .env("HEROKU_METRICS_URL", "http://localhost:3000");
+
+            ctx.start_container(container_config, |container| {
+                wait_for(
+                    || {
+                        assert_contains!(container.logs_now().stdout, "App started");
+                    },
+                    APPLICATION_STARTUP_TIMEOUT,
+                );
+                assert_not_contains!(
+                    container.logs_now().stderr,
+                    "Registering metrics instrumentation"
+                );
+            });
+        },
+    );
+}
+
+#[test]
+#[ignore]
+fn default_web_process_uses_package_json_main() {
+    nodejs_integration_test_with_config(
+        "./fixtures/node-with-indexjs",
+        |config| {
+            config.app_dir_preprocessor(|app_dir| {
+                std::fs::write(
+                    app_dir.join("package.json"),
+                    r#"{ "name": "node-with-main", "main": "server.mjs" }"#,
+                )
+                .unwrap();
+                // `index.js` would be preferred over `main`
+                std::fs::rename(app_dir.join("index.js"), app_dir.join("index.cjs")).unwrap();
+                std::fs::write(
+                    app_dir.join("server.mjs"),
+                    "import { createServer } from 'node:http';\n\
+                    const port = process.env.PORT || 8080;\n\
+                    createServer((req, res) => res.end('Hello from node-with-main!'))\n\
+                    .listen(port, () => console.log(`App started on port ${port}`));\n",
+                )
+                .unwrap();
+            });
+        },
+        |ctx| {
+            assert_contains!(
+                ctx.pack_stdout,
+                "Using `server.mjs` (`main` in package.json, ES module) as the default web process"
+            );
+            assert_contains!(ctx.pack_stdout, "Multiple default web process candidates");
+            assert_contains!(
+                ctx.pack_stdout,
+                "`index.cjs` (file in the app root, CommonJS)"
+            );
+            assert_web_response(&ctx, "node-with-main");
+        },
+    );
+}
+
+#[test]
+#[ignore]
+fn procfile_processes_replace_default_web_process() {
+    nodejs_integration_test_with_config(
+        "./fixtures/node-with-indexjs",
+        |config| {
+            config.app_dir_preprocessor(|app_dir| {
+                std::fs::write(
+                    app_dir.join("Procfile"),
+                    "web: node index.js\nworker: node index.js\n",
+                )
+                .unwrap();
+            });
+        },
+        |ctx| {
+            assert_contains!(
+                ctx.pack_stdout,
+                "Adding `web` process from Procfile: `node index.js`"
+            );
+            assert_contains!(
+                ctx.pack_stdout,
+                "Adding `worker` process from Procfile: `node index.js`"
+            );
+            assert_not_contains!(ctx.pack_stdout, "as the default web process");
+            assert_web_response(&ctx, "node-with-indexjs");
+        },
+    );
+}
+
+#[test]
+#[ignore]
+fn project_toml_processes_replace_default_web_process() {
+    nodejs_integration_test_with_config(
+        "./fixtures/node-with-indexjs",
+        |config| {
+            config.app_dir_preprocessor(|app_dir| {
+                std::fs::write(
+                    app_dir.join("project.toml"),
+                    "[_]\nschema-version = \"0.2\"\n\n[com.heroku.buildpacks.nodejs]\nruntime_metric = false\n\n[com.heroku.buildpacks.nodejs.processes]\nweb = \"node index.js\"\n",
+                )
+                .unwrap();
+            });
+        },
+        |ctx| {
+            assert_contains!(
+                ctx.pack_stdout,
+                "Ignoring unknown key `runtime_metric` in project.toml"
+            );
+            assert_contains!(
+                ctx.pack_stdout,
+                "Adding `web` process from project.toml: `node index.js`"
+            );
+            assert_not_contains!(ctx.pack_stdout, "as the default web process");
+            assert_web_response(&ctx, "node-with-indexjs");
+        },
+    );
+}
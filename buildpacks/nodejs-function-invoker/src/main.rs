@@ -8,6 +8,7 @@ use crate::function::{
 use crate::install_nodejs_function_runtime::{install_nodejs_function_runtime, RuntimeLayerError};
 #[cfg(test)]
 use base64 as _;
+use heroku_nodejs_utils::config::{ConfigError, NodejsConfig};
 #[cfg(test)]
 use hex as _;
 use libcnb::build::{BuildContext, BuildResult, BuildResultBuilder};
@@ -16,7 +17,7 @@ use libcnb::data::launch::{LaunchBuilder, ProcessBuilder};
 use libcnb::data::process_type;
 use libcnb::detect::{DetectContext, DetectResult, DetectResultBuilder};
 use libcnb::generic::GenericPlatform;
-use libcnb::{buildpack_main, Buildpack};
+use libcnb::{buildpack_main, Buildpack, Platform};
 #[cfg(test)]
 use libcnb_test as _;
 use libherokubuildpack::error::on_error;
@@ -72,7 +73,10 @@ impl Buildpack for NodeJsInvokerBuildpack {
     fn build(&self, context: BuildContext<Self>) -> libcnb::Result<BuildResult, Self::Error> {
         log_header("Heroku Node.js Function Invoker Buildpack");
 
-        let app_dir = &context.app_dir;
+        let app_root = NodejsConfig::read(&context.app_dir)
+            .and_then(|config| config.app_root(context.platform.env()))
+            .map_err(NodeJsInvokerBuildpackError::Config)?;
+        let app_dir = &app_root.dir(&context.app_dir);
         let metadata_runtime = &context.buildpack_descriptor.metadata.runtime;
         let package_name = &metadata_runtime.package_name;
         let package_version = &metadata_runtime.package_version;
@@ -115,13 +119,10 @@ impl Buildpack for NodeJsInvokerBuildpack {
                     .process(
                         ProcessBuilder::new(
                             process_type!("web"),
-                            [
-                                NODEJS_RUNTIME_SCRIPT,
-                                command,
-                                &context.app_dir.to_string_lossy(),
-                            ],
+                            [NODEJS_RUNTIME_SCRIPT, command, &app_dir.to_string_lossy()],
                         )
                         .default(true)
+                        .working_directory(app_root.working_directory())
                         .build(),
                     )
                     .build(),
@@ -134,6 +135,9 @@ impl Buildpack for NodeJsInvokerBuildpack {
             |bp_err| {
                 let err_string = bp_err.to_string();
                 match bp_err {
+                    NodeJsInvokerBuildpackError::Config(_) => {
+                        log_error("Node.js Function Invoker configuration error", err_string);
+                    }
                     NodeJsInvokerBuildpackError::MainFunction(_) => {
                         log_error(
                             "Node.js Function Invoker main function detection error",
@@ -161,6 +165,8 @@ impl Buildpack for NodeJsInvokerBuildpack {
 
 #[derive(Error, Debug)]
 enum NodeJsInvokerBuildpackError {
+    #[error("{0}")]
+    Config(#[from] ConfigError),
     #[error("{0}")]
     MainFunction(#[from] MainError),
     #[error("{0}")]
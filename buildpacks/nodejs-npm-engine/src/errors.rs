@@ -3,6 +3,8 @@ use crate::BUILDPACK_NAME;
 use crate::{node, npm};
 use bullet_stream::state::Bullet;
 use bullet_stream::{style, Print};
+use heroku_nodejs_utils::config::ConfigError;
+use heroku_nodejs_utils::mirror::{ArtifactMirrorError, ARTIFACT_MIRROR_ENV_VAR};
 use heroku_nodejs_utils::package_json::PackageJsonError;
 use heroku_nodejs_utils::vrs::Requirement;
 use indoc::formatdoc;
@@ -18,10 +20,12 @@ locally with a minimal example and open an issue in the buildpack's GitHub repos
 
 #[derive(Debug)]
 pub(crate) enum NpmEngineBuildpackError {
+    Config(ConfigError),
     PackageJson(PackageJsonError),
     MissingNpmEngineRequirement,
     InventoryParse(toml::de::Error),
     NpmVersionResolve(Requirement),
+    ArtifactMirror(ArtifactMirrorError),
     NpmEngineLayer(NpmEngineLayerError),
     NodeVersion(node::VersionError),
     NpmVersion(npm::VersionError),
@@ -39,6 +43,7 @@ pub(crate) fn on_error(error: libcnb::Error<NpmEngineBuildpackError>) {
 
 fn on_buildpack_error(error: NpmEngineBuildpackError, logger: Print<Bullet<Stdout>>) {
     match error {
+        NpmEngineBuildpackError::Config(e) => on_config_error(&e, logger),
         NpmEngineBuildpackError::PackageJson(e) => on_package_json_error(e, logger),
         NpmEngineBuildpackError::MissingNpmEngineRequirement => {
             on_missing_npm_engine_requirement_error(logger);
@@ -47,6 +52,7 @@ fn on_buildpack_error(error: NpmEngineBuildpackError, logger: Print<Bullet<Stdou
         NpmEngineBuildpackError::NpmVersionResolve(requirement) => {
             on_npm_version_resolve_error(&requirement, logger);
         }
+        NpmEngineBuildpackError::ArtifactMirror(e) => on_artifact_mirror_error(&e, logger),
         NpmEngineBuildpackError::NpmEngineLayer(e) => on_npm_engine_layer_error(e, logger),
         NpmEngineBuildpackError::NodeVersion(e) => on_node_version_error(e, logger),
         NpmEngineBuildpackError::NpmVersion(e) => on_npm_version_error(e, logger),
@@ -95,6 +101,16 @@ fn on_missing_npm_engine_requirement_error(logger: Print<Bullet<Stdout>>) {
     ", engines_key = style::value("engines.npm"), package_json = style::value("package.json") });
 }
 
+fn on_config_error(error: &ConfigError, logger: Print<Bullet<Stdout>>) {
+    logger.error(formatdoc! {"
+        Invalid Node.js buildpack configuration.
+
+        {error}
+
+        Fix the configuration and retry your build.
+    "});
+}
+
 fn on_inventory_parse_error(error: &toml::de::Error, logger: Print<Bullet<Stdout>>) {
     print_error_details(logger, &error).error(formatdoc! {"
             Failed to load available {npm} versions.
@@ -130,8 +146,50 @@ fn on_npm_version_resolve_error(requirement: &Requirement, logger: Print<Bullet<
     });
 }
 
+fn on_artifact_mirror_error(error: &ArtifactMirrorError, logger: Print<Bullet<Stdout>>) {
+    print_error_details(logger, &error).error(formatdoc! {"
+            Invalid artifact mirror.
+
+            The artifact mirror configured with {mirror_env_var} can’t be used to download {npm}. \
+            Set {mirror_env_var} to an {http}, {https}, or {file} URL that mirrors the {npm} \
+            releases, or unset it to download {npm} from the default location.
+
+            {SUBMIT_AN_ISSUE}
+        ",
+        mirror_env_var = style::value(ARTIFACT_MIRROR_ENV_VAR),
+        npm = style::value("npm"),
+        http = style::value("http://"),
+        https = style::value("https://"),
+        file = style::value("file://"),
+    });
+}
+
 fn on_npm_engine_layer_error(error: NpmEngineLayerError, logger: Print<Bullet<Stdout>>) {
     match error {
+        NpmEngineLayerError::MissingChecksum(version) => {
+            logger.error(formatdoc! {"
+                The inventory has no checksum for {npm} {version}.
+
+                {SUBMIT_AN_ISSUE}
+            ", npm = style::value("npm"), version = style::value(version) });
+        }
+        NpmEngineLayerError::InvalidChecksum(e) => {
+            print_error_details(logger, &e).error(formatdoc! {"
+                The inventory has an unsupported checksum for {npm}.
+
+                {SUBMIT_AN_ISSUE}
+            ", npm = style::value("npm") });
+        }
+        NpmEngineLayerError::ChecksumVerification(e) => {
+            print_error_details(logger, &e)
+                .error(formatdoc! {"
+                    Failed to verify the checksum of {npm}.
+
+                    The downloaded {npm} package doesn't match the checksum in the buildpack inventory. If you use an artifact mirror, check that it serves the same packages as the upstream source.
+
+                    {USE_DEBUG_INFORMATION_AND_RETRY_BUILD}
+                ", npm = style::value("npm") });
+        }
         NpmEngineLayerError::Download(e) => {
             print_error_details(logger, &e)
                 .error(formatdoc! {"
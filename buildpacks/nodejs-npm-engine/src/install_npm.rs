@@ -6,7 +6,6 @@ use libcnb::data::layer_name;
 use libcnb::layer::{
     CachedLayerDefinition, EmptyLayerCause, InvalidMetadataAction, LayerState, RestoredLayerAction,
 };
-use libherokubuildpack::download::{download_file, DownloadError};
 use libherokubuildpack::tar::decompress_tarball;
 use serde::{Deserialize, Serialize};
 use std::fs::File;
@@ -15,6 +14,10 @@ use std::path::Path;
 use std::process::Command;
 
 use heroku_nodejs_utils::inv::Release;
+use heroku_nodejs_utils::mirror::{
+    download_artifact, ArtifactChecksum, ArtifactChecksumError, ArtifactDownloadError,
+    ArtifactMirror, ArtifactVerificationError,
+};
 use heroku_nodejs_utils::vrs::Version;
 
 use crate::errors::NpmEngineBuildpackError;
@@ -24,6 +27,7 @@ pub(crate) fn install_npm(
     context: &BuildContext<NpmEngineBuildpack>,
     npm_release: &Release,
     node_version: &Version,
+    artifact_mirror: Option<&ArtifactMirror>,
     mut logger: Print<SubBullet<Stdout>>,
 ) -> Result<Print<SubBullet<Stdout>>, libcnb::Error<NpmEngineBuildpackError>> {
     let new_metadata = NpmEngineLayerMetadata {
@@ -72,8 +76,21 @@ pub(crate) fn install_npm(
 
             // this install process is generalized from the npm install script at:
             // https://www.npmjs.com/install.sh
+            let download_url = match artifact_mirror {
+                Some(mirror) => {
+                    logger = logger.sub_bullet(format!(
+                        "Using artifact mirror {}",
+                        style::url(mirror.to_string())
+                    ));
+                    mirror
+                        .rewrite(&npm_release.url)
+                        .map_err(NpmEngineBuildpackError::ArtifactMirror)?
+                }
+                None => npm_release.url.clone(),
+            };
             logger = download_and_unpack_release(
-                &npm_release.url,
+                &download_url,
+                &inventory_checksum(npm_release)?,
                 &downloaded_package_path,
                 &npm_engine_layer.path(),
                 logger,
@@ -86,15 +103,33 @@ pub(crate) fn install_npm(
     Ok(logger)
 }
 
+// npm releases are mirrored to S3 as single part uploads, so the inventory
+// `ETag` is the MD5 digest of each tarball.
+fn inventory_checksum(npm_release: &Release) -> Result<ArtifactChecksum, NpmEngineLayerError> {
+    npm_release
+        .etag
+        .as_deref()
+        .ok_or_else(|| NpmEngineLayerError::MissingChecksum(npm_release.version.to_string()))
+        .and_then(|etag| {
+            ArtifactChecksum::from_etag(etag).map_err(NpmEngineLayerError::InvalidChecksum)
+        })
+}
+
 fn download_and_unpack_release(
     download_from: &String,
+    checksum: &ArtifactChecksum,
     download_to: &Path,
     unpack_into: &Path,
     logger: Print<SubBullet<Stdout>>,
 ) -> Result<Print<SubBullet<Stdout>>, NpmEngineLayerError> {
     let timer = logger.start_timer(format!("Downloading {}", style::value(download_from)));
-    download_file(download_from, download_to)
+    download_artifact(download_from, download_to)
         .map_err(NpmEngineLayerError::Download)
+        .and_then(|()| {
+            checksum
+                .verify(download_to)
+                .map_err(NpmEngineLayerError::ChecksumVerification)
+        })
         .and_then(|()| File::open(download_to).map_err(NpmEngineLayerError::OpenTarball))
         .and_then(|mut npm_tgz_file| {
             decompress_tarball(&mut npm_tgz_file, unpack_into)
@@ -178,7 +213,10 @@ pub(crate) struct NpmEngineLayerMetadata {
 
 #[derive(Debug)]
 pub(crate) enum NpmEngineLayerError {
-    Download(DownloadError),
+    MissingChecksum(String),
+    InvalidChecksum(ArtifactChecksumError),
+    Download(ArtifactDownloadError),
+    ChecksumVerification(ArtifactVerificationError),
     OpenTarball(std::io::Error),
     DecompressTarball(std::io::Error),
     RemoveExistingNpmInstall(fun_run::CmdError),
@@ -8,14 +8,17 @@ use crate::install_npm::install_npm;
 use bullet_stream::state::SubBullet;
 use bullet_stream::{style, Print};
 use fun_run::CommandWithName;
+use heroku_nodejs_utils::config::NodejsConfig;
 use heroku_nodejs_utils::inv::{Inventory, Release};
+use heroku_nodejs_utils::mirror::{ArtifactMirror, ARTIFACT_MIRROR_ENV_VAR};
 use heroku_nodejs_utils::package_json::PackageJson;
+use heroku_nodejs_utils::resolved_node::{read_resolved_node, ResolvedNodeError};
 use heroku_nodejs_utils::vrs::{Requirement, Version};
 use libcnb::build::{BuildContext, BuildResult, BuildResultBuilder};
 use libcnb::data::build_plan::BuildPlanBuilder;
 use libcnb::detect::{DetectContext, DetectResult, DetectResultBuilder};
 use libcnb::generic::{GenericMetadata, GenericPlatform};
-use libcnb::{buildpack_main, Buildpack, Env};
+use libcnb::{buildpack_main, Buildpack, Env, Platform};
 #[cfg(test)]
 use libcnb_test as _;
 #[cfg(test)]
@@ -38,7 +41,11 @@ impl Buildpack for NpmEngineBuildpack {
     type Error = NpmEngineBuildpackError;
 
     fn detect(&self, context: DetectContext<Self>) -> libcnb::Result<DetectResult, Self::Error> {
-        let package_json_path = context.app_dir.join("package.json");
+        let package_json_path = NodejsConfig::read(&context.app_dir)
+            .and_then(|config| config.app_root(context.platform.env()))
+            .map_err(NpmEngineBuildpackError::Config)?
+            .dir(&context.app_dir)
+            .join("package.json");
         if package_json_path.exists() {
             let package_json = PackageJson::read(package_json_path)
                 .map_err(NpmEngineBuildpackError::PackageJson)?;
@@ -63,17 +70,33 @@ impl Buildpack for NpmEngineBuildpack {
 
     fn build(&self, context: BuildContext<Self>) -> libcnb::Result<BuildResult, Self::Error> {
         let mut logger = Print::new(stdout()).h1(BUILDPACK_NAME);
+        let app_dir = NodejsConfig::read(&context.app_dir)
+            .and_then(|config| config.app_root(context.platform.env()))
+            .and_then(|app_root| app_root.enter(&context.app_dir))
+            .map_err(NpmEngineBuildpackError::Config)?;
         let env = Env::from_current();
         let inventory: Inventory =
             toml::from_str(INVENTORY).map_err(NpmEngineBuildpackError::InventoryParse)?;
-        let requested_npm_version =
-            read_requested_npm_version(&context.app_dir.join("package.json"))?;
+        let requested_npm_version = read_requested_npm_version(&app_dir.join("package.json"))?;
         let node_version = get_node_version(&env)?;
+        let artifact_mirror = context
+            .platform
+            .env()
+            .get_string_lossy(ARTIFACT_MIRROR_ENV_VAR)
+            .map(|mirror| ArtifactMirror::parse(&mirror))
+            .transpose()
+            .map_err(NpmEngineBuildpackError::ArtifactMirror)?;
 
         let section = logger.bullet("Installing npm");
         let (npm_release, section) =
             resolve_requested_npm_version(&requested_npm_version, &inventory, section)?;
-        let section = install_npm(&context, &npm_release, &node_version, section)?;
+        let section = install_npm(
+            &context,
+            &npm_release,
+            &node_version,
+            artifact_mirror.as_ref(),
+            section,
+        )?;
         let section = log_installed_npm_version(&env, section)?;
         logger = section.done();
 
@@ -128,6 +151,16 @@ fn resolve_requested_npm_version(
 }
 
 fn get_node_version(env: &Env) -> Result<Version, NpmEngineBuildpackError> {
+    match read_resolved_node(env) {
+        Ok(Some(resolved_node)) => return Ok(resolved_node.version),
+        Ok(None) => {}
+        Err(ResolvedNodeError::InvalidVersion(version, e)) => Err(
+            NpmEngineBuildpackError::NodeVersion(node::VersionError::Parse(version, e)),
+        )?,
+    }
+
+    // Node.js was installed by something other than the Node.js engine
+    // buildpack, so ask the `node` binary on the `PATH`.
     Command::from(node::Version { env })
         .named_output()
         .map_err(node::VersionError::Command)
@@ -0,0 +1,468 @@
+use bullet_stream::state::SubBullet;
+use bullet_stream::{style, Print};
+use heroku_nodejs_utils::vrs::Version;
+use libcnb::build::BuildContext;
+use libcnb::data::layer_name;
+use libcnb::layer::{
+    CachedLayerDefinition, EmptyLayerCause, InvalidMetadataAction, LayerState, RestoredLayerAction,
+};
+use libcnb::Target;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Stdout;
+use std::os::unix::fs::symlink;
+use std::path::{Path, PathBuf};
+
+use crate::errors::NpmInstallBuildpackError;
+use crate::NpmInstallBuildpack;
+
+/// Restores `node_modules` from the cache and skips the install when the
+/// lockfile, `package.json` files, `.npmrc`, Node.js version, npm version,
+/// target, and workspace are the same as in the previous build. Otherwise `npm_install` runs and its result is
+/// cached for the next build.
+///
+/// npm installs conflicting versions and `.bin` links of workspace packages to
+/// their own `node_modules`, so the `node_modules` of each workspace in
+/// `workspace_dirs` is cached along with the root one.
+pub(crate) fn install_with_node_modules_cache(
+    context: &BuildContext<NpmInstallBuildpack>,
+    app_dir: &Path,
+    new_metadata: NodeModulesCacheLayerMetadata,
+    workspace_dirs: &[&Path],
+    mut section_logger: Print<SubBullet<Stdout>>,
+    npm_install: impl FnOnce(
+        Print<SubBullet<Stdout>>,
+    ) -> Result<Print<SubBullet<Stdout>>, NpmInstallBuildpackError>,
+) -> Result<Print<SubBullet<Stdout>>, libcnb::Error<NpmInstallBuildpackError>> {
+    let node_modules_layer = context.cached_layer(
+        layer_name!("node_modules"),
+        CachedLayerDefinition {
+            build: false,
+            launch: false,
+            invalid_metadata_action: &|_| InvalidMetadataAction::DeleteLayer,
+            restored_layer_action: &|old_metadata: &NodeModulesCacheLayerMetadata, _| {
+                let changes = old_metadata.changes(&new_metadata);
+                if changes.is_empty() {
+                    (RestoredLayerAction::KeepLayer, changes)
+                } else {
+                    (RestoredLayerAction::DeleteLayer, changes)
+                }
+            },
+        },
+    )?;
+
+    match node_modules_layer.state {
+        LayerState::Restored { .. } => {
+            section_logger = section_logger.sub_bullet(format!(
+                "Restoring {} from cache, skipping install as nothing changed since the last build",
+                style::value("node_modules")
+            ));
+            restore_node_modules(&node_modules_layer.path(), app_dir, workspace_dirs)
+                .map_err(NpmInstallBuildpackError::NodeModulesCache)?;
+            return Ok(section_logger);
+        }
+        LayerState::Empty { ref cause } => match cause {
+            EmptyLayerCause::RestoredLayerAction { cause: changes } => {
+                section_logger = section_logger.sub_bullet(format!(
+                    "Not restoring {} from cache ({})",
+                    style::value("node_modules"),
+                    changes.join(", ")
+                ));
+            }
+            EmptyLayerCause::InvalidMetadataAction { .. } => {
+                section_logger = section_logger.sub_bullet(format!(
+                    "Not restoring {} from cache (cache format changed)",
+                    style::value("node_modules")
+                ));
+            }
+            EmptyLayerCause::NewlyCreated => {}
+        },
+    }
+
+    section_logger = npm_install(section_logger)?;
+
+    section_logger = section_logger.sub_bullet(format!("Caching {}", style::value("node_modules")));
+    cache_node_modules(app_dir, &node_modules_layer.path(), workspace_dirs)
+        .map_err(NpmInstallBuildpackError::NodeModulesCache)?;
+    node_modules_layer.write_metadata(new_metadata)?;
+
+    Ok(section_logger)
+}
+
+/// The `node_modules` directories of the app root and each workspace, relative
+/// to the app directory.
+fn node_modules_dirs<'a>(workspace_dirs: &'a [&Path]) -> impl Iterator<Item = PathBuf> + 'a {
+    std::iter::once(Path::new(""))
+        .chain(workspace_dirs.iter().copied())
+        .map(|dir| dir.join("node_modules"))
+}
+
+/// Replaces the `node_modules` directories in `app_dir` with the cached ones.
+/// A directory that wasn't cached is removed, as `npm ci` wouldn't have kept it.
+fn restore_node_modules(
+    layer_dir: &Path,
+    app_dir: &Path,
+    workspace_dirs: &[&Path],
+) -> Result<(), std::io::Error> {
+    for node_modules in node_modules_dirs(workspace_dirs) {
+        let cached = layer_dir.join(&node_modules);
+        let installed = app_dir.join(&node_modules);
+        if cached.is_dir() {
+            replace_dir(&cached, &installed)?;
+        } else if fs::symlink_metadata(&installed).is_ok() {
+            fs::remove_dir_all(&installed)?;
+        }
+    }
+    Ok(())
+}
+
+/// Copies the `node_modules` directories that `npm ci` created in `app_dir` to
+/// the cache.
+fn cache_node_modules(
+    app_dir: &Path,
+    layer_dir: &Path,
+    workspace_dirs: &[&Path],
+) -> Result<(), std::io::Error> {
+    for node_modules in node_modules_dirs(workspace_dirs) {
+        let installed = app_dir.join(&node_modules);
+        if installed.is_dir() {
+            replace_dir(&installed, &layer_dir.join(&node_modules))?;
+        }
+    }
+    Ok(())
+}
+
+/// Whether a package in `package-lock.json` runs install scripts, like native
+/// modules compiled with node-gyp. These have to run on every build, so
+/// `node_modules` isn't cached for them. Lockfiles without a `packages` field
+/// (v1) don't record install scripts, so they're assumed to have some.
+///
+/// # Errors
+///
+/// Will return an `std::io::Error` if `package-lock.json` can't be read.
+pub(crate) fn lockfile_has_install_scripts(app_dir: &Path) -> Result<bool, std::io::Error> {
+    let lockfile = fs::read(app_dir.join("package-lock.json"))?;
+    Ok(serde_json::from_slice::<Value>(&lockfile)
+        .ok()
+        .as_ref()
+        .and_then(|lockfile| lockfile.get("packages"))
+        .and_then(Value::as_object)
+        .map_or(true, |packages| {
+            packages
+                .values()
+                .any(|package| package.get("hasInstallScript") == Some(&Value::Bool(true)))
+        }))
+}
+
+const LAYER_VERSION: &str = "3";
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct NodeModulesCacheLayerMetadata {
+    layer_version: String,
+    lockfile_sha256: String,
+    /// Digests of the root and workspace `package.json` files, by path.
+    package_json_sha256: BTreeMap<String, String>,
+    npmrc_sha256: Option<String>,
+    node_version: Version,
+    npm_version: Version,
+    target: String,
+    workspace: Option<String>,
+}
+
+impl NodeModulesCacheLayerMetadata {
+    /// Builds the cache key for the current build.
+    ///
+    /// # Errors
+    ///
+    /// Will return an `std::io::Error` if `package-lock.json`, a
+    /// `package.json` file, or `.npmrc` can't be read.
+    pub(crate) fn new(
+        app_dir: &Path,
+        node_version: &Version,
+        npm_version: &Version,
+        target: &Target,
+        workspace_dirs: &[&Path],
+        workspace: Option<&Path>,
+    ) -> Result<Self, std::io::Error> {
+        let package_json_sha256 = std::iter::once(Path::new(""))
+            .chain(workspace_dirs.iter().copied())
+            .map(|dir| {
+                let path = dir.join("package.json");
+                sha256_file(&app_dir.join(&path)).map(|digest| (path.display().to_string(), digest))
+            })
+            .collect::<Result<_, _>>()?;
+        let npmrc_path = app_dir.join(".npmrc");
+        let npmrc_sha256 = if npmrc_path.is_file() {
+            Some(sha256_file(&npmrc_path)?)
+        } else {
+            None
+        };
+        Ok(NodeModulesCacheLayerMetadata {
+            layer_version: LAYER_VERSION.to_string(),
+            lockfile_sha256: sha256_file(&app_dir.join("package-lock.json"))?,
+            package_json_sha256,
+            npmrc_sha256,
+            node_version: node_version.clone(),
+            npm_version: npm_version.clone(),
+            target: format!(
+                "{}-{}{} ({} {})",
+                target.os,
+                target.arch,
+                target
+                    .arch_variant
+                    .as_ref()
+                    .map(|variant| format!("-{variant}"))
+                    .unwrap_or_default(),
+                target.distro_name,
+                target.distro_version
+            ),
+            workspace: workspace.map(|workspace| workspace.display().to_string()),
+        })
+    }
+
+    /// Describes the fields that differ from `other`.
+    fn changes(&self, other: &Self) -> Vec<String> {
+        let mut changes = vec![];
+        if self.layer_version != other.layer_version {
+            changes.push("cache format changed".to_string());
+        }
+        if self.lockfile_sha256 != other.lockfile_sha256 {
+            changes.push("package-lock.json changed".to_string());
+        }
+        let package_json_paths = self
+            .package_json_sha256
+            .keys()
+            .chain(other.package_json_sha256.keys())
+            .collect::<std::collections::BTreeSet<_>>();
+        for path in package_json_paths {
+            if self.package_json_sha256.get(path) != other.package_json_sha256.get(path) {
+                changes.push(format!("{path} changed"));
+            }
+        }
+        if self.npmrc_sha256 != other.npmrc_sha256 {
+            changes.push(".npmrc changed".to_string());
+        }
+        if self.node_version != other.node_version {
+            changes.push(format!(
+                "Node.js version changed from {} to {}",
+                self.node_version, other.node_version
+            ));
+        }
+        if self.npm_version != other.npm_version {
+            changes.push(format!(
+                "npm version changed from {} to {}",
+                self.npm_version, other.npm_version
+            ));
+        }
+        if self.target != other.target {
+            changes.push(format!(
+                "target changed from {} to {}",
+                self.target, other.target
+            ));
+        }
+        if self.workspace != other.workspace {
+            changes.push(format!(
+                "workspace changed from {} to {}",
+                self.workspace.as_deref().unwrap_or("none"),
+                other.workspace.as_deref().unwrap_or("none")
+            ));
+        }
+        changes
+    }
+}
+
+fn sha256_file(path: &Path) -> Result<String, std::io::Error> {
+    fs::read(path).map(|contents| format!("{:x}", Sha256::digest(contents)))
+}
+
+/// Replaces `to` with a copy of `from`, keeping symlinks (like the ones in
+/// `node_modules/.bin`) as symlinks.
+fn replace_dir(from: &Path, to: &Path) -> Result<(), std::io::Error> {
+    if fs::symlink_metadata(to).is_ok() {
+        fs::remove_dir_all(to)?;
+    }
+    copy_dir(from, to)
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<(), std::io::Error> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let target = to.join(entry.file_name());
+        if file_type.is_symlink() {
+            symlink(fs::read_link(entry.path())?, target)?;
+        } else if file_type.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata(files: &[(&str, &str)], node_version: &str) -> NodeModulesCacheLayerMetadata {
+        let app_dir = tempfile::tempdir().unwrap();
+        for (path, contents) in files {
+            let path = app_dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        NodeModulesCacheLayerMetadata::new(
+            app_dir.path(),
+            &node_version.parse().unwrap(),
+            &"10.8.2".parse().unwrap(),
+            &Target {
+                os: "linux".to_string(),
+                arch: "arm64".to_string(),
+                arch_variant: None,
+                distro_name: "ubuntu".to_string(),
+                distro_version: "24.04".to_string(),
+            },
+            &[Path::new("packages/api")],
+            None,
+        )
+        .unwrap()
+    }
+
+    const APP: [(&str, &str); 3] = [
+        ("package-lock.json", "{}"),
+        ("package.json", "{}"),
+        ("packages/api/package.json", "{}"),
+    ];
+
+    #[test]
+    fn metadata_changes() {
+        let old = metadata(&APP, "22.8.0");
+        assert_eq!(old.target, "linux-arm64 (ubuntu 24.04)");
+        assert!(old.changes(&metadata(&APP, "22.8.0")).is_empty());
+        assert_eq!(
+            old.changes(&metadata(
+                &[
+                    ("package-lock.json", r#"{ "lockfileVersion": 3 }"#),
+                    ("package.json", "{}"),
+                    ("packages/api/package.json", "{}"),
+                ],
+                "22.9.0"
+            )),
+            [
+                "package-lock.json changed",
+                "Node.js version changed from 22.8.0 to 22.9.0"
+            ]
+        );
+    }
+
+    #[test]
+    fn metadata_changes_for_package_json_and_npmrc() {
+        let old = metadata(&APP, "22.8.0");
+        assert_eq!(
+            old.changes(&metadata(
+                &[
+                    ("package-lock.json", "{}"),
+                    ("package.json", r#"{ "overrides": { "foo": "1.0.0" } }"#),
+                    ("packages/api/package.json", r#"{ "name": "api" }"#),
+                    (".npmrc", "legacy-peer-deps=true"),
+                ],
+                "22.8.0"
+            )),
+            [
+                "package.json changed",
+                "packages/api/package.json changed",
+                ".npmrc changed"
+            ]
+        );
+    }
+
+    #[test]
+    fn install_scripts_in_lockfile() {
+        let app_dir = tempfile::tempdir().unwrap();
+        let lockfile_has_install_scripts = |lockfile: &str| {
+            fs::write(app_dir.path().join("package-lock.json"), lockfile).unwrap();
+            super::lockfile_has_install_scripts(app_dir.path()).unwrap()
+        };
+        assert!(lockfile_has_install_scripts(
+            r#"{ "lockfileVersion": 3, "packages": { "": {}, "node_modules/dtrace-provider": { "hasInstallScript": true } } }"#
+        ));
+        assert!(!lockfile_has_install_scripts(
+            r#"{ "lockfileVersion": 3, "packages": { "": {}, "node_modules/nan": {} } }"#
+        ));
+        assert!(lockfile_has_install_scripts(
+            r#"{ "lockfileVersion": 1, "dependencies": { "nan": { "version": "2.19.0" } } }"#
+        ));
+    }
+
+    #[test]
+    fn cache_and_restore_workspace_node_modules() {
+        let dir = tempfile::tempdir().unwrap();
+        let app_dir = dir.path().join("app");
+        let layer_dir = dir.path().join("layer");
+        let workspace_dirs = [Path::new("packages/api"), Path::new("packages/web")];
+        for (path, contents) in [
+            ("node_modules/ms/package.json", r#"{ "version": "2.1.3" }"#),
+            (
+                "packages/api/node_modules/ms/package.json",
+                r#"{ "version": "2.0.0" }"#,
+            ),
+            ("packages/api/node_modules/ms/cli.js", "cli"),
+            ("packages/web/package.json", "{}"),
+        ] {
+            let path = app_dir.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        fs::create_dir_all(app_dir.join("packages/api/node_modules/.bin")).unwrap();
+        symlink(
+            "../ms/cli.js",
+            app_dir.join("packages/api/node_modules/.bin/ms"),
+        )
+        .unwrap();
+        cache_node_modules(&app_dir, &layer_dir, &workspace_dirs).unwrap();
+
+        fs::remove_dir_all(&app_dir).unwrap();
+        fs::create_dir_all(app_dir.join("packages/web/node_modules/stale")).unwrap();
+        restore_node_modules(&layer_dir, &app_dir, &workspace_dirs).unwrap();
+        assert_eq!(
+            fs::read_to_string(app_dir.join("node_modules/ms/package.json")).unwrap(),
+            r#"{ "version": "2.1.3" }"#
+        );
+        assert_eq!(
+            fs::read_to_string(app_dir.join("packages/api/node_modules/ms/package.json")).unwrap(),
+            r#"{ "version": "2.0.0" }"#
+        );
+        assert_eq!(
+            fs::read_link(app_dir.join("packages/api/node_modules/.bin/ms")).unwrap(),
+            Path::new("../ms/cli.js")
+        );
+        assert!(!app_dir.join("packages/web/node_modules").exists());
+    }
+
+    #[test]
+    fn replace_dir_keeps_symlinks() {
+        let dir = tempfile::tempdir().unwrap();
+        let from = dir.path().join("from");
+        fs::create_dir_all(from.join("dep")).unwrap();
+        fs::create_dir_all(from.join(".bin")).unwrap();
+        fs::write(from.join("dep/cli.js"), "cli").unwrap();
+        symlink("../dep/cli.js", from.join(".bin/cli")).unwrap();
+
+        let to = dir.path().join("to");
+        fs::create_dir_all(&to).unwrap();
+        fs::write(to.join("stale.js"), "stale").unwrap();
+
+        replace_dir(&from, &to).unwrap();
+        assert!(!to.join("stale.js").exists());
+        assert_eq!(fs::read_to_string(to.join("dep/cli.js")).unwrap(), "cli");
+        assert_eq!(
+            fs::read_link(to.join(".bin/cli")).unwrap(),
+            Path::new("../dep/cli.js")
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use bullet_stream::state::SubBullet;
 use bullet_stream::Print;
 use fun_run::CommandWithName;
+use heroku_nodejs_utils::config::Setting;
 use libcnb::build::BuildContext;
 use libcnb::data::layer_name;
 use libcnb::layer::{
@@ -16,6 +17,7 @@ use crate::{npm, NpmInstallBuildpack};
 pub(crate) fn configure_npm_cache_directory(
     context: &BuildContext<NpmInstallBuildpack>,
     env: &Env,
+    cache: &Setting<bool>,
     mut section_logger: Print<SubBullet<Stdout>>,
 ) -> Result<Print<SubBullet<Stdout>>, libcnb::Error<NpmInstallBuildpackError>> {
     let new_metadata = NpmCacheLayerMetadata {
@@ -29,7 +31,7 @@ pub(crate) fn configure_npm_cache_directory(
             launch: false,
             invalid_metadata_action: &|_| InvalidMetadataAction::DeleteLayer,
             restored_layer_action: &|old_metadata: &NpmCacheLayerMetadata, _| {
-                if old_metadata == &new_metadata {
+                if cache.value && old_metadata == &new_metadata {
                     RestoredLayerAction::KeepLayer
                 } else {
                     RestoredLayerAction::DeleteLayer
@@ -44,7 +46,14 @@ pub(crate) fn configure_npm_cache_directory(
         }
         LayerState::Empty { cause } => {
             if let EmptyLayerCause::RestoredLayerAction { .. } = cause {
-                section_logger = section_logger.sub_bullet("Restoring npm cache");
+                section_logger = if cache.value {
+                    section_logger.sub_bullet("Restoring npm cache")
+                } else {
+                    section_logger.sub_bullet(format!(
+                        "Not restoring npm cache ({})",
+                        cache.reason("cache")
+                    ))
+                };
             }
             section_logger = section_logger.sub_bullet("Creating npm cache");
             npm_cache_layer.write_metadata(new_metadata)?;
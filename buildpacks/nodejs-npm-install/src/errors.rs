@@ -1,13 +1,24 @@
+use crate::node;
 use crate::npm;
 use crate::BUILDPACK_NAME;
 use bullet_stream::state::Bullet;
 use bullet_stream::{style, Print};
 use fun_run::CmdError;
 use heroku_nodejs_utils::application;
+use heroku_nodejs_utils::build_scripts::BuildScriptsError;
 use heroku_nodejs_utils::buildplan::{
     NodeBuildScriptsMetadataError, NODE_BUILD_SCRIPTS_BUILD_PLAN_NAME,
 };
+use heroku_nodejs_utils::config::{
+    ConfigError, ADVISORIES_ENV_VAR, NPM_WORKSPACE_ENV_VAR, PROJECT_TOML, SKIP_PRUNING_ENV_VAR,
+    VULNERABILITIES_FAIL_ON_ENV_VAR,
+};
+use heroku_nodejs_utils::licenses::LicenseCheckError;
 use heroku_nodejs_utils::package_json::PackageJsonError;
+use heroku_nodejs_utils::procfile::ProcfileError;
+use heroku_nodejs_utils::registry_credentials::RegistryCredentialsError;
+use heroku_nodejs_utils::vulnerabilities::{VulnerabilityError, OSV_BINDING_TYPE};
+use heroku_nodejs_utils::workspaces::WorkspaceError;
 use indoc::formatdoc;
 use std::fmt::Display;
 use std::io;
@@ -24,11 +35,28 @@ locally with a minimal example and open an issue in the buildpack's GitHub repos
 pub(crate) enum NpmInstallBuildpackError {
     Application(application::Error),
     BuildScript(CmdError),
+    BuildScripts(BuildScriptsError),
+    Config(ConfigError),
     Detect(io::Error),
+    Licenses(LicenseCheckError),
+    NodeModulesCache(io::Error),
+    NodeVersion(node::VersionError),
     NpmInstall(CmdError),
+    NpmInstallIntegrity(CmdError),
+    NpmInstallLockfileOutOfSync(CmdError),
+    NpmInstallNativeModule(CmdError),
+    NpmInstallPackageNotFound(CmdError),
+    NpmInstallPeerDependencyConflict(CmdError),
+    NpmInstallRegistryAuth(CmdError),
+    NpmInstallUnsupportedLockfile(CmdError),
+    NpmPrune(CmdError),
     NpmSetCacheDir(CmdError),
     NpmVersion(npm::VersionError),
     PackageJson(PackageJsonError),
+    Procfile(ProcfileError),
+    RegistryCredentials(RegistryCredentialsError),
+    Vulnerabilities(VulnerabilityError),
+    Workspace(WorkspaceError),
     NodeBuildScriptsMetadata(NodeBuildScriptsMetadataError),
 }
 
@@ -46,34 +74,53 @@ fn on_buildpack_error(error: NpmInstallBuildpackError, logger: Print<Bullet<Stdo
     match error {
         NpmInstallBuildpackError::Application(e) => on_application_error(&e, logger),
         NpmInstallBuildpackError::BuildScript(e) => on_build_script_error(&e, logger),
+        NpmInstallBuildpackError::BuildScripts(e) => on_build_scripts_error(&e, logger),
+        NpmInstallBuildpackError::Config(e) => on_config_error(&e, logger),
         NpmInstallBuildpackError::Detect(e) => on_detect_error(&e, logger),
+        NpmInstallBuildpackError::Licenses(e) => on_licenses_error(&e, logger),
         NpmInstallBuildpackError::NodeBuildScriptsMetadata(e) => {
-            on_node_build_scripts_metadata_error(e, logger);
+            on_node_build_scripts_metadata_error(&e, logger);
         }
+        NpmInstallBuildpackError::NodeModulesCache(e) => on_node_modules_cache_error(&e, logger),
+        NpmInstallBuildpackError::NodeVersion(e) => on_node_version_error(e, logger),
         NpmInstallBuildpackError::NpmInstall(e) => on_npm_install_error(&e, logger),
+        NpmInstallBuildpackError::NpmInstallIntegrity(e) => on_integrity_error(&e, logger),
+        NpmInstallBuildpackError::NpmInstallLockfileOutOfSync(e) => {
+            on_lockfile_out_of_sync_error(&e, logger);
+        }
+        NpmInstallBuildpackError::NpmInstallNativeModule(e) => on_native_module_error(&e, logger),
+        NpmInstallBuildpackError::NpmInstallPackageNotFound(e) => {
+            on_package_not_found_error(&e, logger);
+        }
+        NpmInstallBuildpackError::NpmInstallPeerDependencyConflict(e) => {
+            on_peer_dependency_conflict_error(&e, logger);
+        }
+        NpmInstallBuildpackError::NpmInstallRegistryAuth(e) => on_registry_auth_error(&e, logger),
+        NpmInstallBuildpackError::NpmInstallUnsupportedLockfile(e) => {
+            on_unsupported_lockfile_error(&e, logger);
+        }
+        NpmInstallBuildpackError::NpmPrune(e) => on_npm_prune_error(&e, logger),
         NpmInstallBuildpackError::NpmSetCacheDir(e) => on_set_cache_dir_error(&e, logger),
         NpmInstallBuildpackError::NpmVersion(e) => on_npm_version_error(e, logger),
         NpmInstallBuildpackError::PackageJson(e) => on_package_json_error(e, logger),
+        NpmInstallBuildpackError::Procfile(e) => on_procfile_error(&e, logger),
+        NpmInstallBuildpackError::RegistryCredentials(e) => {
+            on_registry_credentials_error(&e, logger);
+        }
+        NpmInstallBuildpackError::Vulnerabilities(e) => on_vulnerabilities_error(&e, logger),
+        NpmInstallBuildpackError::Workspace(e) => on_workspace_error(e, logger),
     }
 }
 
 fn on_node_build_scripts_metadata_error(
-    error: NodeBuildScriptsMetadataError,
+    error: &NodeBuildScriptsMetadataError,
     logger: Print<Bullet<Stdout>>,
 ) {
-    let NodeBuildScriptsMetadataError::InvalidEnabledValue(value) = error;
-    let value_type = value.type_str();
     logger.error(formatdoc! { "
         A participating buildpack has set invalid `[requires.metadata]` for the build plan \
         named `{NODE_BUILD_SCRIPTS_BUILD_PLAN_NAME}`.
         
-        Expected metadata format:
-        [requires.metadata]
-        enabled = <bool>
-        
-        But was:
-        [requires.metadata]
-        enabled = <{value_type}>     
+        {error}
     "});
 }
 
@@ -107,6 +154,226 @@ fn on_package_json_error(error: PackageJsonError, logger: Print<Bullet<Stdout>>)
     }
 }
 
+fn on_procfile_error(error: &ProcfileError, logger: Print<Bullet<Stdout>>) {
+    match error {
+        ProcfileError::Read(e) => {
+            print_error_details(logger, &e).error(formatdoc! {"
+                    Error reading {procfile}.
+
+                    This buildpack adds the processes declared in {procfile} to the image but \
+                    the file can’t be read.
+
+                    {USE_DEBUG_INFORMATION_AND_RETRY_BUILD}
+
+                    {SUBMIT_AN_ISSUE}
+                ", procfile = style::value("Procfile") });
+        }
+        e => {
+            logger.error(formatdoc! {"
+                Error parsing {procfile}.
+
+                {e}
+
+                Fix the entry and retry your build.
+            ", procfile = style::value("Procfile") });
+        }
+    }
+}
+
+fn on_config_error(error: &ConfigError, logger: Print<Bullet<Stdout>>) {
+    match error {
+        ConfigError::Read(_) => {
+            print_error_details(logger, &error).error(formatdoc! {"
+                    Error reading {project_toml}.
+
+                    This buildpack reads its configuration from {project_toml} but the file \
+                    can’t be read.
+
+                    {USE_DEBUG_INFORMATION_AND_RETRY_BUILD}
+
+                    {SUBMIT_AN_ISSUE}
+                ", project_toml = style::value(PROJECT_TOML) });
+        }
+        ConfigError::Parse(_)
+        | ConfigError::InvalidValue { .. }
+        | ConfigError::InvalidEnvVar { .. }
+        | ConfigError::AppRoot(..) => {
+            logger.error(formatdoc! {"
+                Invalid Node.js buildpack configuration.
+
+                {error}
+
+                Fix the configuration and retry your build.
+            "});
+        }
+    }
+}
+
+fn on_workspace_error(error: WorkspaceError, logger: Print<Bullet<Stdout>>) {
+    match error {
+        WorkspaceError::ReadDir(_, _) => {
+            print_error_details(logger, &error).error(formatdoc! {"
+                    Error searching for workspaces.
+
+                    An unexpected error occurred while searching the app directory for the \
+                    workspaces declared in {package_json}.
+
+                    {USE_DEBUG_INFORMATION_AND_RETRY_BUILD}
+
+                    {SUBMIT_AN_ISSUE}
+                ", package_json = style::value("package.json") });
+        }
+        WorkspaceError::PackageJson(e) => on_package_json_error(e, logger),
+        WorkspaceError::NoWorkspaces | WorkspaceError::NotFound { .. } => {
+            logger.error(formatdoc! {"
+                Error selecting workspace.
+
+                {error}.
+
+                The {workspace_env} environment variable, or {npm_workspace} in {project_toml}, \
+                selects the workspace to install and build by its package name or its path \
+                relative to the root {package_json}. Update it to match one of the {workspaces} \
+                declared in the root {package_json} and retry your build.
+            ",
+                workspace_env = style::value(NPM_WORKSPACE_ENV_VAR),
+                npm_workspace = style::value("npm.workspace"),
+                project_toml = style::value(PROJECT_TOML),
+                package_json = style::value("package.json"),
+                workspaces = style::value("workspaces"),
+            });
+        }
+    }
+}
+
+fn on_registry_credentials_error(error: &RegistryCredentialsError, logger: Print<Bullet<Stdout>>) {
+    match error {
+        RegistryCredentialsError::ReadBinding(_, _)
+        | RegistryCredentialsError::WriteUserConfig(_) => {
+            print_error_details(logger, &error).error(formatdoc! {"
+                    Error configuring registry credentials.
+
+                    An unexpected error occurred while configuring the registry credentials from \
+                    the service bindings.
+
+                    {USE_DEBUG_INFORMATION_AND_RETRY_BUILD}
+
+                    {SUBMIT_AN_ISSUE}
+                "});
+        }
+        RegistryCredentialsError::MissingEntry { .. }
+        | RegistryCredentialsError::InvalidRegistry { .. } => {
+            logger.error(formatdoc! {"
+                Invalid registry service binding.
+
+                {error}.
+
+                Bindings of type {npmrc} need an {npmrc_entry} entry, and bindings of type \
+                {npm_registry} need a {registry} entry with the registry URL. Update the binding \
+                and retry your build.
+            ",
+                npmrc = style::value("npmrc"),
+                npmrc_entry = style::value(".npmrc"),
+                npm_registry = style::value("npm-registry"),
+                registry = style::value("registry"),
+            });
+        }
+    }
+}
+
+fn on_licenses_error(error: &LicenseCheckError, logger: Print<Bullet<Stdout>>) {
+    match error {
+        LicenseCheckError::Denied(_) => {
+            logger.error(formatdoc! {"
+                Denied dependency licenses.
+
+                {error}
+
+                These licenses are denied by {deny} in {project_toml}. Remove or replace these \
+                dependencies, or allow them with {exceptions}, and retry your build.
+            ",
+                deny = style::value("licenses.deny"),
+                exceptions = style::value("licenses.exceptions"),
+                project_toml = style::value(PROJECT_TOML),
+            });
+        }
+        LicenseCheckError::DependencyGraph(_) => {
+            logger.error(formatdoc! {"
+                Error reading the installed dependencies.
+
+                {error}
+
+                The licenses denied by {deny} in {project_toml} are checked against the \
+                packages in {package_lock}. Make sure {package_lock} is committed and up to \
+                date, and retry your build.
+            ",
+                deny = style::value("licenses.deny"),
+                project_toml = style::value(PROJECT_TOML),
+                package_lock = style::value("package-lock.json"),
+            });
+        }
+        LicenseCheckError::WriteReport(_, _) => {
+            print_error_details(logger, &error).error(formatdoc! {"
+                Error writing the dependency license report.
+
+                An unexpected error occurred while writing the license report.
+
+                {USE_DEBUG_INFORMATION_AND_RETRY_BUILD}
+
+                {SUBMIT_AN_ISSUE}
+            "});
+        }
+    }
+}
+
+fn on_vulnerabilities_error(error: &VulnerabilityError, logger: Print<Bullet<Stdout>>) {
+    match error {
+        VulnerabilityError::Found { .. } => {
+            logger.error(formatdoc! {"
+                Known vulnerabilities in production dependencies.
+
+                {error}
+
+                Update these dependencies to a fixed version and retry your build. To only report \
+                vulnerabilities instead, unset {fail_on} in {project_toml} and {fail_on_env_var}.
+            ",
+                fail_on = style::value("vulnerabilities.fail_on"),
+                fail_on_env_var = style::value(VULNERABILITIES_FAIL_ON_ENV_VAR),
+                project_toml = style::value(PROJECT_TOML),
+            });
+        }
+        VulnerabilityError::ReadAdvisories(_, _) | VulnerabilityError::ParseAdvisory(_, _) => {
+            logger.error(formatdoc! {"
+                Error reading the vulnerability advisories.
+
+                {error}
+
+                The advisories are read from the {advisories} directory in {project_toml}, \
+                {advisories_env_var}, or an {osv} service binding. Make sure it only holds \
+                OSV advisories in {json} files, and retry your build.
+            ",
+                advisories = style::value("vulnerabilities.advisories"),
+                advisories_env_var = style::value(ADVISORIES_ENV_VAR),
+                osv = style::value(OSV_BINDING_TYPE),
+                json = style::value(".json"),
+                project_toml = style::value(PROJECT_TOML),
+            });
+        }
+        VulnerabilityError::DependencyGraph(_) => {
+            logger.error(formatdoc! {"
+                Error reading the installed dependencies.
+
+                {error}
+
+                The vulnerability advisories are checked against the packages in \
+                {package_lock}. Make sure {package_lock} is committed and up to date, and \
+                retry your build.
+            ",
+                package_lock = style::value("package-lock.json"),
+            });
+        }
+    }
+}
+
 fn on_set_cache_dir_error(error: &CmdError, logger: Print<Bullet<Stdout>>) {
     print_error_details(logger, &error).error(formatdoc! {"
             Failed to set the {npm} cache directory.
@@ -142,6 +409,42 @@ fn on_npm_version_error(error: npm::VersionError, logger: Print<Bullet<Stdout>>)
     }
 }
 
+fn on_node_version_error(error: node::VersionError, logger: Print<Bullet<Stdout>>) {
+    match error {
+        node::VersionError::Command(e) => {
+            print_error_details(logger, &e).error(formatdoc! {"
+                    Failed to determine {node} version information.
+
+                    An unexpected error occurred while executing {node_version}.
+
+                    {SUBMIT_AN_ISSUE}
+                ", node = style::value("Node.js"), node_version = style::value(e.name()) });
+        }
+        node::VersionError::Parse(stdout, e) => {
+            print_error_details(logger, &e).error(formatdoc! {"
+                    Failed to parse {node} version information.
+
+                    An unexpected error occurred while parsing version information from {output}.
+
+                    {SUBMIT_AN_ISSUE}
+                ", node = style::value("Node.js"), output = style::value(stdout) });
+        }
+    }
+}
+
+fn on_node_modules_cache_error(error: &io::Error, logger: Print<Bullet<Stdout>>) {
+    print_error_details(logger, &error).error(formatdoc! {"
+            Failed to cache {node_modules}.
+
+            An unexpected error occurred while restoring {node_modules} from, or saving it to, \
+            the build cache.
+
+            {USE_DEBUG_INFORMATION_AND_RETRY_BUILD}
+
+            {SUBMIT_AN_ISSUE}
+        ", node_modules = style::value("node_modules") });
+}
+
 fn on_npm_install_error(error: &CmdError, logger: Print<Bullet<Stdout>>) {
     print_error_details(logger, &error)
         .error(formatdoc! {"
@@ -157,6 +460,163 @@ fn on_npm_install_error(error: &CmdError, logger: Print<Bullet<Stdout>>) {
         ", npm_install = style::value(error.name()), buildpack_name = style::value(BUILDPACK_NAME) });
 }
 
+fn on_lockfile_out_of_sync_error(error: &CmdError, logger: Print<Bullet<Stdout>>) {
+    print_error_details(logger, &error).error(formatdoc! {"
+        Failed to install Node modules: {package_json} and {package_lock} are out of sync.
+
+        The {buildpack_name} uses the command {npm_install} to install the exact dependencies \
+        listed in {package_lock}. This command failed because the dependencies in \
+        {package_json} don't match the lockfile. See the missing or invalid packages in the \
+        log output above.
+
+        Run {npm_install_local} locally to update {package_lock}, commit the changes, and \
+        retry your build.
+    ",
+        package_json = style::value("package.json"),
+        package_lock = style::value("package-lock.json"),
+        npm_install = style::value(error.name()),
+        npm_install_local = style::command("npm install"),
+        buildpack_name = style::value(BUILDPACK_NAME),
+    });
+}
+
+fn on_peer_dependency_conflict_error(error: &CmdError, logger: Print<Bullet<Stdout>>) {
+    print_error_details(logger, &error).error(formatdoc! {"
+        Failed to install Node modules: conflicting peer dependencies.
+
+        npm couldn't resolve the dependency tree in {package_lock} because a package requires \
+        a peer dependency version that conflicts with another dependency. See the conflicting \
+        packages in the log output above.
+
+        Update the conflicting dependencies to versions with compatible peer dependencies, run \
+        {npm_install} locally to update {package_lock}, commit the changes, and retry your \
+        build. To accept the conflict instead, add {legacy_peer_deps} to the {npmrc} file in \
+        your project.
+    ",
+        package_lock = style::value("package-lock.json"),
+        npm_install = style::command("npm install"),
+        legacy_peer_deps = style::value("legacy-peer-deps=true"),
+        npmrc = style::value(".npmrc"),
+    });
+}
+
+fn on_package_not_found_error(error: &CmdError, logger: Print<Bullet<Stdout>>) {
+    print_error_details(logger, &error).error(formatdoc! {"
+        Failed to install Node modules: a package wasn't found in the registry.
+
+        The registry returned {not_found} for a package in {package_lock}. See the package \
+        and registry URL in the log output above.
+
+        Check that the package name and version exist in the registry and that the registry \
+        configured in {npmrc} is correct. Registries also return {not_found} for private \
+        packages when no credentials are configured. Provide credentials with a service \
+        binding of type {npmrc_type} or {npm_registry_type}, and retry your build.
+    ",
+        not_found = style::value("404 Not Found"),
+        package_lock = style::value("package-lock.json"),
+        npmrc = style::value(".npmrc"),
+        npmrc_type = style::value("npmrc"),
+        npm_registry_type = style::value("npm-registry"),
+    });
+}
+
+fn on_registry_auth_error(error: &CmdError, logger: Print<Bullet<Stdout>>) {
+    print_error_details(logger, &error).error(formatdoc! {"
+        Failed to install Node modules: the registry rejected the credentials.
+
+        The registry refused to serve a package in {package_lock} because the request was \
+        unauthenticated or the credentials don't grant access to it.
+
+        Check that the token in the service binding of type {npmrc_type} or \
+        {npm_registry_type} is valid, hasn't expired, and can read the package, and retry \
+        your build.
+    ",
+        package_lock = style::value("package-lock.json"),
+        npmrc_type = style::value("npmrc"),
+        npm_registry_type = style::value("npm-registry"),
+    });
+}
+
+fn on_integrity_error(error: &CmdError, logger: Print<Bullet<Stdout>>) {
+    print_error_details(logger, &error).error(formatdoc! {"
+        Failed to install Node modules: a package failed its integrity check.
+
+        A downloaded package didn't match the {integrity} checksum recorded in \
+        {package_lock}. This happens when {package_lock} was edited by hand, when a package \
+        was republished, or when a registry mirror serves different files than the registry \
+        the lockfile was created with.
+
+        Reinstall the package locally with {npm_install} to update its checksum in \
+        {package_lock}, commit the changes, and retry your build.
+    ",
+        integrity = style::value("integrity"),
+        package_lock = style::value("package-lock.json"),
+        npm_install = style::command("npm install"),
+    });
+}
+
+fn on_native_module_error(error: &CmdError, logger: Print<Bullet<Stdout>>) {
+    print_error_details(logger, &error).error(formatdoc! {"
+        Failed to install Node modules: a native module failed to compile.
+
+        A dependency uses {node_gyp} to compile a native addon during installation, and the \
+        compilation failed. See the compiler errors in the log output above.
+
+        Native modules often need an update to support a new Node.js major version. Check \
+        that the installed version of the module supports the Node.js version of your app, \
+        update the module if needed, and retry your build.
+    ",
+        node_gyp = style::value("node-gyp"),
+    });
+}
+
+fn on_unsupported_lockfile_error(error: &CmdError, logger: Print<Bullet<Stdout>>) {
+    print_error_details(logger, &error).error(formatdoc! {"
+        Failed to install Node modules: unsupported {lockfile_version}.
+
+        {package_lock} was created by a newer version of npm than the one installed, and \
+        this version of npm can't read it. Lockfiles with a {lockfile_version} of 2 or 3 \
+        need npm 7 or newer.
+
+        Set {engines_npm} in {package_json} to the npm version that created the lockfile, or \
+        recreate {package_lock} with the installed npm version, and retry your build.
+    ",
+        lockfile_version = style::value("lockfileVersion"),
+        package_lock = style::value("package-lock.json"),
+        package_json = style::value("package.json"),
+        engines_npm = style::value("engines.npm"),
+    });
+}
+
+fn on_npm_prune_error(error: &CmdError, logger: Print<Bullet<Stdout>>) {
+    print_error_details(logger, &error).error(formatdoc! {"
+            Failed to prune dev dependencies.
+
+            The {buildpack_name} uses the command {npm_prune} to remove dev dependencies from \
+        the launch image after the build scripts have run. This command failed and the buildpack \
+            cannot continue. See the log output above for more information.
+
+            Ensure that this command runs locally without error and retry your build. To keep dev \
+            dependencies in the launch image instead, set {skip_pruning} to {true_value}.
+        ",
+        npm_prune = style::value(error.name()),
+        buildpack_name = style::value(BUILDPACK_NAME),
+        skip_pruning = style::value(SKIP_PRUNING_ENV_VAR),
+        true_value = style::value("true"),
+    });
+}
+
+fn on_build_scripts_error(error: &BuildScriptsError, logger: Print<Bullet<Stdout>>) {
+    logger.error(formatdoc! {"
+        Error selecting build scripts.
+
+        {error}.
+
+        Add the script to {package_json} or update the configured build scripts and retry \
+        your build.
+    ", package_json = style::value("package.json") });
+}
+
 fn on_build_script_error(error: &CmdError, logger: Print<Bullet<Stdout>>) {
     print_error_details(logger, &error)
         .error(formatdoc! {"
@@ -167,6 +627,7 @@ fn on_build_script_error(error: &CmdError, logger: Print<Bullet<Stdout>>) {
             - {heroku_prebuild} 
             - {heroku_build} or {build} 
             - {heroku_postbuild}
+            - {heroku_cleanup}
 
             An unexpected error occurred while executing {build_script}. See the log output above for more information.
 
@@ -179,6 +640,7 @@ fn on_build_script_error(error: &CmdError, logger: Print<Bullet<Stdout>>) {
             heroku_build = style::value("heroku-build"),
             build = style::value("build"),
             heroku_postbuild = style::value("heroku-postbuild"),
+            heroku_cleanup = style::value("heroku-cleanup"),
         });
 }
 
@@ -212,6 +674,42 @@ fn on_framework_error(
         ", buildpack_name = style::value(BUILDPACK_NAME) });
 }
 
+/// Classifies an `npm ci` failure from its output, so the error explains how to fix
+/// the common failures. Failures that aren't recognized stay `NpmInstall` errors.
+pub(crate) fn classify_npm_install_error(error: CmdError) -> NpmInstallBuildpackError {
+    let output = match &error {
+        CmdError::SystemError(_, _) => return NpmInstallBuildpackError::NpmInstall(error),
+        CmdError::NonZeroExitNotStreamed(output) | CmdError::NonZeroExitAlreadyStreamed(output) => {
+            format!("{}\n{}", output.stdout_lossy(), output.stderr_lossy())
+        }
+    };
+    // npm 9 and older prefix errors with `npm ERR!`, newer versions with `npm error`.
+    let code = output.lines().find_map(|line| {
+        line.strip_prefix("npm error code ")
+            .or_else(|| line.strip_prefix("npm ERR! code "))
+            .map(str::trim)
+    });
+    match code {
+        Some("EUSAGE") => NpmInstallBuildpackError::NpmInstallLockfileOutOfSync(error),
+        Some("ERESOLVE") => NpmInstallBuildpackError::NpmInstallPeerDependencyConflict(error),
+        Some("E404") => NpmInstallBuildpackError::NpmInstallPackageNotFound(error),
+        Some("E401" | "E403") => NpmInstallBuildpackError::NpmInstallRegistryAuth(error),
+        Some("EINTEGRITY") => NpmInstallBuildpackError::NpmInstallIntegrity(error),
+        _ if output.contains("gyp ERR!") => NpmInstallBuildpackError::NpmInstallNativeModule(error),
+        // npm 6 warns about newer lockfiles whatever the error, so the warning
+        // only explains failures that nothing else does, like the out of sync
+        // lockfile that npm 6 reports when it can't read the lockfile.
+        _ if output.contains("This version of npm is compatible with lockfileVersion@") => {
+            NpmInstallBuildpackError::NpmInstallUnsupportedLockfile(error)
+        }
+        // npm 6 reports an out of sync lockfile without an error code.
+        None if output.contains("can only install packages when your package.json and") => {
+            NpmInstallBuildpackError::NpmInstallLockfileOutOfSync(error)
+        }
+        _ => NpmInstallBuildpackError::NpmInstall(error),
+    }
+}
+
 fn print_error_details(
     logger: Print<Bullet<Stdout>>,
     error: &impl Display,
@@ -221,3 +719,88 @@ fn print_error_details(
         .sub_bullet(error.to_string())
         .done()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fun_run::nonzero_streamed;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::{ExitStatus, Output};
+
+    fn npm_ci_error(stderr: &str) -> NpmInstallBuildpackError {
+        classify_npm_install_error(
+            nonzero_streamed(
+                "npm ci --production=false".to_string(),
+                Output {
+                    status: ExitStatus::from_raw(1 << 8),
+                    stdout: vec![],
+                    stderr: stderr.as_bytes().to_vec(),
+                },
+            )
+            .unwrap_err(),
+        )
+    }
+
+    #[test]
+    fn classify_npm_install_errors() {
+        assert!(matches!(
+            npm_ci_error(include_str!("../tests/fixtures/npm-ci-errors/eusage.txt")),
+            NpmInstallBuildpackError::NpmInstallLockfileOutOfSync(_)
+        ));
+        assert!(matches!(
+            npm_ci_error(include_str!("../tests/fixtures/npm-ci-errors/eresolve.txt")),
+            NpmInstallBuildpackError::NpmInstallPeerDependencyConflict(_)
+        ));
+        assert!(matches!(
+            npm_ci_error(include_str!("../tests/fixtures/npm-ci-errors/e404.txt")),
+            NpmInstallBuildpackError::NpmInstallPackageNotFound(_)
+        ));
+        assert!(matches!(
+            npm_ci_error(include_str!("../tests/fixtures/npm-ci-errors/e401.txt")),
+            NpmInstallBuildpackError::NpmInstallRegistryAuth(_)
+        ));
+        assert!(matches!(
+            npm_ci_error(include_str!(
+                "../tests/fixtures/npm-ci-errors/eintegrity.txt"
+            )),
+            NpmInstallBuildpackError::NpmInstallIntegrity(_)
+        ));
+        assert!(matches!(
+            npm_ci_error(include_str!("../tests/fixtures/npm-ci-errors/node-gyp.txt")),
+            NpmInstallBuildpackError::NpmInstallNativeModule(_)
+        ));
+        assert!(matches!(
+            npm_ci_error(include_str!(
+                "../tests/fixtures/npm-ci-errors/npm6-lockfile-version.txt"
+            )),
+            NpmInstallBuildpackError::NpmInstallUnsupportedLockfile(_)
+        ));
+        assert!(matches!(
+            npm_ci_error(include_str!(
+                "../tests/fixtures/npm-ci-errors/npm6-lockfile-version-e401.txt"
+            )),
+            NpmInstallBuildpackError::NpmInstallRegistryAuth(_)
+        ));
+    }
+
+    #[test]
+    fn unknown_npm_install_errors_are_not_classified() {
+        assert!(matches!(
+            npm_ci_error(include_str!(
+                "../tests/fixtures/npm-ci-errors/econnrefused.txt"
+            )),
+            NpmInstallBuildpackError::NpmInstall(_)
+        ));
+        assert!(matches!(
+            npm_ci_error(""),
+            NpmInstallBuildpackError::NpmInstall(_)
+        ));
+        assert!(matches!(
+            classify_npm_install_error(CmdError::SystemError(
+                "npm ci".to_string(),
+                io::Error::new(io::ErrorKind::NotFound, "npm not found")
+            )),
+            NpmInstallBuildpackError::NpmInstall(_)
+        ));
+    }
+}
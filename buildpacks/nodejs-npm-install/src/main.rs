@@ -1,33 +1,55 @@
+mod configure_node_modules_cache;
 mod configure_npm_cache_directory;
 mod configure_npm_runtime_env;
 mod errors;
+mod node;
 mod npm;
 
+use crate::configure_node_modules_cache::{
+    install_with_node_modules_cache, lockfile_has_install_scripts, NodeModulesCacheLayerMetadata,
+};
 use crate::configure_npm_cache_directory::configure_npm_cache_directory;
 use crate::configure_npm_runtime_env::configure_npm_runtime_env;
-use crate::errors::NpmInstallBuildpackError;
-use bullet_stream::state::SubBullet;
+use crate::errors::{classify_npm_install_error, NpmInstallBuildpackError};
+use bullet_stream::state::{Bullet, SubBullet};
 use bullet_stream::{style, Print};
 use fun_run::{CommandWithName, NamedOutput};
 use heroku_nodejs_utils::application;
+use heroku_nodejs_utils::build_scripts::{build_scripts_env, resolve_build_scripts, BuildScript};
 use heroku_nodejs_utils::buildplan::{
     read_node_build_scripts_metadata, NodeBuildScriptsMetadata, NODE_BUILD_SCRIPTS_BUILD_PLAN_NAME,
 };
+use heroku_nodejs_utils::config::{
+    AppRoot, ConfigError, NodejsConfig, Setting, NPM_WORKSPACE_ENV_VAR, PROJECT_TOML,
+};
+use heroku_nodejs_utils::licenses::{
+    write_license_report, LicenseCheckError, LicensePolicy, LicenseReportOutcome,
+};
 use heroku_nodejs_utils::package_json::PackageJson;
 use heroku_nodejs_utils::package_manager::PackageManager;
+use heroku_nodejs_utils::prune::{disk_usage, PruneSummary};
+use heroku_nodejs_utils::registry_credentials::{
+    RegistryCredentials, UserConfig, UserConfigFormat,
+};
+use heroku_nodejs_utils::resolved_node::{read_resolved_node, ResolvedNodeError};
+use heroku_nodejs_utils::sbom::read_dependency_sboms;
 use heroku_nodejs_utils::vrs::Version;
+use heroku_nodejs_utils::vulnerabilities::{self, VulnerabilityError};
+use heroku_nodejs_utils::workspaces::{
+    find_workspaces, select_workspace, Workspace, WorkspaceError,
+};
 use libcnb::build::{BuildContext, BuildResult, BuildResultBuilder};
 use libcnb::data::build_plan::BuildPlanBuilder;
 use libcnb::data::launch::{LaunchBuilder, ProcessBuilder};
 use libcnb::data::process_type;
 use libcnb::detect::{DetectContext, DetectResult, DetectResultBuilder};
 use libcnb::generic::{GenericMetadata, GenericPlatform};
-use libcnb::{buildpack_main, Buildpack, Env};
+use libcnb::sbom::Sbom;
+use libcnb::{buildpack_main, Buildpack, Env, Platform};
 #[cfg(test)]
 use libcnb_test as _;
-#[cfg(test)]
-use serde_json as _;
 use std::io::{stdout, Stdout};
+use std::path::{Path, PathBuf};
 #[cfg(test)]
 use test_support as _;
 
@@ -41,13 +63,16 @@ impl Buildpack for NpmInstallBuildpack {
     type Error = NpmInstallBuildpackError;
 
     fn detect(&self, context: DetectContext<Self>) -> libcnb::Result<DetectResult, Self::Error> {
-        let npm_lockfile_exists = context
-            .app_dir
+        let app_dir = NodejsConfig::read(&context.app_dir)
+            .and_then(|config| config.app_root(context.platform.env()))
+            .map_err(NpmInstallBuildpackError::Config)?
+            .dir(&context.app_dir);
+        let npm_lockfile_exists = app_dir
             .join(PackageManager::Npm.lockfile())
             .try_exists()
             .map_err(NpmInstallBuildpackError::Detect)?;
 
-        if let Ok(package_json) = PackageJson::read(context.app_dir.join("package.json")) {
+        if let Ok(package_json) = PackageJson::read(app_dir.join("package.json")) {
             if npm_lockfile_exists || package_json.has_dependencies() {
                 DetectResultBuilder::pass()
                     .build_plan(
@@ -71,29 +96,110 @@ impl Buildpack for NpmInstallBuildpack {
 
     fn build(&self, context: BuildContext<Self>) -> libcnb::Result<BuildResult, Self::Error> {
         let logger = Print::new(stdout()).h1(BUILDPACK_NAME);
-        let env = Env::from_current();
-        let app_dir = &context.app_dir;
+        let mut env = Env::from_current();
+        let config =
+            NodejsConfig::read(&context.app_dir).map_err(NpmInstallBuildpackError::Config)?;
+        let (app_root, app_dir) = enter_app_root(&context, &config)?;
         let package_json = PackageJson::read(app_dir.join("package.json"))
             .map_err(NpmInstallBuildpackError::PackageJson)?;
         let node_build_scripts_metadata = read_node_build_scripts_metadata(&context.buildpack_plan)
             .map_err(NpmInstallBuildpackError::NodeBuildScriptsMetadata)?;
+        let cache = config
+            .cache(context.platform.env())
+            .map_err(NpmInstallBuildpackError::Config)?;
+        let prune = config
+            .prune(context.platform.env())
+            .map_err(NpmInstallBuildpackError::Config)?;
+        let sbom_spdx = config
+            .sbom_spdx(context.platform.env())
+            .map_err(NpmInstallBuildpackError::Config)?;
 
-        application::check_for_singular_lockfile(app_dir)
+        application::check_for_singular_lockfile(&app_dir)
             .map_err(NpmInstallBuildpackError::Application)?;
 
-        let section = logger.bullet("Installing node modules");
-        let section = log_npm_version(&env, section)?;
-        let section = configure_npm_cache_directory(&context, &env, section)?;
-        let section = run_npm_install(&env, section)?;
+        let workspace = read_npm_workspace(&context, &app_dir, &package_json, &config)?;
+        let build_scripts = resolve_build_scripts(
+            workspace
+                .as_ref()
+                .map_or(&package_json, |workspace| &workspace.value.package_json),
+            &config
+                .build_scripts(context.platform.env())
+                .map_err(NpmInstallBuildpackError::Config)?,
+            &node_build_scripts_metadata,
+        )
+        .map_err(NpmInstallBuildpackError::BuildScripts)?;
+
+        let section = log_app_root(&app_root, logger.bullet("Installing node modules"));
+        let (npm_version, section) = log_npm_version(&env, section)?;
+        let section = log_npm_workspace(&package_json, workspace.as_ref(), section);
+        let workspace = workspace.map(|workspace| workspace.value);
+        // Keeps the rendered credentials around until the build finishes.
+        let (_registry_user_config, section) = configure_registry_credentials(&mut env, section)?;
+        let section = configure_npm_cache_directory(&context, &env, &cache, section)?;
+        let section = if cache.value {
+            install_node_modules(
+                &context,
+                &app_dir,
+                &package_json,
+                workspace.as_ref(),
+                &npm_version,
+                &env,
+                section,
+            )?
+        } else {
+            run_npm_install(&env, workspace.as_ref(), log_no_cache(&cache, section))?
+        };
+        let logger = section.done();
+
+        let section = logger.bullet("Checking dependency licenses");
+        let section = check_licenses(&context, &app_dir, &package_json, &config.licenses, section)?;
         let logger = section.done();
+        let logger =
+            check_vulnerabilities(&context, &app_dir, &package_json, &config, &env, logger)?;
 
         let section = logger.bullet("Running scripts");
-        let section =
-            run_build_scripts(&package_json, &node_build_scripts_metadata, &env, section)?;
+        let section = run_build_scripts(
+            &build_scripts,
+            workspace.as_ref(),
+            &build_scripts_env(&env, &node_build_scripts_metadata),
+            section,
+        )?;
+        let logger = section.done();
+
+        let section = logger.bullet("Pruning dev dependencies");
+        let section = prune_dev_dependencies(
+            &app_dir,
+            &npm_version,
+            &prune,
+            &node_build_scripts_metadata,
+            &env,
+            section,
+        )?;
+        let logger = section.done();
+
+        let section = logger.bullet("Generating SBOM");
+        let (sboms, section) = generate_sboms(
+            &app_dir,
+            &package_json,
+            // Dev dependencies are kept when pruning is skipped.
+            prune.value && node_build_scripts_metadata.enabled != Some(false),
+            sbom_spdx.value,
+            section,
+        );
         let logger = section.done();
 
         let section = logger.bullet("Configuring default processes");
-        let (build_result, section) = configure_default_processes(&context, &package_json, section);
+        let (build_result, section) = configure_default_processes(
+            &context,
+            &app_root,
+            &config,
+            &package_json,
+            workspace.as_ref(),
+            sboms
+                .into_iter()
+                .fold(BuildResultBuilder::new(), BuildResultBuilder::launch_sbom),
+            section,
+        );
         let logger = section.done();
 
         configure_npm_runtime_env(&context)?;
@@ -107,10 +213,37 @@ impl Buildpack for NpmInstallBuildpack {
     }
 }
 
+fn enter_app_root(
+    context: &BuildContext<NpmInstallBuildpack>,
+    config: &NodejsConfig,
+) -> Result<(AppRoot, PathBuf), NpmInstallBuildpackError> {
+    let app_root = config
+        .app_root(context.platform.env())
+        .map_err(NpmInstallBuildpackError::Config)?;
+    let app_dir = app_root
+        .enter(&context.app_dir)
+        .map_err(NpmInstallBuildpackError::Config)?;
+    Ok((app_root, app_dir))
+}
+
+fn log_app_root(
+    app_root: &AppRoot,
+    section_logger: Print<SubBullet<Stdout>>,
+) -> Print<SubBullet<Stdout>> {
+    if app_root.is_app_dir() {
+        section_logger
+    } else {
+        section_logger.sub_bullet(format!(
+            "Using app root {}",
+            style::value(app_root.to_string())
+        ))
+    }
+}
+
 fn log_npm_version(
     env: &Env,
     section_logger: Print<SubBullet<Stdout>>,
-) -> Result<Print<SubBullet<Stdout>>, NpmInstallBuildpackError> {
+) -> Result<(Version, Print<SubBullet<Stdout>>), NpmInstallBuildpackError> {
     npm::Version { env }
         .into_command()
         .named_output()
@@ -124,18 +257,203 @@ fn log_npm_version(
         })
         .map_err(NpmInstallBuildpackError::NpmVersion)
         .map(|version| {
-            section_logger.sub_bullet(format!(
+            let section_logger = section_logger.sub_bullet(format!(
                 "Using npm version {}",
                 style::value(version.to_string())
-            ))
+            ));
+            (version, section_logger)
+        })
+}
+
+fn read_node_version(env: &Env) -> Result<Version, NpmInstallBuildpackError> {
+    match read_resolved_node(env) {
+        Ok(Some(resolved_node)) => return Ok(resolved_node.version),
+        Ok(None) => {}
+        Err(ResolvedNodeError::InvalidVersion(version, e)) => Err(
+            NpmInstallBuildpackError::NodeVersion(node::VersionError::Parse(version, e)),
+        )?,
+    }
+
+    // Node.js was installed by something other than the Node.js engine
+    // buildpack, so ask the `node` binary on the `PATH`.
+    node::Version { env }
+        .into_command()
+        .named_output()
+        .and_then(NamedOutput::nonzero_captured)
+        .map_err(node::VersionError::Command)
+        .and_then(|output| {
+            let stdout = output.stdout_lossy();
+            stdout
+                .trim()
+                .trim_start_matches('v')
+                .parse::<Version>()
+                .map_err(|e| node::VersionError::Parse(stdout, e))
         })
+        .map_err(NpmInstallBuildpackError::NodeVersion)
+}
+
+fn read_npm_workspace(
+    context: &BuildContext<NpmInstallBuildpack>,
+    app_dir: &Path,
+    package_json: &PackageJson,
+    config: &NodejsConfig,
+) -> Result<Option<Setting<Workspace>>, NpmInstallBuildpackError> {
+    let Some(selector) = config.npm_workspace(context.platform.env()) else {
+        return Ok(None);
+    };
+    let workspaces = package_json
+        .workspaces
+        .as_ref()
+        .ok_or(WorkspaceError::NoWorkspaces)
+        .and_then(|workspaces| find_workspaces(app_dir, workspaces))
+        .map_err(NpmInstallBuildpackError::Workspace)?;
+    select_workspace(&workspaces, &selector.value)
+        .map(|workspace| {
+            Some(Setting {
+                value: workspace.clone(),
+                source: selector.source,
+            })
+        })
+        .map_err(NpmInstallBuildpackError::Workspace)
+}
+
+fn log_npm_workspace(
+    package_json: &PackageJson,
+    workspace: Option<&Setting<Workspace>>,
+    section_logger: Print<SubBullet<Stdout>>,
+) -> Print<SubBullet<Stdout>> {
+    match workspace {
+        Some(workspace) => section_logger.sub_bullet(format!(
+            "Using workspace {} from {}",
+            style::value(workspace.value.to_string()),
+            style::value(workspace.source.to_string())
+        )),
+        None if package_json.workspaces.is_some() => section_logger.sub_bullet(format!(
+            "Installing all workspaces (set {} or {} in {} to a workspace name or path to select one)",
+            style::value(NPM_WORKSPACE_ENV_VAR),
+            style::value("npm.workspace"),
+            style::value(PROJECT_TOML)
+        )),
+        None => section_logger,
+    }
+}
+
+fn log_no_cache(
+    cache: &Setting<bool>,
+    section_logger: Print<SubBullet<Stdout>>,
+) -> Print<SubBullet<Stdout>> {
+    section_logger.sub_bullet(format!(
+        "Not caching {} ({})",
+        style::value("node_modules"),
+        cache.reason("cache")
+    ))
+}
+
+fn install_node_modules(
+    context: &BuildContext<NpmInstallBuildpack>,
+    app_dir: &Path,
+    package_json: &PackageJson,
+    workspace: Option<&Workspace>,
+    npm_version: &Version,
+    env: &Env,
+    section_logger: Print<SubBullet<Stdout>>,
+) -> Result<Print<SubBullet<Stdout>>, libcnb::Error<NpmInstallBuildpackError>> {
+    let workspaces = package_json
+        .workspaces
+        .as_ref()
+        .map(|workspaces| find_workspaces(app_dir, workspaces))
+        .transpose()
+        .map_err(NpmInstallBuildpackError::Workspace)?
+        .unwrap_or_default();
+    // Install scripts of the app's own packages may write outside of `node_modules`, so
+    // restoring `node_modules` alone wouldn't reproduce the result of `npm ci`.
+    if package_json.has_install_scripts()
+        || workspaces
+            .iter()
+            .any(|workspace| workspace.package_json.has_install_scripts())
+    {
+        let section_logger = section_logger.sub_bullet(format!(
+            "Not caching {} as the app defines install scripts",
+            style::value("node_modules")
+        ));
+        return Ok(run_npm_install(env, workspace, section_logger)?);
+    }
+    // Without a lockfile there's nothing to key the cache on, and `npm ci` reports the problem.
+    if !app_dir.join(PackageManager::Npm.lockfile()).is_file() {
+        return Ok(run_npm_install(env, workspace, section_logger)?);
+    }
+    // Native modules are compiled by their install scripts, which have to run on every build.
+    if lockfile_has_install_scripts(app_dir).map_err(NpmInstallBuildpackError::NodeModulesCache)? {
+        let section_logger = section_logger.sub_bullet(format!(
+            "Not caching {} as dependencies define install scripts",
+            style::value("node_modules")
+        ));
+        return Ok(run_npm_install(env, workspace, section_logger)?);
+    }
+
+    let node_version = read_node_version(env)?;
+    let workspace_dirs = workspaces
+        .iter()
+        .map(|workspace| workspace.path.as_path())
+        .collect::<Vec<_>>();
+    let metadata = NodeModulesCacheLayerMetadata::new(
+        app_dir,
+        &node_version,
+        npm_version,
+        &context.target,
+        &workspace_dirs,
+        workspace.map(|workspace| workspace.path.as_path()),
+    )
+    .map_err(NpmInstallBuildpackError::NodeModulesCache)?;
+
+    install_with_node_modules_cache(
+        context,
+        app_dir,
+        metadata,
+        &workspace_dirs,
+        section_logger,
+        |section_logger| run_npm_install(env, workspace, section_logger),
+    )
+}
+
+fn configure_registry_credentials(
+    env: &mut Env,
+    section_logger: Print<SubBullet<Stdout>>,
+) -> Result<(Option<UserConfig>, Print<SubBullet<Stdout>>), NpmInstallBuildpackError> {
+    let credentials =
+        RegistryCredentials::read(env).map_err(NpmInstallBuildpackError::RegistryCredentials)?;
+    let user_config = credentials
+        .write_user_config(UserConfigFormat::Npmrc)
+        .map_err(NpmInstallBuildpackError::RegistryCredentials)?;
+    if let Some(user_config) = &user_config {
+        *env = user_config.apply(env);
+    }
+    let section_logger = if user_config.is_some() {
+        section_logger.sub_bullet(format!(
+            "Using registry credentials from service bindings {}",
+            credentials
+                .bindings(UserConfigFormat::Npmrc)
+                .into_iter()
+                .map(style::value)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))
+    } else {
+        section_logger
+    };
+    Ok((user_config, section_logger))
 }
 
 fn run_npm_install(
     env: &Env,
+    workspace: Option<&Workspace>,
     mut section_logger: Print<SubBullet<Stdout>>,
 ) -> Result<Print<SubBullet<Stdout>>, NpmInstallBuildpackError> {
-    let mut npm_install = npm::Install { env }.into_command();
+    let mut npm_install = npm::Install {
+        env,
+        workspace: workspace.map(|workspace| workspace.path.as_path()),
+    }
+    .into_command();
     section_logger
         .stream_with(
             format!("Running {}", style::command(npm_install.name())),
@@ -143,66 +461,243 @@ fn run_npm_install(
                 npm_install
                     .stream_output(stdout, stderr)
                     .and_then(NamedOutput::nonzero_captured)
-                    .map_err(NpmInstallBuildpackError::NpmInstall)
+                    .map_err(classify_npm_install_error)
             },
         )
         .map(|_| section_logger)
 }
 
 fn run_build_scripts(
-    package_json: &PackageJson,
-    node_build_scripts_metadata: &NodeBuildScriptsMetadata,
+    build_scripts: &[BuildScript],
+    workspace: Option<&Workspace>,
     env: &Env,
     mut section_logger: Print<SubBullet<Stdout>>,
 ) -> Result<Print<SubBullet<Stdout>>, NpmInstallBuildpackError> {
-    let build_scripts = package_json.build_scripts();
     if build_scripts.is_empty() {
         section_logger = section_logger.sub_bullet("No build scripts found");
-    } else {
-        for script in build_scripts {
-            if let Some(false) = node_build_scripts_metadata.enabled {
-                section_logger = section_logger.sub_bullet(format!(
-                    "Not running {} as it was disabled by a participating buildpack",
-                    style::value(script)
-                ));
-            } else {
-                let mut npm_run = npm::RunScript { env, script }.into_command();
-                section_logger.stream_with(
-                    format!("Running {}", style::command(npm_run.name())),
-                    |stdout, stderr| {
-                        npm_run
-                            .stream_output(stdout, stderr)
-                            .and_then(NamedOutput::nonzero_captured)
-                            .map_err(NpmInstallBuildpackError::BuildScript)
-                    },
-                )?;
+    }
+    for build_script in build_scripts {
+        if build_script.run {
+            let mut npm_run = npm::RunScript {
+                env,
+                script: build_script.name.clone(),
+                workspace: workspace.map(|workspace| workspace.path.as_path()),
             }
+            .into_command();
+            section_logger.stream_with(
+                format!(
+                    "Running {} ({})",
+                    style::command(npm_run.name()),
+                    build_script.reason
+                ),
+                |stdout, stderr| {
+                    npm_run
+                        .stream_output(stdout, stderr)
+                        .and_then(NamedOutput::nonzero_captured)
+                        .map_err(NpmInstallBuildpackError::BuildScript)
+                },
+            )?;
+        } else {
+            section_logger = section_logger.sub_bullet(build_script.skipped_message());
         }
     }
     Ok(section_logger)
 }
 
+fn prune_dev_dependencies(
+    app_dir: &Path,
+    npm_version: &Version,
+    prune: &Setting<bool>,
+    node_build_scripts_metadata: &NodeBuildScriptsMetadata,
+    env: &Env,
+    mut section_logger: Print<SubBullet<Stdout>>,
+) -> Result<Print<SubBullet<Stdout>>, NpmInstallBuildpackError> {
+    if !prune.value {
+        return Ok(
+            section_logger.sub_bullet(format!("Skipping pruning ({})", prune.reason("prune")))
+        );
+    }
+    // A participating buildpack that runs the build scripts still needs the dev dependencies.
+    if let Some(false) = node_build_scripts_metadata.enabled {
+        return Ok(section_logger.sub_bullet(
+            "Skipping pruning as build scripts were disabled by a participating buildpack",
+        ));
+    }
+
+    let node_modules = app_dir.join("node_modules");
+    let size_before = disk_usage(&[&node_modules]);
+    let mut npm_prune = npm::Prune {
+        env,
+        // `--omit` replaced `--production` in npm 7.
+        omit_dev: npm_version.major() >= 7,
+    }
+    .into_command();
+    section_logger.stream_with(
+        format!("Running {}", style::command(npm_prune.name())),
+        |stdout, stderr| {
+            npm_prune
+                .stream_output(stdout, stderr)
+                .and_then(NamedOutput::nonzero_captured)
+                .map_err(NpmInstallBuildpackError::NpmPrune)
+        },
+    )?;
+    let size_after = disk_usage(&[&node_modules]);
+
+    Ok(match (size_before, size_after) {
+        (Ok(before), Ok(after)) => {
+            section_logger.sub_bullet(PruneSummary { before, after }.to_string())
+        }
+        _ => section_logger,
+    })
+}
+
+/// Reports the licenses of the installed dependencies and fails when a
+/// production dependency has a denied license.
+fn check_licenses(
+    context: &BuildContext<NpmInstallBuildpack>,
+    app_dir: &Path,
+    package_json: &PackageJson,
+    policy: &LicensePolicy,
+    section_logger: Print<SubBullet<Stdout>>,
+) -> Result<Print<SubBullet<Stdout>>, libcnb::Error<NpmInstallBuildpackError>> {
+    match write_license_report(context, app_dir, PackageManager::Npm, package_json, policy)? {
+        LicenseReportOutcome::Skipped(error) => {
+            Ok(section_logger.warning(format!("Skipping license report: {error}")))
+        }
+        LicenseReportOutcome::Written { report, path } => {
+            let section_logger = section_logger
+                .sub_bullet(format!(
+                    "Production dependencies: {}",
+                    report.summary(false)
+                ))
+                .sub_bullet(format!("Dev dependencies: {}", report.summary(true)))
+                .sub_bullet(format!(
+                    "Wrote the license report to {}",
+                    style::value(path.to_string_lossy())
+                ));
+            report
+                .check(policy)
+                .map_err(NpmInstallBuildpackError::Licenses)?;
+            Ok(section_logger)
+        }
+    }
+}
+
+/// Checks the installed dependencies against the OSV advisories from the
+/// configuration or a service binding, when there are any. Vulnerabilities
+/// are only reported unless `vulnerabilities.fail_on` is set.
+fn check_vulnerabilities(
+    context: &BuildContext<NpmInstallBuildpack>,
+    app_dir: &Path,
+    package_json: &PackageJson,
+    config: &NodejsConfig,
+    env: &Env,
+    logger: Print<Bullet<Stdout>>,
+) -> Result<Print<Bullet<Stdout>>, libcnb::Error<NpmInstallBuildpackError>> {
+    let Some(report) = vulnerabilities::check_vulnerabilities(
+        context,
+        app_dir,
+        PackageManager::Npm,
+        package_json,
+        config,
+        env,
+    )?
+    else {
+        return Ok(logger);
+    };
+
+    let mut section_logger = logger
+        .bullet("Checking for known vulnerabilities")
+        .sub_bullet(format!(
+            "Using the advisories from {}",
+            report.source.description
+        ));
+    for vulnerability in &report.vulnerabilities {
+        section_logger = section_logger.sub_bullet(vulnerability.to_string());
+    }
+    section_logger = section_logger.sub_bullet(vulnerabilities::summary(&report.vulnerabilities));
+    report
+        .enforce()
+        .map_err(NpmInstallBuildpackError::Vulnerabilities)?;
+    Ok(section_logger.done())
+}
+
+/// Lists the installed dependencies from `package-lock.json`. A lockfile that
+/// can't be read only skips the SBOM, as it doesn't affect the app.
+fn generate_sboms(
+    app_dir: &Path,
+    package_json: &PackageJson,
+    pruned: bool,
+    spdx: bool,
+    section_logger: Print<SubBullet<Stdout>>,
+) -> (Vec<Sbom>, Print<SubBullet<Stdout>>) {
+    match read_dependency_sboms(app_dir, PackageManager::Npm, package_json, pruned, spdx) {
+        Ok(sboms) => (
+            sboms,
+            section_logger.sub_bullet("Listing installed dependencies from package-lock.json"),
+        ),
+        Err(error) => (
+            vec![],
+            section_logger.warning(format!("Skipping SBOM generation: {error}")),
+        ),
+    }
+}
+
 fn configure_default_processes(
     context: &BuildContext<NpmInstallBuildpack>,
+    app_root: &AppRoot,
+    config: &NodejsConfig,
     package_json: &PackageJson,
+    workspace: Option<&Workspace>,
+    result_builder: BuildResultBuilder,
     section_logger: Print<SubBullet<Stdout>>,
 ) -> (
     Result<BuildResult, libcnb::Error<NpmInstallBuildpackError>>,
     Print<SubBullet<Stdout>>,
 ) {
-    if context.app_dir.join("Procfile").exists() {
+    let procfile = match config.declared_processes(&context.app_dir) {
+        Ok(procfile) => procfile,
+        Err(error) => {
+            return (
+                Err(NpmInstallBuildpackError::Procfile(error).into()),
+                section_logger,
+            )
+        }
+    };
+
+    if let Some((procfile, source)) = procfile {
+        if procfile.processes.is_empty() {
+            return (
+                result_builder.build(),
+                section_logger.sub_bullet("Skipping default web process (Procfile detected)"),
+            );
+        }
+        let mut section_logger = section_logger;
+        for process in &procfile.processes {
+            section_logger = section_logger.sub_bullet(format!(
+                "Adding {} process from {source}: {}",
+                style::value(process.process_type.as_str()),
+                style::command(&process.command)
+            ));
+        }
         (
-            BuildResultBuilder::new().build(),
-            section_logger.sub_bullet("Skipping default web process (Procfile detected)"),
+            result_builder.launch(procfile.launch()).build(),
+            section_logger,
         )
-    } else if package_json.has_start_script() {
+    } else if workspace
+        .map_or(package_json, |workspace| &workspace.package_json)
+        .has_start_script()
+    {
+        let mut command = vec!["npm".to_string(), "start".to_string()];
+        command.extend(workspace.map(|workspace| npm::workspace_arg(&workspace.path)));
         (
-            BuildResultBuilder::new()
+            result_builder
                 .launch(
                     LaunchBuilder::new()
                         .process(
-                            ProcessBuilder::new(process_type!("web"), ["npm", "start"])
+                            ProcessBuilder::new(process_type!("web"), command.clone())
                                 .default(true)
+                                .working_directory(app_root.working_directory())
                                 .build(),
                         )
                         .build(),
@@ -210,12 +705,12 @@ fn configure_default_processes(
                 .build(),
             section_logger.sub_bullet(format!(
                 "Adding default web process for {}",
-                style::value("npm start")
+                style::value(command.join(" "))
             )),
         )
     } else {
         (
-            BuildResultBuilder::new().build(),
+            result_builder.build(),
             section_logger.sub_bullet("Skipping default web process (no start script defined)"),
         )
     }
@@ -227,4 +722,22 @@ impl From<NpmInstallBuildpackError> for libcnb::Error<NpmInstallBuildpackError>
     }
 }
 
+impl From<ConfigError> for NpmInstallBuildpackError {
+    fn from(value: ConfigError) -> Self {
+        NpmInstallBuildpackError::Config(value)
+    }
+}
+
+impl From<LicenseCheckError> for NpmInstallBuildpackError {
+    fn from(value: LicenseCheckError) -> Self {
+        NpmInstallBuildpackError::Licenses(value)
+    }
+}
+
+impl From<VulnerabilityError> for NpmInstallBuildpackError {
+    fn from(value: VulnerabilityError) -> Self {
+        NpmInstallBuildpackError::Vulnerabilities(value)
+    }
+}
+
 buildpack_main!(NpmInstallBuildpack);
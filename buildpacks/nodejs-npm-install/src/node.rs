@@ -0,0 +1,28 @@
+use fun_run::CmdError;
+use libcnb::Env;
+use std::process::Command;
+
+#[derive(Debug)]
+pub(crate) enum VersionError {
+    Command(CmdError),
+    Parse(String, heroku_nodejs_utils::vrs::VersionError),
+}
+
+pub(crate) struct Version<'a> {
+    pub(crate) env: &'a Env,
+}
+
+impl Version<'_> {
+    pub(crate) fn into_command(self) -> Command {
+        self.into()
+    }
+}
+
+impl<'a> From<Version<'a>> for Command {
+    fn from(value: Version<'a>) -> Self {
+        let mut cmd = Command::new("node");
+        cmd.arg("--version");
+        cmd.envs(value.env);
+        cmd
+    }
+}
@@ -1,6 +1,6 @@
 use fun_run::CmdError;
 use libcnb::Env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 pub(crate) struct SetCacheConfig<'a> {
@@ -56,6 +56,7 @@ impl<'a> From<Version<'a>> for Command {
 
 pub(crate) struct Install<'a> {
     pub(crate) env: &'a Env,
+    pub(crate) workspace: Option<&'a Path>,
 }
 
 impl Install<'_> {
@@ -69,6 +70,35 @@ impl<'a> From<Install<'a>> for Command {
         let mut cmd = Command::new("npm");
         cmd.arg("ci");
         cmd.arg("--production=false");
+        if let Some(workspace) = value.workspace {
+            cmd.arg(workspace_arg(workspace));
+            cmd.arg("--include-workspace-root");
+        }
+        cmd.envs(value.env);
+        cmd
+    }
+}
+
+pub(crate) struct Prune<'a> {
+    pub(crate) env: &'a Env,
+    pub(crate) omit_dev: bool,
+}
+
+impl Prune<'_> {
+    pub(crate) fn into_command(self) -> Command {
+        self.into()
+    }
+}
+
+impl<'a> From<Prune<'a>> for Command {
+    fn from(value: Prune<'a>) -> Self {
+        let mut cmd = Command::new("npm");
+        cmd.arg("prune");
+        if value.omit_dev {
+            cmd.arg("--omit=dev");
+        } else {
+            cmd.arg("--production");
+        }
         cmd.envs(value.env);
         cmd
     }
@@ -77,6 +107,7 @@ impl<'a> From<Install<'a>> for Command {
 pub(crate) struct RunScript<'a> {
     pub(crate) env: &'a Env,
     pub(crate) script: String,
+    pub(crate) workspace: Option<&'a Path>,
 }
 
 impl RunScript<'_> {
@@ -89,7 +120,15 @@ impl<'a> From<RunScript<'a>> for Command {
     fn from(value: RunScript<'a>) -> Self {
         let mut cmd = Command::new("npm");
         cmd.args(["run", &value.script]);
+        if let Some(workspace) = value.workspace {
+            cmd.arg(workspace_arg(workspace));
+        }
         cmd.envs(value.env);
         cmd
     }
 }
+
+/// The argument that targets a workspace by its path relative to the root package.
+pub(crate) fn workspace_arg(workspace: &Path) -> String {
+    format!("--workspace={}", workspace.display())
+}
@@ -5,12 +5,14 @@
 
 use indoc::indoc;
 use libcnb::data::buildpack_id;
-use libcnb_test::{assert_contains, assert_not_contains, BuildpackReference, PackResult};
+use libcnb::data::sbom::SbomFormat;
+use libcnb_test::{assert_contains, assert_not_contains, BuildpackReference, PackResult, SbomType};
 use serde_json::json;
 use std::path::Path;
 use test_support::{
     add_build_script, add_package_json_dependency, custom_buildpack, integration_test_with_config,
-    nodejs_integration_test, nodejs_integration_test_with_config, update_json_file,
+    nodejs_integration_test, nodejs_integration_test_with_config, set_node_engine,
+    update_json_file,
 };
 
 #[test]
@@ -34,6 +36,28 @@ fn test_npm_install_with_lockfile() {
     });
 }
 
+#[test]
+#[ignore = "integration test"]
+fn test_npm_install_sbom() {
+    nodejs_integration_test("./fixtures/npm-project", |ctx| {
+        assert_contains!(ctx.pack_stdout, "- Generating SBOM");
+        assert_contains!(
+            ctx.pack_stdout,
+            "- Listing installed dependencies from package-lock.json"
+        );
+        ctx.download_sbom_files(|sbom_files| {
+            let sbom = std::fs::read_to_string(sbom_files.path_for(
+                buildpack_id!("heroku/nodejs-npm-install"),
+                SbomType::Launch,
+                SbomFormat::CycloneDxJson,
+            ))
+            .unwrap();
+            assert_contains!(sbom, r#""purl": "pkg:npm/node-fetch@2.6.12""#);
+            assert_contains!(sbom, r#""alg": "SHA-512""#);
+        });
+    });
+}
+
 #[test]
 #[ignore = "integration test"]
 fn test_npm_install_caching() {
@@ -43,7 +67,11 @@ fn test_npm_install_caching() {
         let config = ctx.config.clone();
         ctx.rebuild(config, |ctx| {
             assert_contains!(ctx.pack_stdout, "- Restoring npm cache");
-            assert_contains!(ctx.pack_stdout, "added 4 packages");
+            assert_contains!(
+                ctx.pack_stdout,
+                "- Restoring `node_modules` from cache, skipping install as nothing changed since the last build"
+            );
+            assert_not_contains!(ctx.pack_stdout, "added 4 packages");
         });
     });
 }
@@ -71,6 +99,10 @@ fn test_npm_install_new_package() {
 
         ctx.rebuild(config, |ctx| {
             assert_contains!(ctx.pack_stdout, "- Restoring npm cache");
+            assert_contains!(
+                ctx.pack_stdout,
+                "- Not restoring `node_modules` from cache (package-lock.json changed)"
+            );
             assert_contains!(ctx.pack_stdout, "added 5 packages");
         });
     });
@@ -122,6 +154,144 @@ fn test_npm_build_scripts_prefers_heroku_build_over_build() {
     );
 }
 
+#[test]
+#[ignore = "integration test"]
+fn test_npm_build_scripts_from_project_toml() {
+    nodejs_integration_test_with_config(
+        "./fixtures/npm-project",
+        |config| {
+            config.app_dir_preprocessor(|app_dir| {
+                add_build_script(&app_dir, "build");
+                add_build_script(&app_dir, "build:prod");
+                add_build_script(&app_dir, "docs");
+                std::fs::write(
+                    app_dir.join("project.toml"),
+                    indoc! {r#"
+                        [_]
+                        schema-version = "0.2"
+
+                        [com.heroku.buildpacks.nodejs.scripts]
+                        build = "build:prod"
+                        extra = ["docs"]
+                    "#},
+                )
+                .unwrap();
+            });
+        },
+        |ctx| {
+            assert_contains!(
+                ctx.pack_stdout,
+                "- Not running `build` as it was replaced by `build:prod` from `scripts.build` in project.toml"
+            );
+            assert_contains!(
+                ctx.pack_stdout,
+                "- Running `npm run build:prod` (set with `scripts.build` in project.toml)"
+            );
+            assert_contains!(ctx.pack_stdout, "executed build:prod");
+            assert_contains!(
+                ctx.pack_stdout,
+                "- Running `npm run docs` (listed in `scripts.extra` in project.toml)"
+            );
+            assert_contains!(ctx.pack_stdout, "executed docs");
+            assert_not_contains!(ctx.pack_stdout, "executed build\n");
+        },
+    );
+}
+
+#[test]
+#[ignore = "integration test"]
+fn test_npm_denied_dependency_licenses() {
+    nodejs_integration_test_with_config(
+        "./fixtures/npm-project",
+        |config| {
+            config.expected_pack_result(PackResult::Failure);
+            config.app_dir_preprocessor(|app_dir| {
+                std::fs::write(
+                    app_dir.join("project.toml"),
+                    indoc! {r#"
+                        [_]
+                        schema-version = "0.2"
+
+                        [com.heroku.buildpacks.nodejs.licenses]
+                        deny = ["BSD-*"]
+                    "#},
+                )
+                .unwrap();
+            });
+        },
+        |ctx| {
+            assert_contains!(ctx.pack_stdout, "- Checking dependency licenses");
+            assert_contains!(
+                ctx.pack_stdout,
+                "- Production dependencies: 3 MIT, 1 BSD-2-Clause"
+            );
+            assert_contains!(ctx.pack_stdout, "Denied dependency licenses");
+            assert_contains!(
+                ctx.pack_stdout,
+                "- webidl-conversions@3.0.1 is licensed under `BSD-2-Clause`"
+            );
+        },
+    );
+}
+
+#[test]
+#[ignore = "integration test"]
+fn test_npm_known_vulnerabilities() {
+    nodejs_integration_test_with_config(
+        "./fixtures/npm-project",
+        |config| {
+            config.expected_pack_result(PackResult::Failure);
+            config.app_dir_preprocessor(|app_dir| {
+                std::fs::write(
+                    app_dir.join("project.toml"),
+                    indoc! {r#"
+                        [_]
+                        schema-version = "0.2"
+
+                        [com.heroku.buildpacks.nodejs.vulnerabilities]
+                        advisories = "advisories"
+                        fail_on = "high"
+                    "#},
+                )
+                .unwrap();
+                std::fs::create_dir(app_dir.join("advisories")).unwrap();
+                std::fs::write(
+                    app_dir.join("advisories/TEST-0001.json"),
+                    json!({
+                        "id": "TEST-0001",
+                        "summary": "Test advisory",
+                        "affected": [{
+                            "package": { "ecosystem": "npm", "name": "node-fetch" },
+                            "ranges": [{
+                                "type": "SEMVER",
+                                "events": [{ "introduced": "2.0.0" }, { "fixed": "2.6.13" }]
+                            }]
+                        }],
+                        "database_specific": { "severity": "HIGH" }
+                    })
+                    .to_string(),
+                )
+                .unwrap();
+            });
+        },
+        |ctx| {
+            assert_contains!(ctx.pack_stdout, "- Checking for known vulnerabilities");
+            assert_contains!(
+                ctx.pack_stdout,
+                "- Using the advisories from `vulnerabilities.advisories` in project.toml"
+            );
+            assert_contains!(
+                ctx.pack_stdout,
+                "- node-fetch@2.6.12 (high): TEST-0001 Test advisory, fixed in 2.6.13"
+            );
+            assert_contains!(
+                ctx.pack_stdout,
+                "Known vulnerabilities in production dependencies"
+            );
+        },
+    );
+}
+
 #[test]
 #[ignore = "integration test"]
 fn test_npm_start_script_creates_a_web_process_launcher() {
@@ -220,6 +390,26 @@ fn test_native_modules_are_recompiled_even_on_cache_restore() {
     );
 }
 
+#[test]
+#[ignore = "integration test"]
+fn test_node_modules_cache_is_invalidated_when_node_version_changes() {
+    nodejs_integration_test("./fixtures/npm-project", |ctx| {
+        assert_contains!(ctx.pack_stdout, "added 4 packages");
+        let mut config = ctx.config.clone();
+        config.app_dir_preprocessor(|app_dir| {
+            set_node_engine(&app_dir, "^22.0");
+        });
+        ctx.rebuild(config, |ctx| {
+            assert_contains!(ctx.pack_stdout, "- Restoring npm cache");
+            assert_contains!(
+                ctx.pack_stdout,
+                "- Not restoring `node_modules` from cache (package.json changed, Node.js version changed from"
+            );
+            assert_contains!(ctx.pack_stdout, "added 4 packages");
+        });
+    });
+}
+
 #[test]
 #[ignore = "integration test"]
 fn test_skip_build_scripts_from_buildplan() {
@@ -288,9 +478,198 @@ fn test_default_web_process_registration_is_skipped_if_procfile_exists() {
     );
 }
 
+#[test]
+#[ignore]
+fn test_procfile_processes_are_registered() {
+    nodejs_integration_test_with_config(
+        "./fixtures/npm-project",
+        |config| {
+            config.app_dir_preprocessor(|app_dir| {
+                std::fs::write(
+                    app_dir.join("Procfile"),
+                    "web: npm start\nworker: node -e 'setInterval(() => {}, 1000)'\n",
+                )
+                .unwrap();
+            });
+        },
+        |ctx| {
+            assert_contains!(
+                ctx.pack_stdout,
+                "Adding `web` process from Procfile: `npm start`"
+            );
+            assert_contains!(ctx.pack_stdout, "Adding `worker` process from Procfile:");
+        },
+    );
+}
+
+#[test]
+#[ignore]
+fn test_invalid_procfile_fails_the_build() {
+    nodejs_integration_test_with_config(
+        "./fixtures/npm-project",
+        |config| {
+            config.app_dir_preprocessor(|app_dir| {
+                std::fs::write(
+                    app_dir.join("Procfile"),
+                    "web: npm start\nworker node worker.js\n",
+                )
+                .unwrap();
+            });
+            config.expected_pack_result(PackResult::Failure);
+        },
+        |ctx| {
+            assert_contains!(
+                ctx.pack_stdout,
+                "Invalid Procfile entry on line 2: `worker node worker.js`"
+            );
+        },
+    );
+}
+
+#[test]
+#[ignore = "integration test"]
+fn test_npm_workspace_is_selected() {
+    nodejs_integration_test_with_config(
+        "./fixtures/npm-workspaces",
+        |config| {
+            config.env("NODEJS_NPM_WORKSPACE", "@acme/web");
+        },
+        |ctx| {
+            assert_contains!(
+                ctx.pack_stdout,
+                "- Using workspace `@acme/web (apps/web)` from `NODEJS_NPM_WORKSPACE`"
+            );
+            assert_contains!(
+                ctx.pack_stdout,
+                "- Running `npm ci \"--production=false\" \"--workspace=apps/web\" \"--include-workspace-root\"`"
+            );
+            assert_contains!(
+                ctx.pack_stdout,
+                "- Running `npm run build \"--workspace=apps/web\"`"
+            );
+            assert_contains!(ctx.pack_stdout, "built @acme/web");
+            assert_not_contains!(ctx.pack_stdout, "built @acme/api");
+            assert_contains!(
+                ctx.pack_stdout,
+                "- Adding default web process for `npm start --workspace=apps/web`"
+            );
+        },
+    );
+}
+
+#[test]
+#[ignore = "integration test"]
+fn test_unknown_npm_workspace_fails_the_build() {
+    nodejs_integration_test_with_config(
+        "./fixtures/npm-workspaces",
+        |config| {
+            config.env("NODEJS_NPM_WORKSPACE", "apps/docs");
+            config.expected_pack_result(PackResult::Failure);
+        },
+        |ctx| {
+            assert_contains!(
+                ctx.pack_stdout,
+                "No workspace matches `apps/docs`. Available workspaces: @acme/api (apps/api), @acme/web (apps/web)"
+            );
+        },
+    );
+}
+
+#[test]
+#[ignore = "integration test"]
+fn test_dev_dependencies_are_pruned_after_cleanup_script() {
+    nodejs_integration_test_with_config(
+        "./fixtures/npm-project",
+        |config| {
+            config.app_dir_preprocessor(|app_dir| {
+                add_build_script(&app_dir, "build");
+                add_build_script(&app_dir, "heroku-cleanup");
+            });
+        },
+        |ctx| {
+            assert_contains!(ctx.pack_stdout, "- Running `npm run build`");
+            assert_contains!(ctx.pack_stdout, "- Running `npm run heroku-cleanup`");
+            assert_contains!(ctx.pack_stdout, "executed heroku-cleanup");
+            assert_contains!(ctx.pack_stdout, "- Pruning dev dependencies");
+            assert_contains!(ctx.pack_stdout, "- Running `npm prune --production`");
+            assert_contains!(ctx.pack_stdout, "of dev dependencies");
+        },
+    );
+}
+
+#[test]
+#[ignore = "integration test"]
+fn test_dev_dependency_pruning_can_be_skipped() {
+    nodejs_integration_test_with_config(
+        "./fixtures/npm-project",
+        |config| {
+            config.env("NODEJS_SKIP_PRUNING", "true");
+        },
+        |ctx| {
+            assert_contains!(
+                ctx.pack_stdout,
+                "- Skipping pruning (`NODEJS_SKIP_PRUNING` is set to `true`)"
+            );
+            assert_not_contains!(ctx.pack_stdout, "npm prune");
+        },
+    );
+}
+
 fn add_lockfile_entry(app_dir: &Path, package_name: &str, lockfile_entry: serde_json::Value) {
     update_json_file(&app_dir.join("package-lock.json"), |json| {
         let dependencies = json["dependencies"].as_object_mut().unwrap();
         dependencies.insert(package_name.to_string(), lockfile_entry);
     });
 }
+
+#[test]
+#[ignore = "integration test"]
+fn test_node_modules_cache_is_skipped_with_install_scripts() {
+    nodejs_integration_test_with_config(
+        "./fixtures/npm-project",
+        |config| {
+            config.app_dir_preprocessor(|app_dir| {
+                add_build_script(&app_dir, "postinstall");
+            });
+        },
+        |ctx| {
+            assert_contains!(
+                ctx.pack_stdout,
+                "- Not caching `node_modules` as the app defines install scripts"
+            );
+            let config = ctx.config.clone();
+            ctx.rebuild(config, |ctx| {
+                assert_contains!(ctx.pack_stdout, "added 4 packages");
+            });
+        },
+    );
+}
+
+#[test]
+#[ignore = "integration test"]
+fn test_registry_credentials_from_service_bindings() {
+    nodejs_integration_test_with_config(
+        "./fixtures/npm-project",
+        |config| {
+            config.env("SERVICE_BINDING_ROOT", "/workspace/bindings");
+            config.app_dir_preprocessor(|app_dir| {
+                let binding = app_dir.join("bindings/acme-registry");
+                std::fs::create_dir_all(&binding).unwrap();
+                std::fs::write(binding.join("type"), "npm-registry").unwrap();
+                std::fs::write(binding.join("registry"), "https://npm.acme.example/").unwrap();
+                std::fs::write(binding.join("scope"), "@acme").unwrap();
+                std::fs::write(binding.join("token"), "test-registry-token").unwrap();
+            });
+        },
+        |ctx| {
+            assert_contains!(
+                ctx.pack_stdout,
+                "- Using registry credentials from service bindings `acme-registry`"
+            );
+            assert_contains!(ctx.pack_stdout, "added 4 packages");
+            ctx.run_shell_command(
+                "! grep -rq --exclude-dir=bindings test-registry-token /workspace /layers",
+            );
+        },
+    );
+}
@@ -1,12 +1,14 @@
 use crate::BUILDPACK_NAME;
 use bullet_stream::state::Bullet;
 use bullet_stream::{style, Print};
+use heroku_nodejs_utils::config::ConfigError;
 use indoc::formatdoc;
 use std::fmt::Display;
 use std::io::{stdout, Stdout};
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug)]
 pub(crate) enum PnpmEngineBuildpackError {
+    Config(ConfigError),
     CorepackRequired,
 }
 
@@ -22,6 +24,15 @@ pub(crate) fn on_error(error: libcnb::Error<PnpmEngineBuildpackError>) {
 
 fn on_buildpack_error(error: PnpmEngineBuildpackError, logger: Print<Bullet<Stdout>>) {
     match error {
+        PnpmEngineBuildpackError::Config(e) => {
+            logger.error(formatdoc! {"
+                Invalid Node.js buildpack configuration.
+
+                {e}
+
+                Fix the configuration and retry your build.
+            "});
+        }
         PnpmEngineBuildpackError::CorepackRequired => {
             print_error_details(logger, &"Corepack Requirement Error").error(formatdoc! {"
                     A pnpm lockfile ({pnpm_lockfile}) was detected, but the
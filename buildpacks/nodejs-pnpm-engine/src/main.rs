@@ -1,11 +1,12 @@
 mod errors;
 
 use crate::errors::PnpmEngineBuildpackError;
+use heroku_nodejs_utils::config::NodejsConfig;
 use libcnb::build::{BuildContext, BuildResult};
 use libcnb::data::build_plan::BuildPlanBuilder;
 use libcnb::detect::{DetectContext, DetectResult, DetectResultBuilder};
 use libcnb::generic::{GenericMetadata, GenericPlatform};
-use libcnb::{buildpack_main, Buildpack};
+use libcnb::{buildpack_main, Buildpack, Platform};
 #[cfg(test)]
 use libcnb_test as _;
 #[cfg(test)]
@@ -21,8 +22,12 @@ impl Buildpack for PnpmEngineBuildpack {
     type Error = PnpmEngineBuildpackError;
 
     fn detect(&self, context: DetectContext<Self>) -> libcnb::Result<DetectResult, Self::Error> {
+        let app_dir = NodejsConfig::read(&context.app_dir)
+            .and_then(|config| config.app_root(context.platform.env()))
+            .map_err(PnpmEngineBuildpackError::Config)?
+            .dir(&context.app_dir);
         // pass detect if a `pnpm-lock.yaml` is found
-        if context.app_dir.join("pnpm-lock.yaml").exists() {
+        if app_dir.join("pnpm-lock.yaml").exists() {
             return DetectResultBuilder::pass()
                 .build_plan(
                     BuildPlanBuilder::new()
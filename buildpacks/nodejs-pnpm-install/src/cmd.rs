@@ -79,3 +79,16 @@ pub(crate) fn pnpm_store_prune(pnpm_env: &Env) -> Result<(), Error> {
 
     status.success().then_some(()).ok_or(Error::Exit(status))
 }
+
+/// Execute `pnpm prune --prod` to remove dev dependencies from `node_modules`.
+pub(crate) fn pnpm_prune_prod(pnpm_env: &Env) -> Result<(), Error> {
+    let status = Command::new("pnpm")
+        .args(["prune", "--prod"])
+        .envs(pnpm_env)
+        .spawn()
+        .map_err(Error::Spawn)?
+        .wait()
+        .map_err(Error::Wait)?;
+
+    status.success().then_some(()).ok_or(Error::Exit(status))
+}
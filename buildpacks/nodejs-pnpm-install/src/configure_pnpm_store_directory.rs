@@ -1,3 +1,4 @@
+use heroku_nodejs_utils::config::Setting;
 use libcnb::build::BuildContext;
 use libcnb::data::layer_name;
 use libcnb::layer::{
@@ -12,6 +13,7 @@ use crate::{cmd, PnpmInstallBuildpack, PnpmInstallBuildpackError};
 pub(crate) fn configure_pnpm_store_directory(
     context: &BuildContext<PnpmInstallBuildpack>,
     env: &Env,
+    cache: &Setting<bool>,
 ) -> Result<(), libcnb::Error<PnpmInstallBuildpackError>> {
     let new_metadata = AddressableStoreLayerMetadata {
         layer_version: LAYER_VERSION.to_string(),
@@ -24,7 +26,7 @@ pub(crate) fn configure_pnpm_store_directory(
             launch: false,
             invalid_metadata_action: &|_| InvalidMetadataAction::DeleteLayer,
             restored_layer_action: &|old_metadata: &AddressableStoreLayerMetadata, _| {
-                if old_metadata == &new_metadata {
+                if cache.value && old_metadata == &new_metadata {
                     RestoredLayerAction::KeepLayer
                 } else {
                     RestoredLayerAction::DeleteLayer
@@ -39,7 +41,14 @@ pub(crate) fn configure_pnpm_store_directory(
         }
         LayerState::Empty { cause } => {
             if let EmptyLayerCause::RestoredLayerAction { .. } = cause {
-                log_info("Cached pnpm content-addressable store has expired");
+                if cache.value {
+                    log_info("Cached pnpm content-addressable store has expired");
+                } else {
+                    log_info(format!(
+                        "Not restoring pnpm content-addressable store from cache ({})",
+                        cache.reason("cache")
+                    ));
+                }
             }
             log_info("Creating new pnpm content-addressable store");
             addressable_layer.write_metadata(new_metadata)?;
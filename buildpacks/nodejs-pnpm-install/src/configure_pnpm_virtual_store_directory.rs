@@ -1,5 +1,6 @@
 use std::fs::create_dir;
 use std::os::unix::fs::symlink;
+use std::path::{Path, PathBuf};
 
 use libcnb::build::BuildContext;
 use libcnb::data::layer_name;
@@ -9,10 +10,12 @@ use libherokubuildpack::log::log_info;
 
 use crate::{cmd, PnpmInstallBuildpack, PnpmInstallBuildpackError};
 
+/// Configures pnpm's virtual store in a layer and returns its location.
 pub(crate) fn configure_pnpm_virtual_store_directory(
     context: &BuildContext<PnpmInstallBuildpack>,
+    app_dir: &Path,
     env: &Env,
-) -> Result<(), libcnb::Error<PnpmInstallBuildpackError>> {
+) -> Result<PathBuf, libcnb::Error<PnpmInstallBuildpackError>> {
     let virtual_layer = context.uncached_layer(
         layer_name!("virtual"),
         UncachedLayerDefinition {
@@ -29,14 +32,14 @@ pub(crate) fn configure_pnpm_virtual_store_directory(
         .map_err(PnpmInstallBuildpackError::PnpmDir)?;
 
     // Install a symlink from {virtual_layer}/node_modules to
-    // {app_dir}/node_modules, so that dependencies in
+    // {app_root}/node_modules, so that dependencies in
     // {virtual_layer}/store/ can find their dependencies via the Node
     // module loader's ancestor directory traversal.
     symlink(
-        context.app_dir.join("node_modules"),
+        app_dir.join("node_modules"),
         virtual_layer.path().join("node_modules"),
     )
     .map_err(PnpmInstallBuildpackError::VirtualLayer)?;
 
-    Ok(())
+    Ok(virtual_store_dir)
 }
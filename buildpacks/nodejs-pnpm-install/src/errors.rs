@@ -1,8 +1,14 @@
 use crate::cmd;
 use crate::PnpmInstallBuildpackError;
+use heroku_nodejs_utils::build_scripts::BuildScriptsError;
 use heroku_nodejs_utils::buildplan::{
     NodeBuildScriptsMetadataError, NODE_BUILD_SCRIPTS_BUILD_PLAN_NAME,
 };
+use heroku_nodejs_utils::config::{
+    ConfigError, ADVISORIES_ENV_VAR, SKIP_PRUNING_ENV_VAR, VULNERABILITIES_FAIL_ON_ENV_VAR,
+};
+use heroku_nodejs_utils::licenses::LicenseCheckError;
+use heroku_nodejs_utils::vulnerabilities::{VulnerabilityError, OSV_BINDING_TYPE};
 use indoc::formatdoc;
 use libherokubuildpack::log::log_error;
 
@@ -39,6 +45,9 @@ fn on_buildpack_error(bp_err: PnpmInstallBuildpackError) {
                 "},
             );
         }
+        PnpmInstallBuildpackError::BuildScripts(err) => on_build_scripts_error(&err),
+        PnpmInstallBuildpackError::Config(err) => on_config_error(&err),
+        PnpmInstallBuildpackError::Licenses(err) => on_licenses_error(&err),
         PnpmInstallBuildpackError::PackageJson(err) => log_error(
             "heroku/nodejs-pnpm package.json error",
             formatdoc! {"
@@ -61,6 +70,19 @@ fn on_buildpack_error(bp_err: PnpmInstallBuildpackError) {
                 "},
             );
         }
+        PnpmInstallBuildpackError::PnpmPrune(err) => {
+            let (context, details) = get_cmd_error_context(err);
+            log_error(
+                "pnpm prune error",
+                formatdoc! {"
+                    There was an error while attempting to remove dev dependencies
+                    with pnpm. {context} To keep dev dependencies in the launch
+                    image instead, set {SKIP_PRUNING_ENV_VAR} to `true`.
+
+                    Details: {details}
+                "},
+            );
+        }
         PnpmInstallBuildpackError::PnpmDir(err) => {
             let (context, details) = get_cmd_error_context(err);
             log_error(
@@ -85,6 +107,24 @@ fn on_buildpack_error(bp_err: PnpmInstallBuildpackError) {
                 "},
             );
         }
+        PnpmInstallBuildpackError::Procfile(err) => log_error(
+            "heroku/nodejs-pnpm Procfile error",
+            formatdoc! {"
+                There was an error while attempting to read the processes
+                declared in this project's Procfile.
+
+                Details: {err}
+            "},
+        ),
+        PnpmInstallBuildpackError::RegistryCredentials(err) => log_error(
+            "heroku/nodejs-pnpm registry credentials error",
+            formatdoc! {"
+                There was an error while attempting to configure the registry
+                credentials from the service bindings.
+
+                Details: {err}
+            "},
+        ),
         PnpmInstallBuildpackError::VirtualLayer(err) => {
             log_error(
                 "virtual store layer error",
@@ -96,26 +136,109 @@ fn on_buildpack_error(bp_err: PnpmInstallBuildpackError) {
                 "},
             );
         }
+        PnpmInstallBuildpackError::Vulnerabilities(err) => on_vulnerabilities_error(&err),
         PnpmInstallBuildpackError::NodeBuildScriptsMetadata(err) => {
-            let NodeBuildScriptsMetadataError::InvalidEnabledValue(value) = err;
-            let value_type = value.type_str();
+            on_node_build_scripts_metadata_error(&err);
+        }
+    };
+}
+
+fn on_build_scripts_error(err: &BuildScriptsError) {
+    log_error(
+        "heroku/nodejs-pnpm build scripts error",
+        formatdoc! {"
+            There was an error while attempting to select the build
+            scripts to run from this project's package.json.
+
+            Details: {err}
+        "},
+    );
+}
+
+fn on_config_error(err: &ConfigError) {
+    log_error(
+        "heroku/nodejs-pnpm configuration error",
+        formatdoc! {"
+            There was an error while attempting to read the Node.js
+            buildpack configuration from this project's project.toml
+            or environment variables.
+
+            Details: {err}
+        "},
+    );
+}
+
+fn on_licenses_error(err: &LicenseCheckError) {
+    match err {
+        LicenseCheckError::Denied(_) => log_error(
+            "heroku/nodejs-pnpm denied dependency licenses",
+            formatdoc! {"
+                {err}
+
+                These licenses are denied by `licenses.deny` in project.toml.
+                Remove or replace these dependencies, or allow them with
+                `licenses.exceptions`, and retry your build.
+            "},
+        ),
+        LicenseCheckError::DependencyGraph(_) | LicenseCheckError::WriteReport(_, _) => log_error(
+            "heroku/nodejs-pnpm license report error",
+            formatdoc! {"
+                There was an error while attempting to report the licenses
+                of the installed dependencies from pnpm-lock.yaml.
+
+                Details: {err}
+            "},
+        ),
+    }
+}
+
+fn on_vulnerabilities_error(err: &VulnerabilityError) {
+    match err {
+        VulnerabilityError::Found { .. } => log_error(
+            "heroku/nodejs-pnpm known vulnerabilities",
+            formatdoc! {"
+                {err}
+
+                Update these dependencies to a fixed version and retry your
+                build. To only report vulnerabilities instead, unset
+                `vulnerabilities.fail_on` in project.toml and
+                {VULNERABILITIES_FAIL_ON_ENV_VAR}.
+            "},
+        ),
+        VulnerabilityError::ReadAdvisories(_, _) | VulnerabilityError::ParseAdvisory(_, _) => {
             log_error(
-                format!("metadata error in {NODE_BUILD_SCRIPTS_BUILD_PLAN_NAME} build plan"),
+                "heroku/nodejs-pnpm vulnerability advisories error",
                 formatdoc! {"
-                    A participating buildpack has set invalid `[requires.metadata]` for the 
-                    build plan named `{NODE_BUILD_SCRIPTS_BUILD_PLAN_NAME}`.
-                    
-                    Expected metadata format:
-                    [requires.metadata]
-                    enabled = <bool>
-                    
-                    But was:
-                    [requires.metadata]
-                    enabled = <{value_type}> 
+                    There was an error while attempting to read the OSV
+                    advisories from `vulnerabilities.advisories` in project.toml,
+                    {ADVISORIES_ENV_VAR}, or an `{OSV_BINDING_TYPE}` service binding.
+
+                    Details: {err}
                 "},
             );
         }
-    };
+        VulnerabilityError::DependencyGraph(_) => log_error(
+            "heroku/nodejs-pnpm vulnerability check error",
+            formatdoc! {"
+                There was an error while attempting to check the installed
+                dependencies from pnpm-lock.yaml for known vulnerabilities.
+
+                Details: {err}
+            "},
+        ),
+    }
+}
+
+fn on_node_build_scripts_metadata_error(err: &NodeBuildScriptsMetadataError) {
+    log_error(
+        format!("metadata error in {NODE_BUILD_SCRIPTS_BUILD_PLAN_NAME} build plan"),
+        formatdoc! {"
+            A participating buildpack has set invalid `[requires.metadata]` for the 
+            build plan named `{NODE_BUILD_SCRIPTS_BUILD_PLAN_NAME}`.
+            
+            {err}
+        "},
+    );
 }
 
 fn get_cmd_error_context(err: cmd::Error) -> (&'static str, String) {
@@ -1,4 +1,19 @@
+use heroku_nodejs_utils::build_scripts::{
+    build_scripts_env, resolve_build_scripts, BuildScriptsError,
+};
+use heroku_nodejs_utils::config::{AppRoot, ConfigError, NodejsConfig, Setting};
+use heroku_nodejs_utils::licenses::{
+    write_license_report, LicenseCheckError, LicensePolicy, LicenseReportOutcome,
+};
 use heroku_nodejs_utils::package_json::{PackageJson, PackageJsonError};
+use heroku_nodejs_utils::package_manager::PackageManager;
+use heroku_nodejs_utils::procfile::ProcfileError;
+use heroku_nodejs_utils::prune::{disk_usage, PruneSummary};
+use heroku_nodejs_utils::registry_credentials::{
+    RegistryCredentials, RegistryCredentialsError, UserConfig, UserConfigFormat,
+};
+use heroku_nodejs_utils::sbom::read_dependency_sboms;
+use heroku_nodejs_utils::vulnerabilities::{self, VulnerabilityError};
 use libcnb::build::{BuildContext, BuildResult, BuildResultBuilder};
 use libcnb::data::build_plan::BuildPlanBuilder;
 use libcnb::data::launch::{LaunchBuilder, ProcessBuilder};
@@ -6,13 +21,15 @@ use libcnb::data::process_type;
 use libcnb::data::store::Store;
 use libcnb::detect::{DetectContext, DetectResult, DetectResultBuilder};
 use libcnb::generic::{GenericMetadata, GenericPlatform};
-use libcnb::{buildpack_main, Buildpack, Env};
-use libherokubuildpack::log::{log_header, log_info};
+use libcnb::sbom::Sbom;
+use libcnb::{buildpack_main, Buildpack, Env, Platform};
+use libherokubuildpack::log::{log_header, log_info, log_warning};
+use std::path::{Path, PathBuf};
 
 use crate::configure_pnpm_store_directory::configure_pnpm_store_directory;
 use crate::configure_pnpm_virtual_store_directory::configure_pnpm_virtual_store_directory;
 use heroku_nodejs_utils::buildplan::{
-    read_node_build_scripts_metadata, NodeBuildScriptsMetadataError,
+    read_node_build_scripts_metadata, NodeBuildScriptsMetadata, NodeBuildScriptsMetadataError,
     NODE_BUILD_SCRIPTS_BUILD_PLAN_NAME,
 };
 #[cfg(test)]
@@ -36,42 +53,66 @@ impl Buildpack for PnpmInstallBuildpack {
     type Error = PnpmInstallBuildpackError;
 
     fn detect(&self, context: DetectContext<Self>) -> libcnb::Result<DetectResult, Self::Error> {
-        context
-            .app_dir
-            .join("pnpm-lock.yaml")
-            .exists()
-            .then(|| {
-                DetectResultBuilder::pass()
-                    .build_plan(
-                        BuildPlanBuilder::new()
-                            .provides("node_modules")
-                            .provides(NODE_BUILD_SCRIPTS_BUILD_PLAN_NAME)
-                            .requires("node")
-                            .requires("pnpm")
-                            .requires("node_modules")
-                            .requires(NODE_BUILD_SCRIPTS_BUILD_PLAN_NAME)
-                            .build(),
-                    )
-                    .build()
-            })
-            .unwrap_or_else(|| DetectResultBuilder::fail().build())
+        let app_dir = NodejsConfig::read(&context.app_dir)
+            .and_then(|config| config.app_root(context.platform.env()))
+            .map_err(PnpmInstallBuildpackError::Config)?
+            .dir(&context.app_dir);
+        if app_dir.join("pnpm-lock.yaml").exists() {
+            DetectResultBuilder::pass()
+                .build_plan(
+                    BuildPlanBuilder::new()
+                        .provides("node_modules")
+                        .provides(NODE_BUILD_SCRIPTS_BUILD_PLAN_NAME)
+                        .requires("node")
+                        .requires("pnpm")
+                        .requires("node_modules")
+                        .requires(NODE_BUILD_SCRIPTS_BUILD_PLAN_NAME)
+                        .build(),
+                )
+                .build()
+        } else {
+            DetectResultBuilder::fail().build()
+        }
     }
 
     fn build(&self, context: BuildContext<Self>) -> libcnb::Result<BuildResult, Self::Error> {
-        let env = Env::from_current();
-        let pkg_json = PackageJson::read(context.app_dir.join("package.json"))
+        let mut env = Env::from_current();
+        let config =
+            NodejsConfig::read(&context.app_dir).map_err(PnpmInstallBuildpackError::Config)?;
+        let (app_root, app_dir) = enter_app_root(&context, &config)?;
+        let pkg_json = PackageJson::read(app_dir.join("package.json"))
             .map_err(PnpmInstallBuildpackError::PackageJson)?;
         let node_build_scripts_metadata = read_node_build_scripts_metadata(&context.buildpack_plan)
             .map_err(PnpmInstallBuildpackError::NodeBuildScriptsMetadata)?;
+        let cache = config
+            .cache(context.platform.env())
+            .map_err(PnpmInstallBuildpackError::Config)?;
+        let prune = config
+            .prune(context.platform.env())
+            .map_err(PnpmInstallBuildpackError::Config)?;
+        let sbom_spdx = config
+            .sbom_spdx(context.platform.env())
+            .map_err(PnpmInstallBuildpackError::Config)?;
+        let build_scripts = resolve_build_scripts(
+            &pkg_json,
+            &config
+                .build_scripts(context.platform.env())
+                .map_err(PnpmInstallBuildpackError::Config)?,
+            &node_build_scripts_metadata,
+        )
+        .map_err(PnpmInstallBuildpackError::BuildScripts)?;
+
+        // Keeps the rendered credentials around until the build finishes.
+        let _registry_user_config = configure_registry_credentials(&mut env)?;
 
         log_header("Setting up pnpm dependency store");
-        configure_pnpm_store_directory(&context, &env)?;
-        configure_pnpm_virtual_store_directory(&context, &env)?;
+        configure_pnpm_store_directory(&context, &env, &cache)?;
+        let virtual_store_dir = configure_pnpm_virtual_store_directory(&context, &app_dir, &env)?;
 
         log_header("Installing dependencies");
         cmd::pnpm_install(&env).map_err(PnpmInstallBuildpackError::PnpmInstall)?;
 
-        let mut metadata = context.store.unwrap_or_default().metadata;
+        let mut metadata = context.store.clone().unwrap_or_default().metadata;
         let cache_use_count = store::read_cache_use_count(&metadata);
         if store::should_prune_cache(cache_use_count) {
             log_info("Pruning unused dependencies from pnpm content-addressable store");
@@ -79,43 +120,46 @@ impl Buildpack for PnpmInstallBuildpack {
         }
         store::set_cache_use_count(&mut metadata, cache_use_count + 1);
 
+        log_header("Checking dependency licenses");
+        check_licenses(&context, &app_dir, &pkg_json, &config.licenses)?;
+        check_vulnerabilities(&context, &app_dir, &pkg_json, &config, &env)?;
+
         log_header("Running scripts");
-        let scripts = pkg_json.build_scripts();
-        if scripts.is_empty() {
+        if build_scripts.is_empty() {
             log_info("No build scripts found");
-        } else {
-            for script in scripts {
-                if let Some(false) = node_build_scripts_metadata.enabled {
-                    log_info(format!(
-                        "! Not running `{script}` as it was disabled by a participating buildpack",
-                    ));
-                } else {
-                    log_info(format!("Running `{script}` script"));
-                    cmd::pnpm_run(&env, &script).map_err(PnpmInstallBuildpackError::BuildScript)?;
-                }
+        }
+        let scripts_env = build_scripts_env(&env, &node_build_scripts_metadata);
+        for build_script in build_scripts {
+            if build_script.run {
+                log_info(format!(
+                    "Running `{}` script ({})",
+                    build_script.name, build_script.reason
+                ));
+                cmd::pnpm_run(&scripts_env, &build_script.name)
+                    .map_err(PnpmInstallBuildpackError::BuildScript)?;
+            } else {
+                log_info(format!("! {}", build_script.skipped_message()));
             }
         }
 
-        let result_builder = BuildResultBuilder::new().store(Store { metadata });
+        log_header("Pruning dev dependencies");
+        let pruned = prune_dev_dependencies(
+            &app_dir,
+            virtual_store_dir,
+            &prune,
+            &node_build_scripts_metadata,
+            &env,
+        )?;
 
-        if context.app_dir.join("Procfile").exists() {
-            log_info("Skipping default web process (Procfile detected)");
-            result_builder.build()
-        } else if pkg_json.has_start_script() {
-            result_builder
-                .launch(
-                    LaunchBuilder::new()
-                        .process(
-                            ProcessBuilder::new(process_type!("web"), ["pnpm", "start"])
-                                .default(true)
-                                .build(),
-                        )
-                        .build(),
-                )
-                .build()
-        } else {
-            result_builder.build()
-        }
+        log_header("Generating SBOM");
+        let result_builder = generate_sboms(&app_dir, &pkg_json, pruned, sbom_spdx.value)
+            .into_iter()
+            .fold(
+                BuildResultBuilder::new().store(Store { metadata }),
+                BuildResultBuilder::launch_sbom,
+            );
+
+        configure_launch(&context, &config, &app_root, &pkg_json, result_builder)
     }
 
     fn on_error(&self, err: libcnb::Error<Self::Error>) {
@@ -123,14 +167,208 @@ impl Buildpack for PnpmInstallBuildpack {
     }
 }
 
+/// Uses the processes from the Procfile or project.toml when there are any,
+/// otherwise a default web process that runs `pnpm start` in the app root.
+fn configure_launch(
+    context: &BuildContext<PnpmInstallBuildpack>,
+    config: &NodejsConfig,
+    app_root: &AppRoot,
+    pkg_json: &PackageJson,
+    result_builder: BuildResultBuilder,
+) -> libcnb::Result<BuildResult, PnpmInstallBuildpackError> {
+    if let Some((procfile, source)) = config
+        .declared_processes(&context.app_dir)
+        .map_err(PnpmInstallBuildpackError::Procfile)?
+    {
+        if procfile.processes.is_empty() {
+            log_info("Skipping default web process (Procfile detected)");
+            return result_builder.build();
+        }
+        for process in &procfile.processes {
+            log_info(format!(
+                "Adding `{}` process from {source}: `{}`",
+                process.process_type, process.command
+            ));
+        }
+        result_builder.launch(procfile.launch()).build()
+    } else if pkg_json.has_start_script() {
+        result_builder
+            .launch(
+                LaunchBuilder::new()
+                    .process(
+                        ProcessBuilder::new(process_type!("web"), ["pnpm", "start"])
+                            .default(true)
+                            .working_directory(app_root.working_directory())
+                            .build(),
+                    )
+                    .build(),
+            )
+            .build()
+    } else {
+        result_builder.build()
+    }
+}
+
+fn enter_app_root(
+    context: &BuildContext<PnpmInstallBuildpack>,
+    config: &NodejsConfig,
+) -> Result<(AppRoot, PathBuf), PnpmInstallBuildpackError> {
+    let app_root = config
+        .app_root(context.platform.env())
+        .map_err(PnpmInstallBuildpackError::Config)?;
+    if !app_root.is_app_dir() {
+        log_info(format!("Using app root `{app_root}`"));
+    }
+    let app_dir = app_root
+        .enter(&context.app_dir)
+        .map_err(PnpmInstallBuildpackError::Config)?;
+    Ok((app_root, app_dir))
+}
+
+/// Reports the licenses of the installed dependencies and fails when a
+/// production dependency has a denied license.
+fn check_licenses(
+    context: &BuildContext<PnpmInstallBuildpack>,
+    app_dir: &Path,
+    pkg_json: &PackageJson,
+    policy: &LicensePolicy,
+) -> Result<(), libcnb::Error<PnpmInstallBuildpackError>> {
+    match write_license_report(context, app_dir, PackageManager::Pnpm, pkg_json, policy)? {
+        LicenseReportOutcome::Skipped(error) => {
+            log_warning("Skipping license report", error.to_string());
+        }
+        LicenseReportOutcome::Written { report, path } => {
+            log_info(format!(
+                "Production dependencies: {}",
+                report.summary(false)
+            ));
+            log_info(format!("Dev dependencies: {}", report.summary(true)));
+            log_info(format!("Wrote the license report to {}", path.display()));
+            report
+                .check(policy)
+                .map_err(PnpmInstallBuildpackError::Licenses)?;
+        }
+    }
+    Ok(())
+}
+
+/// Checks the installed dependencies against the OSV advisories from the
+/// configuration or a service binding, when there are any. Vulnerabilities
+/// are only reported unless `vulnerabilities.fail_on` is set.
+fn check_vulnerabilities(
+    context: &BuildContext<PnpmInstallBuildpack>,
+    app_dir: &Path,
+    pkg_json: &PackageJson,
+    config: &NodejsConfig,
+    env: &Env,
+) -> Result<(), PnpmInstallBuildpackError> {
+    let Some(report) = vulnerabilities::check_vulnerabilities(
+        context,
+        app_dir,
+        PackageManager::Pnpm,
+        pkg_json,
+        config,
+        env,
+    )?
+    else {
+        return Ok(());
+    };
+
+    log_header("Checking for known vulnerabilities");
+    log_info(format!(
+        "Using the advisories from {}",
+        report.source.description
+    ));
+    for vulnerability in &report.vulnerabilities {
+        log_info(vulnerability.to_string());
+    }
+    log_info(vulnerabilities::summary(&report.vulnerabilities));
+    report
+        .enforce()
+        .map_err(PnpmInstallBuildpackError::Vulnerabilities)?;
+    Ok(())
+}
+
+/// Removes dev dependencies unless pruning is disabled or a participating
+/// buildpack, which still needs them, runs the build scripts. Returns whether
+/// the dev dependencies were removed.
+fn prune_dev_dependencies(
+    app_dir: &Path,
+    virtual_store_dir: PathBuf,
+    prune: &Setting<bool>,
+    node_build_scripts_metadata: &NodeBuildScriptsMetadata,
+    env: &Env,
+) -> Result<bool, PnpmInstallBuildpackError> {
+    if !prune.value {
+        log_info(format!("Skipping pruning ({})", prune.reason("prune")));
+        return Ok(false);
+    }
+    if let Some(false) = node_build_scripts_metadata.enabled {
+        log_info("Skipping pruning as build scripts were disabled by a participating buildpack");
+        return Ok(false);
+    }
+
+    let dependency_dirs = [app_dir.join("node_modules"), virtual_store_dir];
+    let dependency_dirs: Vec<&Path> = dependency_dirs.iter().map(PathBuf::as_path).collect();
+    let size_before = disk_usage(&dependency_dirs);
+    log_info("Running `pnpm prune --prod`");
+    cmd::pnpm_prune_prod(env).map_err(PnpmInstallBuildpackError::PnpmPrune)?;
+    if let (Ok(before), Ok(after)) = (size_before, disk_usage(&dependency_dirs)) {
+        log_info(PruneSummary { before, after }.to_string());
+    }
+    Ok(true)
+}
+
+/// Lists the installed dependencies from `pnpm-lock.yaml`. A lockfile that
+/// can't be read only skips the SBOM, as it doesn't affect the app.
+fn generate_sboms(app_dir: &Path, pkg_json: &PackageJson, pruned: bool, spdx: bool) -> Vec<Sbom> {
+    match read_dependency_sboms(app_dir, PackageManager::Pnpm, pkg_json, pruned, spdx) {
+        Ok(sboms) => {
+            log_info("Listing installed dependencies from pnpm-lock.yaml");
+            sboms
+        }
+        Err(error) => {
+            log_warning("Skipping SBOM generation", error.to_string());
+            vec![]
+        }
+    }
+}
+
+fn configure_registry_credentials(
+    env: &mut Env,
+) -> Result<Option<UserConfig>, PnpmInstallBuildpackError> {
+    let credentials =
+        RegistryCredentials::read(env).map_err(PnpmInstallBuildpackError::RegistryCredentials)?;
+    let user_config = credentials
+        .write_user_config(UserConfigFormat::Npmrc)
+        .map_err(PnpmInstallBuildpackError::RegistryCredentials)?;
+    if let Some(user_config) = &user_config {
+        *env = user_config.apply(env);
+    }
+    if user_config.is_some() {
+        log_header("Configuring registry credentials");
+        for binding in credentials.bindings(UserConfigFormat::Npmrc) {
+            log_info(format!("Using service binding `{binding}`"));
+        }
+    }
+    Ok(user_config)
+}
+
 #[derive(Debug)]
 enum PnpmInstallBuildpackError {
     BuildScript(cmd::Error),
+    BuildScripts(BuildScriptsError),
+    Config(ConfigError),
+    Licenses(LicenseCheckError),
     PackageJson(PackageJsonError),
     PnpmDir(cmd::Error),
     PnpmInstall(cmd::Error),
+    PnpmPrune(cmd::Error),
     PnpmStorePrune(cmd::Error),
+    Procfile(ProcfileError),
+    RegistryCredentials(RegistryCredentialsError),
     VirtualLayer(std::io::Error),
+    Vulnerabilities(VulnerabilityError),
     NodeBuildScriptsMetadata(NodeBuildScriptsMetadataError),
 }
 
@@ -140,4 +378,22 @@ impl From<PnpmInstallBuildpackError> for libcnb::Error<PnpmInstallBuildpackError
     }
 }
 
+impl From<ConfigError> for PnpmInstallBuildpackError {
+    fn from(e: ConfigError) -> Self {
+        PnpmInstallBuildpackError::Config(e)
+    }
+}
+
+impl From<LicenseCheckError> for PnpmInstallBuildpackError {
+    fn from(e: LicenseCheckError) -> Self {
+        PnpmInstallBuildpackError::Licenses(e)
+    }
+}
+
+impl From<VulnerabilityError> for PnpmInstallBuildpackError {
+    fn from(e: VulnerabilityError) -> Self {
+        PnpmInstallBuildpackError::Vulnerabilities(e)
+    }
+}
+
 buildpack_main!(PnpmInstallBuildpack);
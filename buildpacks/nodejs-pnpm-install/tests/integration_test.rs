@@ -3,7 +3,7 @@
 
 use indoc::{formatdoc, indoc};
 use libcnb::data::buildpack_id;
-use libcnb_test::{assert_contains, assert_empty, BuildpackReference};
+use libcnb_test::{assert_contains, assert_empty, assert_not_contains, BuildpackReference};
 use test_support::{
     add_build_script, assert_web_response, custom_buildpack, integration_test_with_config,
     nodejs_integration_test, nodejs_integration_test_with_config,
@@ -139,7 +139,7 @@ fn pnpm_8_nuxt() {
             ctx.pack_stdout,
             &formatdoc! {"
                 [Running scripts]
-                Running `build` script
+                Running `build` script (defined in package.json)
             "}
         );
     });
@@ -234,3 +234,48 @@ fn test_default_web_process_registration_is_skipped_if_procfile_exists() {
         },
     );
 }
+
+#[test]
+#[ignore = "integration test"]
+fn test_procfile_processes_are_registered() {
+    nodejs_integration_test_with_config(
+        "./fixtures/pnpm-9",
+        |config| {
+            config.app_dir_preprocessor(|app_dir| {
+                std::fs::write(
+                    app_dir.join("Procfile"),
+                    "web: pnpm start\nclock: node clock.js\n",
+                )
+                .unwrap();
+            });
+        },
+        |ctx| {
+            assert_contains!(
+                ctx.pack_stdout,
+                "Adding `web` process from Procfile: `pnpm start`"
+            );
+            assert_contains!(
+                ctx.pack_stdout,
+                "Adding `clock` process from Procfile: `node clock.js`"
+            );
+        },
+    );
+}
+
+#[test]
+#[ignore = "integration test"]
+fn test_dev_dependency_pruning_can_be_skipped() {
+    nodejs_integration_test_with_config(
+        "./fixtures/pnpm-8-hoist",
+        |config| {
+            config.env("NODEJS_SKIP_PRUNING", "true");
+        },
+        |ctx| {
+            assert_contains!(
+                ctx.pack_stdout,
+                "Skipping pruning (`NODEJS_SKIP_PRUNING` is set to `true`)"
+            );
+            assert_not_contains!(ctx.pack_stdout, "pnpm prune --prod");
+        },
+    );
+}
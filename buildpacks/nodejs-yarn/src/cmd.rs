@@ -142,3 +142,50 @@ pub(crate) fn yarn_run(yarn_env: &Env, script: &str) -> Result<(), Error> {
 
     status.success().then_some(()).ok_or(Error::Exit(status))
 }
+
+/// Execute `yarn plugin runtime` to determine if a plugin is active. This
+/// command is only available on yarn >= 2.
+pub(crate) fn yarn_has_plugin(env: &Env, plugin: &str) -> Result<bool, Error> {
+    let output = Command::new("yarn")
+        .args(["plugin", "runtime"])
+        .envs(env)
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(Error::Spawn)?
+        .wait_with_output()
+        .map_err(Error::Wait)?;
+
+    output
+        .status
+        .success()
+        .then_some(())
+        .ok_or(Error::Exit(output.status))?;
+    Ok(String::from_utf8_lossy(&output.stdout).contains(plugin))
+}
+
+/// Remove dev dependencies from `node_modules`. Yarn 1 reinstalls only the
+/// production dependencies, newer versions use `yarn workspaces focus`.
+pub(crate) fn yarn_prune(yarn_line: &Yarn, yarn_env: &Env) -> Result<(), Error> {
+    let args = if yarn_line == &Yarn::Yarn1 {
+        vec![
+            "install",
+            "--production=true",
+            "--frozen-lockfile",
+            "--ignore-engines",
+            "--ignore-scripts",
+            "--prefer-offline",
+        ]
+    } else {
+        vec!["workspaces", "focus", "--all", "--production"]
+    };
+
+    let status = Command::new("yarn")
+        .args(args)
+        .envs(yarn_env)
+        .spawn()
+        .map_err(Error::Spawn)?
+        .wait()
+        .map_err(Error::Wait)?;
+
+    status.success().then_some(()).ok_or(Error::Exit(status))
+}
@@ -1,5 +1,6 @@
 use std::fs;
 
+use heroku_nodejs_utils::config::Setting;
 use libcnb::build::BuildContext;
 use libcnb::data::layer_name;
 use libcnb::layer::{
@@ -22,6 +23,7 @@ pub(crate) fn configure_yarn_cache(
     context: &BuildContext<YarnBuildpack>,
     yarn: &Yarn,
     env: &Env,
+    cache: &Setting<bool>,
 ) -> Result<(), libcnb::Error<YarnBuildpackError>> {
     let new_metadata = DepsLayerMetadata {
         yarn: yarn.clone(),
@@ -36,7 +38,8 @@ pub(crate) fn configure_yarn_cache(
             launch: true,
             invalid_metadata_action: &|_| InvalidMetadataAction::DeleteLayer,
             restored_layer_action: &|old_metadata: &DepsLayerMetadata, _| {
-                let is_reusable = old_metadata.yarn == new_metadata.yarn
+                let is_reusable = cache.value
+                    && old_metadata.yarn == new_metadata.yarn
                     && old_metadata.layer_version == new_metadata.layer_version
                     && old_metadata.cache_usage_count < MAX_CACHE_USAGE_COUNT;
                 if is_reusable {
@@ -54,7 +57,14 @@ pub(crate) fn configure_yarn_cache(
         }
         LayerState::Empty { cause } => {
             if let EmptyLayerCause::RestoredLayerAction { .. } = cause {
-                log_info("Clearing yarn dependency cache");
+                if cache.value {
+                    log_info("Clearing yarn dependency cache");
+                } else {
+                    log_info(format!(
+                        "Not restoring yarn dependency cache ({})",
+                        cache.reason("cache")
+                    ));
+                }
             }
             deps_layer.write_metadata(DepsLayerMetadata {
                 cache_usage_count: new_metadata.cache_usage_count + 1.0,
@@ -7,7 +7,6 @@ use libcnb::layer::{
     CachedLayerDefinition, InvalidMetadataAction, LayerState, RestoredLayerAction,
 };
 use libcnb::layer_env::LayerEnv;
-use libherokubuildpack::download::{download_file, DownloadError};
 use libherokubuildpack::fs::move_directory_contents;
 use libherokubuildpack::log::log_info;
 use libherokubuildpack::tar::decompress_tarball;
@@ -16,12 +15,17 @@ use tempfile::NamedTempFile;
 use thiserror::Error;
 
 use heroku_nodejs_utils::inv::Release;
+use heroku_nodejs_utils::mirror::{
+    download_artifact, ArtifactChecksum, ArtifactChecksumError, ArtifactDownloadError,
+    ArtifactMirror, ArtifactVerificationError,
+};
 
 use crate::{YarnBuildpack, YarnBuildpackError};
 
 pub(crate) fn install_yarn(
     context: &BuildContext<YarnBuildpack>,
     release: &Release,
+    artifact_mirror: Option<&ArtifactMirror>,
 ) -> Result<LayerEnv, libcnb::Error<YarnBuildpackError>> {
     let new_metadata = CliLayerMetadata {
         yarn_version: release.version.to_string(),
@@ -53,10 +57,37 @@ pub(crate) fn install_yarn(
         LayerState::Empty { .. } => {
             dist_layer.write_metadata(new_metadata)?;
 
+            // Yarn releases are mirrored to S3 as single part uploads, so the
+            // inventory `ETag` is the MD5 digest of each tarball.
+            let checksum = release
+                .etag
+                .as_deref()
+                .ok_or_else(|| CliLayerError::MissingChecksum(release.version.to_string()))
+                .and_then(|etag| {
+                    ArtifactChecksum::from_etag(etag).map_err(CliLayerError::InvalidChecksum)
+                })?;
+
             let yarn_tgz = NamedTempFile::new().map_err(CliLayerError::TempFile)?;
 
-            log_info(format!("Downloading yarn {}", release.version));
-            download_file(&release.url, yarn_tgz.path()).map_err(CliLayerError::Download)?;
+            if let Some(mirror) = artifact_mirror {
+                let url = mirror
+                    .rewrite(&release.url)
+                    .map_err(YarnBuildpackError::ArtifactMirror)?;
+                log_info(format!(
+                    "Downloading yarn {} from {url} (mirror {mirror})",
+                    release.version
+                ));
+                download_artifact(&url, yarn_tgz.path()).map_err(CliLayerError::Download)?;
+            } else {
+                log_info(format!("Downloading yarn {}", release.version));
+                download_artifact(&release.url, yarn_tgz.path())
+                    .map_err(CliLayerError::Download)?;
+            }
+
+            log_info(format!("Verifying checksum ({checksum})"));
+            checksum
+                .verify(yarn_tgz.path())
+                .map_err(CliLayerError::ChecksumVerification)?;
 
             log_info(format!("Extracting yarn {}", release.version));
             decompress_tarball(&mut yarn_tgz.into_file(), dist_layer.path())
@@ -100,7 +131,13 @@ pub(crate) enum CliLayerError {
     #[error("Couldn't create tempfile for yarn CLI: {0}")]
     TempFile(std::io::Error),
     #[error("Couldn't download yarn CLI: {0}")]
-    Download(DownloadError),
+    Download(ArtifactDownloadError),
+    #[error("No checksum found in the inventory for yarn {0}")]
+    MissingChecksum(String),
+    #[error("Couldn't read the inventory checksum for yarn CLI: {0}")]
+    InvalidChecksum(ArtifactChecksumError),
+    #[error("Couldn't verify yarn CLI: {0}")]
+    ChecksumVerification(ArtifactVerificationError),
     #[error("Couldn't decompress yarn CLI: {0}")]
     Untar(std::io::Error),
     #[error("Couldn't move yarn CLI to the target location: {0}")]
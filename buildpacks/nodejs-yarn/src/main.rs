@@ -1,23 +1,44 @@
 use crate::yarn::Yarn;
+use heroku_nodejs_utils::build_scripts::{
+    build_scripts_env, resolve_build_scripts, BuildScript, BuildScriptsError,
+};
+use heroku_nodejs_utils::config::{
+    AppRoot, ConfigError, NodejsConfig, Setting, SKIP_PRUNING_ENV_VAR,
+    VULNERABILITIES_FAIL_ON_ENV_VAR,
+};
 use heroku_nodejs_utils::inv::Inventory;
+use heroku_nodejs_utils::licenses::{
+    write_license_report, LicenseCheckError, LicensePolicy, LicenseReportOutcome,
+};
+use heroku_nodejs_utils::mirror::{ArtifactMirror, ArtifactMirrorError, ARTIFACT_MIRROR_ENV_VAR};
 use heroku_nodejs_utils::package_json::{PackageJson, PackageJsonError};
+use heroku_nodejs_utils::package_manager::PackageManager;
+use heroku_nodejs_utils::procfile::ProcfileError;
+use heroku_nodejs_utils::prune::{disk_usage, PruneSummary};
+use heroku_nodejs_utils::registry_credentials::{
+    RegistryCredentials, RegistryCredentialsError, UserConfig, UserConfigFormat,
+};
+use heroku_nodejs_utils::sbom::read_dependency_sboms;
 use heroku_nodejs_utils::vrs::{Requirement, VersionError};
+use heroku_nodejs_utils::vulnerabilities::{self, VulnerabilityError};
 use libcnb::build::{BuildContext, BuildResult, BuildResultBuilder};
 use libcnb::data::build_plan::BuildPlanBuilder;
-use libcnb::data::launch::{LaunchBuilder, ProcessBuilder};
+use libcnb::data::launch::{Launch, LaunchBuilder, ProcessBuilder};
 use libcnb::data::process_type;
 use libcnb::detect::{DetectContext, DetectResult, DetectResultBuilder};
 use libcnb::generic::GenericMetadata;
 use libcnb::generic::GenericPlatform;
 use libcnb::layer_env::Scope;
-use libcnb::{buildpack_main, Buildpack, Env};
-use libherokubuildpack::log::{log_error, log_header, log_info};
+use libcnb::sbom::Sbom;
+use libcnb::{buildpack_main, Buildpack, Env, Platform};
+use libherokubuildpack::log::{log_error, log_header, log_info, log_warning};
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
 use crate::configure_yarn_cache::{configure_yarn_cache, DepsLayerError};
 use crate::install_yarn::{install_yarn, CliLayerError};
 use heroku_nodejs_utils::buildplan::{
-    read_node_build_scripts_metadata, NodeBuildScriptsMetadataError,
+    read_node_build_scripts_metadata, NodeBuildScriptsMetadata, NodeBuildScriptsMetadataError,
     NODE_BUILD_SCRIPTS_BUILD_PLAN_NAME,
 };
 #[cfg(test)]
@@ -37,6 +58,8 @@ mod yarn;
 
 const INVENTORY: &str = include_str!("../inventory.toml");
 const DEFAULT_YARN_REQUIREMENT: &str = "1.22.x";
+/// Provides `yarn workspaces focus`, which is built into yarn 4.
+const WORKSPACE_TOOLS_PLUGIN: &str = "@yarnpkg/plugin-workspace-tools";
 
 struct YarnBuildpack;
 
@@ -46,34 +69,54 @@ impl Buildpack for YarnBuildpack {
     type Error = YarnBuildpackError;
 
     fn detect(&self, context: DetectContext<Self>) -> libcnb::Result<DetectResult, Self::Error> {
-        context
-            .app_dir
-            .join("yarn.lock")
-            .exists()
-            .then(|| {
-                DetectResultBuilder::pass()
-                    .build_plan(
-                        BuildPlanBuilder::new()
-                            .provides("yarn")
-                            .provides("node_modules")
-                            .provides(NODE_BUILD_SCRIPTS_BUILD_PLAN_NAME)
-                            .requires("node")
-                            .requires("yarn")
-                            .requires("node_modules")
-                            .requires(NODE_BUILD_SCRIPTS_BUILD_PLAN_NAME)
-                            .build(),
-                    )
-                    .build()
-            })
-            .unwrap_or_else(|| DetectResultBuilder::fail().build())
+        let app_dir = NodejsConfig::read(&context.app_dir)
+            .and_then(|config| config.app_root(context.platform.env()))
+            .map_err(YarnBuildpackError::Config)?
+            .dir(&context.app_dir);
+        if app_dir.join("yarn.lock").exists() {
+            DetectResultBuilder::pass()
+                .build_plan(
+                    BuildPlanBuilder::new()
+                        .provides("yarn")
+                        .provides("node_modules")
+                        .provides(NODE_BUILD_SCRIPTS_BUILD_PLAN_NAME)
+                        .requires("node")
+                        .requires("yarn")
+                        .requires("node_modules")
+                        .requires(NODE_BUILD_SCRIPTS_BUILD_PLAN_NAME)
+                        .build(),
+                )
+                .build()
+        } else {
+            DetectResultBuilder::fail().build()
+        }
     }
 
     fn build(&self, context: BuildContext<Self>) -> libcnb::Result<BuildResult, Self::Error> {
         let mut env = Env::from_current();
-        let pkg_json = PackageJson::read(context.app_dir.join("package.json"))
+        let config = NodejsConfig::read(&context.app_dir).map_err(YarnBuildpackError::Config)?;
+        let (app_root, app_dir) = enter_app_root(&context, &config)?;
+        let pkg_json = PackageJson::read(app_dir.join("package.json"))
             .map_err(YarnBuildpackError::PackageJson)?;
         let node_build_scripts_metadata = read_node_build_scripts_metadata(&context.buildpack_plan)
             .map_err(YarnBuildpackError::NodeBuildScriptsMetadata)?;
+        let cache = config
+            .cache(context.platform.env())
+            .map_err(YarnBuildpackError::Config)?;
+        let prune = config
+            .prune(context.platform.env())
+            .map_err(YarnBuildpackError::Config)?;
+        let sbom_spdx = config
+            .sbom_spdx(context.platform.env())
+            .map_err(YarnBuildpackError::Config)?;
+        let build_scripts = resolve_build_scripts(
+            &pkg_json,
+            &config
+                .build_scripts(context.platform.env())
+                .map_err(YarnBuildpackError::Config)?,
+            &node_build_scripts_metadata,
+        )
+        .map_err(YarnBuildpackError::BuildScripts)?;
 
         let yarn_version = match cmd::yarn_version(&env) {
             // Install yarn if it's not present.
@@ -106,8 +149,10 @@ impl Buildpack for YarnBuildpack {
                     yarn_cli_release.version
                 ));
 
+                let artifact_mirror = read_artifact_mirror(&context)?;
+
                 log_header("Installing yarn CLI");
-                let yarn_env = install_yarn(&context, yarn_cli_release)?;
+                let yarn_env = install_yarn(&context, yarn_cli_release, artifact_mirror.as_ref())?;
                 env = yarn_env.apply(Scope::Build, &env);
 
                 cmd::yarn_version(&env).map_err(YarnBuildpackError::YarnVersionDetect)?
@@ -122,6 +167,8 @@ impl Buildpack for YarnBuildpack {
 
         log_info(format!("Yarn CLI operating in yarn {yarn_version} mode."));
 
+        let (_registry_user_config, install_env) = configure_registry_credentials(&yarn, &env)?;
+
         log_header("Setting up yarn dependency cache");
         cmd::yarn_disable_global_cache(&yarn, &env)
             .map_err(YarnBuildpackError::YarnDisableGlobalCache)?;
@@ -131,47 +178,40 @@ impl Buildpack for YarnBuildpack {
         if zero_install {
             log_info("Yarn zero-install detected. Skipping dependency cache.");
         } else {
-            configure_yarn_cache(&context, &yarn, &env)?;
+            configure_yarn_cache(&context, &yarn, &env, &cache)?;
         }
 
         log_header("Installing dependencies");
-        cmd::yarn_install(&yarn, zero_install, &env).map_err(YarnBuildpackError::YarnInstall)?;
+        cmd::yarn_install(&yarn, zero_install, &install_env)
+            .map_err(YarnBuildpackError::YarnInstall)?;
+
+        log_header("Checking dependency licenses");
+        check_licenses(&context, &app_dir, &pkg_json, &config.licenses)?;
+        check_vulnerabilities(&context, &app_dir, &pkg_json, &config, &env)?;
 
         log_header("Running scripts");
-        let scripts = pkg_json.build_scripts();
-        if scripts.is_empty() {
-            log_info("No build scripts found");
-        } else {
-            for script in scripts {
-                if let Some(false) = node_build_scripts_metadata.enabled {
-                    log_info(format!(
-                        "! Not running `{script}` as it was disabled by a participating buildpack",
-                    ));
-                } else {
-                    log_info(format!("Running `{script}` script"));
-                    cmd::yarn_run(&env, &script).map_err(YarnBuildpackError::BuildScript)?;
-                }
-            }
-        }
+        run_build_scripts(
+            &build_scripts,
+            &build_scripts_env(&env, &node_build_scripts_metadata),
+        )?;
 
-        if context.app_dir.join("Procfile").exists() {
-            log_info("Skipping default web process (Procfile detected)");
-            BuildResultBuilder::new().build()
-        } else if pkg_json.has_start_script() {
-            BuildResultBuilder::new()
-                .launch(
-                    LaunchBuilder::new()
-                        .process(
-                            ProcessBuilder::new(process_type!("web"), ["yarn", "start"])
-                                .default(true)
-                                .build(),
-                        )
-                        .build(),
-                )
-                .build()
-        } else {
-            BuildResultBuilder::new().build()
+        log_header("Pruning dev dependencies");
+        let pruned = prune_dev_dependencies(
+            &app_dir,
+            &yarn,
+            &prune,
+            &node_build_scripts_metadata,
+            &install_env,
+        )?;
+
+        log_header("Generating SBOM");
+        let mut result_builder = generate_sboms(&app_dir, &pkg_json, pruned, sbom_spdx.value)
+            .into_iter()
+            .fold(BuildResultBuilder::new(), BuildResultBuilder::launch_sbom);
+        if let Some(launch) = configure_launch(&context.app_dir, &app_root, &config, &pkg_json)? {
+            result_builder = result_builder.launch(launch);
         }
+        result_builder.build()
     }
 
     fn on_error(&self, error: libcnb::Error<Self::Error>) {
@@ -179,21 +219,39 @@ impl Buildpack for YarnBuildpack {
             libcnb::Error::BuildpackError(bp_err) => {
                 let err_string = bp_err.to_string();
                 match bp_err {
-                    YarnBuildpackError::BuildScript(_) => {
+                    YarnBuildpackError::BuildScript(_) | YarnBuildpackError::BuildScripts(_) => {
                         log_error("Yarn build script error", err_string);
                     }
-                    YarnBuildpackError::CliLayer(_) => {
+                    YarnBuildpackError::CliLayer(_) | YarnBuildpackError::ArtifactMirror(_) => {
                         log_error("Yarn distribution layer error", err_string);
                     }
+                    YarnBuildpackError::Config(_) => {
+                        log_error("Yarn configuration error", err_string);
+                    }
                     YarnBuildpackError::DepsLayer(_) => {
                         log_error("Yarn dependency layer error", err_string);
                     }
                     YarnBuildpackError::InventoryParse(_) => {
                         log_error("Yarn inventory parse error", err_string);
                     }
+                    YarnBuildpackError::Licenses(LicenseCheckError::Denied(_)) => {
+                        log_error(
+                            "Yarn denied dependency licenses",
+                            format!("{err_string}\n\nThese licenses are denied by `licenses.deny` in project.toml. Remove or replace these dependencies, or allow them with `licenses.exceptions`."),
+                        );
+                    }
+                    YarnBuildpackError::Licenses(_) => {
+                        log_error("Yarn license report error", err_string);
+                    }
                     YarnBuildpackError::PackageJson(_) => {
                         log_error("Yarn package.json error", err_string);
                     }
+                    YarnBuildpackError::Procfile(_) => {
+                        log_error("Yarn Procfile error", err_string);
+                    }
+                    YarnBuildpackError::RegistryCredentials(_) => {
+                        log_error("Yarn registry credentials error", err_string);
+                    }
                     YarnBuildpackError::YarnCacheGet(_)
                     | YarnBuildpackError::YarnDisableGlobalCache(_) => {
                         log_error("Yarn cache error", err_string);
@@ -201,12 +259,27 @@ impl Buildpack for YarnBuildpack {
                     YarnBuildpackError::YarnInstall(_) => {
                         log_error("Yarn install error", err_string);
                     }
+                    YarnBuildpackError::YarnPrune(_) => {
+                        log_error(
+                            "Yarn prune error",
+                            format!("{err_string}. To keep dev dependencies in the launch image instead, set {SKIP_PRUNING_ENV_VAR} to `true`."),
+                        );
+                    }
                     YarnBuildpackError::YarnVersionDetect(_)
                     | YarnBuildpackError::YarnVersionResolve(_)
                     | YarnBuildpackError::YarnVersionUnsupported(_)
                     | YarnBuildpackError::YarnDefaultParse(_) => {
                         log_error("Yarn version error", err_string);
                     }
+                    YarnBuildpackError::Vulnerabilities(VulnerabilityError::Found { .. }) => {
+                        log_error(
+                            "Yarn known vulnerabilities",
+                            format!("{err_string}\n\nUpdate these dependencies to a fixed version. To only report vulnerabilities instead, unset `vulnerabilities.fail_on` in project.toml and {VULNERABILITIES_FAIL_ON_ENV_VAR}."),
+                        );
+                    }
+                    YarnBuildpackError::Vulnerabilities(_) => {
+                        log_error("Yarn vulnerability check error", err_string);
+                    }
                     YarnBuildpackError::NodeBuildScriptsMetadata(_) => {
                         log_error("Yarn buildplan error", err_string);
                     }
@@ -219,24 +292,268 @@ impl Buildpack for YarnBuildpack {
     }
 }
 
+/// Runs the resolved build scripts, logging why each one runs or is skipped.
+fn run_build_scripts(build_scripts: &[BuildScript], env: &Env) -> Result<(), YarnBuildpackError> {
+    if build_scripts.is_empty() {
+        log_info("No build scripts found");
+    }
+    for build_script in build_scripts {
+        if build_script.run {
+            log_info(format!(
+                "Running `{}` script ({})",
+                build_script.name, build_script.reason
+            ));
+            cmd::yarn_run(env, &build_script.name).map_err(YarnBuildpackError::BuildScript)?;
+        } else {
+            log_info(format!("! {}", build_script.skipped_message()));
+        }
+    }
+    Ok(())
+}
+
+/// Reports the licenses of the installed dependencies and fails when a
+/// production dependency has a denied license.
+fn check_licenses(
+    context: &BuildContext<YarnBuildpack>,
+    app_dir: &Path,
+    pkg_json: &PackageJson,
+    policy: &LicensePolicy,
+) -> Result<(), libcnb::Error<YarnBuildpackError>> {
+    match write_license_report(context, app_dir, PackageManager::Yarn, pkg_json, policy)? {
+        LicenseReportOutcome::Skipped(error) => {
+            log_warning("Skipping license report", error.to_string());
+        }
+        LicenseReportOutcome::Written { report, path } => {
+            log_info(format!(
+                "Production dependencies: {}",
+                report.summary(false)
+            ));
+            log_info(format!("Dev dependencies: {}", report.summary(true)));
+            log_info(format!("Wrote the license report to {}", path.display()));
+            report.check(policy).map_err(YarnBuildpackError::Licenses)?;
+        }
+    }
+    Ok(())
+}
+
+/// Checks the installed dependencies against the OSV advisories from the
+/// configuration or a service binding, when there are any. Vulnerabilities
+/// are only reported unless `vulnerabilities.fail_on` is set.
+fn check_vulnerabilities(
+    context: &BuildContext<YarnBuildpack>,
+    app_dir: &Path,
+    pkg_json: &PackageJson,
+    config: &NodejsConfig,
+    env: &Env,
+) -> Result<(), YarnBuildpackError> {
+    let Some(report) = vulnerabilities::check_vulnerabilities(
+        context,
+        app_dir,
+        PackageManager::Yarn,
+        pkg_json,
+        config,
+        env,
+    )?
+    else {
+        return Ok(());
+    };
+
+    log_header("Checking for known vulnerabilities");
+    log_info(format!(
+        "Using the advisories from {}",
+        report.source.description
+    ));
+    for vulnerability in &report.vulnerabilities {
+        log_info(vulnerability.to_string());
+    }
+    log_info(vulnerabilities::summary(&report.vulnerabilities));
+    report
+        .enforce()
+        .map_err(YarnBuildpackError::Vulnerabilities)?;
+    Ok(())
+}
+
+/// Removes dev dependencies from `node_modules` unless pruning is disabled or
+/// a participating buildpack, which still needs them, runs the build scripts.
+/// Returns whether the dev dependencies were removed.
+fn prune_dev_dependencies(
+    app_dir: &Path,
+    yarn: &Yarn,
+    prune: &Setting<bool>,
+    node_build_scripts_metadata: &NodeBuildScriptsMetadata,
+    env: &Env,
+) -> Result<bool, YarnBuildpackError> {
+    if !prune.value {
+        log_info(format!("Skipping pruning ({})", prune.reason("prune")));
+        return Ok(false);
+    }
+    if let Some(false) = node_build_scripts_metadata.enabled {
+        log_info("Skipping pruning as build scripts were disabled by a participating buildpack");
+        return Ok(false);
+    }
+    if matches!(yarn, Yarn::Yarn2 | Yarn::Yarn3)
+        && !cmd::yarn_has_plugin(env, WORKSPACE_TOOLS_PLUGIN)
+            .map_err(YarnBuildpackError::YarnPrune)?
+    {
+        log_info(format!(
+            "! Skipping pruning as `yarn workspaces focus` requires the `{WORKSPACE_TOOLS_PLUGIN}` plugin with yarn 2 and 3"
+        ));
+        return Ok(false);
+    }
+
+    let node_modules = app_dir.join("node_modules");
+    let size_before = disk_usage(&[&node_modules]);
+    log_info("Removing dev dependencies");
+    cmd::yarn_prune(yarn, env).map_err(YarnBuildpackError::YarnPrune)?;
+    if let (Ok(before), Ok(after)) = (size_before, disk_usage(&[&node_modules])) {
+        log_info(PruneSummary { before, after }.to_string());
+    }
+    Ok(true)
+}
+
+/// Lists the installed dependencies from `yarn.lock`. A lockfile that can't
+/// be read only skips the SBOM, as it doesn't affect the app.
+fn generate_sboms(app_dir: &Path, pkg_json: &PackageJson, pruned: bool, spdx: bool) -> Vec<Sbom> {
+    match read_dependency_sboms(app_dir, PackageManager::Yarn, pkg_json, pruned, spdx) {
+        Ok(sboms) => {
+            log_info("Listing installed dependencies from yarn.lock");
+            sboms
+        }
+        Err(error) => {
+            log_warning("Skipping SBOM generation", error.to_string());
+            vec![]
+        }
+    }
+}
+
+/// Uses the processes from the Procfile or project.toml when there are any,
+/// otherwise a default web process for the `start` script in the app root.
+fn configure_launch(
+    app_dir: &Path,
+    app_root: &AppRoot,
+    config: &NodejsConfig,
+    pkg_json: &PackageJson,
+) -> Result<Option<Launch>, YarnBuildpackError> {
+    if let Some((procfile, source)) = config
+        .declared_processes(app_dir)
+        .map_err(YarnBuildpackError::Procfile)?
+    {
+        if procfile.processes.is_empty() {
+            log_info("Skipping default web process (Procfile detected)");
+            return Ok(None);
+        }
+        for process in &procfile.processes {
+            log_info(format!(
+                "Adding `{}` process from {source}: `{}`",
+                process.process_type, process.command
+            ));
+        }
+        Ok(Some(procfile.launch()))
+    } else if pkg_json.has_start_script() {
+        Ok(Some(
+            LaunchBuilder::new()
+                .process(
+                    ProcessBuilder::new(process_type!("web"), ["yarn", "start"])
+                        .default(true)
+                        .working_directory(app_root.working_directory())
+                        .build(),
+                )
+                .build(),
+        ))
+    } else {
+        Ok(None)
+    }
+}
+
+fn enter_app_root(
+    context: &BuildContext<YarnBuildpack>,
+    config: &NodejsConfig,
+) -> Result<(AppRoot, PathBuf), YarnBuildpackError> {
+    let app_root = config
+        .app_root(context.platform.env())
+        .map_err(YarnBuildpackError::Config)?;
+    if !app_root.is_app_dir() {
+        log_info(format!("Using app root `{app_root}`"));
+    }
+    let app_dir = app_root
+        .enter(&context.app_dir)
+        .map_err(YarnBuildpackError::Config)?;
+    Ok((app_root, app_dir))
+}
+
+fn read_artifact_mirror(
+    context: &BuildContext<YarnBuildpack>,
+) -> Result<Option<ArtifactMirror>, YarnBuildpackError> {
+    context
+        .platform
+        .env()
+        .get_string_lossy(ARTIFACT_MIRROR_ENV_VAR)
+        .map(|mirror| ArtifactMirror::parse(&mirror))
+        .transpose()
+        .map_err(YarnBuildpackError::ArtifactMirror)
+}
+
+/// Returns the environment for the commands that fetch packages. Only those see the
+/// credentials, as pointing `HOME` elsewhere would affect every other command.
+fn configure_registry_credentials(
+    yarn: &Yarn,
+    env: &Env,
+) -> Result<(Option<UserConfig>, Env), YarnBuildpackError> {
+    // Yarn 1 reads registry settings from `.npmrc`, newer versions only from `.yarnrc.yml`.
+    let format = if yarn == &Yarn::Yarn1 {
+        UserConfigFormat::Npmrc
+    } else {
+        UserConfigFormat::Yarnrc
+    };
+    let credentials =
+        RegistryCredentials::read(env).map_err(YarnBuildpackError::RegistryCredentials)?;
+    let user_config = credentials
+        .write_user_config(format)
+        .map_err(YarnBuildpackError::RegistryCredentials)?;
+    if user_config.is_some() {
+        log_header("Configuring registry credentials");
+        for binding in credentials.bindings(format) {
+            log_info(format!("Using service binding `{binding}`"));
+        }
+    }
+    let install_env = user_config
+        .as_ref()
+        .map_or_else(|| env.clone(), |user_config| user_config.apply(env));
+    Ok((user_config, install_env))
+}
+
 #[derive(Error, Debug)]
 enum YarnBuildpackError {
     #[error("Couldn't run build script: {0}")]
     BuildScript(cmd::Error),
+    #[error("Couldn't select build scripts: {0}")]
+    BuildScripts(BuildScriptsError),
+    #[error(transparent)]
+    Config(#[from] ConfigError),
     #[error("{0}")]
     CliLayer(#[from] CliLayerError),
+    #[error("Couldn't configure the artifact mirror: {0}")]
+    ArtifactMirror(ArtifactMirrorError),
     #[error("{0}")]
     DepsLayer(#[from] DepsLayerError),
+    #[error("{0}")]
+    Licenses(#[from] LicenseCheckError),
     #[error("Couldn't parse yarn inventory: {0}")]
     InventoryParse(toml::de::Error),
     #[error("Couldn't parse package.json: {0}")]
     PackageJson(PackageJsonError),
+    #[error(transparent)]
+    Procfile(ProcfileError),
+    #[error("Couldn't configure registry credentials: {0}")]
+    RegistryCredentials(RegistryCredentialsError),
     #[error("Couldn't read yarn cache folder: {0}")]
     YarnCacheGet(cmd::Error),
     #[error("Couldn't disable yarn global cache: {0}")]
     YarnDisableGlobalCache(cmd::Error),
     #[error("Yarn install error: {0}")]
     YarnInstall(cmd::Error),
+    #[error("Couldn't prune dev dependencies: {0}")]
+    YarnPrune(cmd::Error),
     #[error("Couldn't determine yarn version: {0}")]
     YarnVersionDetect(cmd::Error),
     #[error("Unsupported yarn version: {0}")]
@@ -245,7 +562,9 @@ enum YarnBuildpackError {
     YarnVersionResolve(Requirement),
     #[error("Couldn't parse yarn default version range: {0}")]
     YarnDefaultParse(VersionError),
-    #[error("Couldn't parse metadata for the buildplan named {NODE_BUILD_SCRIPTS_BUILD_PLAN_NAME}: {0:?}")]
+    #[error(transparent)]
+    Vulnerabilities(#[from] VulnerabilityError),
+    #[error("Couldn't parse metadata for the buildplan named {NODE_BUILD_SCRIPTS_BUILD_PLAN_NAME}:\n{0}")]
     NodeBuildScriptsMetadata(NodeBuildScriptsMetadataError),
 }
 
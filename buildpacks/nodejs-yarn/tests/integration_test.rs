@@ -233,3 +233,76 @@ fn test_default_web_process_registration_is_skipped_if_procfile_exists() {
         },
     );
 }
+
+#[test]
+#[ignore = "integration test"]
+fn test_procfile_processes_are_registered() {
+    nodejs_integration_test_with_config(
+        "./fixtures/yarn-project",
+        |config| {
+            config.app_dir_preprocessor(|app_dir| {
+                std::fs::write(
+                    app_dir.join("Procfile"),
+                    "web: yarn start\nworker: node worker.js\n",
+                )
+                .unwrap();
+            });
+        },
+        |ctx| {
+            assert_contains!(
+                ctx.pack_stdout,
+                "Adding `web` process from Procfile: `yarn start`"
+            );
+            assert_contains!(
+                ctx.pack_stdout,
+                "Adding `worker` process from Procfile: `node worker.js`"
+            );
+        },
+    );
+}
+
+#[test]
+#[ignore = "integration test"]
+fn test_dev_dependencies_are_pruned_after_cleanup_script() {
+    nodejs_integration_test_with_config(
+        "./fixtures/yarn-1-typescript",
+        |config| {
+            config.app_dir_preprocessor(|app_dir| {
+                add_build_script(&app_dir, "heroku-cleanup");
+            });
+        },
+        |ctx| {
+            assert_contains!(ctx.pack_stdout, "Running `heroku-cleanup` script");
+            assert_contains!(ctx.pack_stdout, "executed heroku-cleanup");
+            assert_contains!(ctx.pack_stdout, "[Pruning dev dependencies]");
+            assert_contains!(ctx.pack_stdout, "of dev dependencies");
+            assert_web_response(&ctx, "yarn-1-typescript");
+        },
+    );
+}
+
+#[test]
+#[ignore = "integration test"]
+fn test_registry_credentials_from_service_bindings() {
+    nodejs_integration_test_with_config(
+        "./fixtures/yarn-4-pnp-nonzero",
+        |config| {
+            config.env("SERVICE_BINDING_ROOT", "/workspace/bindings");
+            config.app_dir_preprocessor(|app_dir| {
+                let binding = app_dir.join("bindings/acme-registry");
+                std::fs::create_dir_all(&binding).unwrap();
+                std::fs::write(binding.join("type"), "npm-registry").unwrap();
+                std::fs::write(binding.join("registry"), "https://npm.acme.example/").unwrap();
+                std::fs::write(binding.join("scope"), "@acme").unwrap();
+                std::fs::write(binding.join("token"), "test-registry-token").unwrap();
+            });
+        },
+        |ctx| {
+            assert_contains!(ctx.pack_stdout, "[Configuring registry credentials]");
+            assert_contains!(ctx.pack_stdout, "Using service binding `acme-registry`");
+            ctx.run_shell_command(
+                "! grep -rq --exclude-dir=bindings test-registry-token /workspace /layers",
+            );
+        },
+    );
+}
@@ -2,6 +2,9 @@
 #![allow(unused_crate_dependencies)]
 
 use anyhow::{Context, Result};
+use heroku_nodejs_utils::node_artifact::{Libc, NodeArtifactMetadata};
+use heroku_nodejs_utils::release_schedule::ReleaseSchedule;
+use heroku_nodejs_utils::shasums::{parse_shasums, verify_shasums, ReleaseKeyring};
 use keep_a_changelog_file::{ChangeGroup, Changelog};
 use libherokubuildpack::inventory::artifact::{Arch, Artifact, Os};
 use libherokubuildpack::inventory::checksum::Checksum;
@@ -14,25 +17,52 @@ use std::path::PathBuf;
 use std::str::FromStr;
 use std::{collections::HashMap, env, fs};
 
-const USAGE: &str = "Usage: update_inventory <path/to/inventory.toml> <path/to/CHANGELOG.md>";
+type NodeArtifact = Artifact<Version, Sha256, Option<NodeArtifactMetadata>>;
+
+const USAGE: &str = "Usage: update_inventory [--allow-unsigned-checksums] <path/to/inventory.toml> <path/to/CHANGELOG.md>";
+
+/// Opts into adding nightly and unofficial (musl) builds, which only publish
+/// unsigned checksums.
+const ALLOW_UNSIGNED_CHECKSUMS_FLAG: &str = "--allow-unsigned-checksums";
 
 /// Updates the local node.js inventory.toml with versions published on nodejs.org.
 fn main() -> Result<()> {
-    let inventory_path = env::args()
-        .nth(1)
+    let (flags, mut args): (Vec<String>, Vec<String>) =
+        env::args().skip(1).partition(|arg| arg.starts_with("--"));
+    let allow_unsigned_checksums = match flags.as_slice() {
+        [] => false,
+        [flag] if flag == ALLOW_UNSIGNED_CHECKSUMS_FLAG => true,
+        _ => anyhow::bail!("Unknown flags: {}\n\n{USAGE}", flags.join(" ")),
+    };
+    let mut args = args.drain(..);
+
+    let inventory_path = args
+        .next()
         .context(format!("Missing path to inventory file!\n\n{USAGE}"))?;
 
-    let changelog_path = env::args()
-        .nth(2)
+    let changelog_path = args
+        .next()
         .context(format!("Missing path to changelog file!\n\n{USAGE}"))?;
 
     let inventory_artifacts = fs::read_to_string(&inventory_path)?
-        .parse::<Inventory<Version, Sha256, Option<()>>>()?
+        .parse::<Inventory<Version, Sha256, Option<NodeArtifactMetadata>>>()?
         .artifacts;
 
-    let upstream_artifacts = fetch_upstream_artifacts(&inventory_artifacts)?;
+    let keyring = fs::read_to_string(NODE_RELEASE_KEYRING_PATH)
+        .context(format!(
+            "Failed to read Node.js release keyring at {NODE_RELEASE_KEYRING_PATH}. \
+             Export the Node.js release team keys into it as described in keys/README.md."
+        ))
+        .and_then(|keys| {
+            ReleaseKeyring::from_armored(&keys).context("Failed to parse Node.js release keyring")
+        })?;
+
+    let upstream_artifacts =
+        fetch_upstream_artifacts(&inventory_artifacts, &keyring, allow_unsigned_checksums)?;
 
-    write_inventory(inventory_path, &upstream_artifacts)?;
+    let release_schedule = fetch_release_schedule(&upstream_artifacts)?;
+
+    write_inventory(inventory_path, &release_schedule, &upstream_artifacts)?;
 
     write_changelog(changelog_path, &upstream_artifacts, &inventory_artifacts)?;
 
@@ -41,32 +71,38 @@ fn main() -> Result<()> {
 
 fn write_inventory(
     inventory_path: impl Into<PathBuf>,
-    upstream_artifacts: &[Artifact<Version, Sha256, Option<()>>],
+    release_schedule: &ReleaseSchedule,
+    upstream_artifacts: &[NodeArtifact],
 ) -> Result<()> {
+    let release_lines =
+        toml::to_string(release_schedule).context("Error serializing release schedule")?;
+    let artifacts = Inventory {
+        artifacts: {
+            let mut artifacts = upstream_artifacts.to_vec();
+            artifacts.sort_by(|a, b| {
+                b.version
+                    .cmp(&a.version)
+                    .then_with(|| b.arch.to_string().cmp(&a.arch.to_string()))
+                    .then_with(|| {
+                        Libc::for_artifact(a.metadata.as_ref())
+                            .cmp(&Libc::for_artifact(b.metadata.as_ref()))
+                    })
+            });
+            artifacts
+        },
+    }
+    .to_string();
     fs::write(
         inventory_path.into(),
-        Inventory {
-            artifacts: {
-                let mut artifacts = upstream_artifacts.to_vec();
-                artifacts.sort_by(|a, b| {
-                    if a.version == b.version {
-                        b.arch.to_string().cmp(&a.arch.to_string())
-                    } else {
-                        b.version.cmp(&a.version)
-                    }
-                });
-                artifacts
-            },
-        }
-        .to_string(),
+        format!("{release_lines}\n{artifacts}"),
     )
     .context("Error writing inventory file")
 }
 
 fn write_changelog(
     changelog_path: impl Into<PathBuf>,
-    upstream_artifacts: &[Artifact<Version, Sha256, Option<()>>],
-    inventory_artifacts: &[Artifact<Version, Sha256, Option<()>>],
+    upstream_artifacts: &[NodeArtifact],
+    inventory_artifacts: &[NodeArtifact],
 ) -> Result<()> {
     let changelog_path = changelog_path.into();
 
@@ -103,7 +139,10 @@ fn write_changelog(
                     os_arch_labels_by_version
                         .entry(artifact.version.clone())
                         .or_default()
-                        .insert(format!("{}-{}", artifact.os, artifact.arch));
+                        .insert(match Libc::for_artifact(artifact.metadata.as_ref()) {
+                            Libc::Glibc => format!("{}-{}", artifact.os, artifact.arch),
+                            Libc::Musl => format!("{}-{}-musl", artifact.os, artifact.arch),
+                        });
                 }
                 let mut sorted_versions = os_arch_labels_by_version.into_iter().collect::<Vec<_>>();
                 sorted_versions.sort_by(|(version_a, _), (version_b, _)| version_b.cmp(version_a));
@@ -129,84 +168,256 @@ fn difference<'a, T: Eq>(a: &'a [T], b: &'a [T]) -> Vec<&'a T> {
     a.iter().filter(|&artifact| !b.contains(artifact)).collect()
 }
 
+/// Public keys of the Node.js release team, used to verify the checksums of
+/// official releases.
+const NODE_RELEASE_KEYRING_PATH: &str =
+    concat!(env!("CARGO_MANIFEST_DIR"), "/keys/nodejs-release-keys.asc");
+
+/// Official Node.js builds, which are linked against glibc.
+const NODE_UPSTREAM_DOWNLOAD_URL: &str = "https://nodejs.org/download/release";
+
+/// Official release candidates, published ahead of new major versions.
+const NODE_RC_DOWNLOAD_URL: &str = "https://nodejs.org/download/rc";
+
+/// Official nightly builds.
+const NODE_NIGHTLY_DOWNLOAD_URL: &str = "https://nodejs.org/download/nightly";
+
+/// Community builds of Node.js, which include builds linked against musl for
+/// distributions like Alpine.
+const NODE_UNOFFICIAL_BUILDS_DOWNLOAD_URL: &str =
+    "https://unofficial-builds.nodejs.org/download/release";
+
+/// Whether a download source publishes signed checksums (`SHASUMS256.txt.asc`).
+/// Nightly and unofficial builds only publish unsigned checksums, and are only
+/// added with `--allow-unsigned-checksums`.
+#[derive(Clone, Copy)]
+enum Checksums {
+    Signed,
+    Unsigned,
+}
+
+type SupportedPlatforms = [(&'static str, Os, Arch, Libc); 2];
+
+const GLIBC_PLATFORMS: SupportedPlatforms = [
+    ("linux-arm64", Os::Linux, Arch::Arm64, Libc::Glibc),
+    ("linux-x64", Os::Linux, Arch::Amd64, Libc::Glibc),
+];
+
+const MUSL_PLATFORMS: SupportedPlatforms = [
+    ("linux-arm64-musl", Os::Linux, Arch::Arm64, Libc::Musl),
+    ("linux-x64-musl", Os::Linux, Arch::Amd64, Libc::Musl),
+];
+
 fn fetch_upstream_artifacts(
-    inventory_artifacts: &[Artifact<Version, Sha256, Option<()>>],
-) -> Result<Vec<Artifact<Version, Sha256, Option<()>>>> {
-    let mut upstream_artifacts = vec![];
-    for release in list_releases()? {
-        if release.version >= Version::parse("0.8.6")? {
-            let supported_platforms = [
-                ("linux-arm64", Os::Linux, Arch::Arm64),
-                ("linux-x64", Os::Linux, Arch::Amd64),
-            ];
-
-            for (file, os, arch) in supported_platforms {
-                if !release.files.contains(&file.to_string()) {
-                    continue;
-                }
+    inventory_artifacts: &[NodeArtifact],
+    keyring: &ReleaseKeyring,
+    allow_unsigned_checksums: bool,
+) -> Result<Vec<NodeArtifact>> {
+    let releases = list_releases(NODE_UPSTREAM_DOWNLOAD_URL)?;
 
-                if let Some(artifact) = inventory_artifacts
+    // Prereleases are only kept for major versions that don't have a stable
+    // release yet, and only the newest nightly of each of those majors, so the
+    // inventory doesn't grow with every nightly build.
+    let release_candidates = upcoming_prereleases(list_releases(NODE_RC_DOWNLOAD_URL)?, &releases);
+
+    let mut upstream_artifacts = vec![];
+    let mut unsigned_sources = vec![];
+    if allow_unsigned_checksums {
+        unsigned_sources.push((
+            NODE_NIGHTLY_DOWNLOAD_URL,
+            newest_per_major(upcoming_prereleases(
+                list_releases(NODE_NIGHTLY_DOWNLOAD_URL)?,
+                &releases,
+            )),
+            GLIBC_PLATFORMS,
+            Checksums::Unsigned,
+        ));
+        unsigned_sources.push((
+            NODE_UNOFFICIAL_BUILDS_DOWNLOAD_URL,
+            list_releases(NODE_UNOFFICIAL_BUILDS_DOWNLOAD_URL)?,
+            MUSL_PLATFORMS,
+            Checksums::Unsigned,
+        ));
+    } else {
+        for download_url in [
+            NODE_NIGHTLY_DOWNLOAD_URL,
+            NODE_UNOFFICIAL_BUILDS_DOWNLOAD_URL,
+        ] {
+            eprintln!(
+                "Skipping new builds from {download_url}, which only publishes unsigned \
+                 checksums. Pass {ALLOW_UNSIGNED_CHECKSUMS_FLAG} to add them."
+            );
+            // Builds already in the inventory are kept, so skipping a source
+            // doesn't remove them.
+            upstream_artifacts.extend(
+                inventory_artifacts
                     .iter()
-                    .find(|x| x.arch == arch && x.os == os && x.version == release.version)
-                {
-                    upstream_artifacts.push(artifact.clone());
-                } else {
-                    let filename = format!("node-v{}-{}.tar.gz", release.version, file);
-
-                    let shasums = fetch_checksums(&release.version)?;
-                    let checksum_hex = shasums
-                        .get(&filename)
-                        .ok_or_else(|| anyhow::anyhow!("Checksum not found for {}", filename))?;
-
-                    upstream_artifacts.push(Artifact::<Version, Sha256, Option<()>> {
-                        url: format!(
-                            "https://nodejs.org/download/release/v{}/{filename}",
-                            release.version
-                        ),
-                        version: release.version.clone(),
-                        checksum: format!("sha256:{checksum_hex}").parse::<Checksum<Sha256>>()?,
-                        arch,
-                        os,
-                        metadata: None,
-                    });
-                }
-            }
+                    .filter(|artifact| artifact.url.starts_with(download_url))
+                    .cloned(),
+            );
         }
     }
+
+    let sources = [
+        (
+            NODE_UPSTREAM_DOWNLOAD_URL,
+            releases,
+            GLIBC_PLATFORMS,
+            Checksums::Signed,
+        ),
+        (
+            NODE_RC_DOWNLOAD_URL,
+            release_candidates,
+            GLIBC_PLATFORMS,
+            Checksums::Signed,
+        ),
+    ];
+
+    for (download_url, releases, supported_platforms, checksums) in
+        sources.into_iter().chain(unsigned_sources)
+    {
+        upstream_artifacts.extend(release_artifacts(
+            download_url,
+            releases,
+            supported_platforms,
+            inventory_artifacts,
+            |version| fetch_checksums(download_url, version, checksums, keyring),
+        )?);
+    }
     Ok(upstream_artifacts)
 }
 
-fn fetch_checksums(version: &Version) -> Result<HashMap<String, String>> {
-    ureq::get(&format!(
-        "https://nodejs.org/download/release/v{version}/SHASUMS256.txt"
-    ))
-    .call()?
-    .into_string()
-    .map_err(anyhow::Error::from)
-    .map(|x| parse_shasums(&x))
-}
+/// Lists the artifacts of each release from a download source for the
+/// supported platforms. Artifacts already in the inventory are reused, and
+/// checksums are only fetched for new artifacts.
+fn release_artifacts(
+    download_url: &str,
+    releases: Vec<NodeJSRelease>,
+    supported_platforms: SupportedPlatforms,
+    inventory_artifacts: &[NodeArtifact],
+    mut fetch_checksums: impl FnMut(&Version) -> Result<HashMap<String, String>>,
+) -> Result<Vec<NodeArtifact>> {
+    let mut artifacts = vec![];
+    for release in releases {
+        if release.version < Version::parse("0.8.6")? {
+            continue;
+        }
 
-// Parses a SHASUMS256.txt file into a map of filename to checksum.
-// Lines are expected to be of the form `<checksum> <filename>`.
-fn parse_shasums(input: &str) -> HashMap<String, String> {
-    input
-        .lines()
-        .filter_map(|line| {
-            let mut parts = line.split_whitespace();
-            match (parts.next(), parts.next(), parts.next()) {
-                (Some(checksum), Some(filename), None) => Some((
-                    // Some of the checksum filenames contain a leading `./` (e.g.
-                    // https://nodejs.org/download/release/v0.11.6/SHASUMS256.txt)
-                    filename.trim_start_matches("./").to_string(),
-                    checksum.to_string(),
-                )),
-                _ => None,
+        for (file, os, arch, libc) in supported_platforms {
+            if !release.files.contains(&file.to_string()) {
+                continue;
             }
-        })
+
+            if let Some(artifact) = inventory_artifacts.iter().find(|x| {
+                x.arch == arch
+                    && x.os == os
+                    && x.version == release.version
+                    && Libc::for_artifact(x.metadata.as_ref()) == libc
+            }) {
+                artifacts.push(artifact.clone());
+            } else {
+                let filename = format!("node-v{}-{}.tar.gz", release.version, file);
+
+                let shasums = fetch_checksums(&release.version)?;
+                let checksum_hex = shasums
+                    .get(&filename)
+                    .ok_or_else(|| anyhow::anyhow!("Checksum not found for {}", filename))?;
+
+                artifacts.push(NodeArtifact {
+                    url: format!("{download_url}/v{}/{filename}", release.version),
+                    version: release.version.clone(),
+                    checksum: format!("sha256:{checksum_hex}").parse::<Checksum<Sha256>>()?,
+                    arch,
+                    os,
+                    metadata: match libc {
+                        Libc::Glibc => None,
+                        Libc::Musl => Some(NodeArtifactMetadata { libc }),
+                    },
+                });
+            }
+        }
+    }
+    Ok(artifacts)
+}
+
+/// Keeps the prereleases for major versions newer than any stable release.
+fn upcoming_prereleases(
+    prereleases: Vec<NodeJSRelease>,
+    releases: &[NodeJSRelease],
+) -> Vec<NodeJSRelease> {
+    let newest_major = releases
+        .iter()
+        .map(|release| release.version.major)
+        .max()
+        .unwrap_or_default();
+    prereleases
+        .into_iter()
+        .filter(|prerelease| prerelease.version.major > newest_major)
         .collect()
 }
 
-const NODE_UPSTREAM_LIST_URL: &str = "https://nodejs.org/download/release/index.json";
+/// Keeps the newest release of each major version.
+fn newest_per_major(releases: Vec<NodeJSRelease>) -> Vec<NodeJSRelease> {
+    let mut newest: HashMap<u64, NodeJSRelease> = HashMap::new();
+    for release in releases {
+        match newest.get(&release.version.major) {
+            Some(existing) if existing.version >= release.version => {}
+            _ => {
+                newest.insert(release.version.major, release);
+            }
+        }
+    }
+    newest.into_values().collect()
+}
+
+/// Fetches the checksums for a release. Signed checksums are only returned if
+/// they were signed by a key in the release keyring.
+fn fetch_checksums(
+    download_url: &str,
+    version: &Version,
+    checksums: Checksums,
+    keyring: &ReleaseKeyring,
+) -> Result<HashMap<String, String>> {
+    match checksums {
+        Checksums::Signed => {
+            let url = format!("{download_url}/v{version}/SHASUMS256.txt.asc");
+            let signed_shasums = ureq::get(&url).call()?.into_string()?;
+            verify_shasums(&signed_shasums, keyring)
+                .context(format!("Failed to verify the signature of {url}"))
+        }
+        Checksums::Unsigned => {
+            let url = format!("{download_url}/v{version}/SHASUMS256.txt");
+            eprintln!("Using unsigned checksums from {url} ({ALLOW_UNSIGNED_CHECKSUMS_FLAG})");
+            ureq::get(&url)
+                .call()?
+                .into_string()
+                .map_err(anyhow::Error::from)
+                .map(|x| parse_shasums(&x))
+        }
+    }
+}
+
+const NODE_RELEASE_SCHEDULE_URL: &str =
+    "https://raw.githubusercontent.com/nodejs/Release/main/schedule.json";
+
+/// Fetches lifecycle information for every release line that has artifacts.
+fn fetch_release_schedule(artifacts: &[NodeArtifact]) -> Result<ReleaseSchedule> {
+    let majors = artifacts
+        .iter()
+        .map(|artifact| artifact.version.major)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+
+    let schedule_json = ureq::get(NODE_RELEASE_SCHEDULE_URL)
+        .call()
+        .context("Failed to fetch Node.js release schedule")?
+        .into_string()
+        .context("Failed to read Node.js release schedule")?;
+
+    ReleaseSchedule::from_nodejs_schedule(&schedule_json, &majors)
+        .context("Failed to parse Node.js release schedule from JSON")
+}
 
 #[derive(Deserialize, Debug)]
 struct NodeJSRelease {
@@ -214,10 +425,124 @@ struct NodeJSRelease {
     pub(crate) files: Vec<String>,
 }
 
-fn list_releases() -> Result<Vec<NodeJSRelease>> {
-    ureq::get(NODE_UPSTREAM_LIST_URL)
+fn list_releases(download_url: &str) -> Result<Vec<NodeJSRelease>> {
+    ureq::get(&format!("{download_url}/index.json"))
         .call()
-        .context("Failed to fetch nodejs.org release list")?
+        .context(format!("Failed to fetch release list from {download_url}"))?
         .into_json::<Vec<NodeJSRelease>>()
-        .context("Failed to parse nodejs.org release list from JSON")
+        .context(format!(
+            "Failed to parse release list from {download_url} as JSON"
+        ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UNOFFICIAL_BUILDS_INDEX: &str = r#"[
+        {"version": "v22.13.1", "files": ["linux-arm64-musl", "linux-riscv64", "linux-x64-glibc-217", "linux-x64-musl"]},
+        {"version": "v22.13.0", "files": ["linux-x64-glibc-217", "linux-x64-musl"]},
+        {"version": "v0.8.5", "files": ["linux-x64-musl"]}
+    ]"#;
+
+    const UNOFFICIAL_BUILDS_SHASUMS: &str = "\
+        9c111af1f951e8869615bca3601ce7ab6969374933bdba6397469843b808f222  node-v22.13.1-linux-arm64-musl.tar.gz
+        d8ae35a9e2bb0c0c0611ee9bacf564ea51cc8291ace1447f95ee6aeaf4f1d61d  node-v22.13.1-linux-x64-musl.tar.gz
+        0b0c1f4b5e3e8e2a4a0c0e3f3d4b6a9f1c2d3e4f5a6b7c8d9e0f1a2b3c4d5e6f  node-v22.13.1-linux-x64-glibc-217.tar.gz
+    ";
+
+    #[test]
+    fn release_artifacts_from_unofficial_builds() {
+        let releases: Vec<NodeJSRelease> = serde_json::from_str(UNOFFICIAL_BUILDS_INDEX).unwrap();
+        let existing_musl_artifact = NodeArtifact {
+            url: format!("{NODE_UNOFFICIAL_BUILDS_DOWNLOAD_URL}/v22.13.0/node-v22.13.0-linux-x64-musl.tar.gz"),
+            version: Version::parse("22.13.0").unwrap(),
+            checksum: "sha256:9c111af1f951e8869615bca3601ce7ab6969374933bdba6397469843b808f222"
+                .parse()
+                .unwrap(),
+            arch: Arch::Amd64,
+            os: Os::Linux,
+            metadata: Some(NodeArtifactMetadata { libc: Libc::Musl }),
+        };
+        let glibc_artifact = NodeArtifact {
+            metadata: None,
+            ..existing_musl_artifact.clone()
+        };
+
+        let mut fetched_versions = vec![];
+        let artifacts = release_artifacts(
+            NODE_UNOFFICIAL_BUILDS_DOWNLOAD_URL,
+            releases,
+            MUSL_PLATFORMS,
+            &[glibc_artifact, existing_musl_artifact.clone()],
+            |version| {
+                fetched_versions.push(version.to_string());
+                Ok(parse_shasums(UNOFFICIAL_BUILDS_SHASUMS))
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            artifacts
+                .iter()
+                .map(|artifact| (
+                    artifact.version.to_string(),
+                    artifact.arch.to_string(),
+                    artifact.url.clone(),
+                    artifact.checksum.value.clone(),
+                    Libc::for_artifact(artifact.metadata.as_ref())
+                ))
+                .collect::<Vec<_>>(),
+            [
+                (
+                    "22.13.1".to_string(),
+                    "arm64".to_string(),
+                    format!("{NODE_UNOFFICIAL_BUILDS_DOWNLOAD_URL}/v22.13.1/node-v22.13.1-linux-arm64-musl.tar.gz"),
+                    hex::decode("9c111af1f951e8869615bca3601ce7ab6969374933bdba6397469843b808f222").unwrap(),
+                    Libc::Musl
+                ),
+                (
+                    "22.13.1".to_string(),
+                    "amd64".to_string(),
+                    format!("{NODE_UNOFFICIAL_BUILDS_DOWNLOAD_URL}/v22.13.1/node-v22.13.1-linux-x64-musl.tar.gz"),
+                    hex::decode("d8ae35a9e2bb0c0c0611ee9bacf564ea51cc8291ace1447f95ee6aeaf4f1d61d").unwrap(),
+                    Libc::Musl
+                ),
+                (
+                    "22.13.0".to_string(),
+                    "amd64".to_string(),
+                    existing_musl_artifact.url.clone(),
+                    existing_musl_artifact.checksum.value.clone(),
+                    Libc::Musl
+                ),
+            ]
+        );
+        // Checksums are only fetched for artifacts missing from the inventory,
+        // and the glibc artifact of the same version isn't mistaken for it.
+        assert_eq!(fetched_versions, ["22.13.1", "22.13.1"]);
+    }
+
+    #[test]
+    fn release_artifacts_without_checksum() {
+        let releases: Vec<NodeJSRelease> = serde_json::from_str(UNOFFICIAL_BUILDS_INDEX).unwrap();
+        let error = release_artifacts(
+            NODE_UNOFFICIAL_BUILDS_DOWNLOAD_URL,
+            releases,
+            MUSL_PLATFORMS,
+            &[],
+            |_| Ok(HashMap::new()),
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Checksum not found for node-v22.13.1-linux-arm64-musl.tar.gz"
+        );
+    }
+
+    #[test]
+    #[ignore = "keys/nodejs-release-keys.asc must be exported from nodejs/release-keys first, see keys/README.md"]
+    fn committed_release_keyring() {
+        let keys = fs::read_to_string(NODE_RELEASE_KEYRING_PATH).unwrap();
+        assert!(ReleaseKeyring::from_armored(&keys).is_ok());
+    }
 }
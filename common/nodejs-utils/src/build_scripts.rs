@@ -0,0 +1,440 @@
+use crate::buildplan::{NodeBuildScriptsMetadata, ScriptAnchor, ScriptPosition};
+use crate::config::{BuildScriptsConfig, ConfigSource, PROJECT_TOML};
+use crate::package_json::PackageJson;
+use libcnb::Env;
+use thiserror::Error;
+
+const HEROKU_PREBUILD: &str = "heroku-prebuild";
+const HEROKU_BUILD: &str = "heroku-build";
+const BUILD: &str = "build";
+const HEROKU_POSTBUILD: &str = "heroku-postbuild";
+const HEROKU_CLEANUP: &str = "heroku-cleanup";
+const DISABLED_BY_BUILDPACK: &str = "it was disabled by a participating buildpack";
+
+/// A `package.json` script considered for the build, whether it runs, and why.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildScript {
+    pub name: String,
+    pub run: bool,
+    pub reason: String,
+}
+
+impl BuildScript {
+    fn run(name: &str, reason: impl Into<String>) -> Self {
+        BuildScript {
+            name: name.to_string(),
+            run: true,
+            reason: reason.into(),
+        }
+    }
+
+    fn skip(name: &str, reason: impl Into<String>) -> Self {
+        BuildScript {
+            name: name.to_string(),
+            run: false,
+            reason: reason.into(),
+        }
+    }
+
+    /// The build log line for a skipped script, like "Not running `build` as
+    /// `heroku-build` takes precedence".
+    #[must_use]
+    pub fn skipped_message(&self) -> String {
+        format!("Not running `{}` as {}", self.name, self.reason)
+    }
+}
+
+/// Resolves the scripts the npm, pnpm, and Yarn buildpacks run after installing
+/// dependencies, in order:
+///
+/// 1. `heroku-prebuild`
+/// 2. the configured build script, or `heroku-build`, or `build`
+/// 3. the configured extra scripts
+/// 4. `heroku-postbuild`
+/// 5. `heroku-cleanup`
+///
+/// Scripts added by participating buildpacks in the `node_build_scripts` build
+/// plan run before or after the default script they're anchored to, with the
+/// configured extra scripts counting as part of the build script. Scripts the
+/// package doesn't define are left out, unless they were configured or added.
+/// Participating buildpacks can disable single scripts, and every script is
+/// skipped when build scripts are disabled by the configuration or by a
+/// participating buildpack.
+///
+/// # Errors
+///
+/// Will return a `BuildScriptsError` if a configured script isn't defined in
+/// `package.json`.
+pub fn resolve_build_scripts(
+    package_json: &PackageJson,
+    config: &BuildScriptsConfig,
+    metadata: &NodeBuildScriptsMetadata,
+) -> Result<Vec<BuildScript>, BuildScriptsError> {
+    let defined = |name: &str| package_json.has_script(name);
+    let defined_script =
+        |name: &str| defined(name).then(|| BuildScript::run(name, "defined in package.json"));
+
+    let slots = [
+        (
+            ScriptAnchor::HerokuPrebuild,
+            defined_script(HEROKU_PREBUILD).into_iter().collect(),
+        ),
+        (
+            ScriptAnchor::Build,
+            resolve_build_script(package_json, config)?,
+        ),
+        (
+            ScriptAnchor::HerokuPostbuild,
+            defined_script(HEROKU_POSTBUILD).into_iter().collect(),
+        ),
+        (
+            ScriptAnchor::HerokuCleanup,
+            defined_script(HEROKU_CLEANUP).into_iter().collect(),
+        ),
+    ];
+
+    let added = |position: ScriptPosition| {
+        metadata
+            .add
+            .iter()
+            .filter(move |added| added.position == position)
+            .map(|added| {
+                let name = added.script.as_str();
+                if slots
+                    .iter()
+                    .flat_map(|(_, scripts)| scripts)
+                    .any(|script| script.name == name)
+                {
+                    BuildScript::skip(name, "it's already one of the build scripts")
+                } else if defined(name) {
+                    BuildScript::run(name, "added by a participating buildpack")
+                } else {
+                    BuildScript::skip(name, "it isn't defined in package.json")
+                }
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let mut scripts = vec![];
+    for (anchor, slot) in &slots {
+        scripts.extend(added(ScriptPosition::Before(*anchor)));
+        scripts.extend(slot.iter().cloned());
+        scripts.extend(added(ScriptPosition::After(*anchor)));
+    }
+
+    for script in scripts
+        .iter_mut()
+        .filter(|script| script.run && metadata.is_disabled(&script.name))
+    {
+        script.run = false;
+        script.reason = DISABLED_BY_BUILDPACK.to_string();
+    }
+
+    let disabled_reason = if let Some(false) = metadata.enabled {
+        Some(DISABLED_BY_BUILDPACK.to_string())
+    } else if config.enabled.value {
+        None
+    } else {
+        Some(config.enabled.reason("scripts.enabled"))
+    };
+    if let Some(reason) = disabled_reason {
+        for script in scripts.iter_mut().filter(|script| script.run) {
+            script.run = false;
+            script.reason.clone_from(&reason);
+        }
+    }
+
+    Ok(scripts)
+}
+
+/// Resolves the configured build script, or `heroku-build`, or `build`, followed
+/// by the configured extra scripts.
+fn resolve_build_script(
+    package_json: &PackageJson,
+    config: &BuildScriptsConfig,
+) -> Result<Vec<BuildScript>, BuildScriptsError> {
+    let defined = |name: &str| package_json.has_script(name);
+    let mut scripts = vec![];
+
+    match &config.build {
+        Some(build) => {
+            if !defined(&build.value) {
+                Err(BuildScriptsError::MissingScript {
+                    script: build.value.clone(),
+                    setting: describe_source("scripts.build", build.source),
+                })?;
+            }
+            for replaced in [HEROKU_BUILD, BUILD] {
+                if defined(replaced) && replaced != build.value {
+                    scripts.push(BuildScript::skip(
+                        replaced,
+                        format!(
+                            "it was replaced by `{}` from {}",
+                            build.value,
+                            describe_source("scripts.build", build.source)
+                        ),
+                    ));
+                }
+            }
+            scripts.push(BuildScript::run(
+                &build.value,
+                format!(
+                    "set with {}",
+                    describe_source("scripts.build", build.source)
+                ),
+            ));
+        }
+        None if defined(HEROKU_BUILD) => {
+            scripts.push(BuildScript::run(HEROKU_BUILD, "defined in package.json"));
+            if defined(BUILD) {
+                scripts.push(BuildScript::skip(
+                    BUILD,
+                    format!("`{HEROKU_BUILD}` takes precedence"),
+                ));
+            }
+        }
+        None if defined(BUILD) => {
+            scripts.push(BuildScript::run(BUILD, "defined in package.json"));
+        }
+        None => {}
+    }
+
+    for extra in &config.extra {
+        if !defined(extra) {
+            Err(BuildScriptsError::MissingScript {
+                script: extra.clone(),
+                setting: describe_source("scripts.extra", ConfigSource::ProjectToml),
+            })?;
+        }
+        scripts.push(BuildScript::run(
+            extra,
+            format!("listed in `scripts.extra` in {PROJECT_TOML}"),
+        ));
+    }
+
+    Ok(scripts)
+}
+
+/// The environment for running build scripts, with the `env` variables
+/// participating buildpacks set in the `node_build_scripts` build plan.
+#[must_use]
+pub fn build_scripts_env(env: &Env, metadata: &NodeBuildScriptsMetadata) -> Env {
+    let mut scripts_env = env.clone();
+    for (name, value) in &metadata.env {
+        scripts_env.insert(name, value);
+    }
+    scripts_env
+}
+
+fn describe_source(key: &str, source: ConfigSource) -> String {
+    match source {
+        ConfigSource::EnvVar(name) => format!("`{name}`"),
+        ConfigSource::ProjectToml | ConfigSource::Default => format!("`{key}` in {PROJECT_TOML}"),
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum BuildScriptsError {
+    #[error("The `{script}` script set with {setting} isn't defined in package.json")]
+    MissingScript { script: String, setting: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buildplan::AddedBuildScript;
+    use crate::config::{Setting, BUILD_SCRIPTS_ENV_VAR};
+
+    fn package_json(scripts: &str) -> PackageJson {
+        serde_json::from_str(&format!(r#"{{ "scripts": {scripts} }}"#)).unwrap()
+    }
+
+    fn config() -> BuildScriptsConfig {
+        BuildScriptsConfig {
+            enabled: Setting {
+                value: true,
+                source: ConfigSource::Default,
+            },
+            build: None,
+            extra: vec![],
+        }
+    }
+
+    fn summary(scripts: &[BuildScript]) -> Vec<(&str, bool, &str)> {
+        scripts
+            .iter()
+            .map(|script| (script.name.as_str(), script.run, script.reason.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn default_build_scripts() {
+        let package_json = package_json(
+            r#"{ "heroku-cleanup": "", "heroku-postbuild": "", "build": "", "heroku-build": "", "heroku-prebuild": "" }"#,
+        );
+        let scripts = resolve_build_scripts(
+            &package_json,
+            &config(),
+            &NodeBuildScriptsMetadata::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            summary(&scripts),
+            [
+                ("heroku-prebuild", true, "defined in package.json"),
+                ("heroku-build", true, "defined in package.json"),
+                ("build", false, "`heroku-build` takes precedence"),
+                ("heroku-postbuild", true, "defined in package.json"),
+                ("heroku-cleanup", true, "defined in package.json"),
+            ]
+        );
+        assert_eq!(
+            scripts[2].skipped_message(),
+            "Not running `build` as `heroku-build` takes precedence"
+        );
+
+        assert!(resolve_build_scripts(
+            &PackageJson::default(),
+            &config(),
+            &NodeBuildScriptsMetadata::default()
+        )
+        .unwrap()
+        .is_empty());
+    }
+
+    #[test]
+    fn configured_build_scripts() {
+        let package_json = package_json(
+            r#"{ "build": "", "build:prod": "", "docs": "", "heroku-postbuild": "" }"#,
+        );
+        let config = BuildScriptsConfig {
+            build: Some(Setting {
+                value: "build:prod".to_string(),
+                source: ConfigSource::ProjectToml,
+            }),
+            extra: vec!["docs".to_string()],
+            ..config()
+        };
+        let scripts =
+            resolve_build_scripts(&package_json, &config, &NodeBuildScriptsMetadata::default())
+                .unwrap();
+        assert_eq!(
+            summary(&scripts),
+            [
+                (
+                    "build",
+                    false,
+                    "it was replaced by `build:prod` from `scripts.build` in project.toml"
+                ),
+                (
+                    "build:prod",
+                    true,
+                    "set with `scripts.build` in project.toml"
+                ),
+                ("docs", true, "listed in `scripts.extra` in project.toml"),
+                ("heroku-postbuild", true, "defined in package.json"),
+            ]
+        );
+
+        let config = BuildScriptsConfig {
+            build: Some(Setting {
+                value: "build:staging".to_string(),
+                source: ConfigSource::EnvVar("NODEJS_BUILD_SCRIPT"),
+            }),
+            ..config
+        };
+        assert_eq!(
+            resolve_build_scripts(&package_json, &config, &NodeBuildScriptsMetadata::default())
+                .unwrap_err()
+                .to_string(),
+            "The `build:staging` script set with `NODEJS_BUILD_SCRIPT` isn't defined in package.json"
+        );
+    }
+
+    #[test]
+    fn disabled_build_scripts() {
+        let package_json = package_json(r#"{ "build": "", "heroku-cleanup": "" }"#);
+        let config = BuildScriptsConfig {
+            enabled: Setting {
+                value: false,
+                source: ConfigSource::EnvVar(BUILD_SCRIPTS_ENV_VAR),
+            },
+            ..config()
+        };
+        let scripts =
+            resolve_build_scripts(&package_json, &config, &NodeBuildScriptsMetadata::default())
+                .unwrap();
+        assert_eq!(
+            summary(&scripts),
+            [
+                ("build", false, "`NODEJS_BUILD_SCRIPTS` is set to `false`"),
+                (
+                    "heroku-cleanup",
+                    false,
+                    "`NODEJS_BUILD_SCRIPTS` is set to `false`"
+                ),
+            ]
+        );
+
+        let scripts = resolve_build_scripts(
+            &package_json,
+            &config,
+            &NodeBuildScriptsMetadata {
+                enabled: Some(false),
+                ..NodeBuildScriptsMetadata::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            scripts[0].skipped_message(),
+            "Not running `build` as it was disabled by a participating buildpack"
+        );
+    }
+
+    #[test]
+    fn build_scripts_from_buildplan_metadata() {
+        let package_json = package_json(
+            r#"{ "heroku-prebuild": "", "build": "", "docs": "", "generate": "", "lint": "", "heroku-postbuild": "" }"#,
+        );
+        let config = BuildScriptsConfig {
+            extra: vec!["docs".to_string()],
+            ..config()
+        };
+        let added = |script: &str, position| AddedBuildScript {
+            script: script.to_string(),
+            position,
+        };
+        let metadata = NodeBuildScriptsMetadata {
+            disable: vec!["heroku-postbuild".to_string(), "lint".to_string()],
+            add: vec![
+                added("lint", ScriptPosition::After(ScriptAnchor::Build)),
+                added("generate", ScriptPosition::Before(ScriptAnchor::Build)),
+                added("seed", ScriptPosition::After(ScriptAnchor::HerokuCleanup)),
+                added("docs", ScriptPosition::Before(ScriptAnchor::HerokuPrebuild)),
+            ],
+            ..NodeBuildScriptsMetadata::default()
+        };
+        let scripts = resolve_build_scripts(&package_json, &config, &metadata).unwrap();
+        assert_eq!(
+            summary(&scripts),
+            [
+                ("docs", false, "it's already one of the build scripts"),
+                ("heroku-prebuild", true, "defined in package.json"),
+                ("generate", true, "added by a participating buildpack"),
+                ("build", true, "defined in package.json"),
+                ("docs", true, "listed in `scripts.extra` in project.toml"),
+                (
+                    "lint",
+                    false,
+                    "it was disabled by a participating buildpack"
+                ),
+                (
+                    "heroku-postbuild",
+                    false,
+                    "it was disabled by a participating buildpack"
+                ),
+                ("seed", false, "it isn't defined in package.json"),
+            ]
+        );
+    }
+}
@@ -1,12 +1,107 @@
+use crate::vrs::{Requirement, VersionError};
 use libcnb_data::buildpack_plan::BuildpackPlan;
+use std::collections::BTreeMap;
+use std::fmt;
 
+/// The `node_build_scripts` build plan metadata, merged from every participating
+/// buildpack that requires it, e.g.:
+///
+/// ```toml
+/// [[requires]]
+/// name = "node_build_scripts"
+///
+/// [requires.metadata]
+/// enabled = true
+/// disable = ["heroku-postbuild"]
+///
+/// [requires.metadata.env]
+/// ASSETS_ENV = "production"
+///
+/// [[requires.metadata.add]]
+/// script = "compile-assets"
+/// after = "build"
+/// ```
+///
+/// Entries are merged in build plan order: the last `enabled` value wins,
+/// `disable` lists are combined, later `env` values override earlier ones, and
+/// a script added more than once keeps the position from the last entry.
 #[derive(Debug, Default, PartialEq)]
 pub struct NodeBuildScriptsMetadata {
     pub enabled: Option<bool>,
+    pub disable: Vec<String>,
+    pub add: Vec<AddedBuildScript>,
+    pub env: BTreeMap<String, String>,
+}
+
+impl NodeBuildScriptsMetadata {
+    #[must_use]
+    pub fn is_disabled(&self, script: &str) -> bool {
+        self.disable.iter().any(|name| name == script)
+    }
+}
+
+/// A script a participating buildpack asked to run, and where it runs relative
+/// to the default build scripts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddedBuildScript {
+    pub script: String,
+    pub position: ScriptPosition,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptPosition {
+    Before(ScriptAnchor),
+    After(ScriptAnchor),
+}
+
+impl Default for ScriptPosition {
+    fn default() -> Self {
+        ScriptPosition::After(ScriptAnchor::Build)
+    }
+}
+
+/// The default build scripts that added scripts can be ordered against. `Build`
+/// stands for whichever build script runs: the configured one, `heroku-build`,
+/// or `build`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptAnchor {
+    HerokuPrebuild,
+    Build,
+    HerokuPostbuild,
+    HerokuCleanup,
+}
+
+impl ScriptAnchor {
+    const ALL: [ScriptAnchor; 4] = [
+        ScriptAnchor::HerokuPrebuild,
+        ScriptAnchor::Build,
+        ScriptAnchor::HerokuPostbuild,
+        ScriptAnchor::HerokuCleanup,
+    ];
+
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            ScriptAnchor::HerokuPrebuild => "heroku-prebuild",
+            ScriptAnchor::Build => "build",
+            ScriptAnchor::HerokuPostbuild => "heroku-postbuild",
+            ScriptAnchor::HerokuCleanup => "heroku-cleanup",
+        }
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|anchor| anchor.name() == name)
+    }
 }
 
 pub const NODE_BUILD_SCRIPTS_BUILD_PLAN_NAME: &str = "node_build_scripts";
 const NODE_BUILD_SCRIPTS_METADATA_ENABLED_KEY: &str = "enabled";
+const NODE_BUILD_SCRIPTS_METADATA_DISABLE_KEY: &str = "disable";
+const NODE_BUILD_SCRIPTS_METADATA_ENV_KEY: &str = "env";
+const NODE_BUILD_SCRIPTS_METADATA_ADD_KEY: &str = "add";
+const ADD_SCRIPT_KEY: &str = "script";
+const ADD_BEFORE_KEY: &str = "before";
+const ADD_AFTER_KEY: &str = "after";
 
 pub fn read_node_build_scripts_metadata(
     buildpack_plan: &BuildpackPlan,
@@ -29,14 +124,226 @@ pub fn read_node_build_scripts_metadata(
                     }
                     None => {}
                 }
+                if let Some(value) = entry.metadata.get(NODE_BUILD_SCRIPTS_METADATA_DISABLE_KEY) {
+                    for script in read_disable(value)? {
+                        if !node_build_hooks_metadata.is_disabled(&script) {
+                            node_build_hooks_metadata.disable.push(script);
+                        }
+                    }
+                }
+                if let Some(value) = entry.metadata.get(NODE_BUILD_SCRIPTS_METADATA_ENV_KEY) {
+                    node_build_hooks_metadata.env.extend(read_env(value)?);
+                }
+                if let Some(value) = entry.metadata.get(NODE_BUILD_SCRIPTS_METADATA_ADD_KEY) {
+                    for added in read_add(value)? {
+                        node_build_hooks_metadata
+                            .add
+                            .retain(|existing| existing.script != added.script);
+                        node_build_hooks_metadata.add.push(added);
+                    }
+                }
                 Ok(node_build_hooks_metadata)
             },
         )
 }
 
+fn read_disable(value: &toml::Value) -> Result<Vec<String>, NodeBuildScriptsMetadataError> {
+    let invalid = || NodeBuildScriptsMetadataError::InvalidDisableValue(value.clone());
+    value
+        .as_array()
+        .ok_or_else(invalid)?
+        .iter()
+        .map(|script| match script.as_str() {
+            Some(script) if !script.trim().is_empty() => Ok(script.to_string()),
+            _ => Err(invalid()),
+        })
+        .collect()
+}
+
+fn read_env(
+    value: &toml::Value,
+) -> Result<BTreeMap<String, String>, NodeBuildScriptsMetadataError> {
+    value
+        .as_table()
+        .ok_or_else(|| NodeBuildScriptsMetadataError::InvalidEnvValue(value.clone()))?
+        .iter()
+        .map(|(name, value)| {
+            if name.is_empty() || name.contains('=') {
+                Err(NodeBuildScriptsMetadataError::InvalidEnvName(name.clone()))
+            } else if let toml::Value::String(value) = value {
+                Ok((name.clone(), value.clone()))
+            } else {
+                Err(NodeBuildScriptsMetadataError::InvalidEnvVarValue {
+                    name: name.clone(),
+                    value: value.clone(),
+                })
+            }
+        })
+        .collect()
+}
+
+fn read_add(value: &toml::Value) -> Result<Vec<AddedBuildScript>, NodeBuildScriptsMetadataError> {
+    let entries = value
+        .as_array()
+        .filter(|entries| entries.iter().all(toml::Value::is_table))
+        .ok_or_else(|| NodeBuildScriptsMetadataError::InvalidAddValue(value.clone()))?;
+    entries
+        .iter()
+        .filter_map(toml::Value::as_table)
+        .map(|entry| {
+            let script = match entry.get(ADD_SCRIPT_KEY) {
+                Some(toml::Value::String(script)) if !script.trim().is_empty() => script.clone(),
+                value => Err(NodeBuildScriptsMetadataError::InvalidAddScript(
+                    value.cloned(),
+                ))?,
+            };
+            let anchor = |key: &'static str| {
+                entry
+                    .get(key)
+                    .map(|value| {
+                        value.as_str().and_then(ScriptAnchor::parse).ok_or_else(|| {
+                            NodeBuildScriptsMetadataError::InvalidAddPosition {
+                                script: script.clone(),
+                                key,
+                                value: value.clone(),
+                            }
+                        })
+                    })
+                    .transpose()
+            };
+            let position = match (anchor(ADD_BEFORE_KEY)?, anchor(ADD_AFTER_KEY)?) {
+                (Some(_), Some(_)) => Err(NodeBuildScriptsMetadataError::ConflictingAddPosition(
+                    script.clone(),
+                ))?,
+                (Some(before), None) => ScriptPosition::Before(before),
+                (None, Some(after)) => ScriptPosition::After(after),
+                (None, None) => ScriptPosition::default(),
+            };
+            Ok(AddedBuildScript { script, position })
+        })
+        .collect()
+}
+
+/// Errors from reading `node_build_scripts` metadata. They display as the
+/// expected metadata format followed by what a participating buildpack set.
 #[derive(Debug)]
 pub enum NodeBuildScriptsMetadataError {
     InvalidEnabledValue(toml::Value),
+    InvalidDisableValue(toml::Value),
+    InvalidEnvValue(toml::Value),
+    InvalidEnvName(String),
+    InvalidEnvVarValue {
+        name: String,
+        value: toml::Value,
+    },
+    InvalidAddValue(toml::Value),
+    InvalidAddScript(Option<toml::Value>),
+    InvalidAddPosition {
+        script: String,
+        key: &'static str,
+        value: toml::Value,
+    },
+    ConflictingAddPosition(String),
+}
+
+impl fmt::Display for NodeBuildScriptsMetadataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let anchors = ScriptAnchor::ALL
+            .map(|anchor| format!("\"{}\"", anchor.name()))
+            .join(" | ");
+        let (expected, actual) = match self {
+            NodeBuildScriptsMetadataError::InvalidEnabledValue(value) => (
+                "[requires.metadata]\nenabled = <bool>".to_string(),
+                format!("[requires.metadata]\nenabled = {}", describe(value)),
+            ),
+            NodeBuildScriptsMetadataError::InvalidDisableValue(value) => (
+                "[requires.metadata]\ndisable = [<string>, ...]".to_string(),
+                format!("[requires.metadata]\ndisable = {}", describe(value)),
+            ),
+            NodeBuildScriptsMetadataError::InvalidEnvValue(value) => (
+                "[requires.metadata.env]\n<name> = <string>".to_string(),
+                format!("[requires.metadata]\nenv = {}", describe(value)),
+            ),
+            NodeBuildScriptsMetadataError::InvalidEnvName(name) => (
+                "[requires.metadata.env]\n<name without `=`> = <string>".to_string(),
+                format!("[requires.metadata.env]\n\"{name}\" = <string>"),
+            ),
+            NodeBuildScriptsMetadataError::InvalidEnvVarValue { name, value } => (
+                format!("[requires.metadata.env]\n{name} = <string>"),
+                format!("[requires.metadata.env]\n{name} = {}", describe(value)),
+            ),
+            NodeBuildScriptsMetadataError::InvalidAddValue(value) => (
+                "[[requires.metadata.add]]\nscript = <string>".to_string(),
+                format!("[requires.metadata]\nadd = {}", describe(value)),
+            ),
+            NodeBuildScriptsMetadataError::InvalidAddScript(value) => (
+                "[[requires.metadata.add]]\nscript = <string>".to_string(),
+                match value {
+                    Some(value) => format!("[[requires.metadata.add]]\nscript = {}", describe(value)),
+                    None => "[[requires.metadata.add]]\n# no `script` key".to_string(),
+                },
+            ),
+            NodeBuildScriptsMetadataError::InvalidAddPosition { script, key, value } => (
+                format!("[[requires.metadata.add]]\nscript = \"{script}\"\n{key} = {anchors}"),
+                format!("[[requires.metadata.add]]\nscript = \"{script}\"\n{key} = {value}"),
+            ),
+            NodeBuildScriptsMetadataError::ConflictingAddPosition(script) => (
+                format!("[[requires.metadata.add]]\nscript = \"{script}\"\nbefore = {anchors}\n# or\nafter = {anchors}"),
+                format!("[[requires.metadata.add]]\nscript = \"{script}\"\nbefore = ...\nafter = ..."),
+            ),
+        };
+        write!(
+            f,
+            "Expected metadata format:\n{expected}\n\nBut was:\n{actual}"
+        )
+    }
+}
+
+/// Describes a TOML value by its type, like `<integer>` or `[<string>, <bool>]`.
+fn describe(value: &toml::Value) -> String {
+    match value {
+        toml::Value::Array(values) => format!(
+            "[{}]",
+            values.iter().map(describe).collect::<Vec<_>>().join(", ")
+        ),
+        toml::Value::String(value) if value.trim().is_empty() => format!("\"{value}\""),
+        value => format!("<{}>", value.type_str()),
+    }
+}
+
+pub const NODE_BUILD_PLAN_NAME: &str = "node";
+const NODE_METADATA_VERSION_KEY: &str = "version";
+
+/// Reads the Node.js version requirements declared by buildpacks that require
+/// `node` with `version` metadata, e.g.:
+///
+/// ```toml
+/// [[requires]]
+/// name = "node"
+///
+/// [requires.metadata]
+/// version = ">=20"
+/// ```
+pub fn read_node_version_requirements(
+    buildpack_plan: &BuildpackPlan,
+) -> Result<Vec<Requirement>, NodeVersionMetadataError> {
+    buildpack_plan
+        .entries
+        .iter()
+        .filter(|entry| entry.name == NODE_BUILD_PLAN_NAME)
+        .filter_map(|entry| entry.metadata.get(NODE_METADATA_VERSION_KEY))
+        .map(|value| match value {
+            toml::Value::String(version) => Requirement::parse(version)
+                .map_err(|e| NodeVersionMetadataError::InvalidVersion(version.clone(), e)),
+            _ => Err(NodeVersionMetadataError::InvalidVersionValue(value.clone())),
+        })
+        .collect()
+}
+
+#[derive(Debug)]
+pub enum NodeVersionMetadataError {
+    InvalidVersionValue(toml::Value),
+    InvalidVersion(String, VersionError),
 }
 
 #[cfg(test)]
@@ -81,7 +388,8 @@ mod test {
         assert_eq!(
             read_node_build_scripts_metadata(&buildpack_plan).unwrap(),
             NodeBuildScriptsMetadata {
-                enabled: Some(false)
+                enabled: Some(false),
+                ..NodeBuildScriptsMetadata::default()
             }
         );
     }
@@ -111,7 +419,8 @@ mod test {
         assert_eq!(
             read_node_build_scripts_metadata(&buildpack_plan).unwrap(),
             NodeBuildScriptsMetadata {
-                enabled: Some(true)
+                enabled: Some(true),
+                ..NodeBuildScriptsMetadata::default()
             }
         );
     }
@@ -126,8 +435,164 @@ mod test {
                 },
             }],
         };
-        match read_node_build_scripts_metadata(&buildpack_plan).unwrap_err() {
-            NodeBuildScriptsMetadataError::InvalidEnabledValue(_) => {}
-        }
+        let error = read_node_build_scripts_metadata(&buildpack_plan).unwrap_err();
+        assert!(matches!(
+            error,
+            NodeBuildScriptsMetadataError::InvalidEnabledValue(_)
+        ));
+        assert_eq!(
+            error.to_string(),
+            "Expected metadata format:\n[requires.metadata]\nenabled = <bool>\n\nBut was:\n[requires.metadata]\nenabled = <integer>"
+        );
+    }
+
+    #[test]
+    fn read_node_build_scripts_with_disabled_added_scripts_and_env() {
+        let buildpack_plan = BuildpackPlan {
+            entries: vec![
+                Entry {
+                    name: NODE_BUILD_SCRIPTS_BUILD_PLAN_NAME.to_string(),
+                    metadata: toml! {
+                        disable = ["heroku-postbuild"]
+                        add = [
+                            { script = "compile-assets" },
+                            { script = "generate", before = "build" },
+                        ]
+                        env = { ASSETS_ENV = "staging", SHARED = "first" }
+                    },
+                },
+                Entry {
+                    name: NODE_BUILD_SCRIPTS_BUILD_PLAN_NAME.to_string(),
+                    metadata: toml! {
+                        disable = ["heroku-cleanup", "heroku-postbuild"]
+                        add = [{ script = "compile-assets", after = "heroku-cleanup" }]
+                        env = { ASSETS_ENV = "production" }
+                    },
+                },
+            ],
+        };
+        assert_eq!(
+            read_node_build_scripts_metadata(&buildpack_plan).unwrap(),
+            NodeBuildScriptsMetadata {
+                enabled: None,
+                disable: vec!["heroku-postbuild".to_string(), "heroku-cleanup".to_string()],
+                add: vec![
+                    AddedBuildScript {
+                        script: "generate".to_string(),
+                        position: ScriptPosition::Before(ScriptAnchor::Build),
+                    },
+                    AddedBuildScript {
+                        script: "compile-assets".to_string(),
+                        position: ScriptPosition::After(ScriptAnchor::HerokuCleanup),
+                    },
+                ],
+                env: BTreeMap::from([
+                    ("ASSETS_ENV".to_string(), "production".to_string()),
+                    ("SHARED".to_string(), "first".to_string()),
+                ]),
+            }
+        );
+    }
+
+    #[test]
+    fn read_node_build_scripts_when_entry_contains_invalid_script_metadata() {
+        let error = |metadata: Table| {
+            read_node_build_scripts_metadata(&BuildpackPlan {
+                entries: vec![Entry {
+                    name: NODE_BUILD_SCRIPTS_BUILD_PLAN_NAME.to_string(),
+                    metadata,
+                }],
+            })
+            .unwrap_err()
+            .to_string()
+        };
+        assert_eq!(
+            error(toml! { disable = ["build", 1] }),
+            "Expected metadata format:\n[requires.metadata]\ndisable = [<string>, ...]\n\nBut was:\n[requires.metadata]\ndisable = [<string>, <integer>]"
+        );
+        assert_eq!(
+            error(toml! { env = { NODE_OPTIONS = true } }),
+            "Expected metadata format:\n[requires.metadata.env]\nNODE_OPTIONS = <string>\n\nBut was:\n[requires.metadata.env]\nNODE_OPTIONS = <boolean>"
+        );
+        assert_eq!(
+            error(toml! { add = ["docs"] }),
+            "Expected metadata format:\n[[requires.metadata.add]]\nscript = <string>\n\nBut was:\n[requires.metadata]\nadd = [<string>]"
+        );
+        assert_eq!(
+            error(toml! { add = [{ before = "build" }] }),
+            "Expected metadata format:\n[[requires.metadata.add]]\nscript = <string>\n\nBut was:\n[[requires.metadata.add]]\n# no `script` key"
+        );
+        assert_eq!(
+            error(toml! { add = [{ script = "docs", after = "postbuild" }] }),
+            "Expected metadata format:\n[[requires.metadata.add]]\nscript = \"docs\"\nafter = \"heroku-prebuild\" | \"build\" | \"heroku-postbuild\" | \"heroku-cleanup\"\n\nBut was:\n[[requires.metadata.add]]\nscript = \"docs\"\nafter = \"postbuild\""
+        );
+        assert!(matches!(
+            read_node_build_scripts_metadata(&BuildpackPlan {
+                entries: vec![Entry {
+                    name: NODE_BUILD_SCRIPTS_BUILD_PLAN_NAME.to_string(),
+                    metadata: toml! {
+                        add = [{ script = "docs", before = "build", after = "build" }]
+                    },
+                }],
+            })
+            .unwrap_err(),
+            NodeBuildScriptsMetadataError::ConflictingAddPosition(script) if script == "docs"
+        ));
+    }
+
+    #[test]
+    fn read_node_version_requirements_from_entries_with_version_metadata() {
+        let buildpack_plan = BuildpackPlan {
+            entries: vec![
+                Entry {
+                    name: NODE_BUILD_PLAN_NAME.to_string(),
+                    metadata: Table::new(),
+                },
+                Entry {
+                    name: NODE_BUILD_PLAN_NAME.to_string(),
+                    metadata: toml! {
+                        version = ">=20"
+                    },
+                },
+                Entry {
+                    name: NODE_BUILD_SCRIPTS_BUILD_PLAN_NAME.to_string(),
+                    metadata: toml! {
+                        version = "18.x"
+                    },
+                },
+            ],
+        };
+        let requirements = read_node_version_requirements(&buildpack_plan).unwrap();
+        assert_eq!(requirements.len(), 1);
+        assert_eq!(requirements[0].to_string(), ">=20.0.0");
+    }
+
+    #[test]
+    fn read_node_version_requirements_when_entry_contains_invalid_metadata() {
+        let buildpack_plan = BuildpackPlan {
+            entries: vec![Entry {
+                name: NODE_BUILD_PLAN_NAME.to_string(),
+                metadata: toml! {
+                    version = 20
+                },
+            }],
+        };
+        assert!(matches!(
+            read_node_version_requirements(&buildpack_plan).unwrap_err(),
+            NodeVersionMetadataError::InvalidVersionValue(_)
+        ));
+
+        let buildpack_plan = BuildpackPlan {
+            entries: vec![Entry {
+                name: NODE_BUILD_PLAN_NAME.to_string(),
+                metadata: toml! {
+                    version = "twenty"
+                },
+            }],
+        };
+        assert!(matches!(
+            read_node_version_requirements(&buildpack_plan).unwrap_err(),
+            NodeVersionMetadataError::InvalidVersion(..)
+        ));
     }
 }
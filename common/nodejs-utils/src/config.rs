@@ -0,0 +1,1093 @@
+use crate::licenses::LicensePolicy;
+use crate::procfile::{Procfile, ProcfileError, ProcfileProcess};
+use crate::vulnerabilities::Severity;
+use libcnb::data::launch::{ProcessType, WorkingDirectory};
+use libcnb::Env;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use std::path::{Component, Path, PathBuf};
+use thiserror::Error;
+use toml::Spanned;
+
+/// The file the configuration is read from, in the app directory.
+pub const PROJECT_TOML: &str = "project.toml";
+
+/// The table in `project.toml` that holds the configuration.
+pub const CONFIG_TABLE: &str = "com.heroku.buildpacks.nodejs";
+
+/// Overrides `app_root`.
+pub const APP_ROOT_ENV_VAR: &str = "NODEJS_APP_ROOT";
+
+/// Overrides `runtime_metrics` with `true` or `false`.
+pub const RUNTIME_METRICS_ENV_VAR: &str = "NODEJS_RUNTIME_METRICS";
+
+/// Overrides `cache` with `true` or `false`.
+pub const CACHE_ENV_VAR: &str = "NODEJS_CACHE";
+
+/// Overrides `prune`. Setting it to `true` keeps dev dependencies.
+pub const SKIP_PRUNING_ENV_VAR: &str = "NODEJS_SKIP_PRUNING";
+
+/// Overrides `npm.workspace`.
+pub const NPM_WORKSPACE_ENV_VAR: &str = "NODEJS_NPM_WORKSPACE";
+
+/// Overrides `scripts.enabled` with `true` or `false`.
+pub const BUILD_SCRIPTS_ENV_VAR: &str = "NODEJS_BUILD_SCRIPTS";
+
+/// Overrides `scripts.build`.
+pub const BUILD_SCRIPT_ENV_VAR: &str = "NODEJS_BUILD_SCRIPT";
+
+/// Overrides `sbom.spdx` with `true` or `false`.
+pub const SBOM_SPDX_ENV_VAR: &str = "NODEJS_SBOM_SPDX";
+
+/// Overrides `vulnerabilities.advisories`.
+pub const ADVISORIES_ENV_VAR: &str = "NODEJS_ADVISORIES";
+
+/// Overrides `vulnerabilities.fail_on` with `low`, `moderate`, `high`, or `critical`.
+pub const VULNERABILITIES_FAIL_ON_ENV_VAR: &str = "NODEJS_VULNERABILITIES_FAIL_ON";
+
+const KNOWN_KEYS: [&str; 10] = [
+    "app_root",
+    "cache",
+    "licenses",
+    "npm",
+    "processes",
+    "prune",
+    "runtime_metrics",
+    "sbom",
+    "scripts",
+    "vulnerabilities",
+];
+const KNOWN_TABLE_KEYS: [(&str, &[&str]); 5] = [
+    ("licenses", &["deny", "exceptions"]),
+    ("npm", &["workspace"]),
+    ("sbom", &["spdx"]),
+    ("scripts", &["build", "enabled", "extra"]),
+    ("vulnerabilities", &["advisories", "fail_on"]),
+];
+
+/// The configuration shared by the Node.js buildpacks, read from the
+/// `[com.heroku.buildpacks.nodejs]` table in `project.toml`:
+///
+/// ```toml
+/// [com.heroku.buildpacks.nodejs]
+/// app_root = "apps/web"
+/// runtime_metrics = false
+/// cache = false
+/// prune = false
+///
+/// [com.heroku.buildpacks.nodejs.npm]
+/// workspace = "@acme/web"
+///
+/// [com.heroku.buildpacks.nodejs.scripts]
+/// build = "build:prod"
+/// extra = ["docs"]
+///
+/// [com.heroku.buildpacks.nodejs.processes]
+/// web = "node server.js"
+///
+/// [com.heroku.buildpacks.nodejs.licenses]
+/// deny = ["GPL-*", "AGPL-3.0-only"]
+/// exceptions = ["readline-sync@1.4.10"]
+///
+/// [com.heroku.buildpacks.nodejs.sbom]
+/// spdx = true
+///
+/// [com.heroku.buildpacks.nodejs.vulnerabilities]
+/// advisories = "vendor/advisories"
+/// fail_on = "high"
+/// ```
+///
+/// Unset values fall back to their defaults. Use the resolving methods, like
+/// [`NodejsConfig::prune`], to apply the environment variable overrides.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NodejsConfig {
+    /// The directory with the app's `package.json`, relative to the app directory.
+    pub app_root: Option<PathBuf>,
+    /// Whether the Node.js runtime metrics script is installed.
+    pub runtime_metrics: Option<bool>,
+    /// Whether dependency caches from previous builds are restored.
+    pub cache: Option<bool>,
+    /// Whether dev dependencies are pruned after the build scripts.
+    pub prune: Option<bool>,
+    /// The npm workspace to install, build, and start.
+    pub npm_workspace: Option<String>,
+    /// Whether the build scripts are run.
+    pub build_scripts: Option<bool>,
+    /// The script that replaces `heroku-build` or `build`.
+    pub build_script: Option<String>,
+    /// Scripts that run after the build script.
+    pub extra_scripts: Vec<String>,
+    /// Processes that replace the default web process when there's no `Procfile`.
+    pub processes: Vec<ProcfileProcess>,
+    /// The licenses production dependencies may not use.
+    pub licenses: LicensePolicy,
+    /// Whether an SPDX SBOM is generated along with the CycloneDX one.
+    pub sbom_spdx: Option<bool>,
+    /// The OSV advisory directory, relative to the app directory.
+    pub advisories: Option<String>,
+    /// The lowest vulnerability severity that fails the build.
+    pub vulnerabilities_fail_on: Option<Severity>,
+    /// Keys in the table that aren't part of the configuration.
+    pub unknown_keys: Vec<String>,
+}
+
+/// The directory with the app's `package.json`, lockfile, and sources, for apps
+/// that live in a subdirectory of their repository. `project.toml`, the
+/// `Procfile`, and service bindings are still read from the app directory.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AppRoot(PathBuf);
+
+impl AppRoot {
+    /// The path of the app root in `app_dir`.
+    #[must_use]
+    pub fn dir(&self, app_dir: &Path) -> PathBuf {
+        if self.is_app_dir() {
+            app_dir.to_path_buf()
+        } else {
+            app_dir.join(&self.0)
+        }
+    }
+
+    /// Makes the app root the working directory, so the commands run during
+    /// the build, like the package manager, run in it. Returns its path.
+    ///
+    /// # Errors
+    ///
+    /// Will return a `ConfigError` if the app root isn't a directory.
+    pub fn enter(&self, app_dir: &Path) -> Result<PathBuf, ConfigError> {
+        let dir = self.dir(app_dir);
+        std::env::set_current_dir(&dir).map_err(|e| ConfigError::AppRoot(self.to_string(), e))?;
+        Ok(dir)
+    }
+
+    /// Whether the app root is the app directory itself.
+    #[must_use]
+    pub fn is_app_dir(&self) -> bool {
+        self.0.as_os_str().is_empty()
+    }
+
+    /// The working directory for launch processes, so they start in the app root.
+    #[must_use]
+    pub fn working_directory(&self) -> WorkingDirectory {
+        if self.is_app_dir() {
+            WorkingDirectory::App
+        } else {
+            WorkingDirectory::Directory(self.0.clone())
+        }
+    }
+}
+
+impl Display for AppRoot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_app_dir() {
+            write!(f, ".")
+        } else {
+            write!(f, "{}", self.0.display())
+        }
+    }
+}
+
+/// Where a resolved setting came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigSource {
+    Default,
+    ProjectToml,
+    EnvVar(&'static str),
+}
+
+impl Display for ConfigSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigSource::Default => write!(f, "default"),
+            ConfigSource::ProjectToml => write!(f, "{PROJECT_TOML}"),
+            ConfigSource::EnvVar(name) => write!(f, "{name}"),
+        }
+    }
+}
+
+/// The resolved settings for the `package.json` scripts run during the build.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildScriptsConfig {
+    pub enabled: Setting<bool>,
+    pub build: Option<Setting<String>>,
+    pub extra: Vec<String>,
+}
+
+/// A resolved setting and where its value came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Setting<T> {
+    pub value: T,
+    pub source: ConfigSource,
+}
+
+impl Setting<bool> {
+    /// Explains how the setting got its value, like "`prune` is set to `false`
+    /// in project.toml", for the build log.
+    #[must_use]
+    pub fn reason(&self, key: &str) -> String {
+        match self.source {
+            ConfigSource::Default => format!("`{key}` defaults to `{}`", self.value),
+            ConfigSource::ProjectToml => {
+                format!("`{key}` is set to `{}` in {PROJECT_TOML}", self.value)
+            }
+            ConfigSource::EnvVar(name) => format!(
+                "`{name}` is set to `{}`",
+                if name == SKIP_PRUNING_ENV_VAR {
+                    !self.value
+                } else {
+                    self.value
+                }
+            ),
+        }
+    }
+}
+
+impl NodejsConfig {
+    /// Reads the configuration from `project.toml` in the app directory. A
+    /// missing file or table is an empty configuration.
+    ///
+    /// # Errors
+    ///
+    /// Will return a `ConfigError` with the line and column of the problem if
+    /// `project.toml` isn't valid TOML or has an invalid value.
+    pub fn read(app_dir: &Path) -> Result<Self, ConfigError> {
+        let path = app_dir.join(PROJECT_TOML);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        std::fs::read_to_string(path)
+            .map_err(ConfigError::Read)
+            .and_then(|contents| Self::parse(&contents))
+    }
+
+    /// Parses the configuration from the contents of a `project.toml` file.
+    ///
+    /// # Errors
+    ///
+    /// Will return a `ConfigError` with the line and column of the problem if
+    /// the contents aren't valid TOML or have an invalid value.
+    pub fn parse(contents: &str) -> Result<Self, ConfigError> {
+        let project_toml: ProjectToml = toml::from_str(contents).map_err(ConfigError::Parse)?;
+        let Some(raw) = project_toml.com.heroku.buildpacks.nodejs else {
+            return Ok(Self::default());
+        };
+
+        let invalid = |key: &str, span: Range<usize>, message: &str| {
+            invalid_value(contents, key, span, message)
+        };
+        // Trims each value in an array, failing on empty values.
+        let non_empty = |key: &str, values: Option<Vec<Spanned<String>>>, message: &str| {
+            values
+                .unwrap_or_default()
+                .into_iter()
+                .map(|value| {
+                    if value.get_ref().trim().is_empty() {
+                        Err(invalid(key, value.span(), message))
+                    } else {
+                        Ok(value.into_inner().trim().to_string())
+                    }
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        let npm_workspace = match raw.npm.and_then(|npm| npm.workspace) {
+            Some(workspace) if workspace.get_ref().trim().is_empty() => {
+                return Err(invalid(
+                    "npm.workspace",
+                    workspace.span(),
+                    "expected a workspace name or path",
+                ))
+            }
+            workspace => workspace.map(|workspace| workspace.into_inner().trim().to_string()),
+        };
+
+        let scripts = raw.scripts.unwrap_or_default();
+        let build_script = match scripts.build {
+            Some(build) if build.get_ref().trim().is_empty() => {
+                return Err(invalid(
+                    "scripts.build",
+                    build.span(),
+                    "expected a script name",
+                ))
+            }
+            build => build.map(|build| build.into_inner().trim().to_string()),
+        };
+        let extra_scripts = non_empty("scripts.extra", scripts.extra, "expected a script name")?;
+
+        let licenses = raw.licenses.unwrap_or_default();
+        let licenses = LicensePolicy {
+            deny: non_empty(
+                "licenses.deny",
+                licenses.deny,
+                "expected an SPDX license identifier",
+            )?,
+            exceptions: non_empty(
+                "licenses.exceptions",
+                licenses.exceptions,
+                "expected a package name",
+            )?,
+        };
+
+        let vulnerabilities = raw.vulnerabilities.unwrap_or_default();
+        let advisories = match vulnerabilities.advisories {
+            Some(dir) if dir.get_ref().trim().is_empty() => {
+                return Err(invalid(
+                    "vulnerabilities.advisories",
+                    dir.span(),
+                    "expected a directory path",
+                ))
+            }
+            dir => dir.map(|dir| dir.into_inner().trim().to_string()),
+        };
+        let vulnerabilities_fail_on = vulnerabilities
+            .fail_on
+            .map(|fail_on| {
+                fail_on.get_ref().parse().map_err(|()| {
+                    invalid(
+                        "vulnerabilities.fail_on",
+                        fail_on.span(),
+                        "expected `low`, `moderate`, `high`, or `critical`",
+                    )
+                })
+            })
+            .transpose()?;
+
+        let processes = parse_processes(contents, raw.processes)?;
+
+        Ok(NodejsConfig {
+            app_root: parse_app_root(contents, raw.app_root)?,
+            runtime_metrics: raw.runtime_metrics,
+            cache: raw.cache,
+            prune: raw.prune,
+            npm_workspace,
+            build_scripts: scripts.enabled,
+            build_script,
+            extra_scripts,
+            processes,
+            licenses,
+            sbom_spdx: raw.sbom.and_then(|sbom| sbom.spdx),
+            advisories,
+            vulnerabilities_fail_on,
+            unknown_keys: unknown_keys(contents)?,
+        })
+    }
+
+    /// Warnings about the configuration, like unknown keys, for the build log.
+    #[must_use]
+    pub fn warnings(&self) -> Vec<String> {
+        self.unknown_keys
+            .iter()
+            .map(|key| format!("Ignoring unknown key `{key}` in {PROJECT_TOML}"))
+            .collect()
+    }
+
+    /// The directory with the app's `package.json`. Defaults to the app
+    /// directory, and `NODEJS_APP_ROOT` overrides `app_root`.
+    ///
+    /// # Errors
+    ///
+    /// Will return a `ConfigError` if `NODEJS_APP_ROOT` isn't a relative path.
+    pub fn app_root(&self, env: &Env) -> Result<AppRoot, ConfigError> {
+        if let Some(value) = env.get_string_lossy(APP_ROOT_ENV_VAR) {
+            return relative_dir(&value)
+                .map(AppRoot)
+                .ok_or(ConfigError::InvalidEnvVar {
+                    name: APP_ROOT_ENV_VAR.to_string(),
+                    value,
+                    expected: "a relative path to a directory in the app",
+                });
+        }
+        Ok(AppRoot(self.app_root.clone().unwrap_or_default()))
+    }
+
+    /// Whether to install the runtime metrics script. Defaults to `true`.
+    ///
+    /// # Errors
+    ///
+    /// Will return a `ConfigError` if `NODEJS_RUNTIME_METRICS` isn't `true` or `false`.
+    pub fn runtime_metrics(&self, env: &Env) -> Result<Setting<bool>, ConfigError> {
+        resolve_bool(
+            env,
+            RUNTIME_METRICS_ENV_VAR,
+            false,
+            self.runtime_metrics,
+            true,
+        )
+    }
+
+    /// Whether to restore dependency caches from previous builds. Defaults to `true`.
+    ///
+    /// # Errors
+    ///
+    /// Will return a `ConfigError` if `NODEJS_CACHE` isn't `true` or `false`.
+    pub fn cache(&self, env: &Env) -> Result<Setting<bool>, ConfigError> {
+        resolve_bool(env, CACHE_ENV_VAR, false, self.cache, true)
+    }
+
+    /// Whether to prune dev dependencies. Defaults to `true`.
+    ///
+    /// # Errors
+    ///
+    /// Will return a `ConfigError` if `NODEJS_SKIP_PRUNING` isn't `true` or `false`.
+    pub fn prune(&self, env: &Env) -> Result<Setting<bool>, ConfigError> {
+        resolve_bool(env, SKIP_PRUNING_ENV_VAR, true, self.prune, true)
+    }
+
+    /// Whether to generate an SPDX SBOM along with the CycloneDX one. Defaults to `false`.
+    ///
+    /// # Errors
+    ///
+    /// Will return a `ConfigError` if `NODEJS_SBOM_SPDX` isn't `true` or `false`.
+    pub fn sbom_spdx(&self, env: &Env) -> Result<Setting<bool>, ConfigError> {
+        resolve_bool(env, SBOM_SPDX_ENV_VAR, false, self.sbom_spdx, false)
+    }
+
+    /// The npm workspace to install, build, and start, if one was selected.
+    #[must_use]
+    pub fn npm_workspace(&self, env: &Env) -> Option<Setting<String>> {
+        env.get_string_lossy(NPM_WORKSPACE_ENV_VAR)
+            .map(|workspace| workspace.trim().to_string())
+            .filter(|workspace| !workspace.is_empty())
+            .map(|value| Setting {
+                value,
+                source: ConfigSource::EnvVar(NPM_WORKSPACE_ENV_VAR),
+            })
+            .or_else(|| {
+                self.npm_workspace.clone().map(|value| Setting {
+                    value,
+                    source: ConfigSource::ProjectToml,
+                })
+            })
+    }
+
+    /// Which `package.json` scripts to run during the build. Build scripts are
+    /// enabled by default and `NODEJS_BUILD_SCRIPT` overrides `scripts.build`.
+    ///
+    /// # Errors
+    ///
+    /// Will return a `ConfigError` if `NODEJS_BUILD_SCRIPTS` isn't `true` or `false`.
+    pub fn build_scripts(&self, env: &Env) -> Result<BuildScriptsConfig, ConfigError> {
+        Ok(BuildScriptsConfig {
+            enabled: resolve_bool(env, BUILD_SCRIPTS_ENV_VAR, false, self.build_scripts, true)?,
+            build: env
+                .get_string_lossy(BUILD_SCRIPT_ENV_VAR)
+                .map(|script| script.trim().to_string())
+                .filter(|script| !script.is_empty())
+                .map(|value| Setting {
+                    value,
+                    source: ConfigSource::EnvVar(BUILD_SCRIPT_ENV_VAR),
+                })
+                .or_else(|| {
+                    self.build_script.clone().map(|value| Setting {
+                        value,
+                        source: ConfigSource::ProjectToml,
+                    })
+                }),
+            extra: self.extra_scripts.clone(),
+        })
+    }
+
+    /// The OSV advisory directory to check the dependencies against, if one
+    /// was configured. Service bindings are looked up separately.
+    #[must_use]
+    pub fn advisories(&self, env: &Env) -> Option<Setting<String>> {
+        env.get_string_lossy(ADVISORIES_ENV_VAR)
+            .map(|dir| dir.trim().to_string())
+            .filter(|dir| !dir.is_empty())
+            .map(|value| Setting {
+                value,
+                source: ConfigSource::EnvVar(ADVISORIES_ENV_VAR),
+            })
+            .or_else(|| {
+                self.advisories.clone().map(|value| Setting {
+                    value,
+                    source: ConfigSource::ProjectToml,
+                })
+            })
+    }
+
+    /// The lowest vulnerability severity that fails the build. Vulnerabilities
+    /// are only reported by default.
+    ///
+    /// # Errors
+    ///
+    /// Will return a `ConfigError` if `NODEJS_VULNERABILITIES_FAIL_ON` isn't a
+    /// severity.
+    pub fn vulnerabilities_fail_on(
+        &self,
+        env: &Env,
+    ) -> Result<Option<Setting<Severity>>, ConfigError> {
+        if let Some(value) = env.get_string_lossy(VULNERABILITIES_FAIL_ON_ENV_VAR) {
+            return value
+                .parse()
+                .map(|severity| {
+                    Some(Setting {
+                        value: severity,
+                        source: ConfigSource::EnvVar(VULNERABILITIES_FAIL_ON_ENV_VAR),
+                    })
+                })
+                .map_err(|()| ConfigError::InvalidEnvVar {
+                    name: VULNERABILITIES_FAIL_ON_ENV_VAR.to_string(),
+                    value,
+                    expected: "`low`, `moderate`, `high`, or `critical`",
+                });
+        }
+        Ok(self.vulnerabilities_fail_on.map(|value| Setting {
+            value,
+            source: ConfigSource::ProjectToml,
+        }))
+    }
+
+    /// The processes declared by the app and the file they came from: the
+    /// `Procfile` when there is one, otherwise the `processes` in the
+    /// configuration.
+    ///
+    /// # Errors
+    ///
+    /// Will return a `ProcfileError` if the `Procfile` is invalid.
+    pub fn declared_processes(
+        &self,
+        app_dir: &Path,
+    ) -> Result<Option<(Procfile, &'static str)>, ProcfileError> {
+        Ok(match Procfile::read(app_dir)? {
+            Some(procfile) => Some((procfile, "Procfile")),
+            None if self.processes.is_empty() => None,
+            None => Some((
+                Procfile {
+                    processes: self.processes.clone(),
+                },
+                PROJECT_TOML,
+            )),
+        })
+    }
+}
+
+/// Resolves a boolean setting from an environment variable, then the
+/// configured value, then the default. `inverted` environment variables (like
+/// `NODEJS_SKIP_PRUNING`) disable the setting when they're `true`.
+fn resolve_bool(
+    env: &Env,
+    env_var: &'static str,
+    inverted: bool,
+    configured: Option<bool>,
+    default: bool,
+) -> Result<Setting<bool>, ConfigError> {
+    if let Some(value) = env.get_string_lossy(env_var) {
+        let enabled = match value.trim().to_lowercase().as_str() {
+            "true" => true,
+            "false" => false,
+            _ => {
+                return Err(ConfigError::InvalidEnvVar {
+                    name: env_var.to_string(),
+                    value,
+                    expected: "`true` or `false`",
+                })
+            }
+        };
+        return Ok(Setting {
+            value: enabled != inverted,
+            source: ConfigSource::EnvVar(env_var),
+        });
+    }
+    Ok(configured.map_or(
+        Setting {
+            value: default,
+            source: ConfigSource::Default,
+        },
+        |value| Setting {
+            value,
+            source: ConfigSource::ProjectToml,
+        },
+    ))
+}
+
+/// Parses a path relative to the app directory that doesn't leave it, dropping
+/// `.` components. `.` alone is the app directory itself.
+fn relative_dir(path: &str) -> Option<PathBuf> {
+    let path = path.trim();
+    if path.is_empty() {
+        return None;
+    }
+    Path::new(path)
+        .components()
+        .filter(|component| component != &Component::CurDir)
+        .map(|component| match component {
+            Component::Normal(name) => Some(name),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The keys in the configuration table, and the tables nested in it, that
+/// aren't part of the configuration.
+fn unknown_keys(contents: &str) -> Result<Vec<String>, ConfigError> {
+    let project_toml: toml::Value = toml::from_str(contents).map_err(ConfigError::Parse)?;
+    let Some(toml::Value::Table(table)) = CONFIG_TABLE
+        .split('.')
+        .try_fold(&project_toml, |value, key| value.get(key))
+    else {
+        return Ok(vec![]);
+    };
+    let mut unknown_keys = table
+        .keys()
+        .filter(|key| !KNOWN_KEYS.contains(&key.as_str()))
+        .map(|key| format!("{CONFIG_TABLE}.{key}"))
+        .collect::<Vec<_>>();
+    for (name, known_keys) in KNOWN_TABLE_KEYS {
+        if let Some(toml::Value::Table(nested)) = table.get(name) {
+            unknown_keys.extend(
+                nested
+                    .keys()
+                    .filter(|key| !known_keys.contains(&key.as_str()))
+                    .map(|key| format!("{CONFIG_TABLE}.{name}.{key}")),
+            );
+        }
+    }
+    Ok(unknown_keys)
+}
+
+/// Parses `app_root`, which has to stay inside the app directory.
+fn parse_app_root(
+    contents: &str,
+    app_root: Option<Spanned<String>>,
+) -> Result<Option<PathBuf>, ConfigError> {
+    app_root
+        .map(|app_root| {
+            relative_dir(app_root.get_ref()).ok_or_else(|| {
+                invalid_value(
+                    contents,
+                    "app_root",
+                    app_root.span(),
+                    "expected a relative path to a directory in the app",
+                )
+            })
+        })
+        .transpose()
+}
+
+/// Parses the `processes` table, in the order the process types are declared.
+fn parse_processes(
+    contents: &str,
+    processes: Option<BTreeMap<Spanned<String>, Spanned<String>>>,
+) -> Result<Vec<ProcfileProcess>, ConfigError> {
+    let mut processes = processes
+        .unwrap_or_default()
+        .into_iter()
+        .collect::<Vec<_>>();
+    processes.sort_by_key(|(process_type, _)| process_type.span().start);
+    processes
+        .into_iter()
+        .map(|(process_type, command)| {
+            let key = format!("processes.{}", process_type.get_ref());
+            let parsed_type = process_type.get_ref().parse::<ProcessType>().map_err(|_| {
+                invalid_value(
+                    contents,
+                    &key,
+                    process_type.span(),
+                    "process types may only contain letters, numbers, `.`, `_`, and `-`",
+                )
+            })?;
+            if command.get_ref().trim().is_empty() {
+                return Err(invalid_value(
+                    contents,
+                    &key,
+                    command.span(),
+                    "expected a command",
+                ));
+            }
+            Ok(ProcfileProcess {
+                process_type: parsed_type,
+                command: command.into_inner().trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+fn invalid_value(contents: &str, key: &str, span: Range<usize>, message: &str) -> ConfigError {
+    let (line, column) = line_and_column(contents, span.start);
+    ConfigError::InvalidValue {
+        key: format!("{CONFIG_TABLE}.{key}"),
+        line,
+        column,
+        message: message.to_string(),
+    }
+}
+
+fn line_and_column(contents: &str, offset: usize) -> (usize, usize) {
+    let before = &contents[..offset.min(contents.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rsplit('\n')
+        .next()
+        .map_or(0, |line| line.chars().count())
+        + 1;
+    (line, column)
+}
+
+#[derive(Deserialize, Default)]
+struct ProjectToml {
+    #[serde(default)]
+    com: Com,
+}
+
+#[derive(Deserialize, Default)]
+struct Com {
+    #[serde(default)]
+    heroku: Heroku,
+}
+
+#[derive(Deserialize, Default)]
+struct Heroku {
+    #[serde(default)]
+    buildpacks: Buildpacks,
+}
+
+#[derive(Deserialize, Default)]
+struct Buildpacks {
+    nodejs: Option<RawConfig>,
+}
+
+#[derive(Deserialize)]
+struct RawConfig {
+    app_root: Option<Spanned<String>>,
+    runtime_metrics: Option<bool>,
+    cache: Option<bool>,
+    prune: Option<bool>,
+    npm: Option<RawNpmConfig>,
+    scripts: Option<RawScriptsConfig>,
+    processes: Option<BTreeMap<Spanned<String>, Spanned<String>>>,
+    licenses: Option<RawLicensesConfig>,
+    sbom: Option<RawSbomConfig>,
+    vulnerabilities: Option<RawVulnerabilitiesConfig>,
+}
+
+#[derive(Deserialize)]
+struct RawNpmConfig {
+    workspace: Option<Spanned<String>>,
+}
+
+#[derive(Deserialize, Default)]
+struct RawScriptsConfig {
+    enabled: Option<bool>,
+    build: Option<Spanned<String>>,
+    extra: Option<Vec<Spanned<String>>>,
+}
+
+#[derive(Deserialize, Default)]
+struct RawLicensesConfig {
+    deny: Option<Vec<Spanned<String>>>,
+    exceptions: Option<Vec<Spanned<String>>>,
+}
+
+#[derive(Deserialize)]
+struct RawSbomConfig {
+    spdx: Option<bool>,
+}
+
+#[derive(Deserialize, Default)]
+struct RawVulnerabilitiesConfig {
+    advisories: Option<Spanned<String>>,
+    fail_on: Option<Spanned<String>>,
+}
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Couldn't read {PROJECT_TOML}: {0}")]
+    Read(std::io::Error),
+    #[error("Couldn't parse {PROJECT_TOML}: {0}")]
+    Parse(toml::de::Error),
+    #[error("Invalid `{key}` in {PROJECT_TOML} at line {line}, column {column}: {message}")]
+    InvalidValue {
+        key: String,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    #[error("Invalid `{name}` environment variable value `{value}`, expected {expected}")]
+    InvalidEnvVar {
+        name: String,
+        value: String,
+        expected: &'static str,
+    },
+    #[error("Couldn't use app root `{0}`: {1}")]
+    AppRoot(String, std::io::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libcnb::data::process_type;
+
+    #[test]
+    fn parse_config() {
+        let config = NodejsConfig::parse(
+            r#"
+[_]
+schema-version = "0.2"
+
+[com.heroku.buildpacks.nodejs]
+app_root = "./apps/web/"
+runtime_metrics = false
+prune = true
+prun = false
+
+[com.heroku.buildpacks.nodejs.npm]
+workspace = " @acme/web "
+workspaces = ["apps/*"]
+
+[com.heroku.buildpacks.nodejs.scripts]
+build = "build:prod"
+extra = ["docs", "sitemap"]
+prebuild = "setup"
+
+[com.heroku.buildpacks.nodejs.processes]
+web = "node server.js"
+worker = "node worker.js"
+
+[com.heroku.buildpacks.nodejs.licenses]
+deny = ["GPL-*", " AGPL-3.0-only "]
+exceptions = ["readline-sync@1.4.10"]
+allow = ["MIT"]
+
+[com.heroku.buildpacks.nodejs.sbom]
+spdx = true
+format = "spdx"
+
+[com.heroku.buildpacks.nodejs.vulnerabilities]
+advisories = "vendor/advisories"
+fail_on = "Critical"
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.app_root, Some(PathBuf::from("apps/web")));
+        assert_eq!(config.runtime_metrics, Some(false));
+        assert_eq!(config.cache, None);
+        assert_eq!(config.prune, Some(true));
+        assert_eq!(config.npm_workspace.as_deref(), Some("@acme/web"));
+        assert_eq!(config.build_scripts, None);
+        assert_eq!(config.build_script.as_deref(), Some("build:prod"));
+        assert_eq!(config.extra_scripts, ["docs", "sitemap"]);
+        assert_eq!(
+            config.processes,
+            [
+                ProcfileProcess {
+                    process_type: process_type!("web"),
+                    command: "node server.js".to_string()
+                },
+                ProcfileProcess {
+                    process_type: process_type!("worker"),
+                    command: "node worker.js".to_string()
+                }
+            ]
+        );
+        assert_eq!(
+            config.licenses,
+            LicensePolicy {
+                deny: vec!["GPL-*".to_string(), "AGPL-3.0-only".to_string()],
+                exceptions: vec!["readline-sync@1.4.10".to_string()]
+            }
+        );
+        assert_eq!(config.sbom_spdx, Some(true));
+        assert_eq!(config.advisories.as_deref(), Some("vendor/advisories"));
+        assert_eq!(config.vulnerabilities_fail_on, Some(Severity::Critical));
+        assert_eq!(
+            config.warnings(),
+            [
+                "Ignoring unknown key `com.heroku.buildpacks.nodejs.prun` in project.toml",
+                "Ignoring unknown key `com.heroku.buildpacks.nodejs.licenses.allow` in project.toml",
+                "Ignoring unknown key `com.heroku.buildpacks.nodejs.npm.workspaces` in project.toml",
+                "Ignoring unknown key `com.heroku.buildpacks.nodejs.sbom.format` in project.toml",
+                "Ignoring unknown key `com.heroku.buildpacks.nodejs.scripts.prebuild` in project.toml"
+            ]
+        );
+
+        assert_eq!(
+            NodejsConfig::parse("[_]\nschema-version = \"0.2\"\n").unwrap(),
+            NodejsConfig::default()
+        );
+    }
+
+    #[test]
+    fn parse_invalid_config() {
+        let error =
+            NodejsConfig::parse("[com.heroku.buildpacks.nodejs]\nruntime_metrics = \"no\"\n")
+                .unwrap_err()
+                .to_string();
+        assert!(error.contains("line 2, column 19"), "{error}");
+        assert!(error.contains("expected a boolean"), "{error}");
+
+        assert_eq!(
+            NodejsConfig::parse(
+                "[com.heroku.buildpacks.nodejs.processes]\nweb = \"node server.js\"\n\"web job\" = \"node job.js\"\n"
+            )
+            .unwrap_err()
+            .to_string(),
+            "Invalid `com.heroku.buildpacks.nodejs.processes.web job` in project.toml at line 3, column 1: process types may only contain letters, numbers, `.`, `_`, and `-`"
+        );
+        assert_eq!(
+            NodejsConfig::parse("[com.heroku.buildpacks.nodejs]\napp_root = \"../api\"\n")
+                .unwrap_err()
+                .to_string(),
+            "Invalid `com.heroku.buildpacks.nodejs.app_root` in project.toml at line 2, column 12: expected a relative path to a directory in the app"
+        );
+        assert_eq!(
+            NodejsConfig::parse("[com.heroku.buildpacks.nodejs.npm]\nworkspace = \"\"\n")
+                .unwrap_err()
+                .to_string(),
+            "Invalid `com.heroku.buildpacks.nodejs.npm.workspace` in project.toml at line 2, column 13: expected a workspace name or path"
+        );
+        assert_eq!(
+            NodejsConfig::parse("[com.heroku.buildpacks.nodejs.scripts]\nextra = [\"docs\", \" \"]\n")
+                .unwrap_err()
+                .to_string(),
+            "Invalid `com.heroku.buildpacks.nodejs.scripts.extra` in project.toml at line 2, column 18: expected a script name"
+        );
+        assert_eq!(
+            NodejsConfig::parse("[com.heroku.buildpacks.nodejs.licenses]\ndeny = [\"\"]\n")
+                .unwrap_err()
+                .to_string(),
+            "Invalid `com.heroku.buildpacks.nodejs.licenses.deny` in project.toml at line 2, column 9: expected an SPDX license identifier"
+        );
+        assert_eq!(
+            NodejsConfig::parse("[com.heroku.buildpacks.nodejs.vulnerabilities]\nfail_on = \"severe\"\n")
+                .unwrap_err()
+                .to_string(),
+            "Invalid `com.heroku.buildpacks.nodejs.vulnerabilities.fail_on` in project.toml at line 2, column 11: expected `low`, `moderate`, `high`, or `critical`"
+        );
+    }
+
+    #[test]
+    fn env_vars_override_config() {
+        let config = NodejsConfig {
+            prune: Some(false),
+            npm_workspace: Some("@acme/web".to_string()),
+            ..NodejsConfig::default()
+        };
+        let mut env = Env::new();
+        assert_eq!(
+            config.prune(&env).unwrap(),
+            Setting {
+                value: false,
+                source: ConfigSource::ProjectToml
+            }
+        );
+        assert_eq!(
+            config.prune(&env).unwrap().reason("prune"),
+            "`prune` is set to `false` in project.toml"
+        );
+        assert_eq!(
+            config.cache(&env).unwrap(),
+            Setting {
+                value: true,
+                source: ConfigSource::Default
+            }
+        );
+        assert_eq!(
+            config.npm_workspace(&env).unwrap().source,
+            ConfigSource::ProjectToml
+        );
+        assert_eq!(
+            config.sbom_spdx(&env).unwrap(),
+            Setting {
+                value: false,
+                source: ConfigSource::Default
+            }
+        );
+
+        env.insert(SKIP_PRUNING_ENV_VAR, " FALSE ");
+        env.insert(SBOM_SPDX_ENV_VAR, "true");
+        env.insert(CACHE_ENV_VAR, "false");
+        env.insert(NPM_WORKSPACE_ENV_VAR, "apps/api");
+        assert_eq!(
+            config.prune(&env).unwrap(),
+            Setting {
+                value: true,
+                source: ConfigSource::EnvVar(SKIP_PRUNING_ENV_VAR)
+            }
+        );
+        assert!(!config.cache(&env).unwrap().value);
+        assert!(config.sbom_spdx(&env).unwrap().value);
+        assert_eq!(config.npm_workspace(&env).unwrap().value, "apps/api");
+
+        assert_eq!(
+            config.prune(&env).unwrap().reason("prune"),
+            "`NODEJS_SKIP_PRUNING` is set to `false`"
+        );
+        assert_eq!(
+            config.cache(&env).unwrap().reason("cache"),
+            "`NODEJS_CACHE` is set to `false`"
+        );
+
+        env.insert(BUILD_SCRIPT_ENV_VAR, "build:staging");
+        env.insert(BUILD_SCRIPTS_ENV_VAR, "false");
+        let build_scripts = config.build_scripts(&env).unwrap();
+        assert_eq!(
+            build_scripts.build,
+            Some(Setting {
+                value: "build:staging".to_string(),
+                source: ConfigSource::EnvVar(BUILD_SCRIPT_ENV_VAR)
+            })
+        );
+        assert!(!build_scripts.enabled.value);
+
+        env.insert(RUNTIME_METRICS_ENV_VAR, "no");
+        assert_eq!(
+            config.runtime_metrics(&env).unwrap_err().to_string(),
+            "Invalid `NODEJS_RUNTIME_METRICS` environment variable value `no`, expected `true` or `false`"
+        );
+
+        let config = NodejsConfig {
+            advisories: Some("vendor/advisories".to_string()),
+            vulnerabilities_fail_on: Some(Severity::Critical),
+            ..NodejsConfig::default()
+        };
+        env.insert(ADVISORIES_ENV_VAR, "/opt/advisories");
+        env.insert(VULNERABILITIES_FAIL_ON_ENV_VAR, "medium");
+        assert_eq!(config.advisories(&env).unwrap().value, "/opt/advisories");
+        assert_eq!(
+            config.vulnerabilities_fail_on(&env).unwrap(),
+            Some(Setting {
+                value: Severity::Moderate,
+                source: ConfigSource::EnvVar(VULNERABILITIES_FAIL_ON_ENV_VAR)
+            })
+        );
+        env.insert(VULNERABILITIES_FAIL_ON_ENV_VAR, "severe");
+        assert_eq!(
+            config.vulnerabilities_fail_on(&env).unwrap_err().to_string(),
+            "Invalid `NODEJS_VULNERABILITIES_FAIL_ON` environment variable value `severe`, expected `low`, `moderate`, `high`, or `critical`"
+        );
+    }
+
+    #[test]
+    fn app_root() {
+        let app_dir = Path::new("/workspace");
+        let config = NodejsConfig {
+            app_root: Some(PathBuf::from("apps/web")),
+            ..NodejsConfig::default()
+        };
+        let mut env = Env::new();
+        let app_root = NodejsConfig::default().app_root(&env).unwrap();
+        assert!(app_root.is_app_dir());
+        assert_eq!(app_root.dir(app_dir), app_dir);
+        assert_eq!(app_root.working_directory(), WorkingDirectory::App);
+        assert_eq!(app_root.to_string(), ".");
+
+        let app_root = config.app_root(&env).unwrap();
+        assert_eq!(app_root.dir(app_dir), Path::new("/workspace/apps/web"));
+        assert_eq!(
+            app_root.working_directory(),
+            WorkingDirectory::Directory(PathBuf::from("apps/web"))
+        );
+        assert_eq!(app_root.to_string(), "apps/web");
+
+        env.insert(APP_ROOT_ENV_VAR, ".");
+        assert!(config.app_root(&env).unwrap().is_app_dir());
+        env.insert(APP_ROOT_ENV_VAR, "/srv/app");
+        assert_eq!(
+            config.app_root(&env).unwrap_err().to_string(),
+            "Invalid `NODEJS_APP_ROOT` environment variable value `/srv/app`, expected a relative path to a directory in the app"
+        );
+    }
+}
@@ -0,0 +1,390 @@
+use crate::package_json::PackageJson;
+use crate::package_manager::PackageManager;
+use crate::{package_lock, pnpm_lock, yarn_lock};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Identifies a package in a `DependencyGraph` by its name and resolved version.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PackageId {
+    pub name: String,
+    pub version: String,
+}
+
+impl PackageId {
+    pub fn new(name: impl Into<String>, version: impl Into<String>) -> Self {
+        PackageId {
+            name: name.into(),
+            version: version.into(),
+        }
+    }
+}
+
+impl Display for PackageId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}@{}", self.name, self.version)
+    }
+}
+
+/// A package installed from a lockfile.
+///
+/// `dev` and `optional` are derived from the graph rather than read from the
+/// lockfile, so they mean the same for every package manager: a `dev` package
+/// is only reachable through the root's dev dependencies, and an `optional`
+/// package is only reachable through at least one optional dependency.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Package {
+    pub name: String,
+    pub version: String,
+    /// Where the package was downloaded from, when the lockfile records it.
+    pub resolved: Option<String>,
+    /// The package checksum in the lockfile's format, like a Subresource
+    /// Integrity string for npm and pnpm, or a Yarn Berry cache checksum.
+    pub integrity: Option<String>,
+    pub license: Option<String>,
+    pub dev: bool,
+    pub optional: bool,
+    pub engines: BTreeMap<String, String>,
+    pub os: Vec<String>,
+    pub cpu: Vec<String>,
+    pub dependencies: BTreeSet<PackageId>,
+    pub optional_dependencies: BTreeSet<PackageId>,
+}
+
+impl Package {
+    #[must_use]
+    pub fn id(&self) -> PackageId {
+        PackageId::new(&self.name, &self.version)
+    }
+}
+
+/// The packages the root package, and any workspace packages, depend on
+/// directly.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RootDependencies {
+    pub dependencies: BTreeSet<PackageId>,
+    pub dev_dependencies: BTreeSet<PackageId>,
+    pub optional_dependencies: BTreeSet<PackageId>,
+}
+
+/// The packages installed from a `package-lock.json`, `yarn.lock`, or
+/// `pnpm-lock.yaml`, and the dependencies between them. Packages that are
+/// installed more than once with the same version appear once.
+#[derive(Debug, Clone)]
+pub struct DependencyGraph {
+    pub package_manager: PackageManager,
+    pub lockfile_version: String,
+    pub root: RootDependencies,
+    packages: BTreeMap<PackageId, Package>,
+}
+
+impl DependencyGraph {
+    /// Reads the lockfile of `package_manager` in `app_dir`.
+    ///
+    /// # Errors
+    ///
+    /// Will return a `DependencyGraphError` if the lockfile can't be read or
+    /// parsed, or if its version isn't supported.
+    pub fn read(
+        app_dir: &Path,
+        package_manager: PackageManager,
+        package_json: &PackageJson,
+    ) -> Result<Self, DependencyGraphError> {
+        let path = app_dir.join(package_manager.lockfile());
+        let contents =
+            fs::read_to_string(&path).map_err(|e| DependencyGraphError::Read(path, e))?;
+        Self::parse(package_manager, &contents, package_json)
+    }
+
+    /// Parses a lockfile of `package_manager`:
+    ///
+    /// * `package-lock.json` versions 1 to 3
+    /// * `yarn.lock` from Yarn 1 and from Yarn 2 and later
+    /// * `pnpm-lock.yaml` versions 6 to 9
+    ///
+    /// The root dependencies come from the lockfile when it records them, and
+    /// from `package_json` otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Will return a `DependencyGraphError` if the lockfile can't be parsed or
+    /// if its version isn't supported.
+    pub fn parse(
+        package_manager: PackageManager,
+        contents: &str,
+        package_json: &PackageJson,
+    ) -> Result<Self, DependencyGraphError> {
+        match package_manager {
+            PackageManager::Npm => package_lock::parse(contents, package_json),
+            PackageManager::Pnpm => pnpm_lock::parse(contents),
+            PackageManager::Yarn => yarn_lock::parse(contents, package_json),
+        }
+    }
+
+    #[must_use]
+    pub fn get(&self, id: &PackageId) -> Option<&Package> {
+        self.packages.get(id)
+    }
+
+    /// The packages in the graph, sorted by name and version.
+    pub fn packages(&self) -> impl Iterator<Item = &Package> {
+        self.packages.values()
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.packages.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.packages.is_empty()
+    }
+
+    fn reachable<'a>(
+        &self,
+        from: impl IntoIterator<Item = &'a PackageId>,
+        follow_optional: bool,
+    ) -> BTreeSet<PackageId> {
+        let mut reachable = BTreeSet::new();
+        let mut pending: Vec<&PackageId> = from.into_iter().collect();
+        while let Some(id) = pending.pop() {
+            if let Some(package) = self.packages.get(id) {
+                if reachable.insert(id.clone()) {
+                    pending.extend(&package.dependencies);
+                    if follow_optional {
+                        pending.extend(&package.optional_dependencies);
+                    }
+                }
+            }
+        }
+        reachable
+    }
+}
+
+/// Collects the packages of a lockfile into a `DependencyGraph`.
+#[derive(Default)]
+pub(crate) struct GraphBuilder {
+    pub(crate) root: RootDependencies,
+    packages: BTreeMap<PackageId, Package>,
+}
+
+impl GraphBuilder {
+    /// Adds a package, merging its dependencies into an already added package
+    /// with the same name and version.
+    pub(crate) fn add_package(&mut self, package: Package) -> PackageId {
+        let id = package.id();
+        match self.packages.get_mut(&id) {
+            Some(existing) => {
+                existing.dependencies.extend(package.dependencies);
+                existing
+                    .optional_dependencies
+                    .extend(package.optional_dependencies);
+            }
+            None => {
+                self.packages.insert(id.clone(), package);
+            }
+        }
+        id
+    }
+
+    pub(crate) fn add_dependency(&mut self, from: &PackageId, to: PackageId, optional: bool) {
+        if let Some(package) = self.packages.get_mut(from) {
+            if optional {
+                package.optional_dependencies.insert(to);
+            } else {
+                package.dependencies.insert(to);
+            }
+        }
+    }
+
+    /// Adds the dependencies of `package_json` as root dependencies, for
+    /// lockfiles that don't record them.
+    pub(crate) fn add_package_json_roots(
+        &mut self,
+        package_json: &PackageJson,
+        resolve: impl Fn(&str, &str) -> Option<PackageId>,
+    ) {
+        for (dependencies, roots) in [
+            (&package_json.dependencies, &mut self.root.dependencies),
+            (
+                &package_json.dev_dependencies,
+                &mut self.root.dev_dependencies,
+            ),
+            (
+                &package_json.optional_dependencies,
+                &mut self.root.optional_dependencies,
+            ),
+        ] {
+            roots.extend(
+                dependencies
+                    .iter()
+                    .flatten()
+                    .filter_map(|(name, range)| resolve(name, range)),
+            );
+        }
+    }
+
+    pub(crate) fn build(
+        self,
+        package_manager: PackageManager,
+        lockfile_version: impl Into<String>,
+    ) -> DependencyGraph {
+        let mut graph = DependencyGraph {
+            package_manager,
+            lockfile_version: lockfile_version.into(),
+            root: self.root,
+            packages: self.packages,
+        };
+        let production = graph.reachable(
+            graph
+                .root
+                .dependencies
+                .iter()
+                .chain(&graph.root.optional_dependencies),
+            true,
+        );
+        let required = graph.reachable(
+            graph
+                .root
+                .dependencies
+                .iter()
+                .chain(&graph.root.dev_dependencies),
+            false,
+        );
+        for (id, package) in &mut graph.packages {
+            package.dev = !production.contains(id);
+            package.optional = !required.contains(id);
+        }
+        graph
+    }
+}
+
+/// Splits a `<name>@<range>` descriptor, or a `<name>@<version>` key, into
+/// its name and the rest. Scoped names start with `@`, so the separator is
+/// the first `@` after the first character.
+pub(crate) fn split_descriptor(descriptor: &str) -> Option<(&str, &str)> {
+    let separator = descriptor.get(1..)?.find('@')? + 1;
+    Some((&descriptor[..separator], &descriptor[separator + 1..]))
+}
+
+/// Reads a YAML scalar as a string, as unquoted versions and ranges like `1`
+/// parse as numbers.
+pub(crate) fn yaml_scalar(value: &serde_yaml::Value) -> Option<String> {
+    match value {
+        serde_yaml::Value::String(value) => Some(value.clone()),
+        serde_yaml::Value::Number(value) => Some(value.to_string()),
+        serde_yaml::Value::Bool(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+pub(crate) fn yaml_strings(value: Option<&serde_yaml::Value>) -> Vec<String> {
+    value
+        .and_then(serde_yaml::Value::as_sequence)
+        .into_iter()
+        .flatten()
+        .filter_map(yaml_scalar)
+        .collect()
+}
+
+#[derive(Debug, Error)]
+pub enum DependencyGraphError {
+    #[error("Couldn't read {0}: {1}")]
+    Read(PathBuf, std::io::Error),
+    #[error("Couldn't parse package-lock.json: {0}")]
+    PackageLock(serde_json::Error),
+    #[error("Couldn't parse pnpm-lock.yaml: {0}")]
+    PnpmLock(serde_yaml::Error),
+    #[error("Couldn't parse yarn.lock: {0}")]
+    YarnBerryLock(serde_yaml::Error),
+    #[error("Couldn't parse yarn.lock on line {line}: {message}")]
+    YarnLock { line: usize, message: String },
+    #[error("Unsupported {lockfile} version `{version}`")]
+    UnsupportedVersion {
+        lockfile: &'static str,
+        version: String,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(name: &str, version: &str) -> Package {
+        Package {
+            name: name.to_string(),
+            version: version.to_string(),
+            ..Package::default()
+        }
+    }
+
+    #[test]
+    fn split_descriptors() {
+        assert_eq!(
+            split_descriptor("lodash@^4.17.21"),
+            Some(("lodash", "^4.17.21"))
+        );
+        assert_eq!(
+            split_descriptor("@babel/core@npm:7.24.0"),
+            Some(("@babel/core", "npm:7.24.0"))
+        );
+        assert_eq!(
+            split_descriptor("string-width-cjs@npm:string-width@^4.2.0"),
+            Some(("string-width-cjs", "npm:string-width@^4.2.0"))
+        );
+        assert_eq!(split_descriptor("lodash"), None);
+    }
+
+    #[test]
+    fn build_derives_dev_and_optional_flags() {
+        let mut builder = GraphBuilder::default();
+        let app = builder.add_package(package("app-dep", "1.0.0"));
+        let shared = builder.add_package(package("shared", "1.0.0"));
+        let test = builder.add_package(package("test-dep", "1.0.0"));
+        let native = builder.add_package(package("native", "1.0.0"));
+        let unused = builder.add_package(package("unused", "1.0.0"));
+        builder.add_dependency(&app, shared.clone(), false);
+        builder.add_dependency(&app, native.clone(), true);
+        builder.add_dependency(&test, shared.clone(), false);
+        builder.add_dependency(&test, unused.clone(), false);
+        builder.root.dependencies.insert(app.clone());
+        builder.root.dev_dependencies.insert(test.clone());
+
+        let graph = builder.build(PackageManager::Npm, "3");
+        let flags = |id: &PackageId| {
+            let package = graph.get(id).unwrap();
+            (package.dev, package.optional)
+        };
+        assert_eq!(flags(&app), (false, false));
+        assert_eq!(flags(&shared), (false, false));
+        assert_eq!(flags(&native), (false, true));
+        assert_eq!(flags(&test), (true, false));
+        assert_eq!(flags(&unused), (true, false));
+        assert_eq!(graph.len(), 5);
+    }
+
+    #[test]
+    fn add_package_merges_duplicates() {
+        let mut builder = GraphBuilder::default();
+        let first = builder.add_package(package("a", "1.0.0"));
+        let b = builder.add_package(package("b", "1.0.0"));
+        let c = builder.add_package(package("c", "1.0.0"));
+        builder.add_dependency(&first, b.clone(), false);
+        let second = builder.add_package(Package {
+            dependencies: BTreeSet::from([c.clone()]),
+            ..package("a", "1.0.0")
+        });
+        assert_eq!(first, second);
+
+        let graph = builder.build(PackageManager::Npm, "3");
+        assert_eq!(graph.len(), 3);
+        assert_eq!(
+            graph.get(&first).unwrap().dependencies,
+            BTreeSet::from([b, c])
+        );
+    }
+}
@@ -1,13 +1,31 @@
 use keep_a_changelog_file as _;
-use sha2 as _;
 
 pub mod application;
+pub mod build_scripts;
 pub mod buildplan;
+pub mod config;
+pub mod dependency_graph;
 pub mod distribution;
 pub mod inv;
+pub mod licenses;
+pub mod mirror;
+pub mod node_artifact;
+pub mod node_version_source;
 mod nodejs_org;
 mod npmjs_org;
 pub mod package_json;
+mod package_lock;
 pub mod package_manager;
+mod pnpm_lock;
+pub mod procfile;
+pub mod prune;
+pub mod registry_credentials;
+pub mod release_schedule;
+pub mod resolved_node;
 mod s3;
+pub mod sbom;
+pub mod shasums;
 pub mod vrs;
+pub mod vulnerabilities;
+pub mod workspaces;
+mod yarn_lock;
@@ -0,0 +1,654 @@
+use crate::dependency_graph::{DependencyGraph, DependencyGraphError, PackageId};
+use crate::package_json::PackageJson;
+use crate::package_lock::license;
+use crate::package_manager::PackageManager;
+use libcnb::build::BuildContext;
+use libcnb::data::layer_name;
+use libcnb::layer::UncachedLayerDefinition;
+use libcnb::Buildpack;
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// The license of packages that don't declare one.
+pub const UNKNOWN_LICENSE: &str = "UNKNOWN";
+
+/// The licenses that production dependencies may not use, read from
+/// `[com.heroku.buildpacks.nodejs.licenses]` in `project.toml`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LicensePolicy {
+    /// SPDX license identifiers, like `GPL-3.0-only`, or prefixes ending with
+    /// `*`, like `GPL-*`. Matched case-insensitively.
+    pub deny: Vec<String>,
+    /// Packages that are allowed whatever their license, either by name, like
+    /// `readline-sync`, or by name and version, like `readline-sync@1.4.10`.
+    pub exceptions: Vec<String>,
+}
+
+impl LicensePolicy {
+    fn is_exception(&self, id: &PackageId) -> bool {
+        let name_and_version = id.to_string();
+        self.exceptions
+            .iter()
+            .any(|exception| exception == &id.name || exception == &name_and_version)
+    }
+
+    fn is_denied(&self, license_id: &str) -> bool {
+        self.deny
+            .iter()
+            .any(|denied| match denied.strip_suffix('*') {
+                Some(prefix) => license_id
+                    .get(..prefix.len())
+                    .is_some_and(|start| start.eq_ignore_ascii_case(prefix)),
+                None => license_id.eq_ignore_ascii_case(denied),
+            })
+    }
+
+    /// The denied licenses that an SPDX expression requires. A choice between
+    /// licenses, like `(MIT OR GPL-3.0-only)`, is only denied when every
+    /// alternative is. Expressions that can't be parsed are denied when any
+    /// of their licenses is.
+    fn denied_licenses(&self, expression: &str) -> Vec<String> {
+        let tokens = tokenize(expression);
+        let mut parser = ExpressionParser {
+            policy: self,
+            tokens: &tokens,
+            position: 0,
+        };
+        match parser.or_expression() {
+            Some(denied) if parser.position == tokens.len() => denied,
+            _ => tokens
+                .iter()
+                .filter(|token| !is_operator(token) && !matches!(token.as_str(), "(" | ")"))
+                .filter(|token| self.is_denied(token))
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+/// The license of every installed package, split into production and dev
+/// dependencies.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LicenseReport {
+    pub packages: Vec<LicensedPackage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LicensedPackage {
+    pub id: PackageId,
+    /// The SPDX license expression, or [`UNKNOWN_LICENSE`].
+    pub license: String,
+    pub dev: bool,
+}
+
+/// A production dependency with a license the policy denies.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LicenseViolation {
+    pub package: PackageId,
+    pub license: String,
+    pub denied: Vec<String>,
+}
+
+impl Display for LicenseViolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} is licensed under `{}`", self.package, self.license)?;
+        if self.denied != [self.license.clone()] {
+            write!(f, " (denied: {})", self.denied.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl LicenseReport {
+    /// Reads the installed packages from the lockfile of `package_manager`
+    /// and their licenses from `node_modules`, falling back to the license
+    /// recorded in the lockfile.
+    ///
+    /// # Errors
+    ///
+    /// Will return a `LicenseCheckError` if the lockfile can't be read or parsed.
+    pub fn read(
+        app_dir: &Path,
+        package_manager: PackageManager,
+        package_json: &PackageJson,
+    ) -> Result<Self, LicenseCheckError> {
+        let graph = DependencyGraph::read(app_dir, package_manager, package_json)
+            .map_err(LicenseCheckError::DependencyGraph)?;
+        Ok(Self::new(&graph, &read_installed_licenses(app_dir)))
+    }
+
+    /// Combines the packages in `graph` with the licenses read from their
+    /// installed `package.json` files.
+    #[must_use]
+    pub fn new(graph: &DependencyGraph, installed_licenses: &BTreeMap<PackageId, String>) -> Self {
+        LicenseReport {
+            packages: graph
+                .packages()
+                .map(|package| {
+                    let id = package.id();
+                    LicensedPackage {
+                        license: installed_licenses
+                            .get(&id)
+                            .or(package.license.as_ref())
+                            .map(|license| license.trim())
+                            .filter(|license| !license.is_empty())
+                            .unwrap_or(UNKNOWN_LICENSE)
+                            .to_string(),
+                        id,
+                        dev: package.dev,
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    /// The production (or dev) dependencies grouped by license expression.
+    #[must_use]
+    pub fn by_license(&self, dev: bool) -> BTreeMap<&str, Vec<&PackageId>> {
+        let mut groups: BTreeMap<&str, Vec<&PackageId>> = BTreeMap::new();
+        for package in self.packages.iter().filter(|package| package.dev == dev) {
+            groups
+                .entry(package.license.as_str())
+                .or_default()
+                .push(&package.id);
+        }
+        groups
+    }
+
+    /// A one-line summary of the production (or dev) dependency licenses for
+    /// the build log, like "3 MIT, 1 ISC", with the most common first.
+    #[must_use]
+    pub fn summary(&self, dev: bool) -> String {
+        let mut groups: Vec<_> = self
+            .by_license(dev)
+            .into_iter()
+            .map(|(license, packages)| (packages.len(), license))
+            .collect();
+        if groups.is_empty() {
+            return "none".to_string();
+        }
+        groups.sort_by(|(a_count, a), (b_count, b)| b_count.cmp(a_count).then(a.cmp(b)));
+        groups
+            .iter()
+            .map(|(count, license)| format!("{count} {license}"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// The production dependencies whose license the policy denies. Dev
+    /// dependencies and exceptions are never violations.
+    #[must_use]
+    pub fn violations(&self, policy: &LicensePolicy) -> Vec<LicenseViolation> {
+        self.packages
+            .iter()
+            .filter(|package| !package.dev && !policy.is_exception(&package.id))
+            .filter_map(|package| {
+                let denied = policy.denied_licenses(&package.license);
+                (!denied.is_empty()).then(|| LicenseViolation {
+                    package: package.id.clone(),
+                    license: package.license.clone(),
+                    denied,
+                })
+            })
+            .collect()
+    }
+
+    /// Fails when any production dependency has a license the policy denies.
+    ///
+    /// # Errors
+    ///
+    /// Will return a `LicenseCheckError::Denied` listing the violations.
+    pub fn check(&self, policy: &LicensePolicy) -> Result<(), LicenseCheckError> {
+        let violations = self.violations(policy);
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(LicenseCheckError::Denied(violations))
+        }
+    }
+
+    /// The report as JSON, with the packages grouped by license under
+    /// `production` and `development`.
+    #[must_use]
+    pub fn to_json(&self) -> Value {
+        let groups = |dev: bool| -> Map<String, Value> {
+            self.by_license(dev)
+                .into_iter()
+                .map(|(license, packages)| {
+                    (
+                        license.to_string(),
+                        packages.iter().map(ToString::to_string).collect(),
+                    )
+                })
+                .collect()
+        };
+        json!({ "production": groups(false), "development": groups(true) })
+    }
+
+    /// Writes the JSON report to `path`.
+    ///
+    /// # Errors
+    ///
+    /// Will return a `LicenseCheckError` if the file can't be written.
+    pub fn write(&self, path: &Path) -> Result<(), LicenseCheckError> {
+        let contents =
+            serde_json::to_string_pretty(&self.to_json()).expect("License report should serialize");
+        fs::write(path, contents).map_err(|e| LicenseCheckError::WriteReport(path.to_path_buf(), e))
+    }
+}
+
+/// The result of [`write_license_report`].
+#[derive(Debug)]
+pub enum LicenseReportOutcome {
+    /// The lockfile couldn't be read. This only skips the report when no
+    /// licenses are denied.
+    Skipped(LicenseCheckError),
+    /// The report was written to `path` in the `licenses` layer. Check it
+    /// against the policy with [`LicenseReport::check`] once it's logged.
+    Written {
+        report: LicenseReport,
+        path: PathBuf,
+    },
+}
+
+/// Reads the licenses of the dependencies installed from the lockfile of
+/// `package_manager` and writes the JSON report to `licenses.json` in a
+/// `licenses` launch layer.
+///
+/// # Errors
+///
+/// Will return an error if the layer can't be created, the report can't be
+/// written, or the lockfile can't be read while `policy` denies licenses.
+pub fn write_license_report<B>(
+    context: &BuildContext<B>,
+    app_dir: &Path,
+    package_manager: PackageManager,
+    package_json: &PackageJson,
+    policy: &LicensePolicy,
+) -> libcnb::Result<LicenseReportOutcome, B::Error>
+where
+    B: Buildpack,
+    B::Error: From<LicenseCheckError>,
+{
+    let buildpack_error = |error: LicenseCheckError| libcnb::Error::BuildpackError(error.into());
+    let report = match LicenseReport::read(app_dir, package_manager, package_json) {
+        Ok(report) => report,
+        Err(error @ LicenseCheckError::DependencyGraph(_)) if policy.deny.is_empty() => {
+            return Ok(LicenseReportOutcome::Skipped(error));
+        }
+        Err(error) => return Err(buildpack_error(error)),
+    };
+
+    let licenses_layer = context.uncached_layer(
+        layer_name!("licenses"),
+        UncachedLayerDefinition {
+            build: false,
+            launch: true,
+        },
+    )?;
+    let path = licenses_layer.path().join("licenses.json");
+    report.write(&path).map_err(buildpack_error)?;
+    Ok(LicenseReportOutcome::Written { report, path })
+}
+
+/// Reads the name, version, and license of every package installed under
+/// `node_modules` in `app_dir`, including nested `node_modules` directories.
+/// Symlinks are followed, so packages that pnpm links from its virtual store
+/// are found as well.
+fn read_installed_licenses(app_dir: &Path) -> BTreeMap<PackageId, String> {
+    let mut licenses = BTreeMap::new();
+    let mut visited = HashSet::new();
+    let mut pending = vec![app_dir.join("node_modules")];
+    while let Some(node_modules) = pending.pop() {
+        let Ok(node_modules) = node_modules.canonicalize() else {
+            continue;
+        };
+        if !visited.insert(node_modules.clone()) {
+            continue;
+        }
+        for package_dir in package_dirs(&node_modules) {
+            let Ok(package_dir) = package_dir.canonicalize() else {
+                continue;
+            };
+            if !visited.insert(package_dir.clone()) {
+                continue;
+            }
+            if let Some((id, license)) = read_package_license(&package_dir) {
+                licenses.entry(id).or_insert(license);
+            }
+            pending.push(package_dir.join("node_modules"));
+            // pnpm links a package's dependencies next to it, in the
+            // `node_modules` directory of its virtual store entry.
+            pending.extend(
+                package_dir
+                    .ancestors()
+                    .skip(1)
+                    .find(|dir| dir.file_name().is_some_and(|name| name == "node_modules"))
+                    .map(Path::to_path_buf),
+            );
+        }
+    }
+    licenses
+}
+
+/// The package directories in a `node_modules` directory, including scoped
+/// packages like `@babel/core`. Hidden entries like `.bin` are skipped.
+fn package_dirs(node_modules: &Path) -> Vec<PathBuf> {
+    let entries = |dir: &Path| -> Vec<(String, PathBuf)> {
+        fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| {
+                (
+                    entry.file_name().to_string_lossy().to_string(),
+                    entry.path(),
+                )
+            })
+            .filter(|(name, _)| !name.starts_with('.'))
+            .collect()
+    };
+    entries(node_modules)
+        .into_iter()
+        .flat_map(|(name, path)| {
+            if name.starts_with('@') {
+                entries(&path).into_iter().map(|(_, path)| path).collect()
+            } else {
+                vec![path]
+            }
+        })
+        .collect()
+}
+
+/// Reads `license`, or the legacy `licenses` array, from a package's
+/// `package.json`.
+fn read_package_license(package_dir: &Path) -> Option<(PackageId, String)> {
+    let package_json: Value =
+        serde_json::from_str(&fs::read_to_string(package_dir.join("package.json")).ok()?).ok()?;
+    let id = PackageId::new(
+        package_json.get("name")?.as_str()?,
+        package_json.get("version")?.as_str()?,
+    );
+    let license = package_json.get("license").and_then(license).or_else(|| {
+        let licenses: Vec<String> = package_json
+            .get("licenses")?
+            .as_array()?
+            .iter()
+            .filter_map(license)
+            .collect();
+        match licenses.len() {
+            0 => None,
+            1 => licenses.into_iter().next(),
+            _ => Some(format!("({})", licenses.join(" OR "))),
+        }
+    })?;
+    Some((id, license))
+}
+
+fn tokenize(expression: &str) -> Vec<String> {
+    expression
+        .replace('(', " ( ")
+        .replace(')', " ) ")
+        .split_whitespace()
+        .map(String::from)
+        .collect()
+}
+
+fn is_operator(token: &str) -> bool {
+    ["AND", "OR", "WITH"]
+        .iter()
+        .any(|operator| token.eq_ignore_ascii_case(operator))
+}
+
+/// Evaluates an SPDX license expression to the denied licenses it requires,
+/// where `AND` binds tighter than `OR`.
+struct ExpressionParser<'a> {
+    policy: &'a LicensePolicy,
+    tokens: &'a [String],
+    position: usize,
+}
+
+impl ExpressionParser<'_> {
+    fn or_expression(&mut self) -> Option<Vec<String>> {
+        let mut denied = self.and_expression()?;
+        while self.next_is("OR") {
+            self.position += 1;
+            let alternative = self.and_expression()?;
+            denied = if denied.is_empty() || alternative.is_empty() {
+                vec![]
+            } else {
+                [denied, alternative].concat()
+            };
+        }
+        Some(denied)
+    }
+
+    fn and_expression(&mut self) -> Option<Vec<String>> {
+        let mut denied = self.term()?;
+        while self.next_is("AND") {
+            self.position += 1;
+            denied.extend(self.term()?);
+        }
+        Some(denied)
+    }
+
+    fn term(&mut self) -> Option<Vec<String>> {
+        let token = self.tokens.get(self.position)?;
+        self.position += 1;
+        if token == "(" {
+            let denied = self.or_expression()?;
+            (self.tokens.get(self.position)? == ")").then_some(())?;
+            self.position += 1;
+            return Some(denied);
+        }
+        if token == ")" || is_operator(token) {
+            return None;
+        }
+        // The exception in `GPL-2.0-only WITH Classpath-exception-2.0` only
+        // relaxes the license, so the license decides.
+        if self.next_is("WITH") {
+            self.position += 2;
+        }
+        Some(if self.policy.is_denied(token) {
+            vec![token.clone()]
+        } else {
+            vec![]
+        })
+    }
+
+    fn next_is(&self, operator: &str) -> bool {
+        self.tokens
+            .get(self.position)
+            .is_some_and(|token| token.eq_ignore_ascii_case(operator))
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum LicenseCheckError {
+    #[error("Couldn't read the installed dependencies: {0}")]
+    DependencyGraph(DependencyGraphError),
+    #[error("Couldn't write the license report to {0}: {1}")]
+    WriteReport(PathBuf, std::io::Error),
+    #[error("Production dependencies use denied licenses:\n{}", .0.iter().map(|violation| format!("- {violation}")).collect::<Vec<_>>().join("\n"))]
+    Denied(Vec<LicenseViolation>),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(deny: &[&str], exceptions: &[&str]) -> LicensePolicy {
+        LicensePolicy {
+            deny: deny.iter().map(ToString::to_string).collect(),
+            exceptions: exceptions.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    fn graph() -> DependencyGraph {
+        DependencyGraph::parse(
+            PackageManager::Npm,
+            r#"{
+                "lockfileVersion": 3,
+                "packages": {
+                    "": {
+                        "dependencies": { "express": "^4.0.0", "readline-sync": "^1.4.0", "dual": "^1.0.0" },
+                        "devDependencies": { "gpl-tool": "^2.0.0" }
+                    },
+                    "node_modules/express": {
+                        "version": "4.19.2",
+                        "license": "MIT",
+                        "dependencies": { "unlicensed": "^0.1.0" }
+                    },
+                    "node_modules/readline-sync": { "version": "1.4.10" },
+                    "node_modules/dual": { "version": "1.0.0" },
+                    "node_modules/unlicensed": { "version": "0.1.0" },
+                    "node_modules/gpl-tool": { "version": "2.0.0", "dev": true, "license": "GPL-3.0-only" }
+                }
+            }"#,
+            &PackageJson::default(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn denied_license_expressions() {
+        let policy = policy(&["GPL-*", "AGPL-3.0-only"], &[]);
+        assert_eq!(policy.denied_licenses("MIT"), Vec::<String>::new());
+        assert_eq!(
+            policy.denied_licenses("gpl-3.0-or-later"),
+            ["gpl-3.0-or-later"]
+        );
+        assert_eq!(
+            policy.denied_licenses("LGPL-2.1-only"),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            policy.denied_licenses("(MIT OR GPL-3.0-only)"),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            policy.denied_licenses("MIT AND (GPL-2.0-only OR AGPL-3.0-only)"),
+            ["GPL-2.0-only", "AGPL-3.0-only"]
+        );
+        assert_eq!(
+            policy.denied_licenses("GPL-2.0-only WITH Classpath-exception-2.0"),
+            ["GPL-2.0-only"]
+        );
+        assert_eq!(
+            policy.denied_licenses("MIT OR Apache-2.0 AND GPL-3.0-only"),
+            Vec::<String>::new()
+        );
+        // Unparseable expressions are denied when any license is.
+        assert_eq!(
+            policy.denied_licenses("(MIT OR GPL-3.0-only"),
+            ["GPL-3.0-only"]
+        );
+    }
+
+    #[test]
+    fn license_report() {
+        let installed = BTreeMap::from([
+            (
+                PackageId::new("readline-sync", "1.4.10"),
+                "GPL-2.0-only".to_string(),
+            ),
+            (
+                PackageId::new("dual", "1.0.0"),
+                "(MIT OR GPL-3.0-only)".to_string(),
+            ),
+        ]);
+        let report = LicenseReport::new(&graph(), &installed);
+
+        assert_eq!(
+            report.summary(false),
+            "1 (MIT OR GPL-3.0-only), 1 GPL-2.0-only, 1 MIT, 1 UNKNOWN"
+        );
+        assert_eq!(report.summary(true), "1 GPL-3.0-only");
+        assert_eq!(
+            report.to_json(),
+            json!({
+                "production": {
+                    "(MIT OR GPL-3.0-only)": ["dual@1.0.0"],
+                    "GPL-2.0-only": ["readline-sync@1.4.10"],
+                    "MIT": ["express@4.19.2"],
+                    "UNKNOWN": ["unlicensed@0.1.0"]
+                },
+                "development": { "GPL-3.0-only": ["gpl-tool@2.0.0"] }
+            })
+        );
+
+        assert!(report.check(&LicensePolicy::default()).is_ok());
+        assert_eq!(
+            report
+                .check(&policy(&["GPL-*", "UNKNOWN"], &[]))
+                .unwrap_err()
+                .to_string(),
+            "Production dependencies use denied licenses:\n- readline-sync@1.4.10 is licensed under `GPL-2.0-only`\n- unlicensed@0.1.0 is licensed under `UNKNOWN`"
+        );
+        assert!(report
+            .check(&policy(&["GPL-*"], &["readline-sync@1.4.10"]))
+            .is_ok());
+        assert_eq!(
+            report.violations(&policy(&["GPL-2.0-only"], &["readline-sync@1.4.9"])),
+            [LicenseViolation {
+                package: PackageId::new("readline-sync", "1.4.10"),
+                license: "GPL-2.0-only".to_string(),
+                denied: vec!["GPL-2.0-only".to_string()],
+            }]
+        );
+    }
+
+    #[test]
+    fn read_licenses_from_node_modules() {
+        let app_dir = tempfile::tempdir().unwrap();
+        let write_package = |dir: &str, package_json: &str| {
+            let dir = app_dir.path().join(dir);
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("package.json"), package_json).unwrap();
+        };
+        write_package(
+            "node_modules/express",
+            r#"{ "name": "express", "version": "4.19.2", "license": "MIT" }"#,
+        );
+        write_package(
+            "node_modules/@babel/core",
+            r#"{ "name": "@babel/core", "version": "7.24.0", "license": { "type": "MIT" } }"#,
+        );
+        write_package(
+            "node_modules/express/node_modules/debug",
+            r#"{ "name": "debug", "version": "2.6.9", "licenses": [{ "type": "MIT" }, { "type": "Apache-2.0" }] }"#,
+        );
+        write_package(
+            "store/.pnpm/ms@2.0.0/node_modules/ms",
+            r#"{ "name": "ms", "version": "2.0.0", "license": "MIT" }"#,
+        );
+        write_package(
+            "store/.pnpm/ms@2.0.0/node_modules/ansi",
+            r#"{ "name": "ansi", "version": "1.0.0", "license": "ISC" }"#,
+        );
+        std::os::unix::fs::symlink(
+            app_dir.path().join("store/.pnpm/ms@2.0.0/node_modules/ms"),
+            app_dir.path().join("node_modules/ms"),
+        )
+        .unwrap();
+        fs::create_dir_all(app_dir.path().join("node_modules/.bin")).unwrap();
+
+        assert_eq!(
+            read_installed_licenses(app_dir.path()),
+            BTreeMap::from([
+                (PackageId::new("@babel/core", "7.24.0"), "MIT".to_string()),
+                (PackageId::new("ansi", "1.0.0"), "ISC".to_string()),
+                (
+                    PackageId::new("debug", "2.6.9"),
+                    "(MIT OR Apache-2.0)".to_string()
+                ),
+                (PackageId::new("express", "4.19.2"), "MIT".to_string()),
+                (PackageId::new("ms", "2.0.0"), "MIT".to_string()),
+            ])
+        );
+    }
+}
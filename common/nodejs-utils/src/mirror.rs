@@ -0,0 +1,334 @@
+use base64::prelude::{Engine, BASE64_STANDARD};
+use libherokubuildpack::download::{download_file, DownloadError};
+use md5::Md5;
+use sha2::{Digest, Sha256, Sha512};
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use url::Url;
+
+/// Environment variable used to configure a mirror for Node.js, npm, and Yarn
+/// downloads.
+pub const ARTIFACT_MIRROR_ENV_VAR: &str = "NODEJS_ARTIFACT_MIRROR_URL";
+
+/// A base URL that artifact downloads are served from instead of the URLs
+/// recorded in an inventory. The path of each inventory URL is appended to the
+/// mirror URL, so `https://nodejs.org/download/release/v22.1.0/node.tar.gz`
+/// with a mirror of `https://mirror.example.com/nodejs` is downloaded from
+/// `https://mirror.example.com/nodejs/download/release/v22.1.0/node.tar.gz`.
+///
+/// Mirrors may use `http`, `https`, or `file` URLs, where `file` URLs point at
+/// a local directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArtifactMirror(Url);
+
+impl ArtifactMirror {
+    /// Parses a mirror base URL.
+    ///
+    /// # Errors
+    ///
+    /// Will return an `ArtifactMirrorError` if the value isn't a valid URL or
+    /// uses a scheme other than `http`, `https`, or `file`.
+    pub fn parse(value: &str) -> Result<Self, ArtifactMirrorError> {
+        let url = Url::parse(value.trim())
+            .map_err(|e| ArtifactMirrorError::InvalidUrl(value.to_string(), e))?;
+        match url.scheme() {
+            "http" | "https" | "file" => Ok(ArtifactMirror(url)),
+            scheme => Err(ArtifactMirrorError::UnsupportedScheme(scheme.to_string())),
+        }
+    }
+
+    /// Rewrites an inventory URL so it's served from this mirror.
+    ///
+    /// # Errors
+    ///
+    /// Will return an `ArtifactMirrorError` if the inventory URL is invalid.
+    pub fn rewrite(&self, artifact_url: &str) -> Result<String, ArtifactMirrorError> {
+        let artifact_url = Url::parse(artifact_url)
+            .map_err(|e| ArtifactMirrorError::InvalidUrl(artifact_url.to_string(), e))?;
+        Ok(format!(
+            "{}/{}",
+            self.0.as_str().trim_end_matches('/'),
+            artifact_url.path().trim_start_matches('/')
+        ))
+    }
+}
+
+impl Display for ArtifactMirror {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ArtifactMirrorError {
+    #[error("Invalid artifact mirror URL `{0}`: {1}")]
+    InvalidUrl(String, url::ParseError),
+    #[error("Unsupported artifact mirror URL scheme `{0}`, expected `http`, `https`, or `file`")]
+    UnsupportedScheme(String),
+}
+
+/// Downloads an artifact to `destination`. Supports `http`, `https`, and
+/// `file` URLs.
+///
+/// # Errors
+///
+/// Will return an `ArtifactDownloadError` if the artifact couldn't be
+/// downloaded or copied from a local directory.
+pub fn download_artifact(url: &str, destination: &Path) -> Result<(), ArtifactDownloadError> {
+    match Url::parse(url) {
+        Ok(file_url) if file_url.scheme() == "file" => {
+            let source = file_url
+                .to_file_path()
+                .map_err(|()| ArtifactDownloadError::InvalidFileUrl(url.to_string()))?;
+            fs::copy(&source, destination)
+                .map(|_| ())
+                .map_err(|e| ArtifactDownloadError::Copy(source, e))
+        }
+        _ => download_file(url, destination).map_err(ArtifactDownloadError::Download),
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ArtifactDownloadError {
+    #[error(transparent)]
+    Download(DownloadError),
+    #[error("Invalid file URL `{0}`")]
+    InvalidFileUrl(String),
+    #[error("Couldn't copy artifact from {path}: {error}", path = .0.display(), error = .1)]
+    Copy(PathBuf, std::io::Error),
+}
+
+/// The expected digest of a downloaded artifact. Artifacts served from a
+/// mirror are verified against the same values as artifacts downloaded from
+/// their inventory URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArtifactChecksum {
+    Md5(Vec<u8>),
+    Sha256(Vec<u8>),
+    Sha512(Vec<u8>),
+}
+
+impl ArtifactChecksum {
+    /// Parses a Subresource Integrity value, like the `dist.integrity` field
+    /// of an npm registry package document (e.g.: `sha512-<base64 digest>`).
+    ///
+    /// # Errors
+    ///
+    /// Will return an `ArtifactChecksumError` if the algorithm isn't supported
+    /// or the digest isn't valid base64.
+    pub fn from_integrity(value: &str) -> Result<Self, ArtifactChecksumError> {
+        let (algorithm, digest) = value
+            .trim()
+            .split_once('-')
+            .ok_or_else(|| ArtifactChecksumError::InvalidDigest(value.to_string()))?;
+        let digest = BASE64_STANDARD
+            .decode(digest)
+            .map_err(|_| ArtifactChecksumError::InvalidDigest(value.to_string()))?;
+        match algorithm {
+            "sha256" => Ok(ArtifactChecksum::Sha256(digest)),
+            "sha512" => Ok(ArtifactChecksum::Sha512(digest)),
+            _ => Err(ArtifactChecksumError::UnsupportedAlgorithm(
+                algorithm.to_string(),
+            )),
+        }
+    }
+
+    /// Parses the Amazon S3 `ETag` recorded in an inventory. The `ETag` of an
+    /// object uploaded in a single part is the hex encoded MD5 digest of its
+    /// contents; multipart `ETag`s (e.g.: `<hex>-3`) aren't content digests.
+    ///
+    /// # Errors
+    ///
+    /// Will return an `ArtifactChecksumError` if the `ETag` isn't an MD5
+    /// digest.
+    pub fn from_etag(value: &str) -> Result<Self, ArtifactChecksumError> {
+        match hex::decode(value.trim_matches('"')) {
+            Ok(digest) if digest.len() == 16 => Ok(ArtifactChecksum::Md5(digest)),
+            _ => Err(ArtifactChecksumError::InvalidDigest(value.to_string())),
+        }
+    }
+
+    /// Verifies that the file at `path` matches this checksum.
+    ///
+    /// # Errors
+    ///
+    /// Will return an `ArtifactVerificationError` if the file can't be read
+    /// or its digest doesn't match.
+    pub fn verify(&self, path: &Path) -> Result<(), ArtifactVerificationError> {
+        let actual = match self {
+            ArtifactChecksum::Md5(_) => digest_file::<Md5>(path),
+            ArtifactChecksum::Sha256(_) => digest_file::<Sha256>(path),
+            ArtifactChecksum::Sha512(_) => digest_file::<Sha512>(path),
+        }
+        .map_err(|e| ArtifactVerificationError::Read(path.to_path_buf(), e))?;
+        if actual == self.digest() {
+            Ok(())
+        } else {
+            Err(ArtifactVerificationError::Mismatch {
+                expected: self.to_string(),
+                actual: format!("{}:{}", self.algorithm(), hex::encode(actual)),
+            })
+        }
+    }
+
+    fn algorithm(&self) -> &'static str {
+        match self {
+            ArtifactChecksum::Md5(_) => "md5",
+            ArtifactChecksum::Sha256(_) => "sha256",
+            ArtifactChecksum::Sha512(_) => "sha512",
+        }
+    }
+
+    fn digest(&self) -> &[u8] {
+        match self {
+            ArtifactChecksum::Md5(digest)
+            | ArtifactChecksum::Sha256(digest)
+            | ArtifactChecksum::Sha512(digest) => digest,
+        }
+    }
+}
+
+impl Display for ArtifactChecksum {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.algorithm(), hex::encode(self.digest()))
+    }
+}
+
+fn digest_file<D: Digest>(path: &Path) -> Result<Vec<u8>, std::io::Error> {
+    let mut file = fs::File::open(path)?;
+    let mut buffer = [0x00; 10 * 1024];
+    let mut hasher = D::new();
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher.finalize().to_vec())
+}
+
+#[derive(Error, Debug)]
+pub enum ArtifactChecksumError {
+    #[error("Unsupported checksum algorithm `{0}`")]
+    UnsupportedAlgorithm(String),
+    #[error("Invalid checksum `{0}`")]
+    InvalidDigest(String),
+}
+
+#[derive(Error, Debug)]
+pub enum ArtifactVerificationError {
+    #[error("Couldn't read artifact {path}: {error}", path = .0.display(), error = .1)]
+    Read(PathBuf, std::io::Error),
+    #[error("Artifact checksum mismatch (expected {expected}, got {actual})")]
+    Mismatch { expected: String, actual: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_rejects_unsupported_schemes() {
+        assert!(ArtifactMirror::parse("https://mirror.example.com").is_ok());
+        assert!(ArtifactMirror::parse("file:///srv/mirror").is_ok());
+        assert!(matches!(
+            ArtifactMirror::parse("ftp://mirror.example.com"),
+            Err(ArtifactMirrorError::UnsupportedScheme(_))
+        ));
+        assert!(matches!(
+            ArtifactMirror::parse("mirror.example.com"),
+            Err(ArtifactMirrorError::InvalidUrl(..))
+        ));
+    }
+
+    #[test]
+    fn rewrite_keeps_artifact_path() {
+        let url = "https://nodejs.org/download/release/v22.1.0/node-v22.1.0-linux-x64.tar.gz";
+        assert_eq!(
+            ArtifactMirror::parse("https://mirror.example.com/nodejs/")
+                .unwrap()
+                .rewrite(url)
+                .unwrap(),
+            "https://mirror.example.com/nodejs/download/release/v22.1.0/node-v22.1.0-linux-x64.tar.gz"
+        );
+        assert_eq!(
+            ArtifactMirror::parse("file:///srv/mirror")
+                .unwrap()
+                .rewrite(url)
+                .unwrap(),
+            "file:///srv/mirror/download/release/v22.1.0/node-v22.1.0-linux-x64.tar.gz"
+        );
+    }
+
+    #[test]
+    fn download_artifact_from_local_directory() {
+        let mirror = tempfile::tempdir().unwrap();
+        fs::create_dir_all(mirror.path().join("npm/release")).unwrap();
+        fs::write(mirror.path().join("npm/release/npm-v10.0.0.tar.gz"), "npm").unwrap();
+        let url = ArtifactMirror::parse(&format!("file://{}", mirror.path().display()))
+            .unwrap()
+            .rewrite("https://example.com/npm/release/npm-v10.0.0.tar.gz")
+            .unwrap();
+
+        let destination = mirror.path().join("npm.tgz");
+        download_artifact(&url, &destination).unwrap();
+        assert_eq!(fs::read_to_string(destination).unwrap(), "npm");
+
+        assert!(matches!(
+            download_artifact(&format!("{url}.missing"), &mirror.path().join("missing")),
+            Err(ArtifactDownloadError::Copy(..))
+        ));
+    }
+
+    #[test]
+    fn parse_checksums() {
+        assert_eq!(
+            ArtifactChecksum::from_etag("\"04f0882274fbf688b4a9a1872ca7560f\"")
+                .unwrap()
+                .to_string(),
+            "md5:04f0882274fbf688b4a9a1872ca7560f"
+        );
+        assert!(matches!(
+            ArtifactChecksum::from_etag("04f0882274fbf688b4a9a1872ca7560f-2"),
+            Err(ArtifactChecksumError::InvalidDigest(_))
+        ));
+        assert_eq!(
+            ArtifactChecksum::from_integrity("sha256-LCa0a2j/xo/5m0U8HTBBNBNCLXBkg7+g+YpeiGJm564=")
+                .unwrap()
+                .to_string(),
+            "sha256:2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae"
+        );
+        assert!(matches!(
+            ArtifactChecksum::from_integrity("sha1-C+7Hteo/D9vJXQ3UfzxbwnXaijM="),
+            Err(ArtifactChecksumError::UnsupportedAlgorithm(_))
+        ));
+    }
+
+    #[test]
+    fn verify_checksums() {
+        let artifact = tempfile::NamedTempFile::new().unwrap();
+        fs::write(artifact.path(), "foo").unwrap();
+
+        for checksum in [
+            "acbd18db4cc2f85cedef654fccc4a4d8",
+            "sha256-LCa0a2j/xo/5m0U8HTBBNBNCLXBkg7+g+YpeiGJm564=",
+            "sha512-9/u6bgY2+JDlb7vzKD5STG+jIErimDgtYkdB0NxmODJuKCxBvl5CVNiCB3LFUYosWowMf37aGVlKfrU5RT4e1w==",
+        ] {
+            let checksum = ArtifactChecksum::from_etag(checksum)
+                .or_else(|_| ArtifactChecksum::from_integrity(checksum))
+                .unwrap();
+            checksum.verify(artifact.path()).unwrap();
+        }
+
+        assert!(matches!(
+            ArtifactChecksum::from_etag("d41d8cd98f00b204e9800998ecf8427e")
+                .unwrap()
+                .verify(artifact.path()),
+            Err(ArtifactVerificationError::Mismatch { .. })
+        ));
+    }
+}
@@ -0,0 +1,292 @@
+use crate::vrs::{Requirement, Version};
+use libherokubuildpack::inventory::version::ArtifactRequirement;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Environment variable used to opt into resolving Node.js prereleases from a
+/// prerelease channel.
+pub const PRERELEASE_CHANNEL_ENV_VAR: &str = "NODEJS_PRERELEASE_CHANNEL";
+
+/// Metadata for Node.js artifacts in the inventory. Artifacts without metadata
+/// are builds for glibc-based distributions.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct NodeArtifactMetadata {
+    pub libc: Libc,
+}
+
+/// The C standard library a Node.js build is linked against.
+#[derive(
+    Deserialize, Serialize, Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Libc {
+    #[default]
+    Glibc,
+    Musl,
+}
+
+impl Libc {
+    /// Determines the C standard library for a CNB target distribution, like
+    /// `ubuntu` or `alpine`.
+    #[must_use]
+    pub fn for_distro(distro_name: &str) -> Self {
+        if distro_name.eq_ignore_ascii_case("alpine") {
+            Libc::Musl
+        } else {
+            Libc::Glibc
+        }
+    }
+
+    /// Determines the C standard library of an inventory artifact.
+    #[must_use]
+    pub fn for_artifact(metadata: Option<&NodeArtifactMetadata>) -> Self {
+        metadata.map_or(Libc::Glibc, |metadata| metadata.libc)
+    }
+}
+
+impl Display for Libc {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Libc::Glibc => write!(f, "glibc"),
+            Libc::Musl => write!(f, "musl"),
+        }
+    }
+}
+
+/// Node.js prerelease channels, which are identified by the prerelease part of
+/// their versions (e.g. `24.0.0-rc.1` or `24.0.0-nightly20250301a1b2c3d4e5`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PrereleaseChannel {
+    Rc,
+    Nightly,
+}
+
+impl PrereleaseChannel {
+    /// Determines the prerelease channel a version was published to. Returns
+    /// `None` for stable releases.
+    #[must_use]
+    pub fn for_version(version: &Version) -> Option<Self> {
+        version.prerelease().and_then(|prerelease| {
+            if prerelease.starts_with("rc") {
+                Some(PrereleaseChannel::Rc)
+            } else if prerelease.starts_with("nightly") {
+                Some(PrereleaseChannel::Nightly)
+            } else {
+                None
+            }
+        })
+    }
+}
+
+impl FromStr for PrereleaseChannel {
+    type Err = PrereleaseChannelError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "rc" => Ok(PrereleaseChannel::Rc),
+            "nightly" => Ok(PrereleaseChannel::Nightly),
+            _ => Err(PrereleaseChannelError(value.to_string())),
+        }
+    }
+}
+
+impl Display for PrereleaseChannel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PrereleaseChannel::Rc => write!(f, "rc"),
+            PrereleaseChannel::Nightly => write!(f, "nightly"),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+#[error("Unknown Node.js prerelease channel `{0}`, expected `rc` or `nightly`")]
+pub struct PrereleaseChannelError(String);
+
+/// Matches inventory artifacts by version and C standard library.
+///
+/// Prereleases only match if `version` explicitly names a prerelease (like
+/// `24.0.0-rc.1`), or if they're from the opted-in `prerelease_channel` and
+/// the release they lead up to satisfies `version`.
+#[derive(Debug, Clone)]
+pub struct NodeArtifactRequirement {
+    pub version: Requirement,
+    pub libc: Libc,
+    pub prerelease_channel: Option<PrereleaseChannel>,
+}
+
+impl ArtifactRequirement<Version, Option<NodeArtifactMetadata>> for NodeArtifactRequirement {
+    fn satisfies_metadata(&self, metadata: &Option<NodeArtifactMetadata>) -> bool {
+        Libc::for_artifact(metadata.as_ref()) == self.libc
+    }
+
+    fn satisfies_version(&self, version: &Version) -> bool {
+        self.version.satisfies(version)
+            || self.prerelease_channel.is_some_and(|channel| {
+                PrereleaseChannel::for_version(version) == Some(channel)
+                    && self.version.satisfies_release_of(version)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libherokubuildpack::inventory::artifact::{Arch, Os};
+    use libherokubuildpack::inventory::Inventory;
+    use sha2::Sha256;
+
+    const INVENTORY: &str = r#"
+[[artifacts]]
+version = "22.1.0"
+os = "linux"
+arch = "amd64"
+url = "https://nodejs.org/download/release/v22.1.0/node-v22.1.0-linux-x64.tar.gz"
+checksum = "sha256:d8ae35a9e2bb0c0c0611ee9bacf564ea51cc8291ace1447f95ee6aeaf4f1d61d"
+
+[[artifacts]]
+version = "22.1.0"
+os = "linux"
+arch = "amd64"
+url = "https://unofficial-builds.nodejs.org/download/release/v22.1.0/node-v22.1.0-linux-x64-musl.tar.gz"
+checksum = "sha256:9c111af1f951e8869615bca3601ce7ab6969374933bdba6397469843b808f222"
+
+[artifacts.metadata]
+libc = "musl"
+
+[[artifacts]]
+version = "24.0.0-rc.1"
+os = "linux"
+arch = "amd64"
+url = "https://nodejs.org/download/rc/v24.0.0-rc.1/node-v24.0.0-rc.1-linux-x64.tar.gz"
+checksum = "sha256:d8ae35a9e2bb0c0c0611ee9bacf564ea51cc8291ace1447f95ee6aeaf4f1d61d"
+
+[[artifacts]]
+version = "24.0.0-nightly20250301a1b2c3d4e5"
+os = "linux"
+arch = "amd64"
+url = "https://nodejs.org/download/nightly/v24.0.0-nightly20250301a1b2c3d4e5/node-v24.0.0-nightly20250301a1b2c3d4e5-linux-x64.tar.gz"
+checksum = "sha256:d8ae35a9e2bb0c0c0611ee9bacf564ea51cc8291ace1447f95ee6aeaf4f1d61d"
+"#;
+
+    fn resolve(
+        inventory: &Inventory<Version, Sha256, Option<NodeArtifactMetadata>>,
+        requirement: &str,
+        prerelease_channel: Option<PrereleaseChannel>,
+    ) -> Option<String> {
+        inventory
+            .resolve(
+                Os::Linux,
+                Arch::Amd64,
+                &NodeArtifactRequirement {
+                    version: Requirement::parse(requirement).unwrap(),
+                    libc: Libc::Glibc,
+                    prerelease_channel,
+                },
+            )
+            .map(|artifact| artifact.version.to_string())
+    }
+
+    #[test]
+    fn libc_for_distro() {
+        assert_eq!(Libc::for_distro("alpine"), Libc::Musl);
+        assert_eq!(Libc::for_distro("ubuntu"), Libc::Glibc);
+        assert_eq!(Libc::for_distro(""), Libc::Glibc);
+    }
+
+    #[test]
+    fn resolve_artifacts_by_libc() {
+        let inventory: Inventory<Version, Sha256, Option<NodeArtifactMetadata>> =
+            toml::from_str(INVENTORY).unwrap();
+        let version = Requirement::parse("22.x").unwrap();
+
+        let glibc = inventory
+            .resolve(
+                Os::Linux,
+                Arch::Amd64,
+                &NodeArtifactRequirement {
+                    version: version.clone(),
+                    libc: Libc::Glibc,
+                    prerelease_channel: None,
+                },
+            )
+            .unwrap();
+        assert!(glibc.url.ends_with("linux-x64.tar.gz"));
+
+        let musl = inventory
+            .resolve(
+                Os::Linux,
+                Arch::Amd64,
+                &NodeArtifactRequirement {
+                    version: version.clone(),
+                    libc: Libc::Musl,
+                    prerelease_channel: None,
+                },
+            )
+            .unwrap();
+        assert!(musl.url.ends_with("linux-x64-musl.tar.gz"));
+
+        assert!(inventory
+            .resolve(
+                Os::Linux,
+                Arch::Arm64,
+                &NodeArtifactRequirement {
+                    version,
+                    libc: Libc::Musl,
+                    prerelease_channel: None,
+                },
+            )
+            .is_none());
+    }
+
+    #[test]
+    fn prerelease_channel_for_version() {
+        assert_eq!(
+            PrereleaseChannel::for_version(&Version::parse("24.0.0-rc.1").unwrap()),
+            Some(PrereleaseChannel::Rc)
+        );
+        assert_eq!(
+            PrereleaseChannel::for_version(
+                &Version::parse("24.0.0-nightly20250301a1b2c3d4e5").unwrap()
+            ),
+            Some(PrereleaseChannel::Nightly)
+        );
+        assert_eq!(
+            PrereleaseChannel::for_version(&Version::parse("22.1.0").unwrap()),
+            None
+        );
+        assert_eq!(
+            "RC".parse::<PrereleaseChannel>().unwrap(),
+            PrereleaseChannel::Rc
+        );
+        assert!("beta".parse::<PrereleaseChannel>().is_err());
+    }
+
+    #[test]
+    fn resolve_prereleases_only_when_requested() {
+        let inventory: Inventory<Version, Sha256, Option<NodeArtifactMetadata>> =
+            toml::from_str(INVENTORY).unwrap();
+
+        assert_eq!(resolve(&inventory, "*", None).unwrap(), "22.1.0");
+        assert_eq!(resolve(&inventory, ">=22", None).unwrap(), "22.1.0");
+        assert_eq!(resolve(&inventory, "24.x", None), None);
+        assert_eq!(
+            resolve(&inventory, "24.0.0-rc.1", None).unwrap(),
+            "24.0.0-rc.1"
+        );
+        assert_eq!(
+            resolve(&inventory, "24.x", Some(PrereleaseChannel::Rc)).unwrap(),
+            "24.0.0-rc.1"
+        );
+        assert_eq!(
+            resolve(&inventory, "24.x", Some(PrereleaseChannel::Nightly)).unwrap(),
+            "24.0.0-nightly20250301a1b2c3d4e5"
+        );
+        assert_eq!(
+            resolve(&inventory, "22.x", Some(PrereleaseChannel::Rc)).unwrap(),
+            "22.1.0"
+        );
+    }
+}
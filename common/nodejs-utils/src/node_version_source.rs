@@ -0,0 +1,438 @@
+use crate::package_json::PackageJson;
+use crate::release_schedule::{ReleaseAlias, ReleaseSchedule};
+use crate::vrs::{Requirement, VersionError};
+use chrono::NaiveDate;
+use serde::Deserialize;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+use thiserror::Error;
+
+/// The places a Node.js version can be declared for an application, in
+/// priority order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeVersionSource {
+    /// The `engines.node` field in `package.json`.
+    PackageJsonEngines,
+    /// The `volta.node` field in `package.json`.
+    PackageJsonVolta,
+    /// An `.nvmrc` file used by [nvm](https://github.com/nvm-sh/nvm).
+    Nvmrc,
+    /// A `.node-version` file used by tools like `nodenv` and `fnm`.
+    NodeVersionFile,
+    /// The `nodejs` entry in an [asdf](https://asdf-vm.com) `.tool-versions` file.
+    ToolVersions,
+}
+
+impl NodeVersionSource {
+    const VALUES: [Self; 5] = [
+        Self::PackageJsonEngines,
+        Self::PackageJsonVolta,
+        Self::Nvmrc,
+        Self::NodeVersionFile,
+        Self::ToolVersions,
+    ];
+}
+
+impl Display for NodeVersionSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::PackageJsonEngines => write!(f, "engines.node in package.json"),
+            Self::PackageJsonVolta => write!(f, "volta.node in package.json"),
+            Self::Nvmrc => write!(f, ".nvmrc"),
+            Self::NodeVersionFile => write!(f, ".node-version"),
+            Self::ToolVersions => write!(f, ".tool-versions"),
+        }
+    }
+}
+
+/// A requested Node.js version, which is either a semver range or a release
+/// alias like `lts/*` that is resolved using the inventory's release schedule.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(try_from = "String")]
+pub enum NodeRequirement {
+    Alias(ReleaseAlias),
+    Range(Requirement),
+}
+
+impl NodeRequirement {
+    /// Parses a requested Node.js version. Handles these cases in addition to
+    /// the ones supported by `Requirement::parse`:
+    ///
+    /// * Release aliases like "lts/*", "lts/iron" and "current"
+    /// * "node" as "*"
+    ///
+    /// # Errors
+    ///
+    /// Invalid version strings wil return a `VersionError`
+    pub fn parse(value: &str) -> Result<Self, VersionError> {
+        if let Ok(alias) = value.parse::<ReleaseAlias>() {
+            return Ok(NodeRequirement::Alias(alias));
+        }
+        if value.trim() == "node" {
+            return Requirement::parse("latest").map(NodeRequirement::Range);
+        }
+        Requirement::parse(value).map(NodeRequirement::Range)
+    }
+
+    /// Resolves this request to a version range, looking up release aliases
+    /// in `release_schedule`.
+    #[must_use]
+    pub fn resolve(
+        &self,
+        release_schedule: &ReleaseSchedule,
+        today: NaiveDate,
+    ) -> Option<Requirement> {
+        match self {
+            NodeRequirement::Alias(alias) => release_schedule.resolve_alias(alias, today),
+            NodeRequirement::Range(requirement) => Some(requirement.clone()),
+        }
+    }
+}
+
+impl TryFrom<String> for NodeRequirement {
+    type Error = VersionError;
+    fn try_from(val: String) -> Result<Self, Self::Error> {
+        NodeRequirement::parse(&val)
+    }
+}
+
+impl Display for NodeRequirement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NodeRequirement::Alias(alias) => write!(f, "{alias}"),
+            NodeRequirement::Range(requirement) => write!(f, "{requirement}"),
+        }
+    }
+}
+
+/// A Node.js version request, where it was declared, and the version range
+/// it resolved to.
+#[derive(Debug, Clone)]
+pub struct DetectedNodeVersion {
+    pub source: NodeVersionSource,
+    pub requested: NodeRequirement,
+    pub requirement: Requirement,
+}
+
+/// Collects every Node.js version requirement declared by the application,
+/// ordered from highest to lowest priority. The first entry, if any, is the
+/// one that should be used to resolve the Node.js version. Release aliases
+/// are resolved with `release_schedule` as of `today`.
+///
+/// # Errors
+///
+/// Will return an `Err` when:
+/// - A version file exists but can't be read.
+/// - A version file doesn't contain a valid version requirement.
+/// - A release alias doesn't match any release line in `release_schedule`.
+/// - Two sources declare requirements that can't be satisfied by the same version.
+pub fn detect_node_versions(
+    app_dir: &Path,
+    package_json: &PackageJson,
+    release_schedule: &ReleaseSchedule,
+    today: NaiveDate,
+) -> Result<Vec<DetectedNodeVersion>, NodeVersionSourceError> {
+    let mut detected_versions: Vec<DetectedNodeVersion> = vec![];
+
+    for source in NodeVersionSource::VALUES {
+        let Some(requested) = read_requirement(source, app_dir, package_json)? else {
+            continue;
+        };
+
+        let requirement = requested.resolve(release_schedule, today).ok_or_else(|| {
+            NodeVersionSourceError::UnknownAlias {
+                origin: source,
+                alias: requested.to_string(),
+            }
+        })?;
+
+        if let Some(conflict) = detected_versions
+            .iter()
+            .find(|detected| !detected.requirement.allows_any(&requirement))
+        {
+            return Err(NodeVersionSourceError::Conflict {
+                preferred: Box::new(conflict.clone()),
+                conflicting: Box::new(DetectedNodeVersion {
+                    source,
+                    requested,
+                    requirement,
+                }),
+            });
+        }
+
+        detected_versions.push(DetectedNodeVersion {
+            source,
+            requested,
+            requirement,
+        });
+    }
+
+    Ok(detected_versions)
+}
+
+fn read_requirement(
+    source: NodeVersionSource,
+    app_dir: &Path,
+    package_json: &PackageJson,
+) -> Result<Option<NodeRequirement>, NodeVersionSourceError> {
+    let filename = match source {
+        NodeVersionSource::PackageJsonEngines => {
+            return Ok(package_json
+                .engines
+                .as_ref()
+                .and_then(|engines| engines.node.clone()));
+        }
+        NodeVersionSource::PackageJsonVolta => {
+            return Ok(package_json
+                .volta
+                .as_ref()
+                .and_then(|volta| volta.node.clone())
+                .map(NodeRequirement::Range));
+        }
+        NodeVersionSource::Nvmrc => ".nvmrc",
+        NodeVersionSource::NodeVersionFile => ".node-version",
+        NodeVersionSource::ToolVersions => ".tool-versions",
+    };
+
+    let contents = match fs::read_to_string(app_dir.join(filename)) {
+        Ok(contents) => contents,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(error) => return Err(NodeVersionSourceError::Read(source, error)),
+    };
+
+    let value = match source {
+        NodeVersionSource::ToolVersions => match parse_tool_versions(&contents) {
+            Some(value) => value,
+            None => return Ok(None),
+        },
+        _ => parse_version_file(&contents).ok_or(NodeVersionSourceError::Empty(source))?,
+    };
+
+    NodeRequirement::parse(value)
+        .map(Some)
+        .map_err(|error| NodeVersionSourceError::Parse {
+            origin: source,
+            value: value.to_string(),
+            error,
+        })
+}
+
+/// Reads the first version declared in an `.nvmrc` or `.node-version` file.
+/// Blank lines and `#` comments are ignored.
+fn parse_version_file(contents: &str) -> Option<&str> {
+    contents
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .find(|line| !line.is_empty())
+}
+
+/// Reads the preferred `nodejs` version from a `.tool-versions` file. Each
+/// line contains a tool name followed by one or more versions, where the
+/// first version is the preferred one.
+fn parse_tool_versions(contents: &str) -> Option<&str> {
+    contents.lines().find_map(|line| {
+        let mut parts = line
+            .split('#')
+            .next()
+            .unwrap_or_default()
+            .split_whitespace();
+        match parts.next() {
+            Some("nodejs" | "node") => parts.next(),
+            _ => None,
+        }
+    })
+}
+
+#[derive(Error, Debug)]
+pub enum NodeVersionSourceError {
+    #[error("Could not read {0}. {1}")]
+    Read(NodeVersionSource, std::io::Error),
+    #[error("{0} does not declare a Node.js version")]
+    Empty(NodeVersionSource),
+    #[error("Could not parse Node.js version `{value}` from {origin}. {error}")]
+    Parse {
+        origin: NodeVersionSource,
+        value: String,
+        error: VersionError,
+    },
+    #[error(
+        "No Node.js release line matches `{alias}` from {origin}. Check the spelling of the \
+        release alias or use a version range instead."
+    )]
+    UnknownAlias {
+        origin: NodeVersionSource,
+        alias: String,
+    },
+    #[error(
+        "Node.js version `{}` from {} conflicts with `{}` from {}. Update these \
+        sources so they declare compatible versions, or remove the one that's out of date.",
+        preferred.requested,
+        preferred.source,
+        conflicting.requested,
+        conflicting.source
+    )]
+    Conflict {
+        preferred: Box<DetectedNodeVersion>,
+        conflicting: Box<DetectedNodeVersion>,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::package_json::{Engines, Volta};
+    use crate::release_schedule::ReleaseLine;
+    use tempfile::TempDir;
+
+    fn package_json(engines_node: Option<&str>, volta_node: Option<&str>) -> PackageJson {
+        PackageJson {
+            engines: engines_node.map(|node| Engines {
+                node: Some(NodeRequirement::parse(node).unwrap()),
+                ..Engines::default()
+            }),
+            volta: volta_node.map(|node| Volta {
+                node: Some(Requirement::parse(node).unwrap()),
+            }),
+            ..PackageJson::default()
+        }
+    }
+
+    fn app_dir(files: &[(&str, &str)]) -> TempDir {
+        let app_dir = tempfile::tempdir().unwrap();
+        for (name, contents) in files {
+            fs::write(app_dir.path().join(name), contents).unwrap();
+        }
+        app_dir
+    }
+
+    fn today() -> NaiveDate {
+        "2025-01-01".parse().unwrap()
+    }
+
+    fn release_schedule() -> ReleaseSchedule {
+        ReleaseSchedule {
+            release_lines: vec![ReleaseLine {
+                major: 22,
+                codename: Some("Jod".to_string()),
+                start: "2024-04-24".parse().unwrap(),
+                lts_start: Some("2024-10-29".parse().unwrap()),
+                maintenance_start: Some("2025-10-21".parse().unwrap()),
+                end_of_life: "2027-04-30".parse().unwrap(),
+            }],
+        }
+    }
+
+    fn detect(
+        app_dir: &TempDir,
+        package_json: &PackageJson,
+    ) -> Result<Vec<DetectedNodeVersion>, NodeVersionSourceError> {
+        detect_node_versions(app_dir.path(), package_json, &release_schedule(), today())
+    }
+
+    fn detected_sources(detected: &[DetectedNodeVersion]) -> Vec<NodeVersionSource> {
+        detected.iter().map(|detected| detected.source).collect()
+    }
+
+    #[test]
+    fn detect_node_versions_with_no_sources() {
+        let app_dir = app_dir(&[]);
+        let detected = detect(&app_dir, &PackageJson::default()).unwrap();
+        assert!(detected.is_empty());
+    }
+
+    #[test]
+    fn detect_node_versions_orders_sources_by_precedence() {
+        let app_dir = app_dir(&[
+            (".tool-versions", "ruby 3.3.0\nnodejs 20.11.0 18.19.0\n"),
+            (".node-version", "20.11\n"),
+            (".nvmrc", "v20\n"),
+        ]);
+        let detected = detect(&app_dir, &package_json(Some("20.x"), Some("20.11.0"))).unwrap();
+        assert_eq!(
+            detected_sources(&detected),
+            NodeVersionSource::VALUES.to_vec()
+        );
+        assert_eq!(detected[0].requirement.to_string(), ">=20.0.0 <21.0.0-0");
+    }
+
+    #[test]
+    fn detect_node_versions_from_nvmrc_only() {
+        let app_dir = app_dir(&[(".nvmrc", "# pinned for local development\n\nv18.19.0\n")]);
+        let detected = detect(&app_dir, &PackageJson::default()).unwrap();
+        assert_eq!(detected_sources(&detected), vec![NodeVersionSource::Nvmrc]);
+        assert_eq!(detected[0].requirement.to_string(), "18.19.0");
+    }
+
+    #[test]
+    fn detect_node_versions_treats_node_alias_as_latest() {
+        let app_dir = app_dir(&[(".nvmrc", "node")]);
+        let detected = detect(&app_dir, &PackageJson::default()).unwrap();
+        assert_eq!(detected[0].requirement.to_string(), "*");
+    }
+
+    #[test]
+    fn detect_node_versions_ignores_tool_versions_without_nodejs() {
+        let app_dir = app_dir(&[(".tool-versions", "ruby 3.3.0\npython 3.12.1\n")]);
+        let detected = detect(&app_dir, &PackageJson::default()).unwrap();
+        assert!(detected.is_empty());
+    }
+
+    #[test]
+    fn detect_node_versions_errors_on_conflicting_sources() {
+        let app_dir = app_dir(&[(".nvmrc", "18.19.0")]);
+        let error = detect(&app_dir, &package_json(Some("20.x"), None)).unwrap_err();
+        match error {
+            NodeVersionSourceError::Conflict {
+                preferred,
+                conflicting,
+            } => {
+                assert_eq!(preferred.source, NodeVersionSource::PackageJsonEngines);
+                assert_eq!(conflicting.source, NodeVersionSource::Nvmrc);
+            }
+            e => panic!("Expected a conflict error but got {e:?}"),
+        }
+    }
+
+    #[test]
+    fn detect_node_versions_resolves_release_aliases() {
+        let app_dir = app_dir(&[(".nvmrc", "lts/jod\n")]);
+        let detected = detect(&app_dir, &package_json(Some("lts/*"), None)).unwrap();
+        assert_eq!(detected[0].requested.to_string(), "lts/*");
+        assert_eq!(detected[0].requirement.to_string(), ">=22.0.0 <23.0.0-0");
+        assert_eq!(detected[1].requested.to_string(), "lts/jod");
+    }
+
+    #[test]
+    fn detect_node_versions_errors_on_unknown_release_alias() {
+        let app_dir = app_dir(&[(".nvmrc", "lts/argon")]);
+        let error = detect(&app_dir, &PackageJson::default()).unwrap_err();
+        assert!(matches!(
+            error,
+            NodeVersionSourceError::UnknownAlias {
+                origin: NodeVersionSource::Nvmrc,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn detect_node_versions_errors_on_empty_version_file() {
+        let app_dir = app_dir(&[(".node-version", "\n")]);
+        let error = detect(&app_dir, &PackageJson::default()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            ".node-version does not declare a Node.js version"
+        );
+    }
+
+    #[test]
+    fn detect_node_versions_errors_on_invalid_version() {
+        let app_dir = app_dir(&[(".tool-versions", "nodejs ref:main")]);
+        let error = detect(&app_dir, &PackageJson::default()).unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("Could not parse Node.js version `ref:main` from .tool-versions."));
+    }
+}
@@ -1,4 +1,6 @@
+use crate::node_version_source::NodeRequirement;
 use crate::vrs::{Requirement, Version};
+use crate::workspaces::Workspaces;
 use serde::{de, Deserialize, Deserializer};
 use std::collections::HashMap;
 use std::fs::File;
@@ -14,15 +16,22 @@ pub struct PackageJson {
     pub engines: Option<Engines>,
     pub scripts: Option<Scripts>,
     pub main: Option<String>,
+    pub bin: Option<Bin>,
+    #[serde(rename = "type")]
+    pub module_type: Option<String>,
     pub dependencies: Option<HashMap<String, String>>,
     #[serde(rename = "devDependencies")]
     pub dev_dependencies: Option<HashMap<String, String>>,
+    #[serde(rename = "optionalDependencies")]
+    pub optional_dependencies: Option<HashMap<String, String>>,
     #[serde(
         default,
         deserialize_with = "deserialize_package_manager",
         rename = "packageManager"
     )]
     pub package_manager: Option<PackageManager>,
+    pub volta: Option<Volta>,
+    pub workspaces: Option<Workspaces>,
 }
 
 impl PackageJson {
@@ -32,16 +41,37 @@ impl PackageJson {
             .iter()
             .any(|dep_group| dep_group.is_some_and(|deps| !deps.is_empty()))
     }
+
+    /// Whether `.js` files in the package are ES modules (`"type": "module"`).
+    #[must_use]
+    pub fn is_module(&self) -> bool {
+        self.module_type.as_deref() == Some("module")
+    }
+}
+
+/// The executables declared in the `bin` field, either a single path named
+/// after the package or a map of command names to paths.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum Bin {
+    Path(String),
+    Commands(HashMap<String, String>),
 }
 
 #[derive(Deserialize, Debug, Default, Clone)]
 pub struct Engines {
-    pub node: Option<Requirement>,
+    pub node: Option<NodeRequirement>,
     pub npm: Option<Requirement>,
     pub pnpm: Option<Requirement>,
     pub yarn: Option<Requirement>,
 }
 
+/// Tool versions pinned by [Volta](https://volta.sh) in the `volta` field.
+#[derive(Deserialize, Debug, Default, Clone)]
+pub struct Volta {
+    pub node: Option<Requirement>,
+}
+
 #[derive(Deserialize, Debug, Default, Clone)]
 pub struct Scripts {
     pub start: Option<String>,
@@ -52,6 +82,15 @@ pub struct Scripts {
     pub heroku_build: Option<String>,
     #[serde(rename = "heroku-postbuild")]
     pub heroku_postbuild: Option<String>,
+    #[serde(rename = "heroku-cleanup")]
+    pub heroku_cleanup: Option<String>,
+    pub preinstall: Option<String>,
+    pub install: Option<String>,
+    pub postinstall: Option<String>,
+    pub prepare: Option<String>,
+    /// The other scripts, by name.
+    #[serde(flatten)]
+    pub other: HashMap<String, serde_json::Value>,
 }
 
 #[derive(Debug, Clone)]
@@ -82,24 +121,34 @@ impl PackageJson {
         serde_json::from_reader(rdr).map_err(PackageJsonError::ParseError)
     }
 
+    /// Whether the package defines lifecycle scripts that the package manager
+    /// runs against the package itself during an install.
     #[must_use]
-    /// Fetches the build scripts from a `PackageJson` and returns them in priority order
-    pub fn build_scripts(&self) -> Vec<String> {
-        let mut scripts = vec![];
-        if let Some(s) = &self.scripts {
-            if s.heroku_prebuild.is_some() {
-                scripts.push("heroku-prebuild".to_owned());
-            }
-            if s.heroku_build.is_some() {
-                scripts.push("heroku-build".to_owned());
-            } else if s.build.is_some() {
-                scripts.push("build".to_owned());
-            }
-            if s.heroku_postbuild.is_some() {
-                scripts.push("heroku-postbuild".to_owned());
-            }
-        }
-        scripts
+    pub fn has_install_scripts(&self) -> bool {
+        self.scripts.as_ref().is_some_and(|scripts| {
+            scripts.preinstall.is_some()
+                || scripts.install.is_some()
+                || scripts.postinstall.is_some()
+                || scripts.prepare.is_some()
+        })
+    }
+
+    /// Whether the package defines a script with the given name.
+    #[must_use]
+    pub fn has_script(&self, name: &str) -> bool {
+        self.scripts.as_ref().is_some_and(|scripts| match name {
+            "start" => scripts.start.is_some(),
+            "build" => scripts.build.is_some(),
+            "heroku-prebuild" => scripts.heroku_prebuild.is_some(),
+            "heroku-build" => scripts.heroku_build.is_some(),
+            "heroku-postbuild" => scripts.heroku_postbuild.is_some(),
+            "heroku-cleanup" => scripts.heroku_cleanup.is_some(),
+            "preinstall" => scripts.preinstall.is_some(),
+            "install" => scripts.install.is_some(),
+            "postinstall" => scripts.postinstall.is_some(),
+            "prepare" => scripts.prepare.is_some(),
+            _ => scripts.other.contains_key(name),
+        })
     }
 
     #[must_use]
@@ -185,6 +234,23 @@ mod tests {
         assert_eq!(&pkg.engines.unwrap().node.unwrap().to_string(), "16.0.0");
     }
 
+    #[test]
+    fn read_valid_package_with_node_engine_release_alias() {
+        let mut f = Builder::new().tempfile().unwrap();
+        write!(
+            f,
+            "{{
+            \"name\": \"foo\",
+            \"engines\": {{
+                \"node\": \"lts/iron\"
+            }}
+        }}"
+        )
+        .unwrap();
+        let pkg = PackageJson::read(f.path()).unwrap();
+        assert_eq!(&pkg.engines.unwrap().node.unwrap().to_string(), "lts/iron");
+    }
+
     #[test]
     fn read_valid_package_with_package_manager() {
         let mut f = Builder::new().tempfile().unwrap();
@@ -222,6 +288,35 @@ mod tests {
         assert!(err.contains("some-package-manager"));
     }
 
+    #[test]
+    fn read_valid_package_with_bin_and_type() {
+        let mut f = Builder::new().tempfile().unwrap();
+        write!(
+            f,
+            "{{
+            \"name\": \"foo\",
+            \"type\": \"module\",
+            \"bin\": {{ \"foo\": \"./bin/foo.js\" }}
+            }}"
+        )
+        .unwrap();
+        let pkg = PackageJson::read(f.path()).unwrap();
+        assert!(pkg.is_module());
+        assert_eq!(
+            pkg.bin,
+            Some(Bin::Commands(HashMap::from([(
+                "foo".to_string(),
+                "./bin/foo.js".to_string()
+            )])))
+        );
+
+        let mut f = Builder::new().tempfile().unwrap();
+        write!(f, "{{ \"bin\": \"cli.js\" }}").unwrap();
+        let pkg = PackageJson::read(f.path()).unwrap();
+        assert!(!pkg.is_module());
+        assert_eq!(pkg.bin, Some(Bin::Path("cli.js".to_string())));
+    }
+
     #[test]
     fn read_missing_package() {
         let res = PackageJson::read(Path::new("/over/there/package.json"));
@@ -243,35 +338,26 @@ mod tests {
     }
 
     #[test]
-    fn test_build_scripts_all() {
-        let pkg_json = PackageJson {
-            scripts: Some(Scripts {
-                build: Some("echo 'build'".to_owned()),
-                heroku_prebuild: Some("echo 'heroku-prebuild'".to_owned()),
-                heroku_build: Some("echo 'build'".to_owned()),
-                heroku_postbuild: Some("echo 'heroku-postbuild'".to_owned()),
-                ..Scripts::default()
-            }),
-            ..PackageJson::default()
-        };
-        let build_scripts = pkg_json.build_scripts();
-
-        assert_eq!("heroku-prebuild", build_scripts[0]);
-        assert_eq!("heroku-build", build_scripts[1]);
-        assert_eq!("heroku-postbuild", build_scripts[2]);
+    fn test_has_install_scripts() {
+        let pkg_json: PackageJson =
+            serde_json::from_str(r#"{ "scripts": { "postinstall": "patch-package" } }"#).unwrap();
+        assert!(pkg_json.has_install_scripts());
+        let pkg_json: PackageJson =
+            serde_json::from_str(r#"{ "scripts": { "build": "tsc" } }"#).unwrap();
+        assert!(!pkg_json.has_install_scripts());
+        assert!(!PackageJson::default().has_install_scripts());
     }
 
     #[test]
-    fn test_build_scripts_build_only() {
-        let pkg_json = PackageJson {
-            scripts: Some(Scripts {
-                build: Some("echo 'build'".to_owned()),
-                ..Scripts::default()
-            }),
-            ..PackageJson::default()
-        };
-        let build_scripts = pkg_json.build_scripts();
-
-        assert_eq!("build", build_scripts[0]);
+    fn test_has_script() {
+        let pkg_json: PackageJson = serde_json::from_str(
+            r#"{ "scripts": { "build": "tsc", "build:prod": "tsc -p prod" } }"#,
+        )
+        .unwrap();
+        assert!(pkg_json.has_script("build"));
+        assert!(pkg_json.has_script("build:prod"));
+        assert!(!pkg_json.has_script("heroku-build"));
+        assert!(!pkg_json.has_script("test"));
+        assert!(!PackageJson::default().has_script("build"));
     }
 }
@@ -0,0 +1,494 @@
+use crate::dependency_graph::{
+    DependencyGraph, DependencyGraphError, GraphBuilder, Package, PackageId,
+};
+use crate::package_json::PackageJson;
+use crate::package_manager::PackageManager;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+const NODE_MODULES: &str = "node_modules/";
+
+#[derive(Deserialize)]
+struct PackageLock {
+    #[serde(rename = "lockfileVersion")]
+    lockfile_version: u32,
+    #[serde(default)]
+    packages: BTreeMap<String, Entry>,
+    #[serde(default)]
+    dependencies: BTreeMap<String, LegacyEntry>,
+}
+
+/// An entry of `packages`, keyed by its install path, in lockfile versions 2
+/// and 3.
+#[derive(Deserialize)]
+struct Entry {
+    name: Option<String>,
+    version: Option<String>,
+    resolved: Option<String>,
+    integrity: Option<String>,
+    license: Option<Value>,
+    #[serde(default)]
+    link: bool,
+    #[serde(default)]
+    dependencies: BTreeMap<String, String>,
+    #[serde(default, rename = "devDependencies")]
+    dev_dependencies: BTreeMap<String, String>,
+    #[serde(default, rename = "optionalDependencies")]
+    optional_dependencies: BTreeMap<String, String>,
+    #[serde(default, rename = "peerDependencies")]
+    peer_dependencies: BTreeMap<String, String>,
+    engines: Option<Value>,
+    os: Option<Value>,
+    cpu: Option<Value>,
+}
+
+/// An entry of the nested `dependencies` tree in lockfile version 1.
+#[derive(Deserialize)]
+struct LegacyEntry {
+    version: String,
+    resolved: Option<String>,
+    integrity: Option<String>,
+    #[serde(default)]
+    requires: BTreeMap<String, String>,
+    #[serde(default)]
+    dependencies: BTreeMap<String, LegacyEntry>,
+}
+
+pub(crate) fn parse(
+    contents: &str,
+    package_json: &PackageJson,
+) -> Result<DependencyGraph, DependencyGraphError> {
+    let lock: PackageLock =
+        serde_json::from_str(contents).map_err(DependencyGraphError::PackageLock)?;
+    let builder = match lock.lockfile_version {
+        1 => from_dependencies(&lock.dependencies, package_json),
+        2 | 3 => from_packages(&lock.packages),
+        version => Err(DependencyGraphError::UnsupportedVersion {
+            lockfile: "package-lock.json",
+            version: version.to_string(),
+        })?,
+    };
+    Ok(builder.build(PackageManager::Npm, lock.lockfile_version.to_string()))
+}
+
+fn from_packages(packages: &BTreeMap<String, Entry>) -> GraphBuilder {
+    let mut builder = GraphBuilder::default();
+    let ids: BTreeMap<&str, PackageId> = packages
+        .iter()
+        .filter(|(path, entry)| is_installed(path) && !entry.link)
+        .filter_map(|(path, entry)| {
+            let name = entry
+                .name
+                .as_deref()
+                .or_else(|| path.rsplit(NODE_MODULES).next())?;
+            let package = Package {
+                name: name.to_string(),
+                version: entry.version.clone()?,
+                resolved: entry.resolved.clone(),
+                integrity: entry.integrity.clone(),
+                license: entry.license.as_ref().and_then(license),
+                engines: entry.engines.as_ref().map(engines).unwrap_or_default(),
+                os: entry.os.as_ref().map(strings).unwrap_or_default(),
+                cpu: entry.cpu.as_ref().map(strings).unwrap_or_default(),
+                ..Package::default()
+            };
+            Some((path.as_str(), builder.add_package(package)))
+        })
+        .collect();
+
+    let resolve =
+        |from: &str, name: &str| resolve(packages, from, name).and_then(|path| ids.get(path));
+    for (path, entry) in packages {
+        if let Some(id) = ids.get(path.as_str()) {
+            for name in entry
+                .dependencies
+                .keys()
+                .chain(entry.peer_dependencies.keys())
+            {
+                if let Some(dependency) = resolve(path, name) {
+                    builder.add_dependency(id, dependency.clone(), false);
+                }
+            }
+            for name in entry.optional_dependencies.keys() {
+                if let Some(dependency) = resolve(path, name) {
+                    builder.add_dependency(id, dependency.clone(), true);
+                }
+            }
+        } else if !is_installed(path) && !entry.link {
+            // The root package, at "", and workspace packages.
+            for (dependencies, roots) in [
+                (&entry.dependencies, &mut builder.root.dependencies),
+                (&entry.dev_dependencies, &mut builder.root.dev_dependencies),
+                (
+                    &entry.optional_dependencies,
+                    &mut builder.root.optional_dependencies,
+                ),
+            ] {
+                roots.extend(
+                    dependencies
+                        .keys()
+                        .filter_map(|name| resolve(path, name).cloned()),
+                );
+            }
+        }
+    }
+    builder
+}
+
+fn is_installed(path: &str) -> bool {
+    path.starts_with(NODE_MODULES) || path.contains(&format!("/{NODE_MODULES}"))
+}
+
+/// Finds the install path `name` resolves to from the package at `from`, like
+/// Node.js does: in the package's own `node_modules`, then in the
+/// `node_modules` of each parent. Dependencies on linked workspace packages
+/// don't resolve.
+fn resolve<'a>(packages: &'a BTreeMap<String, Entry>, from: &str, name: &str) -> Option<&'a str> {
+    let mut base = from;
+    loop {
+        let candidate = if base.is_empty() {
+            format!("{NODE_MODULES}{name}")
+        } else {
+            format!("{base}/{NODE_MODULES}{name}")
+        };
+        if let Some((path, entry)) = packages.get_key_value(&candidate) {
+            return (!entry.link).then_some(path.as_str());
+        }
+        if base.is_empty() {
+            return None;
+        }
+        base = base
+            .rfind(&format!("/{NODE_MODULES}"))
+            .map_or("", |index| &base[..index]);
+    }
+}
+
+fn from_dependencies(
+    dependencies: &BTreeMap<String, LegacyEntry>,
+    package_json: &PackageJson,
+) -> GraphBuilder {
+    let mut builder = GraphBuilder::default();
+    add_legacy_entries(&mut builder, &mut vec![dependencies]);
+    builder.add_package_json_roots(package_json, |name, _| {
+        dependencies
+            .get(name)
+            .map(|entry| PackageId::new(name, &entry.version))
+    });
+    builder
+}
+
+/// Adds the entries of the innermost scope in `scopes`, resolving their
+/// `requires` from the innermost scope outwards.
+fn add_legacy_entries(
+    builder: &mut GraphBuilder,
+    scopes: &mut Vec<&BTreeMap<String, LegacyEntry>>,
+) {
+    let Some(entries) = scopes.last().copied() else {
+        return;
+    };
+    for (name, entry) in entries {
+        let id = builder.add_package(Package {
+            name: name.clone(),
+            version: entry.version.clone(),
+            resolved: entry.resolved.clone(),
+            integrity: entry.integrity.clone(),
+            ..Package::default()
+        });
+        scopes.push(&entry.dependencies);
+        for dependency in entry.requires.keys() {
+            if let Some(resolved) = scopes.iter().rev().find_map(|scope| scope.get(dependency)) {
+                builder.add_dependency(&id, PackageId::new(dependency, &resolved.version), false);
+            }
+        }
+        add_legacy_entries(builder, scopes);
+        scopes.pop();
+    }
+}
+
+/// Reads `license` as either an SPDX expression or a legacy `{ "type": ... }`
+/// object.
+pub(crate) fn license(value: &Value) -> Option<String> {
+    match value {
+        Value::String(license) => Some(license.clone()),
+        Value::Object(object) => object.get("type")?.as_str().map(String::from),
+        _ => None,
+    }
+}
+
+/// Reads `engines` as an object of ranges. Legacy arrays like
+/// `["node >= 0.4"]` are ignored.
+fn engines(value: &Value) -> BTreeMap<String, String> {
+    value
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter_map(|(engine, range)| Some((engine.clone(), range.as_str()?.to_string())))
+        .collect()
+}
+
+fn strings(value: &Value) -> Vec<String> {
+    match value {
+        Value::String(value) => vec![value.clone()],
+        Value::Array(values) => values
+            .iter()
+            .filter_map(|value| value.as_str().map(String::from))
+            .collect(),
+        _ => vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    fn package_json(json: &str) -> PackageJson {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn id(name: &str, version: &str) -> PackageId {
+        PackageId::new(name, version)
+    }
+
+    #[test]
+    fn parse_lockfile_v3() {
+        let graph = parse(
+            r#"{
+                "name": "app",
+                "lockfileVersion": 3,
+                "packages": {
+                    "": {
+                        "name": "app",
+                        "workspaces": ["packages/*"],
+                        "dependencies": { "express": "^4.18.0" },
+                        "devDependencies": { "jest": "^29.0.0" },
+                        "optionalDependencies": { "fsevents": "^2.3.0" }
+                    },
+                    "node_modules/express": {
+                        "version": "4.18.2",
+                        "resolved": "https://registry.npmjs.org/express/-/express-4.18.2.tgz",
+                        "integrity": "sha512-express",
+                        "license": "MIT",
+                        "dependencies": { "debug": "2.6.9" },
+                        "engines": { "node": ">= 0.10.0" }
+                    },
+                    "node_modules/express/node_modules/debug": {
+                        "version": "2.6.9",
+                        "license": { "type": "MIT" },
+                        "dependencies": { "ms": "2.0.0" }
+                    },
+                    "node_modules/express/node_modules/ms": { "version": "2.0.0" },
+                    "node_modules/debug": {
+                        "version": "4.3.4",
+                        "dev": true,
+                        "dependencies": { "ms": "2.1.2" }
+                    },
+                    "node_modules/ms": { "version": "2.1.2", "dev": true },
+                    "node_modules/jest": {
+                        "version": "29.7.0",
+                        "dev": true,
+                        "dependencies": { "debug": "^4.3.4", "lib": "*" }
+                    },
+                    "node_modules/fsevents": {
+                        "version": "2.3.3",
+                        "optional": true,
+                        "os": ["darwin"],
+                        "cpu": ["arm64", "x64"]
+                    },
+                    "node_modules/lib": { "resolved": "packages/lib", "link": true },
+                    "node_modules/string-width-cjs": {
+                        "name": "string-width",
+                        "version": "4.2.3"
+                    },
+                    "packages/lib": {
+                        "version": "1.0.0",
+                        "dependencies": { "string-width-cjs": "npm:string-width@^4.2.0" }
+                    }
+                }
+            }"#,
+            &PackageJson::default(),
+        )
+        .unwrap();
+
+        assert_eq!(graph.lockfile_version, "3");
+        assert_eq!(
+            graph.root.dependencies,
+            BTreeSet::from([id("express", "4.18.2"), id("string-width", "4.2.3")])
+        );
+        assert_eq!(
+            graph.root.dev_dependencies,
+            BTreeSet::from([id("jest", "29.7.0")])
+        );
+        assert_eq!(
+            graph.root.optional_dependencies,
+            BTreeSet::from([id("fsevents", "2.3.3")])
+        );
+        assert_eq!(
+            graph.packages().map(Package::id).collect::<Vec<_>>(),
+            [
+                id("debug", "2.6.9"),
+                id("debug", "4.3.4"),
+                id("express", "4.18.2"),
+                id("fsevents", "2.3.3"),
+                id("jest", "29.7.0"),
+                id("ms", "2.0.0"),
+                id("ms", "2.1.2"),
+                id("string-width", "4.2.3"),
+            ]
+        );
+
+        let express = graph.get(&id("express", "4.18.2")).unwrap();
+        assert_eq!(
+            express.resolved.as_deref(),
+            Some("https://registry.npmjs.org/express/-/express-4.18.2.tgz")
+        );
+        assert_eq!(express.integrity.as_deref(), Some("sha512-express"));
+        assert_eq!(express.license.as_deref(), Some("MIT"));
+        assert_eq!(express.engines["node"], ">= 0.10.0");
+        assert_eq!(express.dependencies, BTreeSet::from([id("debug", "2.6.9")]));
+        assert!(!express.dev && !express.optional);
+
+        let debug = graph.get(&id("debug", "2.6.9")).unwrap();
+        assert_eq!(debug.license.as_deref(), Some("MIT"));
+        assert_eq!(debug.dependencies, BTreeSet::from([id("ms", "2.0.0")]));
+
+        assert!(graph.get(&id("ms", "2.1.2")).unwrap().dev);
+        assert!(!graph.get(&id("string-width", "4.2.3")).unwrap().dev);
+
+        let fsevents = graph.get(&id("fsevents", "2.3.3")).unwrap();
+        assert!(fsevents.optional && !fsevents.dev);
+        assert_eq!(fsevents.os, ["darwin"]);
+        assert_eq!(fsevents.cpu, ["arm64", "x64"]);
+    }
+
+    #[test]
+    fn parse_lockfile_v2() {
+        let graph = parse(
+            r#"{
+                "name": "app",
+                "lockfileVersion": 2,
+                "requires": true,
+                "packages": {
+                    "": {
+                        "name": "app",
+                        "workspaces": ["packages/*"],
+                        "dependencies": { "debug": "^4.3.4" }
+                    },
+                    "node_modules/debug": {
+                        "version": "4.3.4",
+                        "resolved": "https://registry.npmjs.org/debug/-/debug-4.3.4.tgz",
+                        "integrity": "sha512-debug",
+                        "dependencies": { "ms": "2.1.2" }
+                    },
+                    "node_modules/lib": { "resolved": "packages/lib", "link": true },
+                    "node_modules/ms": { "version": "2.1.2" },
+                    "node_modules/semver": { "version": "7.6.0", "dev": true },
+                    "packages/lib": {
+                        "version": "1.0.0",
+                        "devDependencies": { "semver": "^7.6.0" }
+                    }
+                },
+                "dependencies": {
+                    "debug": {
+                        "version": "4.3.4",
+                        "resolved": "https://registry.npmjs.org/debug/-/debug-4.3.4.tgz",
+                        "integrity": "sha512-debug",
+                        "requires": { "ms": "2.1.2" }
+                    },
+                    "lib": {
+                        "version": "file:packages/lib",
+                        "requires": { "semver": "^7.6.0" }
+                    },
+                    "ms": { "version": "2.1.2" },
+                    "semver": { "version": "7.6.0", "dev": true }
+                }
+            }"#,
+            &PackageJson::default(),
+        )
+        .unwrap();
+
+        assert_eq!(graph.lockfile_version, "2");
+        assert_eq!(
+            graph.root.dependencies,
+            BTreeSet::from([id("debug", "4.3.4")])
+        );
+        assert_eq!(
+            graph.root.dev_dependencies,
+            BTreeSet::from([id("semver", "7.6.0")])
+        );
+        assert_eq!(
+            graph.packages().map(Package::id).collect::<Vec<_>>(),
+            [
+                id("debug", "4.3.4"),
+                id("ms", "2.1.2"),
+                id("semver", "7.6.0")
+            ]
+        );
+        assert_eq!(
+            graph.get(&id("debug", "4.3.4")).unwrap().dependencies,
+            BTreeSet::from([id("ms", "2.1.2")])
+        );
+        assert!(graph.get(&id("semver", "7.6.0")).unwrap().dev);
+    }
+
+    #[test]
+    fn parse_lockfile_v1() {
+        let graph = parse(
+            r#"{
+                "name": "app",
+                "lockfileVersion": 1,
+                "requires": true,
+                "dependencies": {
+                    "debug": {
+                        "version": "2.6.9",
+                        "resolved": "https://registry.npmjs.org/debug/-/debug-2.6.9.tgz",
+                        "integrity": "sha512-debug",
+                        "requires": { "ms": "2.0.0" },
+                        "dependencies": {
+                            "ms": { "version": "2.0.0" }
+                        }
+                    },
+                    "ms": { "version": "2.1.2", "dev": true },
+                    "mocha": {
+                        "version": "10.0.0",
+                        "dev": true,
+                        "requires": { "ms": "^2.1.0" }
+                    }
+                }
+            }"#,
+            &package_json(
+                r#"{ "dependencies": { "debug": "^2.6.0" }, "devDependencies": { "mocha": "^10.0.0" } }"#,
+            ),
+        )
+        .unwrap();
+
+        assert_eq!(graph.lockfile_version, "1");
+        assert_eq!(graph.len(), 4);
+        assert_eq!(
+            graph.root.dependencies,
+            BTreeSet::from([id("debug", "2.6.9")])
+        );
+        let debug = graph.get(&id("debug", "2.6.9")).unwrap();
+        assert_eq!(debug.integrity.as_deref(), Some("sha512-debug"));
+        assert_eq!(debug.dependencies, BTreeSet::from([id("ms", "2.0.0")]));
+        assert_eq!(
+            graph.get(&id("mocha", "10.0.0")).unwrap().dependencies,
+            BTreeSet::from([id("ms", "2.1.2")])
+        );
+        assert!(!graph.get(&id("ms", "2.0.0")).unwrap().dev);
+        assert!(graph.get(&id("ms", "2.1.2")).unwrap().dev);
+    }
+
+    #[test]
+    fn parse_invalid_lockfiles() {
+        assert!(matches!(
+            parse(r#"{ "lockfileVersion": 4 }"#, &PackageJson::default()).unwrap_err(),
+            DependencyGraphError::UnsupportedVersion { version, .. } if version == "4"
+        ));
+        assert!(matches!(
+            parse("{", &PackageJson::default()).unwrap_err(),
+            DependencyGraphError::PackageLock(_)
+        ));
+    }
+}
@@ -0,0 +1,359 @@
+use crate::dependency_graph::{
+    split_descriptor, yaml_scalar as scalar, yaml_strings as strings, DependencyGraph,
+    DependencyGraphError, GraphBuilder, Package, PackageId,
+};
+use crate::package_manager::PackageManager;
+use serde_yaml::{Mapping, Value};
+use std::collections::BTreeMap;
+
+/// Parses `pnpm-lock.yaml` versions 6 to 9. Version 9 splits each package into
+/// its metadata under `packages`, keyed like `lodash@4.17.21`, and its
+/// installed instances under `snapshots`, keyed with any peer dependencies
+/// like `react-dom@18.2.0(react@18.2.0)`. Earlier versions have both under
+/// `packages`, keyed like `/lodash@4.17.21`.
+pub(crate) fn parse(contents: &str) -> Result<DependencyGraph, DependencyGraphError> {
+    let lockfile: Value = serde_yaml::from_str(contents).map_err(DependencyGraphError::PnpmLock)?;
+    let version = lockfile
+        .get("lockfileVersion")
+        .and_then(scalar)
+        .unwrap_or_default();
+    if !matches!(
+        version.split('.').next().map(str::parse::<u32>),
+        Some(Ok(6..=9))
+    ) {
+        Err(DependencyGraphError::UnsupportedVersion {
+            lockfile: "pnpm-lock.yaml",
+            version: version.clone(),
+        })?;
+    }
+
+    let empty = Mapping::new();
+    let packages = lockfile
+        .get("packages")
+        .and_then(Value::as_mapping)
+        .unwrap_or(&empty);
+    let snapshots = lockfile.get("snapshots").and_then(Value::as_mapping);
+    let instances = snapshots.unwrap_or(packages);
+
+    let mut builder = GraphBuilder::default();
+    let mut ids = BTreeMap::new();
+    for (key, instance) in instances {
+        let Some(key) = key.as_str() else { continue };
+        let metadata = if snapshots.is_some() {
+            packages.get(strip_peers(key)).unwrap_or(&Value::Null)
+        } else {
+            instance
+        };
+        if let Some(package) = package(key, metadata) {
+            ids.insert(key, builder.add_package(package));
+        }
+    }
+
+    let resolve = |name: &str, reference: &str| -> Option<PackageId> {
+        [
+            format!("{name}@{reference}"),
+            format!("/{name}@{reference}"),
+            reference.to_string(),
+            format!("/{reference}"),
+        ]
+        .iter()
+        .find_map(|key| ids.get(key.as_str()))
+        .cloned()
+    };
+
+    for (key, instance) in instances {
+        let Some(id) = key.as_str().and_then(|key| ids.get(key)) else {
+            continue;
+        };
+        for (field, optional) in [("dependencies", false), ("optionalDependencies", true)] {
+            for (name, reference) in references(instance.get(field)) {
+                if let Some(dependency) = resolve(&name, &reference) {
+                    builder.add_dependency(id, dependency, optional);
+                }
+            }
+        }
+    }
+
+    // Workspaces list each package under `importers`, keyed by its path.
+    // Version 6 lockfiles without workspaces list the root package's
+    // dependencies at the top level instead.
+    let importers: Vec<&Value> = match lockfile.get("importers").and_then(Value::as_mapping) {
+        Some(importers) => importers.values().collect(),
+        None => vec![&lockfile],
+    };
+    for importer in importers {
+        for (field, roots) in [
+            ("dependencies", &mut builder.root.dependencies),
+            ("devDependencies", &mut builder.root.dev_dependencies),
+            (
+                "optionalDependencies",
+                &mut builder.root.optional_dependencies,
+            ),
+        ] {
+            roots.extend(
+                references(importer.get(field))
+                    .into_iter()
+                    .filter_map(|(name, reference)| resolve(&name, &reference)),
+            );
+        }
+    }
+
+    Ok(builder.build(PackageManager::Pnpm, version))
+}
+
+fn package(key: &str, metadata: &Value) -> Option<Package> {
+    let (name, version) = if let (Some(name), Some(version)) = (
+        metadata.get("name").and_then(scalar),
+        metadata.get("version").and_then(scalar),
+    ) {
+        (name, version)
+    } else {
+        let (name, version) = split_descriptor(strip_peers(key.trim_start_matches('/')))?;
+        (name.to_string(), version.to_string())
+    };
+    let resolution = metadata.get("resolution");
+    Some(Package {
+        name,
+        version,
+        resolved: resolution.and_then(|resolution| scalar(resolution.get("tarball")?)),
+        integrity: resolution.and_then(|resolution| scalar(resolution.get("integrity")?)),
+        engines: references(metadata.get("engines")).into_iter().collect(),
+        os: strings(metadata.get("os")),
+        cpu: strings(metadata.get("cpu")),
+        ..Package::default()
+    })
+}
+
+/// Removes the peer dependencies suffix from an instance key, like the
+/// `(react@18.2.0)` in `react-dom@18.2.0(react@18.2.0)`.
+fn strip_peers(key: &str) -> &str {
+    key.find('(').map_or(key, |index| &key[..index])
+}
+
+/// Reads a mapping of dependency names to the version they resolved to. In
+/// `importers`, and at the top level of version 6 lockfiles, the version is
+/// nested next to the specifier from `package.json`:
+///
+/// ```yaml
+/// dependencies:
+///   lodash:
+///     specifier: ^4.17.21
+///     version: 4.17.21
+/// ```
+fn references(value: Option<&Value>) -> Vec<(String, String)> {
+    value
+        .and_then(Value::as_mapping)
+        .into_iter()
+        .flatten()
+        .filter_map(|(name, reference)| {
+            let reference = match reference {
+                Value::Mapping(_) => scalar(reference.get("version")?)?,
+                reference => scalar(reference)?,
+            };
+            Some((scalar(name)?, reference))
+        })
+        .filter(|(_, reference)| !reference.starts_with("link:"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use std::collections::BTreeSet;
+
+    fn id(name: &str, version: &str) -> PackageId {
+        PackageId::new(name, version)
+    }
+
+    #[test]
+    fn parse_lockfile_v9() {
+        let graph = parse(indoc! {"
+            lockfileVersion: '9.0'
+
+            settings:
+              autoInstallPeers: true
+              excludeLinksFromLockfile: false
+
+            importers:
+
+              .:
+                dependencies:
+                  react-dom:
+                    specifier: ^18.2.0
+                    version: 18.2.0(react@18.2.0)
+                  lib:
+                    specifier: workspace:*
+                    version: link:packages/lib
+                devDependencies:
+                  typescript:
+                    specifier: ^5.4.0
+                    version: 5.4.5
+
+              packages/lib:
+                dependencies:
+                  string-width-cjs:
+                    specifier: npm:string-width@^4.2.0
+                    version: string-width@4.2.3
+                optionalDependencies:
+                  fsevents:
+                    specifier: ^2.3.2
+                    version: 2.3.3
+
+            packages:
+
+              fsevents@2.3.3:
+                resolution: {integrity: sha512-fsevents}
+                engines: {node: ^8.16.0 || ^10.6.0 || >=11.0.0}
+                os: [darwin]
+
+              js-tokens@4.0.0:
+                resolution: {integrity: sha512-js-tokens}
+
+              react-dom@18.2.0:
+                resolution: {integrity: sha512-react-dom}
+                peerDependencies:
+                  react: ^18.2.0
+
+              react@18.2.0:
+                resolution: {integrity: sha512-react}
+                engines: {node: '>=0.10.0'}
+
+              string-width@4.2.3:
+                resolution: {tarball: https://example.com/string-width-4.2.3.tgz}
+                engines: {node: '>=8'}
+
+              typescript@5.4.5:
+                resolution: {integrity: sha512-typescript}
+                engines: {node: '>=14.17'}
+                hasBin: true
+
+            snapshots:
+
+              fsevents@2.3.3:
+                optional: true
+
+              js-tokens@4.0.0: {}
+
+              react-dom@18.2.0(react@18.2.0):
+                dependencies:
+                  react: 18.2.0
+
+              react@18.2.0:
+                dependencies:
+                  js-tokens: 4.0.0
+
+              string-width@4.2.3: {}
+
+              typescript@5.4.5: {}
+        "})
+        .unwrap();
+
+        assert_eq!(graph.lockfile_version, "9.0");
+        assert_eq!(graph.len(), 6);
+        assert_eq!(
+            graph.root.dependencies,
+            BTreeSet::from([id("react-dom", "18.2.0"), id("string-width", "4.2.3")])
+        );
+        assert_eq!(
+            graph.root.dev_dependencies,
+            BTreeSet::from([id("typescript", "5.4.5")])
+        );
+        assert_eq!(
+            graph.root.optional_dependencies,
+            BTreeSet::from([id("fsevents", "2.3.3")])
+        );
+
+        let react_dom = graph.get(&id("react-dom", "18.2.0")).unwrap();
+        assert_eq!(react_dom.integrity.as_deref(), Some("sha512-react-dom"));
+        assert_eq!(
+            react_dom.dependencies,
+            BTreeSet::from([id("react", "18.2.0")])
+        );
+        assert!(!graph.get(&id("js-tokens", "4.0.0")).unwrap().dev);
+        assert!(graph.get(&id("typescript", "5.4.5")).unwrap().dev);
+        assert_eq!(
+            graph.get(&id("react", "18.2.0")).unwrap().engines["node"],
+            ">=0.10.0"
+        );
+        assert_eq!(
+            graph
+                .get(&id("string-width", "4.2.3"))
+                .unwrap()
+                .resolved
+                .as_deref(),
+            Some("https://example.com/string-width-4.2.3.tgz")
+        );
+        let fsevents = graph.get(&id("fsevents", "2.3.3")).unwrap();
+        assert_eq!(fsevents.os, ["darwin"]);
+        assert!(fsevents.optional && !fsevents.dev);
+    }
+
+    #[test]
+    fn parse_lockfile_v6() {
+        let graph = parse(indoc! {"
+            lockfileVersion: '6.0'
+
+            dependencies:
+              debug:
+                specifier: ^4.3.4
+                version: 4.3.4
+
+            devDependencies:
+              ms:
+                specifier: 2.1.3
+                version: 2.1.3
+
+            packages:
+
+              /debug@4.3.4:
+                resolution: {integrity: sha512-debug}
+                engines: {node: '>=6.0'}
+                peerDependencies:
+                  supports-color: '*'
+                peerDependenciesMeta:
+                  supports-color:
+                    optional: true
+                dependencies:
+                  ms: 2.1.2
+                dev: false
+
+              /ms@2.1.2:
+                resolution: {integrity: sha512-ms-2.1.2}
+                dev: false
+
+              /ms@2.1.3:
+                resolution: {integrity: sha512-ms-2.1.3}
+                dev: true
+        "})
+        .unwrap();
+
+        assert_eq!(graph.lockfile_version, "6.0");
+        assert_eq!(
+            graph.packages().map(Package::id).collect::<Vec<_>>(),
+            [id("debug", "4.3.4"), id("ms", "2.1.2"), id("ms", "2.1.3")]
+        );
+        assert_eq!(
+            graph.root.dependencies,
+            BTreeSet::from([id("debug", "4.3.4")])
+        );
+        assert_eq!(
+            graph.get(&id("debug", "4.3.4")).unwrap().dependencies,
+            BTreeSet::from([id("ms", "2.1.2")])
+        );
+        assert!(!graph.get(&id("ms", "2.1.2")).unwrap().dev);
+        assert!(graph.get(&id("ms", "2.1.3")).unwrap().dev);
+    }
+
+    #[test]
+    fn parse_unsupported_lockfile() {
+        assert_eq!(
+            parse("lockfileVersion: 5.4\n").unwrap_err().to_string(),
+            "Unsupported pnpm-lock.yaml version `5.4`"
+        );
+        assert!(matches!(
+            parse("lockfileVersion: [").unwrap_err(),
+            DependencyGraphError::PnpmLock(_)
+        ));
+    }
+}
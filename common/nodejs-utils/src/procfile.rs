@@ -0,0 +1,208 @@
+use libcnb::data::launch::{Launch, LaunchBuilder, ProcessBuilder, ProcessType};
+use std::path::Path;
+use thiserror::Error;
+
+/// The process type that's made the default process when it's declared.
+const DEFAULT_PROCESS_TYPE: &str = "web";
+
+/// The processes declared in an app's `Procfile`, in the order they appear.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Procfile {
+    pub processes: Vec<ProcfileProcess>,
+}
+
+/// A single `<process type>: <command>` entry from a `Procfile`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcfileProcess {
+    pub process_type: ProcessType,
+    pub command: String,
+}
+
+impl Procfile {
+    /// Reads and parses the `Procfile` in the app directory, if there is one.
+    ///
+    /// # Errors
+    ///
+    /// Will return a `ProcfileError` if the `Procfile` can't be read or has an
+    /// invalid entry.
+    pub fn read(app_dir: &Path) -> Result<Option<Self>, ProcfileError> {
+        let path = app_dir.join("Procfile");
+        if !path.exists() {
+            return Ok(None);
+        }
+        std::fs::read_to_string(path)
+            .map_err(ProcfileError::Read)
+            .and_then(|contents| Self::parse(&contents))
+            .map(Some)
+    }
+
+    /// Parses the contents of a `Procfile`. Blank lines and lines starting with
+    /// `#` are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Will return a `ProcfileError` with the line number of the first entry
+    /// that isn't formatted as `<process type>: <command>`, has an invalid
+    /// process type, or declares a process type that was already declared.
+    pub fn parse(contents: &str) -> Result<Self, ProcfileError> {
+        let mut processes: Vec<(usize, ProcfileProcess)> = vec![];
+        for (index, line) in contents.lines().enumerate() {
+            let line_number = index + 1;
+            let trimmed = line.trim().trim_start_matches('\u{feff}');
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            let (process_type, command) = trimmed
+                .split_once(':')
+                .map(|(process_type, command)| (process_type.trim(), command.trim()))
+                .filter(|(process_type, command)| !process_type.is_empty() && !command.is_empty())
+                .ok_or_else(|| ProcfileError::InvalidEntry {
+                    line: line_number,
+                    content: trimmed.to_string(),
+                })?;
+
+            let process_type = process_type.parse::<ProcessType>().map_err(|_| {
+                ProcfileError::InvalidProcessType {
+                    line: line_number,
+                    process_type: process_type.to_string(),
+                }
+            })?;
+
+            if let Some((first_line, _)) = processes
+                .iter()
+                .find(|(_, process)| process.process_type == process_type)
+            {
+                return Err(ProcfileError::DuplicateProcessType {
+                    line: line_number,
+                    first_line: *first_line,
+                    process_type: process_type.to_string(),
+                });
+            }
+
+            processes.push((
+                line_number,
+                ProcfileProcess {
+                    process_type,
+                    command: command.to_string(),
+                },
+            ));
+        }
+        Ok(Procfile {
+            processes: processes.into_iter().map(|(_, process)| process).collect(),
+        })
+    }
+
+    /// Converts the processes into launch processes that run their commands
+    /// with `bash -c`. The `web` process is the default.
+    #[must_use]
+    pub fn launch(&self) -> Launch {
+        let mut launch_builder = LaunchBuilder::new();
+        for process in &self.processes {
+            launch_builder.process(
+                ProcessBuilder::new(
+                    process.process_type.clone(),
+                    ["bash", "-c", process.command.as_str()],
+                )
+                .default(process.process_type.as_str() == DEFAULT_PROCESS_TYPE)
+                .build(),
+            );
+        }
+        launch_builder.build()
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ProcfileError {
+    #[error("Couldn't read Procfile: {0}")]
+    Read(std::io::Error),
+    #[error("Invalid Procfile entry on line {line}: `{content}`. Entries must be formatted as `<process type>: <command>`, like `web: node server.js`.")]
+    InvalidEntry { line: usize, content: String },
+    #[error("Invalid process type `{process_type}` in Procfile on line {line}. Process types may only contain letters, numbers, `.`, `_`, and `-`.")]
+    InvalidProcessType { line: usize, process_type: String },
+    #[error("Duplicate process type `{process_type}` in Procfile on line {line}, it was already declared on line {first_line}.")]
+    DuplicateProcessType {
+        line: usize,
+        first_line: usize,
+        process_type: String,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libcnb::data::process_type;
+
+    #[test]
+    fn parse_procfile() {
+        let procfile = Procfile::parse(
+            "# processes\nweb: node server.js --port $PORT\n\n  worker:npm run worker  \r\nclock: node clock.js # every minute\n",
+        )
+        .unwrap();
+        assert_eq!(
+            procfile.processes,
+            [
+                ProcfileProcess {
+                    process_type: process_type!("web"),
+                    command: "node server.js --port $PORT".to_string(),
+                },
+                ProcfileProcess {
+                    process_type: process_type!("worker"),
+                    command: "npm run worker".to_string(),
+                },
+                ProcfileProcess {
+                    process_type: process_type!("clock"),
+                    command: "node clock.js # every minute".to_string(),
+                },
+            ]
+        );
+        assert_eq!(Procfile::parse("").unwrap(), Procfile::default());
+    }
+
+    #[test]
+    fn parse_procfile_errors() {
+        assert!(matches!(
+            Procfile::parse("web: node server.js\nworker node worker.js"),
+            Err(ProcfileError::InvalidEntry { line: 2, content }) if content == "worker node worker.js"
+        ));
+        assert!(matches!(
+            Procfile::parse("web:"),
+            Err(ProcfileError::InvalidEntry { line: 1, .. })
+        ));
+        assert!(matches!(
+            Procfile::parse("\n\nweb job: node server.js"),
+            Err(ProcfileError::InvalidProcessType { line: 3, process_type }) if process_type == "web job"
+        ));
+        assert!(matches!(
+            Procfile::parse("web: node a.js\nworker: node b.js\nweb: node c.js"),
+            Err(ProcfileError::DuplicateProcessType { line: 3, first_line: 1, process_type }) if process_type == "web"
+        ));
+    }
+
+    #[test]
+    fn procfile_launch_processes() {
+        let launch = Procfile::parse("worker: node worker.js\nweb: node server.js")
+            .unwrap()
+            .launch();
+        assert_eq!(launch.processes.len(), 2);
+        assert_eq!(launch.processes[0].r#type, process_type!("worker"));
+        assert_eq!(
+            launch.processes[0].command,
+            ["bash", "-c", "node worker.js"]
+        );
+        assert!(!launch.processes[0].default);
+        assert_eq!(launch.processes[1].r#type, process_type!("web"));
+        assert!(launch.processes[1].default);
+    }
+
+    #[test]
+    fn read_procfile() {
+        let app_dir = tempfile::tempdir().unwrap();
+        assert_eq!(Procfile::read(app_dir.path()).unwrap(), None);
+        std::fs::write(app_dir.path().join("Procfile"), "web: node index.js").unwrap();
+        assert_eq!(
+            Procfile::read(app_dir.path()).unwrap().unwrap().processes[0].command,
+            "node index.js"
+        );
+    }
+}
@@ -0,0 +1,80 @@
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+/// Sums the size of the files in the given directories, without following
+/// symlinks. Directories that don't exist are counted as empty.
+///
+/// # Errors
+///
+/// Will return an `std::io::Error` if a directory can't be read.
+pub fn disk_usage(paths: &[&Path]) -> Result<u64, std::io::Error> {
+    paths.iter().try_fold(0, |total, path| {
+        match fs::symlink_metadata(path) {
+            Ok(metadata) if metadata.is_dir() => fs::read_dir(path)?
+                .try_fold(0, |size, entry| Ok(size + disk_usage(&[&entry?.path()])?)),
+            Ok(metadata) => Ok(metadata.len()),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(0),
+            Err(error) => Err(error),
+        }
+        .map(|size| total + size)
+    })
+}
+
+/// The size of the installed dependencies before and after pruning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PruneSummary {
+    pub before: u64,
+    pub after: u64,
+}
+
+impl Display for PruneSummary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Removed {} of dev dependencies ({} to {})",
+            format_mib(self.before.saturating_sub(self.after)),
+            format_mib(self.before),
+            format_mib(self.after)
+        )
+    }
+}
+
+fn format_mib(bytes: u64) -> String {
+    let tenths_of_mib = bytes * 10 / (1024 * 1024);
+    format!("{}.{} MiB", tenths_of_mib / 10, tenths_of_mib % 10)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disk_usage_of_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let node_modules = dir.path().join("node_modules");
+        fs::create_dir_all(node_modules.join("dep")).unwrap();
+        fs::write(node_modules.join("dep/index.js"), "12345").unwrap();
+        fs::write(node_modules.join(".package-lock.json"), "123").unwrap();
+        std::os::unix::fs::symlink(dir.path(), node_modules.join("link")).unwrap();
+
+        let size = disk_usage(&[&node_modules, &dir.path().join("missing")]).unwrap();
+        let link_size = fs::symlink_metadata(node_modules.join("link"))
+            .unwrap()
+            .len();
+        assert_eq!(size, 8 + link_size);
+    }
+
+    #[test]
+    fn prune_summary() {
+        assert_eq!(
+            PruneSummary {
+                before: 52_428_800,
+                after: 1_572_864
+            }
+            .to_string(),
+            "Removed 48.5 MiB of dev dependencies (50.0 MiB to 1.5 MiB)"
+        );
+    }
+}
@@ -0,0 +1,512 @@
+use libcnb::Env;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+use thiserror::Error;
+use url::Url;
+
+/// Environment variable that points at the directory containing the service
+/// bindings. Falls back to `$CNB_PLATFORM_DIR/bindings` when it isn't set.
+pub const SERVICE_BINDING_ROOT_ENV_VAR: &str = "SERVICE_BINDING_ROOT";
+
+/// Binding type whose `.npmrc` entry is used as-is by npm, pnpm, and Yarn 1.
+pub const NPMRC_BINDING_TYPE: &str = "npmrc";
+/// Binding type whose `.yarnrc.yml` entry is used as-is by Yarn 2+.
+pub const YARNRC_BINDING_TYPE: &str = "yarnrc";
+/// Binding type with `registry`, and optionally `scope` and `token`, entries
+/// that's rendered into the config of every package manager.
+pub const NPM_REGISTRY_BINDING_TYPE: &str = "npm-registry";
+
+/// The per-user config file a package manager reads registry credentials from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserConfigFormat {
+    /// `.npmrc`, read by npm, pnpm, and Yarn 1 from `NPM_CONFIG_USERCONFIG`.
+    Npmrc,
+    /// `.yarnrc.yml`, read by Yarn 2+ from `$HOME`.
+    Yarnrc,
+}
+
+/// A registry credential read from a service binding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegistryCredential {
+    Npmrc {
+        binding: String,
+        contents: String,
+    },
+    Yarnrc {
+        binding: String,
+        contents: String,
+    },
+    Registry {
+        binding: String,
+        registry: Url,
+        scope: Option<String>,
+        token: Option<String>,
+    },
+}
+
+impl RegistryCredential {
+    #[must_use]
+    pub fn binding(&self) -> &str {
+        match self {
+            RegistryCredential::Npmrc { binding, .. }
+            | RegistryCredential::Yarnrc { binding, .. }
+            | RegistryCredential::Registry { binding, .. } => binding,
+        }
+    }
+}
+
+/// The registry credentials from all service bindings with a supported type,
+/// sorted by binding name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RegistryCredentials(pub Vec<RegistryCredential>);
+
+impl RegistryCredentials {
+    /// Reads the registry credentials from the service bindings in
+    /// `$SERVICE_BINDING_ROOT` (or `$CNB_PLATFORM_DIR/bindings`). Bindings of
+    /// other types are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Will return a `RegistryCredentialsError` if the bindings can't be read
+    /// or a supported binding is missing an entry or has an invalid registry.
+    pub fn read(env: &Env) -> Result<Self, RegistryCredentialsError> {
+        match service_bindings_dir(env) {
+            Some(bindings_dir) => Self::read_dir(&bindings_dir),
+            None => Ok(Self::default()),
+        }
+    }
+
+    fn read_dir(bindings_dir: &Path) -> Result<Self, RegistryCredentialsError> {
+        let read_error =
+            |path: &Path, e| RegistryCredentialsError::ReadBinding(path.to_path_buf(), e);
+        let entries = match fs::read_dir(bindings_dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(read_error(bindings_dir, e)),
+        };
+
+        let mut credentials = vec![];
+        for entry in entries {
+            let path = entry.map_err(|e| read_error(bindings_dir, e))?.path();
+            let Some(binding) = binding_name(&path) else {
+                continue;
+            };
+            if !path.is_dir() {
+                continue;
+            }
+            let binding_type = match fs::read_to_string(path.join("type")) {
+                Ok(binding_type) => binding_type.trim().to_string(),
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => return Err(read_error(&path.join("type"), e)),
+            };
+            let entry = |name: &str| match fs::read_to_string(path.join(name)) {
+                Ok(value) => Ok(Some(value)),
+                Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(read_error(&path.join(name), e)),
+            };
+            let required_entry = |name: &str| {
+                entry(name)?.ok_or_else(|| RegistryCredentialsError::MissingEntry {
+                    binding: binding.clone(),
+                    entry: name.to_string(),
+                })
+            };
+
+            credentials.push(match binding_type.as_str() {
+                NPMRC_BINDING_TYPE => RegistryCredential::Npmrc {
+                    contents: required_entry(".npmrc")?,
+                    binding,
+                },
+                YARNRC_BINDING_TYPE => RegistryCredential::Yarnrc {
+                    contents: required_entry(".yarnrc.yml")?,
+                    binding,
+                },
+                NPM_REGISTRY_BINDING_TYPE => {
+                    let registry = required_entry("registry")?;
+                    RegistryCredential::Registry {
+                        registry: parse_registry(registry.trim()).ok_or_else(|| {
+                            RegistryCredentialsError::InvalidRegistry {
+                                binding: binding.clone(),
+                                registry: registry.trim().to_string(),
+                            }
+                        })?,
+                        scope: entry("scope")?
+                            .map(|scope| scope.trim().trim_start_matches('@').to_string())
+                            .filter(|scope| !scope.is_empty()),
+                        token: entry("token")?
+                            .map(|token| token.trim().to_string())
+                            .filter(|token| !token.is_empty()),
+                        binding,
+                    }
+                }
+                _ => continue,
+            });
+        }
+        credentials.sort_by(|a, b| a.binding().cmp(b.binding()));
+        Ok(RegistryCredentials(credentials))
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The names of the bindings that apply to the given config format.
+    #[must_use]
+    pub fn bindings(&self, format: UserConfigFormat) -> Vec<&str> {
+        self.applicable(format)
+            .map(RegistryCredential::binding)
+            .collect()
+    }
+
+    fn applicable(
+        &self,
+        format: UserConfigFormat,
+    ) -> impl Iterator<Item = &RegistryCredential> + '_ {
+        self.0.iter().filter(move |credential| {
+            matches!(
+                (credential, format),
+                (RegistryCredential::Registry { .. }, _)
+                    | (RegistryCredential::Npmrc { .. }, UserConfigFormat::Npmrc)
+                    | (RegistryCredential::Yarnrc { .. }, UserConfigFormat::Yarnrc)
+            )
+        })
+    }
+
+    /// Renders the credentials as an `.npmrc` file.
+    #[must_use]
+    pub fn render_npmrc(&self) -> String {
+        let mut npmrc = String::new();
+        for credential in self.applicable(UserConfigFormat::Npmrc) {
+            match credential {
+                RegistryCredential::Npmrc { contents, .. } => {
+                    npmrc.push_str(contents);
+                    if !contents.ends_with('\n') {
+                        npmrc.push('\n');
+                    }
+                }
+                RegistryCredential::Registry {
+                    registry,
+                    scope,
+                    token,
+                    ..
+                } => {
+                    match scope {
+                        Some(scope) => writeln!(npmrc, "@{scope}:registry={registry}"),
+                        None => writeln!(npmrc, "registry={registry}"),
+                    }
+                    .expect("Writing to a String shouldn't fail");
+                    if let Some(token) = token {
+                        // Auth settings are keyed by the registry URL without its scheme.
+                        let (_, registry) = registry.as_str().split_once(':').unwrap_or_default();
+                        writeln!(npmrc, "{registry}:_authToken={token}")
+                            .expect("Writing to a String shouldn't fail");
+                    }
+                }
+                RegistryCredential::Yarnrc { .. } => {}
+            }
+        }
+        npmrc
+    }
+
+    /// Renders the credentials as a `.yarnrc.yml` file for Yarn 2+.
+    #[must_use]
+    pub fn render_yarnrc(&self) -> String {
+        let mut yarnrc = String::new();
+        let mut registries = String::new();
+        let mut scopes = String::new();
+        for credential in self.applicable(UserConfigFormat::Yarnrc) {
+            match credential {
+                RegistryCredential::Yarnrc { contents, .. } => {
+                    yarnrc.push_str(contents);
+                    if !contents.ends_with('\n') {
+                        yarnrc.push('\n');
+                    }
+                }
+                RegistryCredential::Registry {
+                    registry,
+                    scope,
+                    token,
+                    ..
+                } => {
+                    let server = yaml_string(registry.as_str().trim_end_matches('/'));
+                    match scope {
+                        Some(scope) => writeln!(
+                            scopes,
+                            "  {}:\n    npmRegistryServer: {server}",
+                            yaml_string(scope)
+                        ),
+                        None => writeln!(yarnrc, "npmRegistryServer: {server}"),
+                    }
+                    .expect("Writing to a String shouldn't fail");
+                    if let Some(token) = token {
+                        writeln!(
+                            registries,
+                            "  {server}:\n    npmAlwaysAuth: true\n    npmAuthToken: {}",
+                            yaml_string(token)
+                        )
+                        .expect("Writing to a String shouldn't fail");
+                    }
+                }
+                RegistryCredential::Npmrc { .. } => {}
+            }
+        }
+        if !registries.is_empty() {
+            yarnrc.push_str("npmRegistries:\n");
+            yarnrc.push_str(&registries);
+        }
+        if !scopes.is_empty() {
+            yarnrc.push_str("npmScopes:\n");
+            yarnrc.push_str(&scopes);
+        }
+        yarnrc
+    }
+
+    /// Writes the credentials that apply to `format` to a per-user config file
+    /// in a new temporary directory. Returns `None` if no credentials apply.
+    ///
+    /// The credentials only exist for as long as the returned `UserConfig` is
+    /// kept alive. Use `UserConfig::apply` to build the environment for the
+    /// commands that need them, so they never end up in a layer or the app
+    /// directory.
+    ///
+    /// # Errors
+    ///
+    /// Will return a `RegistryCredentialsError` if the config can't be written.
+    pub fn write_user_config(
+        &self,
+        format: UserConfigFormat,
+    ) -> Result<Option<UserConfig>, RegistryCredentialsError> {
+        if self.applicable(format).next().is_none() {
+            return Ok(None);
+        }
+        let dir = tempfile::tempdir().map_err(RegistryCredentialsError::WriteUserConfig)?;
+        let (env_var, env_value) = match format {
+            UserConfigFormat::Npmrc => {
+                let npmrc = dir.path().join(".npmrc");
+                fs::write(&npmrc, self.render_npmrc())
+                    .map_err(RegistryCredentialsError::WriteUserConfig)?;
+                ("NPM_CONFIG_USERCONFIG", npmrc)
+            }
+            UserConfigFormat::Yarnrc => {
+                fs::write(dir.path().join(".yarnrc.yml"), self.render_yarnrc())
+                    .map_err(RegistryCredentialsError::WriteUserConfig)?;
+                ("HOME", dir.path().to_path_buf())
+            }
+        };
+        Ok(Some(UserConfig {
+            env_var,
+            env_value,
+            _dir: dir,
+        }))
+    }
+}
+
+/// A temporary directory holding rendered per-user config. It's deleted when
+/// dropped.
+#[derive(Debug)]
+pub struct UserConfig {
+    env_var: &'static str,
+    env_value: PathBuf,
+    _dir: TempDir,
+}
+
+impl UserConfig {
+    /// Returns a copy of `env` that points the package manager at this config:
+    /// `NPM_CONFIG_USERCONFIG` for `.npmrc`, or `HOME` for `.yarnrc.yml`.
+    #[must_use]
+    pub fn apply(&self, env: &Env) -> Env {
+        let mut env = env.clone();
+        env.insert(self.env_var, &self.env_value);
+        env
+    }
+}
+
+/// The directory with the service bindings: `$SERVICE_BINDING_ROOT`, or
+/// `$CNB_PLATFORM_DIR/bindings`.
+pub(crate) fn service_bindings_dir(env: &Env) -> Option<PathBuf> {
+    env.get(SERVICE_BINDING_ROOT_ENV_VAR)
+        .map(PathBuf::from)
+        .or_else(|| {
+            env.get("CNB_PLATFORM_DIR")
+                .map(|dir| Path::new(&dir).join("bindings"))
+        })
+}
+
+// Kubernetes mounts bindings with hidden `..data` style entries next to the
+// binding directories.
+pub(crate) fn binding_name(path: &Path) -> Option<String> {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .filter(|name| !name.starts_with('.'))
+        .map(ToString::to_string)
+}
+
+fn parse_registry(registry: &str) -> Option<Url> {
+    let mut url = Url::parse(registry).ok()?;
+    if !matches!(url.scheme(), "http" | "https") || url.cannot_be_a_base() {
+        return None;
+    }
+    if !url.path().ends_with('/') {
+        url.set_path(&format!("{}/", url.path()));
+    }
+    Some(url)
+}
+
+fn yaml_string(value: &str) -> String {
+    serde_json::to_string(value).expect("Serializing a string shouldn't fail")
+}
+
+#[derive(Error, Debug)]
+pub enum RegistryCredentialsError {
+    #[error("Couldn't read service binding {0}: {1}")]
+    ReadBinding(PathBuf, std::io::Error),
+    #[error("Service binding `{binding}` is missing the `{entry}` entry")]
+    MissingEntry { binding: String, entry: String },
+    #[error("Service binding `{binding}` has an invalid `registry` URL: {registry}")]
+    InvalidRegistry { binding: String, registry: String },
+    #[error("Couldn't write the registry credentials: {0}")]
+    WriteUserConfig(std::io::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn binding(root: &Path, name: &str, entries: &[(&str, &str)]) {
+        let dir = root.join(name);
+        fs::create_dir_all(&dir).unwrap();
+        for (entry, value) in entries {
+            fs::write(dir.join(entry), value).unwrap();
+        }
+    }
+
+    fn credentials(root: &Path) -> RegistryCredentials {
+        let mut env = Env::new();
+        env.insert(SERVICE_BINDING_ROOT_ENV_VAR, root);
+        RegistryCredentials::read(&env).unwrap()
+    }
+
+    #[test]
+    fn read_bindings() {
+        let root = tempfile::tempdir().unwrap();
+        binding(
+            root.path(),
+            "private",
+            &[
+                ("type", "npm-registry\n"),
+                ("registry", "https://npm.acme.com/api"),
+                ("scope", "@acme"),
+                ("token", "s3cr3t\n"),
+            ],
+        );
+        binding(
+            root.path(),
+            "github",
+            &[("type", "npmrc"), (".npmrc", "a=b")],
+        );
+        binding(root.path(), "postgres", &[("type", "postgresql")]);
+        binding(root.path(), "..data", &[("type", "npmrc")]);
+
+        let credentials = credentials(root.path());
+        assert_eq!(
+            credentials.bindings(UserConfigFormat::Npmrc),
+            ["github", "private"]
+        );
+        assert_eq!(credentials.bindings(UserConfigFormat::Yarnrc), ["private"]);
+        assert_eq!(
+            credentials.0[1],
+            RegistryCredential::Registry {
+                binding: "private".to_string(),
+                registry: Url::parse("https://npm.acme.com/api/").unwrap(),
+                scope: Some("acme".to_string()),
+                token: Some("s3cr3t".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn read_invalid_bindings() {
+        let root = tempfile::tempdir().unwrap();
+        binding(root.path(), "npmrc", &[("type", "npmrc")]);
+        let mut env = Env::new();
+        env.insert(SERVICE_BINDING_ROOT_ENV_VAR, root.path());
+        assert_eq!(
+            RegistryCredentials::read(&env).unwrap_err().to_string(),
+            "Service binding `npmrc` is missing the `.npmrc` entry"
+        );
+
+        fs::remove_dir_all(root.path().join("npmrc")).unwrap();
+        binding(
+            root.path(),
+            "private",
+            &[("type", "npm-registry"), ("registry", "npm.acme.com")],
+        );
+        assert_eq!(
+            RegistryCredentials::read(&env).unwrap_err().to_string(),
+            "Service binding `private` has an invalid `registry` URL: npm.acme.com"
+        );
+
+        assert!(credentials(&root.path().join("missing")).is_empty());
+    }
+
+    #[test]
+    fn render_user_config() {
+        let credentials = RegistryCredentials(vec![
+            RegistryCredential::Npmrc {
+                binding: "a".to_string(),
+                contents: "always-auth=true".to_string(),
+            },
+            RegistryCredential::Registry {
+                binding: "b".to_string(),
+                registry: Url::parse("https://npm.acme.com/api/").unwrap(),
+                scope: Some("acme".to_string()),
+                token: Some("s3cr3t".to_string()),
+            },
+            RegistryCredential::Registry {
+                binding: "c".to_string(),
+                registry: Url::parse("https://mirror.acme.com/").unwrap(),
+                scope: None,
+                token: None,
+            },
+        ]);
+
+        assert_eq!(
+            credentials.render_npmrc(),
+            "always-auth=true\n\
+             @acme:registry=https://npm.acme.com/api/\n\
+             //npm.acme.com/api/:_authToken=s3cr3t\n\
+             registry=https://mirror.acme.com/\n"
+        );
+        assert_eq!(
+            credentials.render_yarnrc(),
+            "npmRegistryServer: \"https://mirror.acme.com\"\n\
+             npmRegistries:\n  \"https://npm.acme.com/api\":\n    npmAlwaysAuth: true\n    npmAuthToken: \"s3cr3t\"\n\
+             npmScopes:\n  \"acme\":\n    npmRegistryServer: \"https://npm.acme.com/api\"\n"
+        );
+    }
+
+    #[test]
+    fn write_user_config() {
+        let credentials = RegistryCredentials(vec![RegistryCredential::Npmrc {
+            binding: "a".to_string(),
+            contents: "always-auth=true\n".to_string(),
+        }]);
+        assert!(credentials
+            .write_user_config(UserConfigFormat::Yarnrc)
+            .unwrap()
+            .is_none());
+
+        let user_config = credentials
+            .write_user_config(UserConfigFormat::Npmrc)
+            .unwrap()
+            .unwrap();
+        let env = user_config.apply(&Env::new());
+        let npmrc = PathBuf::from(env.get("NPM_CONFIG_USERCONFIG").unwrap());
+        assert_eq!(fs::read_to_string(&npmrc).unwrap(), "always-auth=true\n");
+        drop(user_config);
+        assert!(!npmrc.exists());
+    }
+}
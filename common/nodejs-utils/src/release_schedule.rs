@@ -0,0 +1,361 @@
+use crate::vrs::{Requirement, Version};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use thiserror::Error;
+
+/// The number of days before a release line's end-of-life date where builds
+/// should start warning about the upcoming end of support.
+pub const END_OF_LIFE_WARNING_DAYS: i64 = 90;
+
+/// Lifecycle information for the Node.js release lines (major versions) in an
+/// inventory, as published in the Node.js Release Working Group
+/// [schedule](https://github.com/nodejs/Release/blob/main/schedule.json).
+#[derive(Deserialize, Serialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct ReleaseSchedule {
+    #[serde(default)]
+    pub release_lines: Vec<ReleaseLine>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ReleaseLine {
+    pub major: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub codename: Option<String>,
+    pub start: NaiveDate,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lts_start: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub maintenance_start: Option<NaiveDate>,
+    pub end_of_life: NaiveDate,
+}
+
+impl ReleaseLine {
+    fn is_released(&self, today: NaiveDate) -> bool {
+        self.start <= today
+    }
+
+    fn is_lts(&self, today: NaiveDate) -> bool {
+        self.lts_start.is_some_and(|lts_start| lts_start <= today)
+    }
+
+    fn requirement(&self) -> Requirement {
+        Requirement::parse(&format!("{}.x", self.major))
+            .expect("A major version range should be a valid requirement")
+    }
+}
+
+#[derive(Deserialize)]
+struct UpstreamReleaseLine {
+    start: NaiveDate,
+    lts: Option<NaiveDate>,
+    maintenance: Option<NaiveDate>,
+    end: NaiveDate,
+    codename: Option<String>,
+}
+
+/// How well a Node.js version is supported upstream on a given date.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SupportStatus {
+    Supported,
+    NearingEndOfLife(NaiveDate),
+    EndOfLife(NaiveDate),
+    Unknown,
+}
+
+impl ReleaseSchedule {
+    /// Reads the Node.js Release Working Group's `schedule.json`, keeping only
+    /// the release lines for `majors`. Entries for pre-1.0 release lines (like
+    /// `v0.12`) are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Invalid/malformed JSON will return a `serde_json::Error`
+    pub fn from_nodejs_schedule(
+        schedule_json: &str,
+        majors: &[u64],
+    ) -> Result<Self, serde_json::Error> {
+        let upstream: HashMap<String, UpstreamReleaseLine> = serde_json::from_str(schedule_json)?;
+        let mut release_lines = upstream
+            .into_iter()
+            .filter_map(|(key, line)| {
+                key.strip_prefix('v')
+                    .and_then(|major| major.parse::<u64>().ok())
+                    .filter(|major| majors.contains(major))
+                    .map(|major| ReleaseLine {
+                        major,
+                        codename: line.codename.filter(|codename| !codename.is_empty()),
+                        start: line.start,
+                        lts_start: line.lts,
+                        maintenance_start: line.maintenance,
+                        end_of_life: line.end,
+                    })
+            })
+            .collect::<Vec<_>>();
+        release_lines.sort_by_key(|line| Reverse(line.major));
+        Ok(ReleaseSchedule { release_lines })
+    }
+
+    /// Finds the lifecycle information for a Node.js major version.
+    #[must_use]
+    pub fn release_line(&self, major: u64) -> Option<&ReleaseLine> {
+        self.release_lines.iter().find(|line| line.major == major)
+    }
+
+    /// The newest release line that has entered LTS as of `today`.
+    #[must_use]
+    pub fn latest_lts(&self, today: NaiveDate) -> Option<&ReleaseLine> {
+        self.release_lines
+            .iter()
+            .filter(|line| line.is_lts(today))
+            .max_by_key(|line| line.major)
+    }
+
+    /// The newest release line that has been released as of `today`.
+    #[must_use]
+    pub fn current(&self, today: NaiveDate) -> Option<&ReleaseLine> {
+        self.release_lines
+            .iter()
+            .filter(|line| line.is_released(today))
+            .max_by_key(|line| line.major)
+    }
+
+    /// The requirement used when an application doesn't request a Node.js
+    /// version, which is the newest LTS release line.
+    #[must_use]
+    pub fn default_requirement(&self, today: NaiveDate) -> Option<Requirement> {
+        self.latest_lts(today).map(ReleaseLine::requirement)
+    }
+
+    /// Resolves a release alias to the version range of its release line.
+    #[must_use]
+    pub fn resolve_alias(&self, alias: &ReleaseAlias, today: NaiveDate) -> Option<Requirement> {
+        match alias {
+            ReleaseAlias::LatestLts => self.latest_lts(today),
+            ReleaseAlias::Current => self.current(today),
+            ReleaseAlias::Lts(codename) => self.release_lines.iter().find(|line| {
+                line.is_lts(today)
+                    && line
+                        .codename
+                        .as_ref()
+                        .is_some_and(|name| name.eq_ignore_ascii_case(codename))
+            }),
+        }
+        .map(ReleaseLine::requirement)
+    }
+
+    /// Determines the upstream support status of a Node.js version on `today`.
+    #[must_use]
+    pub fn support_status(&self, version: &Version, today: NaiveDate) -> SupportStatus {
+        match self.release_line(version.major()) {
+            None => SupportStatus::Unknown,
+            Some(line) if line.end_of_life <= today => SupportStatus::EndOfLife(line.end_of_life),
+            Some(line) if (line.end_of_life - today).num_days() <= END_OF_LIFE_WARNING_DAYS => {
+                SupportStatus::NearingEndOfLife(line.end_of_life)
+            }
+            Some(_) => SupportStatus::Supported,
+        }
+    }
+}
+
+/// Named aliases for Node.js release lines, as supported by tools like `nvm`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReleaseAlias {
+    /// `lts/*`, the newest LTS release line.
+    LatestLts,
+    /// `lts/<codename>`, the LTS release line with the given codename.
+    Lts(String),
+    /// `current`, the newest release line.
+    Current,
+}
+
+#[derive(Error, Debug)]
+#[error("Unknown Node.js release alias `{0}`")]
+pub struct ReleaseAliasError(String);
+
+impl FromStr for ReleaseAlias {
+    type Err = ReleaseAliasError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim().to_lowercase();
+        match value.as_str() {
+            "lts/*" | "lts" => Ok(ReleaseAlias::LatestLts),
+            "current" => Ok(ReleaseAlias::Current),
+            _ => match value.strip_prefix("lts/") {
+                Some(codename) if !codename.is_empty() => {
+                    Ok(ReleaseAlias::Lts(codename.to_string()))
+                }
+                _ => Err(ReleaseAliasError(value)),
+            },
+        }
+    }
+}
+
+impl Display for ReleaseAlias {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReleaseAlias::LatestLts => write!(f, "lts/*"),
+            ReleaseAlias::Lts(codename) => write!(f, "lts/{codename}"),
+            ReleaseAlias::Current => write!(f, "current"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(value: &str) -> NaiveDate {
+        value.parse().unwrap()
+    }
+
+    fn schedule() -> ReleaseSchedule {
+        toml::from_str(
+            r#"
+[[release_lines]]
+major = 20
+codename = "Iron"
+start = "2023-04-18"
+lts_start = "2023-10-24"
+maintenance_start = "2024-10-22"
+end_of_life = "2026-04-30"
+
+[[release_lines]]
+major = 21
+start = "2023-10-17"
+maintenance_start = "2024-04-01"
+end_of_life = "2024-06-01"
+
+[[release_lines]]
+major = 22
+codename = "Jod"
+start = "2024-04-24"
+lts_start = "2024-10-29"
+maintenance_start = "2025-10-21"
+end_of_life = "2027-04-30"
+
+[[release_lines]]
+major = 23
+start = "2024-10-16"
+maintenance_start = "2025-04-01"
+end_of_life = "2025-06-01"
+"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn from_nodejs_schedule_keeps_requested_majors() {
+        let schedule = ReleaseSchedule::from_nodejs_schedule(
+            r#"{
+                "v0.12": { "start": "2015-02-06", "end": "2016-12-31" },
+                "v21": { "start": "2023-10-17", "codename": "", "maintenance": "2024-04-01", "end": "2024-06-01" },
+                "v22": { "start": "2024-04-24", "lts": "2024-10-29", "maintenance": "2025-10-21", "end": "2027-04-30", "codename": "Jod" },
+                "v24": { "start": "2025-05-06", "lts": "2025-10-28", "maintenance": "2026-10-20", "end": "2028-04-30", "codename": "" }
+            }"#,
+            &[0, 21, 22],
+        )
+        .unwrap();
+        assert_eq!(
+            schedule.release_lines,
+            vec![
+                ReleaseLine {
+                    major: 22,
+                    codename: Some("Jod".to_string()),
+                    start: date("2024-04-24"),
+                    lts_start: Some(date("2024-10-29")),
+                    maintenance_start: Some(date("2025-10-21")),
+                    end_of_life: date("2027-04-30"),
+                },
+                ReleaseLine {
+                    major: 21,
+                    codename: None,
+                    start: date("2023-10-17"),
+                    lts_start: None,
+                    maintenance_start: Some(date("2024-04-01")),
+                    end_of_life: date("2024-06-01"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_release_aliases() {
+        assert_eq!(
+            "lts/*".parse::<ReleaseAlias>().unwrap(),
+            ReleaseAlias::LatestLts
+        );
+        assert_eq!(
+            "lts/Iron".parse::<ReleaseAlias>().unwrap(),
+            ReleaseAlias::Lts("iron".to_string())
+        );
+        assert_eq!(
+            "current".parse::<ReleaseAlias>().unwrap(),
+            ReleaseAlias::Current
+        );
+        assert!("lts/".parse::<ReleaseAlias>().is_err());
+        assert!("20.x".parse::<ReleaseAlias>().is_err());
+    }
+
+    #[test]
+    fn latest_lts_depends_on_date() {
+        let schedule = schedule();
+        assert_eq!(schedule.latest_lts(date("2024-10-28")).unwrap().major, 20);
+        assert_eq!(schedule.latest_lts(date("2024-10-29")).unwrap().major, 22);
+        assert_eq!(
+            schedule
+                .default_requirement(date("2025-01-01"))
+                .unwrap()
+                .to_string(),
+            ">=22.0.0 <23.0.0-0"
+        );
+    }
+
+    #[test]
+    fn resolve_aliases() {
+        let schedule = schedule();
+        let today = date("2025-01-01");
+        assert_eq!(
+            schedule
+                .resolve_alias(&ReleaseAlias::Lts("iron".to_string()), today)
+                .unwrap()
+                .to_string(),
+            ">=20.0.0 <21.0.0-0"
+        );
+        assert_eq!(
+            schedule
+                .resolve_alias(&ReleaseAlias::Current, today)
+                .unwrap()
+                .to_string(),
+            ">=23.0.0 <24.0.0-0"
+        );
+        assert!(schedule
+            .resolve_alias(&ReleaseAlias::Lts("argon".to_string()), today)
+            .is_none());
+    }
+
+    #[test]
+    fn support_status_for_versions() {
+        let schedule = schedule();
+        let today = date("2026-02-15");
+        assert_eq!(
+            schedule.support_status(&Version::parse("22.1.0").unwrap(), today),
+            SupportStatus::Supported
+        );
+        assert_eq!(
+            schedule.support_status(&Version::parse("20.11.0").unwrap(), today),
+            SupportStatus::NearingEndOfLife(date("2026-04-30"))
+        );
+        assert_eq!(
+            schedule.support_status(&Version::parse("23.7.0").unwrap(), today),
+            SupportStatus::EndOfLife(date("2025-06-01"))
+        );
+        assert_eq!(
+            schedule.support_status(&Version::parse("0.8.6").unwrap(), today),
+            SupportStatus::Unknown
+        );
+    }
+}
@@ -0,0 +1,81 @@
+use crate::vrs::{Version, VersionError};
+use libcnb::Env;
+use std::path::PathBuf;
+
+/// Environment variable the Node.js engine buildpack sets to the installed
+/// Node.js version.
+pub const NODE_VERSION_ENV_VAR: &str = "NODE_VERSION";
+
+/// Environment variable the Node.js engine buildpack sets to the directory
+/// Node.js is installed into.
+pub const NODE_HOME_ENV_VAR: &str = "NODE_HOME";
+
+/// The Node.js installation published by the Node.js engine buildpack for
+/// later buildpacks in the group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedNode {
+    pub version: Version,
+    pub home: PathBuf,
+}
+
+/// Reads the Node.js installation published by the Node.js engine buildpack.
+/// Returns `None` if no installation was published, which happens when Node.js
+/// was provided by something other than the Node.js engine buildpack.
+pub fn read_resolved_node(env: &Env) -> Result<Option<ResolvedNode>, ResolvedNodeError> {
+    let (Some(version), Some(home)) = (
+        env.get_string_lossy(NODE_VERSION_ENV_VAR),
+        env.get(NODE_HOME_ENV_VAR),
+    ) else {
+        return Ok(None);
+    };
+    Version::parse(&version)
+        .map(|version| {
+            Some(ResolvedNode {
+                version,
+                home: PathBuf::from(home),
+            })
+        })
+        .map_err(|e| ResolvedNodeError::InvalidVersion(version, e))
+}
+
+#[derive(Debug)]
+pub enum ResolvedNodeError {
+    InvalidVersion(String, VersionError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_resolved_node_when_published() {
+        let mut env = Env::new();
+        env.insert(NODE_VERSION_ENV_VAR, "22.13.1");
+        env.insert(NODE_HOME_ENV_VAR, "/layers/heroku_nodejs-engine/dist");
+        assert_eq!(
+            read_resolved_node(&env).unwrap(),
+            Some(ResolvedNode {
+                version: Version::parse("22.13.1").unwrap(),
+                home: PathBuf::from("/layers/heroku_nodejs-engine/dist"),
+            })
+        );
+    }
+
+    #[test]
+    fn read_resolved_node_when_not_published() {
+        let mut env = Env::new();
+        env.insert(NODE_VERSION_ENV_VAR, "22.13.1");
+        assert_eq!(read_resolved_node(&env).unwrap(), None);
+    }
+
+    #[test]
+    fn read_resolved_node_with_invalid_version() {
+        let mut env = Env::new();
+        env.insert(NODE_VERSION_ENV_VAR, "twenty-two");
+        env.insert(NODE_HOME_ENV_VAR, "/layers/heroku_nodejs-engine/dist");
+        assert!(matches!(
+            read_resolved_node(&env).unwrap_err(),
+            ResolvedNodeError::InvalidVersion(..)
+        ));
+    }
+}
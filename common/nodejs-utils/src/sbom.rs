@@ -0,0 +1,670 @@
+use crate::dependency_graph::{DependencyGraph, DependencyGraphError, Package};
+use crate::package_json::PackageJson;
+use crate::package_manager::PackageManager;
+use base64::prelude::{Engine, BASE64_STANDARD};
+use libcnb::data::sbom::SbomFormat;
+use libcnb::sbom::Sbom;
+use regex::Regex;
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::sync::LazyLock;
+
+const CYCLONEDX_SPEC_VERSION: &str = "1.5";
+const SPDX_VERSION: &str = "SPDX-2.3";
+const SPDX_NAMESPACE_BASE: &str = "https://github.com/heroku/buildpacks-nodejs/spdx";
+/// The creation time of SPDX SBOMs. The lifecycle gives images the same
+/// timestamp so builds are reproducible, and the SBOMs only change along with
+/// the packages they list.
+const SPDX_CREATED: &str = "1980-01-01T00:00:01Z";
+const NOASSERTION: &str = "NOASSERTION";
+/// The CycloneDX npm taxonomy property for development dependencies.
+const CYCLONEDX_DEVELOPMENT_PROPERTY: &str = "cdx:npm:package:development";
+
+/// Matches licenses that are SPDX expressions, like `MIT` or
+/// `(Apache-2.0 OR MIT)`, rather than free text like `SEE LICENSE IN LICENSE`.
+static LICENSE_EXPRESSION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\(?[A-Za-z0-9.+-]+\)?( (AND|OR|WITH) \(?[A-Za-z0-9.+-]+\)?)*$")
+        .expect("License expression regex should be valid")
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComponentType {
+    Library,
+    /// A runtime that executes the application, like Node.js.
+    Platform,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    Sha1,
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl HashAlgorithm {
+    fn cyclonedx(self) -> &'static str {
+        match self {
+            HashAlgorithm::Sha1 => "SHA-1",
+            HashAlgorithm::Sha256 => "SHA-256",
+            HashAlgorithm::Sha384 => "SHA-384",
+            HashAlgorithm::Sha512 => "SHA-512",
+        }
+    }
+
+    fn spdx(self) -> &'static str {
+        match self {
+            HashAlgorithm::Sha1 => "SHA1",
+            HashAlgorithm::Sha256 => "SHA256",
+            HashAlgorithm::Sha384 => "SHA384",
+            HashAlgorithm::Sha512 => "SHA512",
+        }
+    }
+}
+
+/// A software component listed in an SBOM, identified by its package URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SbomComponent {
+    pub component_type: ComponentType,
+    pub name: String,
+    pub version: String,
+    pub purl: String,
+    /// Hashes of the downloaded package, as lowercase hex.
+    pub hashes: Vec<(HashAlgorithm, String)>,
+    pub download_url: Option<String>,
+    pub license: Option<String>,
+    pub dev: bool,
+    pub optional: bool,
+    /// Whether the component was removed before the image was exported, like
+    /// dev dependencies after pruning.
+    pub pruned: bool,
+    /// The package URLs of the components this one depends on.
+    pub dependencies: Vec<String>,
+}
+
+impl SbomComponent {
+    /// A component for a package from the lockfile. Dev dependencies are
+    /// marked as `pruned` when `pruned` is set.
+    #[must_use]
+    pub fn from_package(package: &Package, pruned: bool) -> Self {
+        SbomComponent {
+            component_type: ComponentType::Library,
+            name: package.name.clone(),
+            version: package.version.clone(),
+            purl: npm_purl(&package.name, &package.version),
+            hashes: package
+                .integrity
+                .as_deref()
+                .map(parse_integrity)
+                .unwrap_or_default(),
+            download_url: package.resolved.clone().filter(|resolved| {
+                resolved.starts_with("https://") || resolved.starts_with("http://")
+            }),
+            license: package.license.clone(),
+            dev: package.dev,
+            optional: package.optional,
+            pruned: pruned && package.dev,
+            dependencies: package
+                .dependencies
+                .iter()
+                .chain(&package.optional_dependencies)
+                .map(|id| npm_purl(&id.name, &id.version))
+                .collect(),
+        }
+    }
+
+    /// A component for the Node.js runtime downloaded from `download_url`.
+    #[must_use]
+    pub fn node_runtime(version: &str, download_url: &str, sha256: &[u8]) -> Self {
+        SbomComponent {
+            component_type: ComponentType::Platform,
+            name: "node".to_string(),
+            version: version.to_string(),
+            purl: format!(
+                "pkg:generic/node@{}?download_url={}",
+                percent_encode(version),
+                percent_encode(download_url)
+            ),
+            hashes: vec![(HashAlgorithm::Sha256, hex::encode(sha256))],
+            download_url: Some(download_url.to_string()),
+            license: Some("MIT".to_string()),
+            dev: false,
+            optional: false,
+            pruned: false,
+            dependencies: vec![],
+        }
+    }
+}
+
+/// Reads the SBOMs of the dependencies installed from the app's lockfile.
+/// When `pruned` is set, dev dependencies are listed as excluded from the
+/// image. See [`SbomDocument::sboms`] for `spdx`.
+///
+/// # Errors
+///
+/// Will return an error if the lockfile can't be read or parsed.
+pub fn read_dependency_sboms(
+    app_dir: &Path,
+    package_manager: PackageManager,
+    package_json: &PackageJson,
+    pruned: bool,
+    spdx: bool,
+) -> Result<Vec<Sbom>, DependencyGraphError> {
+    let graph = DependencyGraph::read(app_dir, package_manager, package_json)?;
+    let name = package_json.name.as_deref().unwrap_or("app");
+    Ok(SbomDocument::from_dependency_graph(name, &graph, pruned).sboms(spdx))
+}
+
+/// An SBOM that renders as CycloneDX or SPDX JSON.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SbomDocument {
+    /// The name of the application or artifact the SBOM describes.
+    pub name: String,
+    pub components: Vec<SbomComponent>,
+    /// The package URLs of the components the application depends on
+    /// directly. When empty, the SBOM describes every component.
+    pub direct_dependencies: Vec<String>,
+}
+
+impl SbomDocument {
+    /// An SBOM of the packages installed from a lockfile. When `pruned` is
+    /// set, dev dependencies are listed as excluded from the image.
+    #[must_use]
+    pub fn from_dependency_graph(name: &str, graph: &DependencyGraph, pruned: bool) -> Self {
+        SbomDocument {
+            name: name.to_string(),
+            components: graph
+                .packages()
+                .map(|package| SbomComponent::from_package(package, pruned))
+                .collect(),
+            direct_dependencies: graph
+                .root
+                .dependencies
+                .iter()
+                .chain(&graph.root.dev_dependencies)
+                .chain(&graph.root.optional_dependencies)
+                .map(|id| npm_purl(&id.name, &id.version))
+                .collect(),
+        }
+    }
+
+    /// The SBOM as CycloneDX, and as SPDX too when `spdx` is set, which the
+    /// `sbom.spdx` configuration key opts into.
+    #[must_use]
+    pub fn sboms(&self, spdx: bool) -> Vec<Sbom> {
+        if spdx {
+            vec![self.cyclonedx(), self.spdx()]
+        } else {
+            vec![self.cyclonedx()]
+        }
+    }
+
+    /// Renders a CycloneDX 1.5 JSON SBOM.
+    #[must_use]
+    pub fn cyclonedx(&self) -> Sbom {
+        let components: Vec<Value> = self.components.iter().map(cyclonedx_component).collect();
+        let mut dependencies: Vec<Value> = self
+            .components
+            .iter()
+            .map(|component| json!({ "ref": component.purl, "dependsOn": component.dependencies }))
+            .collect();
+        let mut bom = json!({
+            "bomFormat": "CycloneDX",
+            "specVersion": CYCLONEDX_SPEC_VERSION,
+            "version": 1,
+        });
+        if !self.direct_dependencies.is_empty() {
+            bom["metadata"] = json!({
+                "component": { "type": "application", "bom-ref": self.name, "name": self.name }
+            });
+            dependencies.insert(
+                0,
+                json!({ "ref": self.name, "dependsOn": self.direct_dependencies }),
+            );
+        }
+        bom["components"] = Value::Array(components);
+        bom["dependencies"] = Value::Array(dependencies);
+        Sbom::from_bytes(SbomFormat::CycloneDxJson, to_json(&bom))
+    }
+
+    /// Renders an SPDX 2.3 JSON SBOM.
+    #[must_use]
+    pub fn spdx(&self) -> Sbom {
+        let spdx_id = |index: usize| format!("SPDXRef-Package-{index}");
+        let index_of = |purl: &str| {
+            self.components
+                .iter()
+                .position(|component| component.purl == purl)
+        };
+        let packages: Vec<Value> = self
+            .components
+            .iter()
+            .enumerate()
+            .map(|(index, component)| spdx_package(&spdx_id(index), component))
+            .collect();
+
+        let mut relationships = vec![];
+        let described: Vec<usize> = if self.direct_dependencies.is_empty() {
+            (0..self.components.len()).collect()
+        } else {
+            self.direct_dependencies
+                .iter()
+                .filter_map(|purl| index_of(purl))
+                .collect()
+        };
+        for index in described {
+            relationships.push(spdx_relationship(
+                "SPDXRef-DOCUMENT",
+                "DESCRIBES",
+                &spdx_id(index),
+            ));
+        }
+        for (index, component) in self.components.iter().enumerate() {
+            for dependency in component
+                .dependencies
+                .iter()
+                .filter_map(|purl| index_of(purl))
+            {
+                relationships.push(spdx_relationship(
+                    &spdx_id(index),
+                    "DEPENDS_ON",
+                    &spdx_id(dependency),
+                ));
+            }
+        }
+
+        let document_hash = hex::encode(Sha256::digest(to_json(&json!(packages))));
+        let document = json!({
+            "spdxVersion": SPDX_VERSION,
+            "dataLicense": "CC0-1.0",
+            "SPDXID": "SPDXRef-DOCUMENT",
+            "name": self.name,
+            "documentNamespace": format!("{SPDX_NAMESPACE_BASE}/{}-{document_hash}", percent_encode(&self.name)),
+            "creationInfo": {
+                "created": SPDX_CREATED,
+                "creators": ["Tool: heroku-nodejs-utils"],
+            },
+            "packages": packages,
+            "relationships": relationships,
+        });
+        Sbom::from_bytes(SbomFormat::SpdxJson, to_json(&document))
+    }
+}
+
+fn cyclonedx_component(component: &SbomComponent) -> Value {
+    let mut value = Map::new();
+    value.insert(
+        "type".to_string(),
+        json!(match component.component_type {
+            ComponentType::Library => "library",
+            ComponentType::Platform => "platform",
+        }),
+    );
+    value.insert("bom-ref".to_string(), json!(component.purl));
+    value.insert("name".to_string(), json!(component.name));
+    value.insert("version".to_string(), json!(component.version));
+    value.insert("purl".to_string(), json!(component.purl));
+    value.insert(
+        "scope".to_string(),
+        json!(if component.pruned {
+            "excluded"
+        } else if component.optional {
+            "optional"
+        } else {
+            "required"
+        }),
+    );
+    if !component.hashes.is_empty() {
+        value.insert(
+            "hashes".to_string(),
+            component
+                .hashes
+                .iter()
+                .map(|(algorithm, content)| json!({ "alg": algorithm.cyclonedx(), "content": content }))
+                .collect(),
+        );
+    }
+    if let Some(license) = &component.license {
+        value.insert(
+            "licenses".to_string(),
+            if LICENSE_EXPRESSION.is_match(license) {
+                json!([{ "expression": license }])
+            } else {
+                json!([{ "license": { "name": license } }])
+            },
+        );
+    }
+    if let Some(url) = &component.download_url {
+        value.insert(
+            "externalReferences".to_string(),
+            json!([{ "type": "distribution", "url": url }]),
+        );
+    }
+    if component.dev {
+        value.insert(
+            "properties".to_string(),
+            json!([{ "name": CYCLONEDX_DEVELOPMENT_PROPERTY, "value": "true" }]),
+        );
+    }
+    Value::Object(value)
+}
+
+fn spdx_package(spdx_id: &str, component: &SbomComponent) -> Value {
+    let license = component
+        .license
+        .as_deref()
+        .filter(|license| LICENSE_EXPRESSION.is_match(license))
+        .unwrap_or(NOASSERTION);
+    let mut package = json!({
+        "SPDXID": spdx_id,
+        "name": component.name,
+        "versionInfo": component.version,
+        "downloadLocation": component.download_url.as_deref().unwrap_or(NOASSERTION),
+        "filesAnalyzed": false,
+        "licenseConcluded": NOASSERTION,
+        "licenseDeclared": license,
+        "primaryPackagePurpose": match component.component_type {
+            ComponentType::Library => "LIBRARY",
+            ComponentType::Platform => "APPLICATION",
+        },
+        "externalRefs": [{
+            "referenceCategory": "PACKAGE-MANAGER",
+            "referenceType": "purl",
+            "referenceLocator": component.purl,
+        }],
+    });
+    if !component.hashes.is_empty() {
+        package["checksums"] = component
+            .hashes
+            .iter()
+            .map(|(algorithm, value)| json!({ "algorithm": algorithm.spdx(), "checksumValue": value }))
+            .collect();
+    }
+    if component.pruned {
+        package["comment"] = json!("Development dependency, pruned from the image");
+    } else if component.dev {
+        package["comment"] = json!("Development dependency");
+    }
+    package
+}
+
+fn spdx_relationship(element: &str, relationship_type: &str, related_element: &str) -> Value {
+    json!({
+        "spdxElementId": element,
+        "relationshipType": relationship_type,
+        "relatedSpdxElement": related_element,
+    })
+}
+
+/// The package URL of an npm package, like `pkg:npm/%40babel/core@7.24.0`.
+#[must_use]
+pub fn npm_purl(name: &str, version: &str) -> String {
+    let name = match name.split_once('/') {
+        Some((scope, name)) => format!("{}/{}", percent_encode(scope), percent_encode(name)),
+        None => percent_encode(name),
+    };
+    format!("pkg:npm/{name}@{}", percent_encode(version))
+}
+
+/// Percent-encodes everything but unreserved characters, as package URL
+/// components require.
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            byte => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+/// Reads the hashes of a lockfile integrity value: a Subresource Integrity
+/// string like `sha512-<base64>`, possibly with several space-separated
+/// hashes, or a Yarn Berry checksum like `10c0/<hex>`, which is a SHA-512.
+fn parse_integrity(integrity: &str) -> Vec<(HashAlgorithm, String)> {
+    if let Some((_, checksum)) = integrity.split_once('/') {
+        if checksum.len() == 128 && checksum.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+            return vec![(HashAlgorithm::Sha512, checksum.to_ascii_lowercase())];
+        }
+    }
+    integrity
+        .split_whitespace()
+        .filter_map(|hash| {
+            let (algorithm, digest) = hash.split_once('-')?;
+            let algorithm = match algorithm {
+                "sha1" => HashAlgorithm::Sha1,
+                "sha256" => HashAlgorithm::Sha256,
+                "sha384" => HashAlgorithm::Sha384,
+                "sha512" => HashAlgorithm::Sha512,
+                _ => None?,
+            };
+            // Options like `?foo` may follow the digest.
+            let digest = digest.split('?').next()?;
+            Some((algorithm, hex::encode(BASE64_STANDARD.decode(digest).ok()?)))
+        })
+        .collect()
+}
+
+fn to_json(value: &Value) -> Vec<u8> {
+    serde_json::to_vec_pretty(value).expect("SBOM JSON should serialize")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dependency_graph::PackageId;
+
+    fn graph() -> DependencyGraph {
+        DependencyGraph::parse(
+            PackageManager::Npm,
+            r#"{
+                "lockfileVersion": 3,
+                "packages": {
+                    "": {
+                        "dependencies": { "@babel/code-frame": "^7.0.0" },
+                        "devDependencies": { "jest": "^29.0.0" }
+                    },
+                    "node_modules/@babel/code-frame": {
+                        "version": "7.24.2",
+                        "resolved": "https://registry.npmjs.org/@babel/code-frame/-/code-frame-7.24.2.tgz",
+                        "integrity": "sha512-AAEC",
+                        "license": "MIT",
+                        "dependencies": { "picocolors": "^1.0.0" }
+                    },
+                    "node_modules/picocolors": {
+                        "version": "1.0.0",
+                        "license": "SEE LICENSE IN LICENSE"
+                    },
+                    "node_modules/jest": { "version": "29.7.0", "dev": true }
+                }
+            }"#,
+            &PackageJson::default(),
+        )
+        .unwrap()
+    }
+
+    fn json(sbom: &Sbom) -> Value {
+        serde_json::from_slice(&sbom.data).unwrap()
+    }
+
+    #[test]
+    fn package_urls() {
+        assert_eq!(npm_purl("lodash", "4.17.21"), "pkg:npm/lodash@4.17.21");
+        assert_eq!(
+            npm_purl("@babel/core", "7.24.0"),
+            "pkg:npm/%40babel/core@7.24.0"
+        );
+        assert_eq!(
+            npm_purl("left-pad", "1.3.0+build"),
+            "pkg:npm/left-pad@1.3.0%2Bbuild"
+        );
+    }
+
+    #[test]
+    fn integrity_hashes() {
+        assert_eq!(
+            parse_integrity("sha512-AAEC sha1-/w=="),
+            [
+                (HashAlgorithm::Sha512, "000102".to_string()),
+                (HashAlgorithm::Sha1, "ff".to_string())
+            ]
+        );
+        let berry_checksum = format!("10c0/{}", "AB".repeat(64));
+        assert_eq!(
+            parse_integrity(&berry_checksum),
+            [(HashAlgorithm::Sha512, "ab".repeat(64))]
+        );
+        assert!(parse_integrity("md5-AAEC").is_empty());
+    }
+
+    #[test]
+    fn cyclonedx_sbom_of_pruned_dependencies() {
+        let sbom = SbomDocument::from_dependency_graph("app", &graph(), true).cyclonedx();
+        assert_eq!(sbom.format, SbomFormat::CycloneDxJson);
+        let bom = json(&sbom);
+        assert_eq!(bom["specVersion"], "1.5");
+        assert_eq!(bom["metadata"]["component"]["name"], "app");
+
+        let code_frame = &bom["components"][0];
+        assert_eq!(code_frame["purl"], "pkg:npm/%40babel/code-frame@7.24.2");
+        assert_eq!(code_frame["scope"], "required");
+        assert_eq!(
+            code_frame["hashes"],
+            json!([{ "alg": "SHA-512", "content": "000102" }])
+        );
+        assert_eq!(code_frame["licenses"], json!([{ "expression": "MIT" }]));
+        assert_eq!(
+            code_frame["externalReferences"][0]["url"],
+            "https://registry.npmjs.org/@babel/code-frame/-/code-frame-7.24.2.tgz"
+        );
+
+        let jest = &bom["components"][1];
+        assert_eq!(jest["name"], "jest");
+        assert_eq!(jest["scope"], "excluded");
+        assert_eq!(
+            jest["properties"],
+            json!([{ "name": "cdx:npm:package:development", "value": "true" }])
+        );
+        assert_eq!(
+            bom["components"][2]["licenses"],
+            json!([{ "license": { "name": "SEE LICENSE IN LICENSE" } }])
+        );
+
+        assert_eq!(
+            bom["dependencies"][0],
+            json!({
+                "ref": "app",
+                "dependsOn": ["pkg:npm/%40babel/code-frame@7.24.2", "pkg:npm/jest@29.7.0"]
+            })
+        );
+        assert_eq!(
+            bom["dependencies"][1],
+            json!({
+                "ref": "pkg:npm/%40babel/code-frame@7.24.2",
+                "dependsOn": ["pkg:npm/picocolors@1.0.0"]
+            })
+        );
+
+        let unpruned =
+            json(&SbomDocument::from_dependency_graph("app", &graph(), false).cyclonedx());
+        assert_eq!(unpruned["components"][1]["scope"], "required");
+    }
+
+    #[test]
+    fn spdx_sbom_of_pruned_dependencies() {
+        let document = SbomDocument::from_dependency_graph("app", &graph(), true);
+        let sbom = document.spdx();
+        assert_eq!(sbom.format, SbomFormat::SpdxJson);
+        assert_eq!(sbom.data, document.spdx().data);
+        let spdx = json(&sbom);
+        assert_eq!(spdx["spdxVersion"], "SPDX-2.3");
+        assert_eq!(spdx["creationInfo"]["created"], "1980-01-01T00:00:01Z");
+        assert!(spdx["documentNamespace"]
+            .as_str()
+            .unwrap()
+            .starts_with("https://github.com/heroku/buildpacks-nodejs/spdx/app-"));
+
+        let code_frame = &spdx["packages"][0];
+        assert_eq!(code_frame["SPDXID"], "SPDXRef-Package-0");
+        assert_eq!(code_frame["licenseDeclared"], "MIT");
+        assert_eq!(
+            code_frame["checksums"],
+            json!([{ "algorithm": "SHA512", "checksumValue": "000102" }])
+        );
+        assert_eq!(
+            code_frame["externalRefs"][0]["referenceLocator"],
+            "pkg:npm/%40babel/code-frame@7.24.2"
+        );
+        assert_eq!(
+            spdx["packages"][1]["comment"],
+            "Development dependency, pruned from the image"
+        );
+        assert_eq!(spdx["packages"][2]["licenseDeclared"], "NOASSERTION");
+        assert_eq!(spdx["packages"][2]["downloadLocation"], "NOASSERTION");
+
+        assert_eq!(
+            spdx["relationships"],
+            json!([
+                { "spdxElementId": "SPDXRef-DOCUMENT", "relationshipType": "DESCRIBES", "relatedSpdxElement": "SPDXRef-Package-0" },
+                { "spdxElementId": "SPDXRef-DOCUMENT", "relationshipType": "DESCRIBES", "relatedSpdxElement": "SPDXRef-Package-1" },
+                { "spdxElementId": "SPDXRef-Package-0", "relationshipType": "DEPENDS_ON", "relatedSpdxElement": "SPDXRef-Package-2" },
+            ])
+        );
+    }
+
+    #[test]
+    fn node_runtime_sbom() {
+        let document = SbomDocument {
+            name: "node".to_string(),
+            components: vec![SbomComponent::node_runtime(
+                "22.1.0",
+                "https://nodejs.org/download/release/v22.1.0/node-v22.1.0-linux-x64.tar.gz",
+                &[0xab, 0xcd],
+            )],
+            direct_dependencies: vec![],
+        };
+        let bom = json(&document.cyclonedx());
+        assert!(bom.get("metadata").is_none());
+        let node = &bom["components"][0];
+        assert_eq!(node["type"], "platform");
+        assert_eq!(
+            node["purl"],
+            "pkg:generic/node@22.1.0?download_url=https%3A%2F%2Fnodejs.org%2Fdownload%2Frelease%2Fv22.1.0%2Fnode-v22.1.0-linux-x64.tar.gz"
+        );
+        assert_eq!(
+            node["hashes"],
+            json!([{ "alg": "SHA-256", "content": "abcd" }])
+        );
+
+        let formats = |spdx: bool| {
+            document
+                .sboms(spdx)
+                .into_iter()
+                .map(|sbom| sbom.format)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(formats(false), [SbomFormat::CycloneDxJson]);
+        assert_eq!(
+            formats(true),
+            [SbomFormat::CycloneDxJson, SbomFormat::SpdxJson]
+        );
+
+        let spdx = json(&document.spdx());
+        assert_eq!(spdx["packages"][0]["primaryPackagePurpose"], "APPLICATION");
+        assert_eq!(
+            spdx["relationships"],
+            json!([{ "spdxElementId": "SPDXRef-DOCUMENT", "relationshipType": "DESCRIBES", "relatedSpdxElement": "SPDXRef-Package-0" }])
+        );
+        assert_eq!(
+            PackageId::new("node", "22.1.0").to_string(),
+            format!(
+                "{}@{}",
+                node["name"].as_str().unwrap(),
+                node["version"].as_str().unwrap()
+            )
+        );
+    }
+}
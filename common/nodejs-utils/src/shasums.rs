@@ -0,0 +1,189 @@
+use pgp::cleartext::CleartextSignedMessage;
+use pgp::{Deserializable, SignedPublicKey};
+use std::collections::HashMap;
+use thiserror::Error;
+
+const PUBLIC_KEY_BLOCK_HEADER: &str = "-----BEGIN PGP PUBLIC KEY BLOCK-----";
+
+/// The public keys of the Node.js release team, used to verify the signed
+/// `SHASUMS256.txt.asc` files published with each release.
+#[derive(Debug)]
+pub struct ReleaseKeyring(Vec<SignedPublicKey>);
+
+impl ReleaseKeyring {
+    /// Reads a keyring of ASCII-armored public keys. Each key may be in its
+    /// own armor block, as in <https://github.com/nodejs/release-keys>.
+    ///
+    /// # Errors
+    ///
+    /// Will return a `ShasumsError` if a key can't be parsed or its self
+    /// signatures are invalid, or if the keyring contains no keys.
+    pub fn from_armored(armored_keys: &str) -> Result<Self, ShasumsError> {
+        let mut keys = vec![];
+        for block in armored_keys
+            .split(PUBLIC_KEY_BLOCK_HEADER)
+            .filter(|block| !block.trim().is_empty())
+        {
+            let armored_key = format!("{PUBLIC_KEY_BLOCK_HEADER}{block}");
+            let (parsed_keys, _) = SignedPublicKey::from_string_many(&armored_key)
+                .map_err(ShasumsError::InvalidKeyring)?;
+            for key in parsed_keys {
+                let key = key.map_err(ShasumsError::InvalidKeyring)?;
+                key.verify().map_err(ShasumsError::InvalidKeyring)?;
+                keys.push(key);
+            }
+        }
+        if keys.is_empty() {
+            Err(ShasumsError::EmptyKeyring)
+        } else {
+            Ok(ReleaseKeyring(keys))
+        }
+    }
+
+    fn verifies(&self, message: &CleartextSignedMessage) -> bool {
+        self.0.iter().any(|key| {
+            message.verify(&key.primary_key).is_ok()
+                || key
+                    .public_subkeys
+                    .iter()
+                    .any(|subkey| message.verify(&subkey.key).is_ok())
+        })
+    }
+}
+
+/// Verifies a clearsigned `SHASUMS256.txt.asc` file against the keyring and
+/// parses the signed checksums into a map of filename to checksum. Only the
+/// signed text is parsed, so nothing outside the signature is trusted.
+///
+/// # Errors
+///
+/// Will return a `ShasumsError` if the file isn't a clearsigned message, or if
+/// none of its signatures were made by a key in the keyring.
+pub fn verify_shasums(
+    signed_shasums: &str,
+    keyring: &ReleaseKeyring,
+) -> Result<HashMap<String, String>, ShasumsError> {
+    let (message, _) = CleartextSignedMessage::from_string(signed_shasums)
+        .map_err(ShasumsError::InvalidSignedMessage)?;
+    if keyring.verifies(&message) {
+        Ok(parse_shasums(&message.signed_text()))
+    } else {
+        Err(ShasumsError::UnverifiedSignature)
+    }
+}
+
+/// Parses a SHASUMS256.txt file into a map of filename to checksum.
+/// Lines are expected to be of the form `<checksum> <filename>`.
+#[must_use]
+pub fn parse_shasums(input: &str) -> HashMap<String, String> {
+    input
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            match (parts.next(), parts.next(), parts.next()) {
+                (Some(checksum), Some(filename), None) => Some((
+                    // Some of the checksum filenames contain a leading `./` (e.g.
+                    // https://nodejs.org/download/release/v0.11.6/SHASUMS256.txt)
+                    filename.trim_start_matches("./").to_string(),
+                    checksum.to_string(),
+                )),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+#[derive(Error, Debug)]
+pub enum ShasumsError {
+    #[error("Invalid release keyring: {0}")]
+    InvalidKeyring(pgp::errors::Error),
+    #[error("Release keyring doesn't contain any keys")]
+    EmptyKeyring,
+    #[error("Invalid signed checksums: {0}")]
+    InvalidSignedMessage(pgp::errors::Error),
+    #[error("Checksums aren't signed by a key in the release keyring")]
+    UnverifiedSignature,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEYRING: &str = include_str!("../tests/fixtures/shasums/keyring.asc");
+
+    fn keyring() -> ReleaseKeyring {
+        ReleaseKeyring::from_armored(KEYRING).unwrap()
+    }
+
+    #[test]
+    fn verify_signed_shasums() {
+        let shasums = verify_shasums(
+            include_str!("../tests/fixtures/shasums/SHASUMS256.txt.asc"),
+            &keyring(),
+        )
+        .unwrap();
+        assert_eq!(shasums.len(), 2);
+        assert_eq!(
+            shasums["node-v22.1.0-linux-x64.tar.gz"],
+            "9c111af1f951e8869615bca3601ce7ab6969374933bdba6397469843b808f222"
+        );
+    }
+
+    #[test]
+    fn verify_rejects_tampered_shasums() {
+        assert!(matches!(
+            verify_shasums(
+                include_str!("../tests/fixtures/shasums/SHASUMS256-tampered.txt.asc"),
+                &keyring(),
+            ),
+            Err(ShasumsError::UnverifiedSignature)
+        ));
+    }
+
+    #[test]
+    fn verify_rejects_shasums_signed_by_unknown_keys() {
+        assert!(matches!(
+            verify_shasums(
+                include_str!("../tests/fixtures/shasums/SHASUMS256-untrusted.txt.asc"),
+                &keyring(),
+            ),
+            Err(ShasumsError::UnverifiedSignature)
+        ));
+    }
+
+    #[test]
+    fn verify_rejects_unsigned_shasums() {
+        assert!(matches!(
+            verify_shasums(
+                "d8ae35a9e2bb0c0c0611ee9bacf564ea51cc8291ace1447f95ee6aeaf4f1d61d  node-v22.1.0-linux-arm64.tar.gz",
+                &keyring(),
+            ),
+            Err(ShasumsError::InvalidSignedMessage(_))
+        ));
+    }
+
+    #[test]
+    fn keyring_from_multiple_armor_blocks() {
+        assert_eq!(
+            ReleaseKeyring::from_armored(&format!("{KEYRING}\n{KEYRING}"))
+                .unwrap()
+                .0
+                .len(),
+            2
+        );
+        assert!(matches!(
+            ReleaseKeyring::from_armored(""),
+            Err(ShasumsError::EmptyKeyring)
+        ));
+    }
+
+    #[test]
+    fn parse_shasums_strips_leading_dot_slash() {
+        let shasums = parse_shasums(
+            "abc  ./node-v0.11.6-linux-x64.tar.gz\ninvalid line with parts\n\ndef  SHASUMS.txt\r\n",
+        );
+        assert_eq!(shasums.len(), 2);
+        assert_eq!(shasums["node-v0.11.6-linux-x64.tar.gz"], "abc");
+        assert_eq!(shasums["SHASUMS.txt"], "def");
+    }
+}
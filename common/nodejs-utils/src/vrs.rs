@@ -50,6 +50,19 @@ impl Version {
     pub fn patch(&self) -> u64 {
         self.0.patch
     }
+
+    /// Returns the prerelease identifiers (e.g. `rc.1` for `24.0.0-rc.1`), if any.
+    #[must_use]
+    pub fn prerelease(&self) -> Option<String> {
+        self.0.is_prerelease().then(|| {
+            self.0
+                .pre_release
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(".")
+        })
+    }
 }
 
 impl TryFrom<String> for Version {
@@ -114,6 +127,32 @@ impl Requirement {
     pub fn satisfies(&self, ver: &Version) -> bool {
         self.0.satisfies(&ver.0)
     }
+
+    /// Determines if the release a prerelease version leads up to satisfies
+    /// this requirement, so `24.x` is satisfied by `24.0.0-rc.1`. Unlike
+    /// `satisfies`, this doesn't require the requirement to name a prerelease.
+    #[must_use]
+    pub fn satisfies_release_of(&self, ver: &Version) -> bool {
+        self.0.satisfies(&NSVersion {
+            pre_release: vec![],
+            build: vec![],
+            ..ver.0.clone()
+        })
+    }
+
+    /// Determines if there is at least one version that satisfies both this
+    /// requirement and `other`.
+    #[must_use]
+    pub fn allows_any(&self, other: &Requirement) -> bool {
+        self.0.allows_any(&other.0)
+    }
+
+    /// Combines this requirement with `other` into a requirement that only
+    /// allows versions satisfying both. Returns `None` if there is no overlap.
+    #[must_use]
+    pub fn intersect(&self, other: &Requirement) -> Option<Requirement> {
+        self.0.intersect(&other.0).map(Requirement)
+    }
 }
 
 impl TryFrom<String> for Requirement {
@@ -278,6 +317,45 @@ mod tests {
         }
     }
 
+    #[test]
+    fn allows_any_for_overlapping_and_disjoint_requirements() {
+        let twenty = Requirement::parse("20.x").unwrap();
+        assert!(twenty.allows_any(&Requirement::parse("20.11.0").unwrap()));
+        assert!(twenty.allows_any(&Requirement::parse(">=18").unwrap()));
+        assert!(!twenty.allows_any(&Requirement::parse("^22").unwrap()));
+    }
+
+    #[test]
+    fn intersect_overlapping_and_disjoint_requirements() {
+        let twenty = Requirement::parse("20.x").unwrap();
+        let intersection = twenty
+            .intersect(&Requirement::parse(">=20.10").unwrap())
+            .unwrap();
+        assert!(intersection.satisfies(&Version::parse("20.11.0").unwrap()));
+        assert!(!intersection.satisfies(&Version::parse("20.9.0").unwrap()));
+        assert!(!intersection.satisfies(&Version::parse("21.0.0").unwrap()));
+        assert!(twenty
+            .intersect(&Requirement::parse("^22").unwrap())
+            .is_none());
+    }
+
+    #[test]
+    fn prerelease_versions_only_satisfy_explicit_prerelease_requirements() {
+        let rc = Version::parse("24.0.0-rc.1").unwrap();
+        assert_eq!(rc.prerelease(), Some("rc.1".to_string()));
+        assert_eq!(Version::parse("24.0.0").unwrap().prerelease(), None);
+
+        assert!(Requirement::parse("24.0.0-rc.1").unwrap().satisfies(&rc));
+        assert!(!Requirement::parse("24.x").unwrap().satisfies(&rc));
+        assert!(!Requirement::parse(">=22").unwrap().satisfies(&rc));
+        assert!(Requirement::parse("24.x")
+            .unwrap()
+            .satisfies_release_of(&rc));
+        assert!(!Requirement::parse("22.x")
+            .unwrap()
+            .satisfies_release_of(&rc));
+    }
+
     #[test]
     fn parse_returns_error_for_invalid_reqs() {
         let result = Requirement::parse("12.%");
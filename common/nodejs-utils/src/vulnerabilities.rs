@@ -0,0 +1,892 @@
+use crate::config::{ConfigError, ConfigSource, NodejsConfig, Setting};
+use crate::dependency_graph::{DependencyGraph, DependencyGraphError, PackageId};
+use crate::package_json::PackageJson;
+use crate::package_manager::PackageManager;
+use crate::registry_credentials::{binding_name, service_bindings_dir};
+use crate::vrs::Version;
+use libcnb::build::BuildContext;
+use libcnb::{Buildpack, Env, Platform};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use thiserror::Error;
+
+/// The type of the service bindings that hold an OSV advisory database.
+pub const OSV_BINDING_TYPE: &str = "osv";
+
+/// The OSV ecosystem of packages from the npm registry.
+const NPM_ECOSYSTEM: &str = "npm";
+
+/// The severity of an advisory, using the npm and GitHub advisory names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Low,
+    Moderate,
+    High,
+    Critical,
+}
+
+impl Severity {
+    /// The severity rating of a CVSS base score.
+    fn from_score(score: f64) -> Option<Self> {
+        match score {
+            score if score >= 9.0 => Some(Severity::Critical),
+            score if score >= 7.0 => Some(Severity::High),
+            score if score >= 4.0 => Some(Severity::Moderate),
+            score if score > 0.0 => Some(Severity::Low),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Severity {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "low" => Ok(Severity::Low),
+            "moderate" | "medium" => Ok(Severity::Moderate),
+            "high" => Ok(Severity::High),
+            "critical" => Ok(Severity::Critical),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Low => write!(f, "low"),
+            Severity::Moderate => write!(f, "moderate"),
+            Severity::High => write!(f, "high"),
+            Severity::Critical => write!(f, "critical"),
+        }
+    }
+}
+
+/// Where the advisory database was found, for the build log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdvisorySource {
+    pub path: PathBuf,
+    pub description: String,
+}
+
+impl AdvisorySource {
+    /// The advisory database set with `vulnerabilities.advisories` (or
+    /// `NODEJS_ADVISORIES`), relative to the app directory, or else the first
+    /// service binding of type `osv`.
+    ///
+    /// # Errors
+    ///
+    /// Will return a `VulnerabilityError` if the service bindings can't be read.
+    pub fn find(
+        app_dir: &Path,
+        configured: Option<Setting<String>>,
+        env: &Env,
+    ) -> Result<Option<Self>, VulnerabilityError> {
+        if let Some(setting) = configured {
+            let description = match setting.source {
+                ConfigSource::EnvVar(name) => format!("`{name}`"),
+                source => format!("`vulnerabilities.advisories` in {source}"),
+            };
+            return Ok(Some(AdvisorySource {
+                path: app_dir.join(setting.value),
+                description,
+            }));
+        }
+        let Some(bindings_dir) = service_bindings_dir(env) else {
+            return Ok(None);
+        };
+        let entries = match fs::read_dir(&bindings_dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(VulnerabilityError::ReadAdvisories(bindings_dir, e)),
+        };
+        let mut bindings = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .filter_map(|path| Some((binding_name(&path)?, path)))
+            .filter(|(_, path)| {
+                fs::read_to_string(path.join("type"))
+                    .is_ok_and(|binding_type| binding_type.trim() == OSV_BINDING_TYPE)
+            })
+            .collect::<Vec<_>>();
+        bindings.sort();
+        Ok(bindings
+            .into_iter()
+            .next()
+            .map(|(name, path)| AdvisorySource {
+                path,
+                description: format!("the `{name}` service binding"),
+            }))
+    }
+}
+
+/// The npm advisories from a directory of OSV JSON files, by package name.
+#[derive(Debug, Clone, Default)]
+pub struct AdvisoryDatabase {
+    advisories: HashMap<String, Vec<Advisory>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Advisory {
+    id: String,
+    #[serde(default)]
+    summary: Option<String>,
+    #[serde(default)]
+    withdrawn: Option<String>,
+    #[serde(default)]
+    severity: Vec<CvssSeverity>,
+    #[serde(default)]
+    affected: Vec<Affected>,
+    #[serde(default)]
+    database_specific: Option<Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct CvssSeverity {
+    #[serde(rename = "type")]
+    severity_type: String,
+    score: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Affected {
+    package: Option<AffectedPackage>,
+    #[serde(default)]
+    ranges: Vec<AffectedRange>,
+    #[serde(default)]
+    versions: Vec<String>,
+    #[serde(default)]
+    database_specific: Option<Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct AffectedPackage {
+    ecosystem: String,
+    name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct AffectedRange {
+    #[serde(rename = "type")]
+    range_type: String,
+    #[serde(default)]
+    events: Vec<HashMap<String, String>>,
+}
+
+/// An installed package that's affected by an advisory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Vulnerability {
+    pub package: PackageId,
+    pub dev: bool,
+    pub advisory: String,
+    pub summary: Option<String>,
+    /// The severity, when the advisory has one or a CVSS v3 vector.
+    pub severity: Option<Severity>,
+    /// The versions that fix the vulnerability, when the advisory lists them.
+    pub fixed_versions: Vec<String>,
+}
+
+impl Display for Vulnerability {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({}{}): {}",
+            self.package,
+            self.severity
+                .map_or_else(|| "unknown severity".to_string(), |s| s.to_string()),
+            if self.dev { ", dev" } else { "" },
+            self.advisory
+        )?;
+        if let Some(summary) = &self.summary {
+            write!(f, " {summary}")?;
+        }
+        if self.fixed_versions.is_empty() {
+            write!(f, ", no fixed version")
+        } else {
+            write!(f, ", fixed in {}", self.fixed_versions.join(", "))
+        }
+    }
+}
+
+impl AdvisoryDatabase {
+    /// Reads the `.json` OSV advisories in `dir` and its subdirectories.
+    /// Hidden entries, withdrawn advisories, and advisories for other
+    /// ecosystems are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Will return a `VulnerabilityError` if a file can't be read or isn't a
+    /// valid OSV advisory.
+    pub fn read(dir: &Path) -> Result<Self, VulnerabilityError> {
+        let mut database = AdvisoryDatabase::default();
+        let mut pending = vec![dir.to_path_buf()];
+        while let Some(dir) = pending.pop() {
+            let entries = fs::read_dir(&dir)
+                .map_err(|e| VulnerabilityError::ReadAdvisories(dir.clone(), e))?;
+            for entry in entries {
+                let path = entry
+                    .map_err(|e| VulnerabilityError::ReadAdvisories(dir.clone(), e))?
+                    .path();
+                if binding_name(&path).is_none() {
+                    continue;
+                }
+                if path.is_dir() {
+                    pending.push(path);
+                } else if path
+                    .extension()
+                    .is_some_and(|extension| extension == "json")
+                {
+                    let contents = fs::read_to_string(&path)
+                        .map_err(|e| VulnerabilityError::ReadAdvisories(path.clone(), e))?;
+                    database.add(
+                        &serde_json::from_str(&contents)
+                            .map_err(|e| VulnerabilityError::ParseAdvisory(path.clone(), e))?,
+                    );
+                }
+            }
+        }
+        Ok(database)
+    }
+
+    fn add(&mut self, advisory: &Advisory) {
+        if advisory.withdrawn.is_some() {
+            return;
+        }
+        let mut names: Vec<&str> = advisory
+            .affected
+            .iter()
+            .filter_map(|affected| affected.package.as_ref())
+            .filter(|package| package.ecosystem == NPM_ECOSYSTEM)
+            .map(|package| package.name.as_str())
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        let names: Vec<String> = names.into_iter().map(String::from).collect();
+        for name in names {
+            self.advisories
+                .entry(name)
+                .or_default()
+                .push(advisory.clone());
+        }
+    }
+
+    /// The number of advisories, counted once per affected package.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.advisories.values().map(Vec::len).sum()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.advisories.is_empty()
+    }
+
+    /// The installed packages that are affected by an advisory, sorted by
+    /// package.
+    #[must_use]
+    pub fn check(&self, graph: &DependencyGraph) -> Vec<Vulnerability> {
+        let mut vulnerabilities = vec![];
+        for package in graph.packages() {
+            let Some(advisories) = self.advisories.get(&package.name) else {
+                continue;
+            };
+            let Ok(version) = Version::parse(&package.version) else {
+                continue;
+            };
+            for advisory in advisories {
+                let affected: Vec<&Affected> = advisory
+                    .affected
+                    .iter()
+                    .filter(|affected| {
+                        affected.package.as_ref().is_some_and(|affected_package| {
+                            affected_package.ecosystem == NPM_ECOSYSTEM
+                                && affected_package.name == package.name
+                        }) && affected.affects(&package.version, &version)
+                    })
+                    .collect();
+                if affected.is_empty() {
+                    continue;
+                }
+                let mut fixed_versions: Vec<String> = affected
+                    .iter()
+                    .flat_map(|affected| affected.fixed_versions())
+                    .collect();
+                fixed_versions.sort_by_key(|fixed| Version::parse(fixed).ok());
+                fixed_versions.dedup();
+                vulnerabilities.push(Vulnerability {
+                    package: package.id(),
+                    dev: package.dev,
+                    advisory: advisory.id.clone(),
+                    summary: advisory.summary.clone(),
+                    severity: affected
+                        .iter()
+                        .find_map(|affected| database_severity(affected.database_specific.as_ref()))
+                        .or_else(|| advisory.severity()),
+                    fixed_versions,
+                });
+            }
+        }
+        vulnerabilities
+    }
+}
+
+impl Advisory {
+    fn severity(&self) -> Option<Severity> {
+        database_severity(self.database_specific.as_ref()).or_else(|| {
+            self.severity
+                .iter()
+                .filter(|severity| severity.severity_type.starts_with("CVSS_V3"))
+                .find_map(|severity| Severity::from_score(cvss3_base_score(&severity.score)?))
+        })
+    }
+}
+
+impl Affected {
+    fn affects(&self, raw_version: &str, version: &Version) -> bool {
+        self.versions.iter().any(|affected| affected == raw_version)
+            || self
+                .ranges
+                .iter()
+                .filter(|range| matches!(range.range_type.as_str(), "SEMVER" | "ECOSYSTEM"))
+                .any(|range| range.affects(version))
+    }
+
+    fn fixed_versions(&self) -> Vec<String> {
+        self.ranges
+            .iter()
+            .flat_map(|range| &range.events)
+            .filter_map(|event| event.get("fixed").cloned())
+            .collect()
+    }
+}
+
+impl AffectedRange {
+    /// Evaluates the range events in version order, as the OSV schema
+    /// describes: a version is affected after an `introduced` event, until a
+    /// `fixed` event or past a `last_affected` event.
+    fn affects(&self, version: &Version) -> bool {
+        let mut events: Vec<(Version, &str)> = self
+            .events
+            .iter()
+            .flat_map(|event| event.iter())
+            .filter_map(|(kind, value)| {
+                let value = if kind == "introduced" && value == "0" {
+                    "0.0.0"
+                } else {
+                    value
+                };
+                Some((Version::parse(value).ok()?, kind.as_str()))
+            })
+            .collect();
+        events.sort();
+        let mut affected = false;
+        for (event_version, kind) in events {
+            match kind {
+                "introduced" if version >= &event_version => affected = true,
+                "fixed" if version >= &event_version => affected = false,
+                "last_affected" if version > &event_version => affected = false,
+                _ => {}
+            }
+        }
+        affected
+    }
+}
+
+/// Reads a GitHub advisory style `{ "severity": "HIGH" }`.
+fn database_severity(database_specific: Option<&Value>) -> Option<Severity> {
+    database_specific?.get("severity")?.as_str()?.parse().ok()
+}
+
+/// Calculates the base score of a CVSS v3 vector like
+/// `CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H`.
+fn cvss3_base_score(vector: &str) -> Option<f64> {
+    let metrics: HashMap<&str, &str> = vector
+        .split('/')
+        .skip(1)
+        .filter_map(|metric| metric.split_once(':'))
+        .collect();
+    let metric = |name: &str| metrics.get(name).copied();
+    let scope_changed = metric("S")? == "C";
+    let attack_vector = match metric("AV")? {
+        "N" => 0.85,
+        "A" => 0.62,
+        "L" => 0.55,
+        "P" => 0.2,
+        _ => None?,
+    };
+    let attack_complexity = match metric("AC")? {
+        "L" => 0.77,
+        "H" => 0.44,
+        _ => None?,
+    };
+    let privileges_required = match (metric("PR")?, scope_changed) {
+        ("N", _) => 0.85,
+        ("L", false) => 0.62,
+        ("L", true) => 0.68,
+        ("H", false) => 0.27,
+        ("H", true) => 0.5,
+        _ => None?,
+    };
+    let user_interaction = match metric("UI")? {
+        "N" => 0.85,
+        "R" => 0.62,
+        _ => None?,
+    };
+    let impact_metric = |name: &str| match metric(name)? {
+        "H" => Some(0.56),
+        "L" => Some(0.22),
+        "N" => Some(0.0),
+        _ => None,
+    };
+    let impact_subscore: f64 = 1.0
+        - (1.0 - impact_metric("C")?) * (1.0 - impact_metric("I")?) * (1.0 - impact_metric("A")?);
+    let impact = if scope_changed {
+        7.52 * (impact_subscore - 0.029) - 3.25 * (impact_subscore - 0.02).powi(15)
+    } else {
+        6.42 * impact_subscore
+    };
+    let exploitability =
+        8.22 * attack_vector * attack_complexity * privileges_required * user_interaction;
+    if impact <= 0.0 {
+        return Some(0.0);
+    }
+    let score = if scope_changed {
+        1.08 * (impact + exploitability)
+    } else {
+        impact + exploitability
+    };
+    Some(round_up(score.min(10.0)))
+}
+
+/// Rounds up to one decimal, as the CVSS v3.1 specification defines it to
+/// avoid floating point errors.
+#[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+fn round_up(value: f64) -> f64 {
+    let scaled = (value * 100_000.0).round() as i64;
+    if scaled % 10_000 == 0 {
+        scaled as f64 / 100_000.0
+    } else {
+        ((scaled / 10_000) + 1) as f64 / 10.0
+    }
+}
+
+/// Reads the lockfile of `package_manager` in `app_dir` and matches the
+/// installed packages against the advisory database.
+///
+/// # Errors
+///
+/// Will return a `VulnerabilityError` if the advisories or the lockfile
+/// can't be read.
+pub fn find_vulnerabilities(
+    app_dir: &Path,
+    package_manager: PackageManager,
+    package_json: &PackageJson,
+    advisories_dir: &Path,
+) -> Result<Vec<Vulnerability>, VulnerabilityError> {
+    let database = AdvisoryDatabase::read(advisories_dir)?;
+    let graph = DependencyGraph::read(app_dir, package_manager, package_json)
+        .map_err(VulnerabilityError::DependencyGraph)?;
+    Ok(database.check(&graph))
+}
+
+/// Counts the vulnerabilities for the build log, like "3 known
+/// vulnerabilities (1 in production dependencies)".
+#[must_use]
+pub fn summary(vulnerabilities: &[Vulnerability]) -> String {
+    if vulnerabilities.is_empty() {
+        return "No known vulnerabilities".to_string();
+    }
+    let production = vulnerabilities
+        .iter()
+        .filter(|vulnerability| !vulnerability.dev)
+        .count();
+    format!(
+        "{} known {} ({production} in production dependencies)",
+        vulnerabilities.len(),
+        if vulnerabilities.len() == 1 {
+            "vulnerability"
+        } else {
+            "vulnerabilities"
+        }
+    )
+}
+
+/// Fails when a production dependency has a vulnerability of at least the
+/// `fail_on` severity. Vulnerabilities without a known severity never fail.
+///
+/// # Errors
+///
+/// Will return a `VulnerabilityError::Found` listing those vulnerabilities.
+pub fn enforce_severity(
+    vulnerabilities: &[Vulnerability],
+    fail_on: Severity,
+) -> Result<(), VulnerabilityError> {
+    let failing: Vec<Vulnerability> = vulnerabilities
+        .iter()
+        .filter(|vulnerability| {
+            !vulnerability.dev && vulnerability.severity.is_some_and(|s| s >= fail_on)
+        })
+        .cloned()
+        .collect();
+    if failing.is_empty() {
+        Ok(())
+    } else {
+        Err(VulnerabilityError::Found {
+            vulnerabilities: failing,
+            fail_on,
+        })
+    }
+}
+
+/// The result of checking the installed dependencies against an advisory
+/// database, for the build log.
+#[derive(Debug)]
+pub struct VulnerabilityReport {
+    pub source: AdvisorySource,
+    pub vulnerabilities: Vec<Vulnerability>,
+    pub fail_on: Option<Setting<Severity>>,
+}
+
+impl VulnerabilityReport {
+    /// Fails when `vulnerabilities.fail_on` is set and a production dependency
+    /// has a vulnerability of at least that severity.
+    ///
+    /// # Errors
+    ///
+    /// Will return a `VulnerabilityError::Found` listing those vulnerabilities.
+    pub fn enforce(&self) -> Result<(), VulnerabilityError> {
+        match &self.fail_on {
+            Some(fail_on) => enforce_severity(&self.vulnerabilities, fail_on.value),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Checks the dependencies installed in `app_dir` against the OSV advisories
+/// from the configuration or a service binding. Returns `None` when there are
+/// no advisories to check against.
+///
+/// # Errors
+///
+/// Will return an error if the configuration is invalid, or the advisories or
+/// the lockfile can't be read.
+pub fn check_vulnerabilities<B>(
+    context: &BuildContext<B>,
+    app_dir: &Path,
+    package_manager: PackageManager,
+    package_json: &PackageJson,
+    config: &NodejsConfig,
+    env: &Env,
+) -> Result<Option<VulnerabilityReport>, B::Error>
+where
+    B: Buildpack,
+    B::Error: From<VulnerabilityError> + From<ConfigError>,
+{
+    let Some(source) = AdvisorySource::find(
+        &context.app_dir,
+        config.advisories(context.platform.env()),
+        env,
+    )?
+    else {
+        return Ok(None);
+    };
+    let fail_on = config.vulnerabilities_fail_on(context.platform.env())?;
+    let vulnerabilities =
+        find_vulnerabilities(app_dir, package_manager, package_json, &source.path)?;
+    Ok(Some(VulnerabilityReport {
+        source,
+        vulnerabilities,
+        fail_on,
+    }))
+}
+
+#[derive(Error, Debug)]
+pub enum VulnerabilityError {
+    #[error("Couldn't read the advisories in {0}: {1}")]
+    ReadAdvisories(PathBuf, std::io::Error),
+    #[error("Couldn't parse the OSV advisory {0}: {1}")]
+    ParseAdvisory(PathBuf, serde_json::Error),
+    #[error("Couldn't read the installed dependencies: {0}")]
+    DependencyGraph(DependencyGraphError),
+    #[error("Production dependencies have known vulnerabilities of {fail_on} severity or higher:\n{}", vulnerabilities.iter().map(|vulnerability| format!("- {vulnerability}")).collect::<Vec<_>>().join("\n"))]
+    Found {
+        vulnerabilities: Vec<Vulnerability>,
+        fail_on: Severity,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph() -> DependencyGraph {
+        DependencyGraph::parse(
+            PackageManager::Npm,
+            r#"{
+                "lockfileVersion": 3,
+                "packages": {
+                    "": {
+                        "dependencies": { "lodash": "^4.0.0", "minimist": "^1.0.0" },
+                        "devDependencies": { "semver": "^5.0.0" }
+                    },
+                    "node_modules/lodash": { "version": "4.17.20" },
+                    "node_modules/minimist": { "version": "1.2.6" },
+                    "node_modules/semver": { "version": "5.7.1", "dev": true }
+                }
+            }"#,
+            &PackageJson::default(),
+        )
+        .unwrap()
+    }
+
+    fn write_advisory(dir: &Path, file: &str, advisory: &Value) {
+        let path = dir.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, advisory.to_string()).unwrap();
+    }
+
+    #[test]
+    fn parse_severity() {
+        assert_eq!("Moderate".parse(), Ok(Severity::Moderate));
+        assert_eq!("MEDIUM".parse(), Ok(Severity::Moderate));
+        assert_eq!(" critical ".parse(), Ok(Severity::Critical));
+        assert_eq!("severe".parse::<Severity>(), Err(()));
+        assert!(Severity::High > Severity::Moderate);
+    }
+
+    #[test]
+    fn cvss3_base_scores() {
+        assert_eq!(
+            cvss3_base_score("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H"),
+            Some(9.8)
+        );
+        assert_eq!(
+            cvss3_base_score("CVSS:3.1/AV:N/AC:L/PR:N/UI:R/S:C/C:L/I:L/A:N"),
+            Some(6.1)
+        );
+        assert_eq!(
+            cvss3_base_score("CVSS:3.0/AV:L/AC:H/PR:H/UI:R/S:U/C:N/I:N/A:N"),
+            Some(0.0)
+        );
+        assert_eq!(cvss3_base_score("CVSS:3.1/AV:N/AC:L"), None);
+    }
+
+    #[test]
+    fn affected_ranges() {
+        let range: AffectedRange = serde_json::from_value(serde_json::json!({
+            "type": "SEMVER",
+            "events": [
+                { "introduced": "0" },
+                { "fixed": "1.2.6" },
+                { "introduced": "2.0.0" },
+                { "last_affected": "2.1.0" }
+            ]
+        }))
+        .unwrap();
+        let affects = |version: &str| range.affects(&Version::parse(version).unwrap());
+        assert!(affects("0.0.1"));
+        assert!(affects("1.2.5"));
+        assert!(!affects("1.2.6"));
+        assert!(!affects("1.9.0"));
+        assert!(affects("2.0.0"));
+        assert!(affects("2.1.0"));
+        assert!(!affects("2.1.1"));
+    }
+
+    fn advisories() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        write_advisory(
+            dir.path(),
+            "npm/GHSA-35jh-r3h4-6jhm.json",
+            &serde_json::json!({
+                "id": "GHSA-35jh-r3h4-6jhm",
+                "summary": "Command Injection in lodash",
+                "affected": [{
+                    "package": { "ecosystem": "npm", "name": "lodash" },
+                    "ranges": [{ "type": "ECOSYSTEM", "events": [{ "introduced": "0" }, { "fixed": "4.17.21" }] }]
+                }],
+                "database_specific": { "severity": "HIGH" }
+            }),
+        );
+        write_advisory(
+            dir.path(),
+            "npm/GHSA-xvch-5gv4-984h.json",
+            &serde_json::json!({
+                "id": "GHSA-xvch-5gv4-984h",
+                "affected": [{
+                    "package": { "ecosystem": "npm", "name": "minimist" },
+                    "ranges": [{ "type": "ECOSYSTEM", "events": [{ "introduced": "0" }, { "fixed": "1.2.6" }] }]
+                }]
+            }),
+        );
+        write_advisory(
+            dir.path(),
+            "npm/GHSA-c2qf-rxjj-qqgw.json",
+            &serde_json::json!({
+                "id": "GHSA-c2qf-rxjj-qqgw",
+                "severity": [{ "type": "CVSS_V3", "score": "CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:N/I:N/A:L" }],
+                "affected": [{
+                    "package": { "ecosystem": "npm", "name": "semver" },
+                    "versions": ["5.7.1"]
+                }]
+            }),
+        );
+        write_advisory(
+            dir.path(),
+            "withdrawn.json",
+            &serde_json::json!({
+                "id": "GHSA-withdrawn",
+                "withdrawn": "2024-01-01T00:00:00Z",
+                "affected": [{ "package": { "ecosystem": "npm", "name": "minimist" }, "versions": ["1.2.6"] }]
+            }),
+        );
+        write_advisory(
+            dir.path(),
+            "pypi/PYSEC-2021-1.json",
+            &serde_json::json!({
+                "id": "PYSEC-2021-1",
+                "affected": [{ "package": { "ecosystem": "PyPI", "name": "lodash" }, "versions": ["4.17.20"] }]
+            }),
+        );
+        fs::write(dir.path().join(".hidden.json"), "not json").unwrap();
+        fs::write(dir.path().join("README.md"), "# Advisories").unwrap();
+        dir
+    }
+
+    #[test]
+    fn check_dependencies() {
+        let dir = advisories();
+        let database = AdvisoryDatabase::read(dir.path()).unwrap();
+        assert_eq!(database.len(), 3);
+        let vulnerabilities = database.check(&graph());
+        assert_eq!(
+            vulnerabilities,
+            [
+                Vulnerability {
+                    package: PackageId::new("lodash", "4.17.20"),
+                    dev: false,
+                    advisory: "GHSA-35jh-r3h4-6jhm".to_string(),
+                    summary: Some("Command Injection in lodash".to_string()),
+                    severity: Some(Severity::High),
+                    fixed_versions: vec!["4.17.21".to_string()],
+                },
+                Vulnerability {
+                    package: PackageId::new("semver", "5.7.1"),
+                    dev: true,
+                    advisory: "GHSA-c2qf-rxjj-qqgw".to_string(),
+                    summary: None,
+                    severity: Some(Severity::Moderate),
+                    fixed_versions: vec![],
+                }
+            ]
+        );
+        assert_eq!(
+            vulnerabilities[0].to_string(),
+            "lodash@4.17.20 (high): GHSA-35jh-r3h4-6jhm Command Injection in lodash, fixed in 4.17.21"
+        );
+        assert_eq!(
+            vulnerabilities[1].to_string(),
+            "semver@5.7.1 (moderate, dev): GHSA-c2qf-rxjj-qqgw, no fixed version"
+        );
+
+        fs::write(dir.path().join("invalid.json"), "{").unwrap();
+        assert!(matches!(
+            AdvisoryDatabase::read(dir.path()),
+            Err(VulnerabilityError::ParseAdvisory(_, _))
+        ));
+    }
+
+    #[test]
+    fn enforce_fail_on_severity() {
+        let vulnerabilities = AdvisoryDatabase::read(advisories().path())
+            .unwrap()
+            .check(&graph());
+        assert_eq!(
+            summary(&vulnerabilities),
+            "2 known vulnerabilities (1 in production dependencies)"
+        );
+        assert_eq!(summary(&[]), "No known vulnerabilities");
+        assert!(enforce_severity(&vulnerabilities, Severity::Moderate).is_err());
+        assert!(enforce_severity(&vulnerabilities, Severity::Critical).is_ok());
+        let Err(VulnerabilityError::Found {
+            vulnerabilities, ..
+        }) = enforce_severity(&vulnerabilities, Severity::Low)
+        else {
+            panic!("expected production vulnerabilities");
+        };
+        assert_eq!(vulnerabilities.len(), 1);
+    }
+
+    #[test]
+    fn enforce_report_only_with_fail_on() {
+        let mut report = VulnerabilityReport {
+            source: AdvisorySource {
+                path: PathBuf::from("/workspace/advisories"),
+                description: "`NODEJS_ADVISORIES`".to_string(),
+            },
+            vulnerabilities: AdvisoryDatabase::read(advisories().path())
+                .unwrap()
+                .check(&graph()),
+            fail_on: None,
+        };
+        assert!(report.enforce().is_ok());
+        report.fail_on = Some(Setting {
+            value: Severity::High,
+            source: ConfigSource::ProjectToml,
+        });
+        assert!(matches!(
+            report.enforce(),
+            Err(VulnerabilityError::Found {
+                fail_on: Severity::High,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn find_advisory_source() {
+        let app_dir = Path::new("/workspace");
+        assert_eq!(
+            AdvisorySource::find(
+                app_dir,
+                Some(Setting {
+                    value: "vendor/advisories".to_string(),
+                    source: ConfigSource::ProjectToml
+                }),
+                &Env::new()
+            )
+            .unwrap(),
+            Some(AdvisorySource {
+                path: PathBuf::from("/workspace/vendor/advisories"),
+                description: "`vulnerabilities.advisories` in project.toml".to_string()
+            })
+        );
+
+        let bindings = tempfile::tempdir().unwrap();
+        for (name, binding_type) in [("registry", "npmrc"), ("osv-mirror", "osv")] {
+            fs::create_dir(bindings.path().join(name)).unwrap();
+            fs::write(bindings.path().join(name).join("type"), binding_type).unwrap();
+        }
+        let mut env = Env::new();
+        env.insert("SERVICE_BINDING_ROOT", bindings.path());
+        assert_eq!(
+            AdvisorySource::find(app_dir, None, &env).unwrap(),
+            Some(AdvisorySource {
+                path: bindings.path().join("osv-mirror"),
+                description: "the `osv-mirror` service binding".to_string()
+            })
+        );
+        assert_eq!(
+            AdvisorySource::find(app_dir, None, &Env::new()).unwrap(),
+            None
+        );
+    }
+}
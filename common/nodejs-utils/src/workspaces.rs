@@ -0,0 +1,386 @@
+use crate::package_json::{PackageJson, PackageJsonError};
+use serde::Deserialize;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use thiserror::Error;
+
+/// Directories that are never searched for workspaces.
+const IGNORED_DIRECTORIES: [&str; 2] = ["node_modules", ".git"];
+
+/// The `workspaces` field of a root `package.json`, either a list of patterns
+/// (npm, Yarn) or an object with a `packages` list (Yarn classic).
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum Workspaces {
+    Patterns(Vec<String>),
+    Config { packages: Vec<String> },
+}
+
+impl Workspaces {
+    /// The patterns that match workspace directories. Patterns starting with
+    /// `!` exclude directories matched by other patterns.
+    #[must_use]
+    pub fn patterns(&self) -> &[String] {
+        match self {
+            Workspaces::Patterns(patterns) | Workspaces::Config { packages: patterns } => patterns,
+        }
+    }
+}
+
+/// A package in a workspace, with its path relative to the root package.
+#[derive(Debug, Clone)]
+pub struct Workspace {
+    pub path: PathBuf,
+    pub package_json: PackageJson,
+}
+
+impl Workspace {
+    #[must_use]
+    pub fn name(&self) -> Option<&str> {
+        self.package_json.name.as_deref()
+    }
+
+    /// Whether the workspace is identified by `selector`, which is either its
+    /// package name or its path relative to the root package.
+    #[must_use]
+    pub fn matches(&self, selector: &str) -> bool {
+        self.name() == Some(selector)
+            || normalize(Path::new(selector.trim())).is_some_and(|path| path == self.path)
+    }
+}
+
+impl Display for Workspace {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.name() {
+            Some(name) => write!(f, "{name} ({})", self.path.display()),
+            None => write!(f, "{}", self.path.display()),
+        }
+    }
+}
+
+/// Finds the workspaces declared by the root package in `app_dir`, sorted by
+/// path. Patterns support `*` within a path segment and `**` for any number of
+/// segments, and only match directories with a `package.json`. Symlinked
+/// directories aren't followed.
+///
+/// # Errors
+///
+/// Will return a `WorkspaceError` if the app directory can't be searched or a
+/// workspace's `package.json` can't be read.
+pub fn find_workspaces(
+    app_dir: &Path,
+    workspaces: &Workspaces,
+) -> Result<Vec<Workspace>, WorkspaceError> {
+    let (excludes, includes): (Vec<_>, Vec<_>) = workspaces
+        .patterns()
+        .iter()
+        .filter_map(|pattern| {
+            let (exclude, pattern) = match pattern.trim().strip_prefix('!') {
+                Some(pattern) => (true, pattern),
+                None => (false, pattern.trim()),
+            };
+            normalize(Path::new(pattern)).map(|pattern| (exclude, pattern))
+        })
+        .partition(|(exclude, _)| *exclude);
+
+    let include_patterns: Vec<_> = includes.iter().map(|(_, pattern)| pattern).collect();
+    let mut package_dirs = vec![];
+    find_package_dirs(app_dir, Path::new(""), &include_patterns, &mut package_dirs)?;
+    package_dirs.sort();
+
+    package_dirs
+        .into_iter()
+        .filter(|path| {
+            includes
+                .iter()
+                .any(|(_, pattern)| path_matches(pattern, path))
+                && !excludes
+                    .iter()
+                    .any(|(_, pattern)| path_matches(pattern, path))
+        })
+        .map(|path| {
+            PackageJson::read(app_dir.join(&path).join("package.json"))
+                .map(|package_json| Workspace { path, package_json })
+                .map_err(WorkspaceError::PackageJson)
+        })
+        .collect()
+}
+
+/// Selects the workspace identified by `selector` (a package name or a path).
+///
+/// # Errors
+///
+/// Will return a `WorkspaceError` if no workspace matches.
+pub fn select_workspace<'a>(
+    workspaces: &'a [Workspace],
+    selector: &str,
+) -> Result<&'a Workspace, WorkspaceError> {
+    workspaces
+        .iter()
+        .find(|workspace| workspace.matches(selector))
+        .ok_or_else(|| WorkspaceError::NotFound {
+            selector: selector.to_string(),
+            available: workspaces.iter().map(ToString::to_string).collect(),
+        })
+}
+
+/// Collects the directories with a `package.json` below `relative_dir`. Only
+/// directories that a pattern can still match are searched, and symlinks
+/// aren't followed so links out of the app directory or loops aren't walked.
+fn find_package_dirs(
+    app_dir: &Path,
+    relative_dir: &Path,
+    patterns: &[&PathBuf],
+    package_dirs: &mut Vec<PathBuf>,
+) -> Result<(), WorkspaceError> {
+    let dir = app_dir.join(relative_dir);
+    for entry in fs::read_dir(&dir).map_err(|e| WorkspaceError::ReadDir(dir.clone(), e))? {
+        let entry = entry.map_err(|e| WorkspaceError::ReadDir(dir.clone(), e))?;
+        let file_name = entry.file_name();
+        let file_type = entry
+            .file_type()
+            .map_err(|e| WorkspaceError::ReadDir(dir.clone(), e))?;
+        let relative_path = relative_dir.join(&file_name);
+        if IGNORED_DIRECTORIES
+            .iter()
+            .any(|ignored| file_name == *ignored)
+            || !file_type.is_dir()
+            || !patterns
+                .iter()
+                .any(|pattern| path_prefix_matches(pattern, &relative_path))
+        {
+            continue;
+        }
+        if entry.path().join("package.json").is_file() {
+            package_dirs.push(relative_path.clone());
+        }
+        find_package_dirs(app_dir, &relative_path, patterns, package_dirs)?;
+    }
+    Ok(())
+}
+
+/// Whether `path` or a directory below it can match `pattern`.
+fn path_prefix_matches(pattern: &Path, path: &Path) -> bool {
+    let pattern: Vec<_> = pattern.iter().filter_map(|s| s.to_str()).collect();
+    let path: Vec<_> = path.iter().filter_map(|s| s.to_str()).collect();
+    segments_prefix_match(&pattern, &path)
+}
+
+fn segments_prefix_match(pattern: &[&str], path: &[&str]) -> bool {
+    match (pattern.split_first(), path.split_first()) {
+        (_, None) | (Some((&"**", _)), _) => true,
+        (Some((segment, rest)), Some((name, path_rest))) => {
+            segment_matches(segment.as_bytes(), name.as_bytes())
+                && segments_prefix_match(rest, path_rest)
+        }
+        (None, Some(_)) => false,
+    }
+}
+
+fn path_matches(pattern: &Path, path: &Path) -> bool {
+    let pattern: Vec<_> = pattern.iter().filter_map(|s| s.to_str()).collect();
+    let path: Vec<_> = path.iter().filter_map(|s| s.to_str()).collect();
+    segments_match(&pattern, &path)
+}
+
+fn segments_match(pattern: &[&str], path: &[&str]) -> bool {
+    match (pattern.split_first(), path.split_first()) {
+        (None, _) => path.is_empty(),
+        (Some((&"**", rest)), _) => {
+            segments_match(rest, path)
+                || path
+                    .split_first()
+                    .is_some_and(|(_, path_rest)| segments_match(pattern, path_rest))
+        }
+        (Some((segment, rest)), Some((name, path_rest))) => {
+            segment_matches(segment.as_bytes(), name.as_bytes()) && segments_match(rest, path_rest)
+        }
+        (Some(_), None) => false,
+    }
+}
+
+fn segment_matches(pattern: &[u8], name: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some((b'*', rest)) => {
+            segment_matches(rest, name)
+                || name
+                    .split_first()
+                    .is_some_and(|(_, name_rest)| segment_matches(pattern, name_rest))
+        }
+        Some((c, rest)) => name
+            .split_first()
+            .is_some_and(|(n, name_rest)| c == n && segment_matches(rest, name_rest)),
+    }
+}
+
+fn normalize(path: &Path) -> Option<PathBuf> {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => normalized.push(part),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    Some(normalized)
+}
+
+#[derive(Error, Debug)]
+pub enum WorkspaceError {
+    #[error("Couldn't search {0} for workspaces: {1}")]
+    ReadDir(PathBuf, std::io::Error),
+    #[error(transparent)]
+    PackageJson(PackageJsonError),
+    #[error("The root package.json doesn't declare any `workspaces`")]
+    NoWorkspaces,
+    #[error("No workspace matches `{selector}`. Available workspaces: {}", if available.is_empty() { "none".to_string() } else { available.join(", ") })]
+    NotFound {
+        selector: String,
+        available: Vec<String>,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app(packages: &[(&str, &str)]) -> tempfile::TempDir {
+        let app_dir = tempfile::tempdir().unwrap();
+        for (path, name) in packages {
+            let dir = app_dir.path().join(path);
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(
+                dir.join("package.json"),
+                format!(r#"{{ "name": "{name}" }}"#),
+            )
+            .unwrap();
+        }
+        app_dir
+    }
+
+    fn paths(workspaces: &[Workspace]) -> Vec<String> {
+        workspaces
+            .iter()
+            .map(|workspace| workspace.path.display().to_string())
+            .collect()
+    }
+
+    fn patterns(patterns: &[&str]) -> Workspaces {
+        Workspaces::Patterns(patterns.iter().map(ToString::to_string).collect())
+    }
+
+    #[test]
+    fn parse_workspaces_field() {
+        let package_json: PackageJson =
+            serde_json::from_str(r#"{ "workspaces": ["apps/*"] }"#).unwrap();
+        assert_eq!(
+            package_json.workspaces.unwrap().patterns(),
+            ["apps/*".to_string()]
+        );
+        let package_json: PackageJson =
+            serde_json::from_str(r#"{ "workspaces": { "packages": ["libs/**"] } }"#).unwrap();
+        assert_eq!(
+            package_json.workspaces.unwrap().patterns(),
+            ["libs/**".to_string()]
+        );
+    }
+
+    #[test]
+    fn find_workspaces_by_pattern() {
+        let app_dir = app(&[
+            ("apps/web", "@acme/web"),
+            ("apps/api", "@acme/api"),
+            ("apps/api/node_modules/dep", "dep"),
+            ("libs/ui/button", "@acme/button"),
+            ("libs/ui-legacy", "@acme/ui-legacy"),
+            ("tools", "tools"),
+        ]);
+
+        assert_eq!(
+            paths(&find_workspaces(app_dir.path(), &patterns(&["apps/*"])).unwrap()),
+            ["apps/api", "apps/web"]
+        );
+        assert_eq!(
+            paths(&find_workspaces(app_dir.path(), &patterns(&["./libs/**", "tools"])).unwrap()),
+            ["libs/ui/button", "libs/ui-legacy", "tools"]
+        );
+        assert_eq!(
+            paths(&find_workspaces(app_dir.path(), &patterns(&["libs/ui*"])).unwrap()),
+            ["libs/ui-legacy"]
+        );
+        assert_eq!(
+            paths(&find_workspaces(app_dir.path(), &patterns(&["apps/*", "!apps/api"])).unwrap()),
+            ["apps/web"]
+        );
+        assert!(find_workspaces(app_dir.path(), &patterns(&["../outside"]))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn find_workspaces_without_following_symlinks() {
+        let app_dir = app(&[("apps/web", "@acme/web")]);
+        let outside = app(&[("api", "@acme/api")]);
+        std::os::unix::fs::symlink(outside.path().join("api"), app_dir.path().join("apps/api"))
+            .unwrap();
+        std::os::unix::fs::symlink(app_dir.path(), app_dir.path().join("apps/web/loop")).unwrap();
+
+        assert_eq!(
+            paths(&find_workspaces(app_dir.path(), &patterns(&["apps/**"])).unwrap()),
+            ["apps/web"]
+        );
+    }
+
+    #[test]
+    fn prune_directories_by_pattern_prefix() {
+        assert!(path_prefix_matches(Path::new("apps/*"), Path::new("apps")));
+        assert!(path_prefix_matches(
+            Path::new("apps/*"),
+            Path::new("apps/web")
+        ));
+        assert!(!path_prefix_matches(
+            Path::new("apps/*"),
+            Path::new("apps/web/src")
+        ));
+        assert!(!path_prefix_matches(Path::new("apps/*"), Path::new("docs")));
+        assert!(path_prefix_matches(
+            Path::new("libs/**"),
+            Path::new("libs/ui/button/src")
+        ));
+        assert!(path_prefix_matches(
+            Path::new("**/pkg"),
+            Path::new("any/where")
+        ));
+        assert!(path_prefix_matches(
+            Path::new("libs/ui*"),
+            Path::new("libs/ui-legacy")
+        ));
+        assert!(!path_prefix_matches(
+            Path::new("libs/ui*"),
+            Path::new("libs/core")
+        ));
+    }
+
+    #[test]
+    fn select_workspace_by_name_or_path() {
+        let app_dir = app(&[("apps/web", "@acme/web"), ("apps/api", "@acme/api")]);
+        let workspaces = find_workspaces(app_dir.path(), &patterns(&["apps/*"])).unwrap();
+
+        assert_eq!(
+            select_workspace(&workspaces, "@acme/web").unwrap().path,
+            Path::new("apps/web")
+        );
+        assert_eq!(
+            select_workspace(&workspaces, "./apps/api/").unwrap().path,
+            Path::new("apps/api")
+        );
+        assert_eq!(
+            select_workspace(&workspaces, "apps/docs")
+                .unwrap_err()
+                .to_string(),
+            "No workspace matches `apps/docs`. Available workspaces: @acme/api (apps/api), @acme/web (apps/web)"
+        );
+    }
+}
@@ -0,0 +1,539 @@
+use crate::dependency_graph::{
+    split_descriptor, yaml_scalar as scalar, DependencyGraph, DependencyGraphError, GraphBuilder,
+    Package, PackageId,
+};
+use crate::package_json::PackageJson;
+use crate::package_manager::PackageManager;
+use serde_yaml::Value;
+use std::collections::BTreeMap;
+
+const METADATA_KEY: &str = "__metadata";
+const CLASSIC_LOCKFILE_VERSION: &str = "1";
+
+/// An entry of a `yarn.lock`, shared by all the descriptors resolving to it.
+#[derive(Default)]
+struct Entry {
+    line: usize,
+    descriptors: Vec<String>,
+    version: String,
+    resolved: Option<String>,
+    integrity: Option<String>,
+    dependencies: BTreeMap<String, String>,
+    optional_dependencies: BTreeMap<String, String>,
+    os: Vec<String>,
+    cpu: Vec<String>,
+    workspace: bool,
+}
+
+pub(crate) fn parse(
+    contents: &str,
+    package_json: &PackageJson,
+) -> Result<DependencyGraph, DependencyGraphError> {
+    if contents
+        .lines()
+        .any(|line| line.starts_with(&format!("{METADATA_KEY}:")))
+    {
+        parse_berry(contents, package_json)
+    } else {
+        let entries = parse_classic(contents)?;
+        Ok(build(&entries, package_json).build(PackageManager::Yarn, CLASSIC_LOCKFILE_VERSION))
+    }
+}
+
+/// Parses the Yarn 1 format, where each entry lists its descriptors followed
+/// by indented fields, and dependencies are nested one level deeper:
+///
+/// ```text
+/// "@babel/code-frame@^7.0.0", "@babel/code-frame@^7.10.4":
+///   version "7.12.13"
+///   resolved "https://registry.yarnpkg.com/@babel/code-frame/-/code-frame-7.12.13.tgz"
+///   integrity sha512-...
+///   dependencies:
+///     "@babel/highlight" "^7.12.13"
+/// ```
+fn parse_classic(contents: &str) -> Result<Vec<Entry>, DependencyGraphError> {
+    let mut entries: Vec<Entry> = vec![];
+    let mut section = None;
+    for (index, line) in contents.lines().enumerate() {
+        let error = |message: &str| DependencyGraphError::YarnLock {
+            line: index + 1,
+            message: message.to_string(),
+        };
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let indent = line.len() - line.trim_start_matches(' ').len();
+        match indent {
+            0 => {
+                let descriptors = trimmed
+                    .strip_suffix(':')
+                    .ok_or_else(|| error("expected an entry like `name@range:`"))?;
+                entries.push(Entry {
+                    line: index + 1,
+                    descriptors: descriptors.split(", ").map(unquote).collect(),
+                    ..Entry::default()
+                });
+                section = None;
+            }
+            2 => {
+                let entry = entries
+                    .last_mut()
+                    .ok_or_else(|| error("expected an entry before its fields"))?;
+                if let Some(key) = trimmed.strip_suffix(':') {
+                    section = Some(key.to_string());
+                    continue;
+                }
+                section = None;
+                let (key, value) =
+                    split_field(trimmed).ok_or_else(|| error("expected `key value`"))?;
+                match key.as_str() {
+                    "version" => entry.version = value,
+                    "resolved" => entry.resolved = Some(value),
+                    "integrity" => entry.integrity = Some(value),
+                    _ => {}
+                }
+            }
+            4 => {
+                let entry = entries
+                    .last_mut()
+                    .ok_or_else(|| error("expected an entry before its fields"))?;
+                let (name, range) =
+                    split_field(trimmed).ok_or_else(|| error("expected `name range`"))?;
+                match section.as_deref() {
+                    Some("dependencies") => entry.dependencies.insert(name, range),
+                    Some("optionalDependencies") => entry.optional_dependencies.insert(name, range),
+                    Some(_) => None,
+                    None => Err(error("expected a field like `dependencies:` before"))?,
+                };
+            }
+            _ => Err(error("unexpected indentation"))?,
+        }
+    }
+    match entries.iter().find(|entry| entry.version.is_empty()) {
+        Some(entry) => Err(DependencyGraphError::YarnLock {
+            line: entry.line,
+            message: format!("`{}` has no version", entry.descriptors.join(", ")),
+        }),
+        None => Ok(entries),
+    }
+}
+
+fn split_field(field: &str) -> Option<(String, String)> {
+    let (key, value) = if let Some(quoted) = field.strip_prefix('"') {
+        let end = quoted.find('"')?;
+        (&quoted[..end], &quoted[end + 1..])
+    } else {
+        field.split_once(' ')?
+    };
+    Some((key.to_string(), unquote(value.trim())))
+}
+
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value)
+        .to_string()
+}
+
+/// Parses the YAML format of Yarn 2 and later. Entries are keyed by their
+/// descriptors, and the workspace packages are entries with a `workspace:`
+/// resolution.
+fn parse_berry(
+    contents: &str,
+    package_json: &PackageJson,
+) -> Result<DependencyGraph, DependencyGraphError> {
+    let lockfile: BTreeMap<String, Value> =
+        serde_yaml::from_str(contents).map_err(DependencyGraphError::YarnBerryLock)?;
+    let version = lockfile
+        .get(METADATA_KEY)
+        .and_then(|metadata| scalar(metadata.get("version")?))
+        .unwrap_or_default();
+    if !matches!(version.parse::<u32>(), Ok(4..)) {
+        Err(DependencyGraphError::UnsupportedVersion {
+            lockfile: "yarn.lock",
+            version: version.clone(),
+        })?;
+    }
+    let entries: Vec<Entry> = lockfile
+        .iter()
+        .filter(|(key, _)| key.as_str() != METADATA_KEY)
+        .map(|(key, value)| berry_entry(key, value))
+        .collect();
+    Ok(build(&entries, package_json).build(PackageManager::Yarn, version))
+}
+
+fn berry_entry(key: &str, value: &Value) -> Entry {
+    let resolution = value.get("resolution").and_then(scalar).unwrap_or_default();
+    let reference = split_descriptor(&resolution).map_or("", |(_, reference)| reference);
+    let optional: Vec<&str> = value
+        .get("dependenciesMeta")
+        .and_then(Value::as_mapping)
+        .into_iter()
+        .flatten()
+        .filter(|(_, meta)| meta.get("optional").and_then(Value::as_bool) == Some(true))
+        .filter_map(|(name, _)| name.as_str())
+        .collect();
+    let (optional_dependencies, dependencies) = mapping(value.get("dependencies"))
+        .into_iter()
+        .partition(|(name, _)| optional.contains(&name.as_str()));
+    let mut entry = Entry {
+        descriptors: key.split(", ").map(unquote).collect(),
+        version: value.get("version").and_then(scalar).unwrap_or_default(),
+        resolved: (!reference.starts_with("npm:") && !reference.is_empty())
+            .then(|| reference.to_string()),
+        integrity: value.get("checksum").and_then(scalar),
+        dependencies,
+        optional_dependencies,
+        workspace: reference.starts_with("workspace:"),
+        ..Entry::default()
+    };
+    entry
+        .optional_dependencies
+        .extend(mapping(value.get("optionalDependencies")));
+    for condition in value
+        .get("conditions")
+        .and_then(scalar)
+        .unwrap_or_default()
+        .split('&')
+    {
+        match condition.trim().split_once('=') {
+            Some(("os", os)) => entry.os.push(os.to_string()),
+            Some(("cpu", cpu)) => entry.cpu.push(cpu.to_string()),
+            _ => {}
+        }
+    }
+    entry
+}
+
+fn mapping(value: Option<&Value>) -> BTreeMap<String, String> {
+    value
+        .and_then(Value::as_mapping)
+        .into_iter()
+        .flatten()
+        .filter_map(|(key, value)| Some((scalar(key)?, scalar(value)?)))
+        .collect()
+}
+
+/// Builds the graph by resolving each dependency range to the entry listing
+/// the matching descriptor. Yarn 2 and later descriptors carry a protocol, so
+/// `lodash@^4.17.21` also resolves to `lodash@npm:^4.17.21`.
+fn build(entries: &[Entry], package_json: &PackageJson) -> GraphBuilder {
+    let mut builder = GraphBuilder::default();
+    let mut descriptors = BTreeMap::new();
+    let mut workspaces = vec![];
+    for entry in entries {
+        if entry.workspace {
+            workspaces.push(entry);
+            continue;
+        }
+        let Some((name, _)) = entry.descriptors.first().and_then(|d| split_descriptor(d)) else {
+            continue;
+        };
+        let id = builder.add_package(Package {
+            name: name.to_string(),
+            version: entry.version.clone(),
+            resolved: entry.resolved.clone(),
+            integrity: entry.integrity.clone(),
+            os: entry.os.clone(),
+            cpu: entry.cpu.clone(),
+            ..Package::default()
+        });
+        for descriptor in &entry.descriptors {
+            descriptors.insert(descriptor.as_str(), id.clone());
+        }
+    }
+    let resolve = |name: &str, range: &str| -> Option<PackageId> {
+        descriptors
+            .get(format!("{name}@{range}").as_str())
+            .or_else(|| descriptors.get(format!("{name}@npm:{range}").as_str()))
+            .cloned()
+    };
+
+    for entry in entries.iter().filter(|entry| !entry.workspace) {
+        let Some(id) = entry
+            .descriptors
+            .first()
+            .and_then(|descriptor| descriptors.get(descriptor.as_str()))
+        else {
+            continue;
+        };
+        for (dependencies, optional) in [
+            (&entry.dependencies, false),
+            (&entry.optional_dependencies, true),
+        ] {
+            for (name, range) in dependencies {
+                if let Some(dependency) = resolve(name, range) {
+                    builder.add_dependency(id, dependency, optional);
+                }
+            }
+        }
+    }
+
+    builder.add_package_json_roots(package_json, resolve);
+    // The root workspace is read from package.json, which tells dev
+    // dependencies apart. Other Yarn 2+ workspaces don't, so all of their
+    // dependencies count as production dependencies.
+    for workspace in workspaces.iter().filter(|workspace| {
+        !workspace
+            .descriptors
+            .iter()
+            .any(|d| d.ends_with("@workspace:."))
+    }) {
+        for (name, range) in &workspace.dependencies {
+            builder.root.dependencies.extend(resolve(name, range));
+        }
+        for (name, range) in &workspace.optional_dependencies {
+            builder
+                .root
+                .optional_dependencies
+                .extend(resolve(name, range));
+        }
+    }
+    builder
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use std::collections::BTreeSet;
+
+    fn id(name: &str, version: &str) -> PackageId {
+        PackageId::new(name, version)
+    }
+
+    fn package_json() -> PackageJson {
+        serde_json::from_str(
+            r#"{
+                "dependencies": { "@babel/code-frame": "^7.0.0", "chalk": "^2.4.1" },
+                "devDependencies": { "mocha": "^10.0.0" },
+                "optionalDependencies": { "fsevents": "~2.3.2" }
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn parse_classic_lockfile() {
+        let graph = parse(
+            indoc! {r#"
+                # THIS IS AN AUTOGENERATED FILE. DO NOT EDIT THIS FILE DIRECTLY.
+                # yarn lockfile v1
+
+
+                "@babel/code-frame@^7.0.0", "@babel/code-frame@^7.10.4":
+                  version "7.12.13"
+                  resolved "https://registry.yarnpkg.com/@babel/code-frame/-/code-frame-7.12.13.tgz#dcfc826beef65e75c50e21d3837d7d95798dd658"
+                  integrity sha512-code-frame
+                  dependencies:
+                    chalk "^2.0.0"
+
+                chalk@^2.0.0, chalk@^2.4.1:
+                  version "2.4.2"
+                  integrity sha512-chalk
+
+                fsevents@~2.3.2:
+                  version "2.3.3"
+
+                mocha@^10.0.0:
+                  version "10.2.0"
+                  dependencies:
+                    "@babel/code-frame" "^7.10.4"
+                    chalk "^4.1.0"
+                  optionalDependencies:
+                    fsevents "~2.3.2"
+
+                chalk@^4.1.0:
+                  version "4.1.2"
+            "#},
+            &package_json(),
+        )
+        .unwrap();
+
+        assert_eq!(graph.lockfile_version, "1");
+        assert_eq!(graph.len(), 5);
+        assert_eq!(
+            graph.root.dependencies,
+            BTreeSet::from([id("@babel/code-frame", "7.12.13"), id("chalk", "2.4.2")])
+        );
+        assert_eq!(
+            graph.root.dev_dependencies,
+            BTreeSet::from([id("mocha", "10.2.0")])
+        );
+        let code_frame = graph.get(&id("@babel/code-frame", "7.12.13")).unwrap();
+        assert_eq!(
+            code_frame.resolved.as_deref(),
+            Some("https://registry.yarnpkg.com/@babel/code-frame/-/code-frame-7.12.13.tgz#dcfc826beef65e75c50e21d3837d7d95798dd658")
+        );
+        assert_eq!(code_frame.integrity.as_deref(), Some("sha512-code-frame"));
+        assert_eq!(
+            code_frame.dependencies,
+            BTreeSet::from([id("chalk", "2.4.2")])
+        );
+
+        let mocha = graph.get(&id("mocha", "10.2.0")).unwrap();
+        assert!(mocha.dev);
+        assert_eq!(
+            mocha.dependencies,
+            BTreeSet::from([id("@babel/code-frame", "7.12.13"), id("chalk", "4.1.2")])
+        );
+        assert_eq!(
+            mocha.optional_dependencies,
+            BTreeSet::from([id("fsevents", "2.3.3")])
+        );
+        assert!(graph.get(&id("chalk", "4.1.2")).unwrap().dev);
+        let fsevents = graph.get(&id("fsevents", "2.3.3")).unwrap();
+        assert!(!fsevents.dev && fsevents.optional);
+    }
+
+    #[test]
+    fn parse_invalid_classic_lockfile() {
+        assert_eq!(
+            parse(
+                "chalk@^2.0.0:\n  version \"2.4.2\"\n      chalk \"^2.0.0\"\n",
+                &PackageJson::default()
+            )
+            .unwrap_err()
+            .to_string(),
+            "Couldn't parse yarn.lock on line 3: unexpected indentation"
+        );
+        assert_eq!(
+            parse(
+                "chalk@^2.0.0:\n  integrity sha512-chalk\n",
+                &PackageJson::default()
+            )
+            .unwrap_err()
+            .to_string(),
+            "Couldn't parse yarn.lock on line 1: `chalk@^2.0.0` has no version"
+        );
+    }
+
+    const BERRY_LOCKFILE: &str = indoc! {r#"
+        # This file is generated by running "yarn install" inside your project.
+        # Manual changes might be lost - proceed with caution!
+
+        __metadata:
+          version: 8
+          cacheKey: 10c0
+
+        "@babel/code-frame@npm:^7.0.0, @babel/code-frame@npm:^7.10.4":
+          version: 7.12.13
+          resolution: "@babel/code-frame@npm:7.12.13"
+          dependencies:
+            chalk: "npm:^2.0.0"
+          checksum: 10c0/code-frame
+          languageName: node
+          linkType: hard
+
+        "app@workspace:.":
+          version: 0.0.0-use.local
+          resolution: "app@workspace:."
+          dependencies:
+            "@babel/code-frame": "npm:^7.0.0"
+            chalk: "npm:^2.4.1"
+            fsevents: "npm:~2.3.2"
+            mocha: "npm:^10.0.0"
+          dependenciesMeta:
+            fsevents:
+              optional: true
+          languageName: unknown
+          linkType: soft
+
+        "chalk@npm:^2.0.0, chalk@npm:^2.4.1":
+          version: 2.4.2
+          resolution: "chalk@npm:2.4.2"
+          checksum: 10c0/chalk
+          languageName: node
+          linkType: hard
+
+        "fsevents@npm:~2.3.2":
+          version: 2.3.3
+          resolution: "fsevents@npm:2.3.3"
+          conditions: os=darwin
+          languageName: node
+          linkType: hard
+
+        "lib@workspace:packages/lib":
+          version: 0.0.0-use.local
+          resolution: "lib@workspace:packages/lib"
+          dependencies:
+            left-pad: "https://github.com/left-pad/left-pad/archive/refs/tags/v1.3.0.tar.gz"
+          languageName: unknown
+          linkType: soft
+
+        "left-pad@https://github.com/left-pad/left-pad/archive/refs/tags/v1.3.0.tar.gz":
+          version: 1.3.0
+          resolution: "left-pad@https://github.com/left-pad/left-pad/archive/refs/tags/v1.3.0.tar.gz"
+          languageName: node
+          linkType: hard
+
+        "mocha@npm:^10.0.0":
+          version: 10.2.0
+          resolution: "mocha@npm:10.2.0"
+          dependencies:
+            chalk: "npm:^2.0.0"
+          languageName: node
+          linkType: hard
+    "#};
+
+    #[test]
+    fn parse_berry_lockfile() {
+        let graph = parse(BERRY_LOCKFILE, &package_json()).unwrap();
+
+        assert_eq!(graph.lockfile_version, "8");
+        assert_eq!(
+            graph.packages().map(Package::id).collect::<Vec<_>>(),
+            [
+                id("@babel/code-frame", "7.12.13"),
+                id("chalk", "2.4.2"),
+                id("fsevents", "2.3.3"),
+                id("left-pad", "1.3.0"),
+                id("mocha", "10.2.0"),
+            ]
+        );
+        assert_eq!(
+            graph.root.dependencies,
+            BTreeSet::from([
+                id("@babel/code-frame", "7.12.13"),
+                id("chalk", "2.4.2"),
+                id("left-pad", "1.3.0"),
+            ])
+        );
+        assert_eq!(
+            graph.root.optional_dependencies,
+            BTreeSet::from([id("fsevents", "2.3.3")])
+        );
+        let code_frame = graph.get(&id("@babel/code-frame", "7.12.13")).unwrap();
+        assert_eq!(code_frame.resolved, None);
+        assert_eq!(code_frame.integrity.as_deref(), Some("10c0/code-frame"));
+        assert_eq!(
+            code_frame.dependencies,
+            BTreeSet::from([id("chalk", "2.4.2")])
+        );
+        assert_eq!(
+            graph
+                .get(&id("left-pad", "1.3.0"))
+                .unwrap()
+                .resolved
+                .as_deref(),
+            Some("https://github.com/left-pad/left-pad/archive/refs/tags/v1.3.0.tar.gz")
+        );
+        let fsevents = graph.get(&id("fsevents", "2.3.3")).unwrap();
+        assert_eq!(fsevents.os, ["darwin"]);
+        assert!(fsevents.optional && !fsevents.dev);
+        assert!(graph.get(&id("mocha", "10.2.0")).unwrap().dev);
+    }
+
+    #[test]
+    fn parse_unsupported_berry_lockfile() {
+        assert_eq!(
+            parse("__metadata:\n  version: 3\n", &PackageJson::default())
+                .unwrap_err()
+                .to_string(),
+            "Unsupported yarn.lock version `3`"
+        );
+    }
+}